@@ -4,20 +4,39 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 #![forbid(unsafe_code)]
-#![forbid(where_clauses_object_safety)]
 #![allow(deprecated)]
 
 use clap::Parser as ClapParser;
 use ethers_core::utils::hex;
-use huff_codegen::Codegen;
-use huff_core::Compiler;
+use huff_codegen::config::CodegenConfig;
+use huff_core::{
+    asm::AsmListing,
+    call_graph::CallGraph,
+    cfg::ControlFlowGraph,
+    daemon::{self, Daemon},
+    docs::ContractDocs,
+    mutation::{self, CallCase},
+    packages::{self, PackageSpec},
+    retained::{self, RetainedBuilds},
+    scheduling, simulation, symbolic,
+    workspace::{NetworkProfile, Workspace},
+    Compiler,
+};
 use huff_utils::prelude::{
-    export_interfaces, gen_sol_interfaces, str_to_bytes32, unpack_files, AstSpan, CodegenError,
-    CodegenErrorKind, CompilerError, FileSource, Literal, OutputLocation, Span,
+    export_interfaces, gen_sol_interfaces, str_to_bytes32, unpack_files, Artifact, ArtifactOutput,
+    AstSpan, BinRuntimeSerializer, BinSerializer, CallKind, CallWrapper, CancellationToken,
+    CodegenError, CodegenErrorKind, CompilerError, EToken, EvmVersion, FileSource, JsonSerializer,
+    Literal, OutputLocation, Remapping, Serializer, SerializerRegistry, Span, REMAPPINGS_FILE,
 };
 use isatty::stdout_isatty;
 use spinners::{Spinner, Spinners};
-use std::{collections::BTreeMap, io::Write, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
 use yansi::Paint;
 
 /// The Huff CLI Args
@@ -74,6 +93,648 @@ struct Huff {
     /// Override / set constants for the compilation environment.
     #[clap(short = 'c', long = "constants", multiple_values = true)]
     constants: Option<Vec<String>>,
+
+    /// Check the storage layout against a previous artifact.
+    #[clap(short = 'l', long = "storage-layout")]
+    storage_layout: Option<String>,
+
+    /// The artifact output format.
+    #[clap(short = 'f', long = "format", default_value = "json")]
+    format: String,
+
+    /// Write json artifacts without indentation.
+    #[clap(long = "compact")]
+    compact: bool,
+
+    /// Also export the deployed bytecode as a raw hex `.bin` file.
+    #[clap(long = "bin")]
+    bin: bool,
+
+    /// Also export the runtime bytecode as a raw hex `.bin-runtime` file.
+    #[clap(long = "bin-runtime")]
+    bin_runtime: bool,
+
+    /// Export artifacts to an output subdirectory named by the build settings, keeping the builds
+    /// of other settings.
+    #[clap(long = "retain")]
+    retain: bool,
+
+    /// Simulate the constructor, failing if it reverts.
+    #[clap(short = 'm', long = "simulate")]
+    simulate: bool,
+
+    /// Fail if the runtime bytecode exceeds this many bytes.
+    #[clap(short = 'x', long = "size-budget")]
+    size_budget: Option<usize>,
+
+    /// Check the abi against a previous artifact.
+    #[clap(short = 'e', long = "abi-diff")]
+    abi_diff: Option<String>,
+
+    /// Print the duration of each compilation stage per contract.
+    #[clap(long = "timings")]
+    timings: bool,
+
+    /// Print the runtime assembly annotated with static gas.
+    #[clap(long = "asm")]
+    asm: bool,
+
+    /// Print an experimental Yul object embedding the bytecode.
+    #[clap(long = "yul")]
+    yul: bool,
+
+    /// Compile a debug build, expanding __DEBUG macros.
+    #[clap(long = "debug")]
+    debug: bool,
+
+    /// Check that expanded macros match their takes and returns.
+    #[clap(long = "check-stack")]
+    check_stack: bool,
+
+    /// Append a metadata trailer to the runtime bytecode.
+    #[clap(long = "metadata")]
+    metadata: bool,
+
+    /// Replace the constructor bootstrap with a hex template.
+    #[clap(long = "bootstrap")]
+    bootstrap: Option<String>,
+
+    /// The artifact outputs to include, ie. `abi,bytecode`. Defaults to all but `asm`.
+    #[clap(long = "output-selection", use_value_delimiter = true)]
+    output_selection: Option<Vec<ArtifactOutput>>,
+
+    /// Push padded hex literals with the width they were written with.
+    #[clap(long = "preserve-push-width")]
+    preserve_push_width: bool,
+
+    /// Accept historical opcode aliases, ie. `sha3`, without warning.
+    #[clap(long = "allow-opcode-aliases")]
+    allow_opcode_aliases: bool,
+
+    /// Fail if macros are expanded within each other more than this many times [default: 128].
+    #[clap(long = "max-expansion-depth")]
+    max_expansion_depth: Option<usize>,
+
+    /// Fail if the expanded bytecode exceeds this many bytes [default: 1048576].
+    #[clap(long = "max-expanded-size")]
+    max_expanded_size: Option<usize>,
+
+    /// Compile every member of a huff.toml workspace.
+    #[clap(short = 'w', long = "workspace")]
+    workspace: Option<String>,
+
+    /// Build with a huff.toml network profile's constants and evm version.
+    #[clap(long = "network")]
+    network: Option<String>,
+
+    /// The evm version to target, overriding the network profile's.
+    #[clap(long = "evm-version")]
+    evm_version: Option<EvmVersion>,
+
+    /// A subcommand to run instead of compiling.
+    #[clap(subcommand)]
+    command: Option<HuffCommand>,
+}
+
+/// The Huff CLI Subcommands
+#[derive(clap::Subcommand, Debug, Clone)]
+enum HuffCommand {
+    /// Vendor Huff libraries into lib/ and add their remappings.
+    Install {
+        /// The packages to install, as owner/repo[@tag].
+        #[clap(required = true)]
+        packages: Vec<String>,
+    },
+    /// Generate a markdown or json reference from doc comments.
+    Doc {
+        /// The contract(s) to document.
+        #[clap(default_value = "./contracts")]
+        path: String,
+        /// The directory to write the docs to.
+        #[clap(short = 'o', long = "output", default_value = "./docs")]
+        output: String,
+        /// Generate json instead of markdown.
+        #[clap(short = 'j', long = "json")]
+        json: bool,
+    },
+    /// Generate a macro calling an external contract with the calldata.
+    Wrapper {
+        /// The macro name.
+        name: String,
+        /// Pushes the address called, ie. "[TARGET] sload".
+        target: String,
+        /// The call opcode: call, staticcall or delegatecall.
+        #[clap(short = 'k', long = "kind", default_value = "call")]
+        kind: CallKind,
+        /// Pushes the gas forwarded to the call.
+        #[clap(short = 'g', long = "gas", default_value = "gas")]
+        gas: String,
+        /// Pushes the value sent with the call.
+        #[clap(long = "value", default_value = "callvalue")]
+        value: String,
+        /// Continue execution on success instead of returning the returndata.
+        #[clap(long = "no-return")]
+        no_return: bool,
+        /// The file to append the macro to, instead of printing it.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+    /// Decode calldata, revert data, or return data against an artifact's abi.
+    Decode {
+        /// The json artifact to read the abi from.
+        artifact: String,
+        /// The hex data to decode.
+        data: String,
+        /// Decode the data as the return data of this function.
+        #[clap(short = 'f', long = "function")]
+        function: Option<String>,
+    },
+    /// Simulate a call to an artifact against a local state, printing its trace.
+    Simulate {
+        /// The json artifact to deploy.
+        artifact: String,
+        /// The hex calldata.
+        #[clap(default_value = "")]
+        data: String,
+        /// The value sent with the call, in wei.
+        #[clap(long = "value", default_value = "0")]
+        value: u128,
+        /// A json file of accounts to simulate against, in the format of geth's prestateTracer.
+        #[clap(long = "prestate")]
+        prestate: Option<String>,
+    },
+    /// Mutation test an artifact against a json file of call cases.
+    Mutate {
+        /// The json artifact to mutate.
+        artifact: String,
+        /// The json file of call cases to run against each mutant.
+        cases: String,
+    },
+    /// Export an artifact's runtime, entry points, and assertions for symbolic tools.
+    Symbolic {
+        /// The json artifact to export.
+        artifact: String,
+        /// The json file to write the export to, instead of printing it.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+        /// Check each entry point for assertion failures with hevm, which must be installed.
+        #[clap(long = "hevm")]
+        hevm: bool,
+    },
+    /// Export the control flow graph of an artifact's runtime as DOT or json.
+    Cfg {
+        /// The json artifact to graph.
+        artifact: String,
+        /// The file to write the graph to, instead of printing it.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+        /// Generate json instead of DOT.
+        #[clap(short = 'j', long = "json")]
+        json: bool,
+    },
+    /// Print the macro invocation graph of a contract as a tree, DOT, or json.
+    Callgraph {
+        /// The contract to graph.
+        path: String,
+        /// The file to write the graph to, instead of printing it.
+        #[clap(short = 'o', long = "output")]
+        output: Option<String>,
+        /// Generate DOT instead of a tree.
+        #[clap(long = "dot")]
+        dot: bool,
+        /// Generate json instead of a tree.
+        #[clap(short = 'j', long = "json")]
+        json: bool,
+        /// Include the macros invoked by __DEBUG, as in a debug build.
+        #[clap(short = 'd', long = "debug")]
+        debug: bool,
+    },
+    /// Suggest shorter dup, swap and pop sequences between stack comments.
+    StackHints {
+        /// The contract(s) to check.
+        #[clap(default_value = "./contracts")]
+        path: String,
+        /// Generate json instead of text.
+        #[clap(short = 'j', long = "json")]
+        json: bool,
+    },
+    /// Compare the sizes of the contracts in the builds retained with --retain.
+    Compare {
+        /// The output directory of the retained builds.
+        #[clap(default_value = "./artifacts")]
+        dir: String,
+        /// The build to compare against, ie. `shanghai-1f2e3d4c`.
+        #[clap(short = 'b', long = "baseline")]
+        baseline: Option<String>,
+        /// Generate json instead of text.
+        #[clap(short = 'j', long = "json")]
+        json: bool,
+    },
+    /// Serve JSON-RPC compile requests over a local socket, keeping artifacts warm between them.
+    Daemon {
+        /// The address to listen on.
+        #[clap(long = "address", default_value = daemon::DEFAULT_DAEMON_ADDRESS)]
+        address: String,
+    },
+}
+
+/// Installs packages into the lib directory and writes their remappings
+fn install_packages(packages: &[String]) {
+    let specs = packages
+        .iter()
+        .map(|p| match PackageSpec::from_str(p) {
+            Ok(spec) => spec,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        })
+        .collect::<Vec<PackageSpec>>();
+    let mut remappings = vec![];
+    for spec in specs {
+        match packages::install(&spec, Path::new(packages::LIB_DIR)) {
+            Ok(r) => {
+                println!("Installed {} ({})", Paint::blue(&spec), r);
+                remappings.push(r);
+            }
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Err(e) = packages::write_remappings(Path::new(REMAPPINGS_FILE), remappings) {
+        eprintln!("{}", Paint::red(e));
+        std::process::exit(1);
+    }
+}
+
+/// Writes the documentation of each contract to the output directory
+fn generate_docs(path: &str, output: &str, json: bool) {
+    let files = match unpack_files(path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", Paint::red(CompilerError::FileUnpackError(e)));
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(output) {
+        eprintln!("{}", Paint::red(e));
+        std::process::exit(1);
+    }
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to read \"{}\": {}", file, e)));
+                std::process::exit(1);
+            }
+        };
+        let contract_docs = match ContractDocs::generate(&file, &source) {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        let (contents, extension) = match json {
+            true => match contract_docs.to_json() {
+                Ok(json) => (json, "json"),
+                Err(e) => {
+                    eprintln!("{}", Paint::red(e));
+                    std::process::exit(1);
+                }
+            },
+            false => (contract_docs.to_markdown(), "md"),
+        };
+        let stem = Path::new(&file).file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let out = Path::new(output).join(format!("{}.{}", stem, extension));
+        if let Err(e) = std::fs::write(&out, contents) {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+        println!("Documented {} ({})", Paint::blue(&file), out.display());
+    }
+}
+
+/// Prints a call wrapper macro, or appends it to the output file
+fn generate_wrapper(wrapper: &CallWrapper, output: &Option<String>) {
+    let output = match output {
+        Some(o) => o,
+        None => return print!("{}", wrapper),
+    };
+    let written = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output)
+        .and_then(|mut f| write!(f, "\n{}", wrapper));
+    if let Err(e) = written {
+        eprintln!("{}", Paint::red(format!("Failed to write \"{}\": {}", output, e)));
+        std::process::exit(1);
+    }
+    println!("Generated {} ({})", Paint::blue(&wrapper.name), output);
+}
+
+/// Decodes data against an artifact's abi and prints it
+fn decode_data(artifact: &str, data: &str, function: &Option<String>) {
+    let abi = match Artifact::import(artifact) {
+        Ok(a) => a.abi.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read artifact \"{}\": {}", artifact, e)));
+            std::process::exit(1);
+        }
+    };
+    let bytes = match hex::decode(data.trim_start_matches("0x")) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Invalid hex data \"{}\": {}", data, e)));
+            std::process::exit(1);
+        }
+    };
+    let decoded = match function {
+        Some(f) => abi.decode_returndata(f, &bytes),
+        None => abi.decode_calldata(&bytes),
+    };
+    match decoded {
+        Ok(d) => print!("{}", d),
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Deploys an artifact and prints the trace of a call to it
+fn simulate_call(artifact: &str, data: &str, value: u128, prestate: &Option<String>) {
+    let artifact = match Artifact::import(artifact) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read artifact \"{}\": {}", artifact, e)));
+            std::process::exit(1);
+        }
+    };
+    let calldata = match hex::decode(data.trim_start_matches("0x")) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Invalid hex data \"{}\": {}", data, e)));
+            std::process::exit(1);
+        }
+    };
+    let prestate = match prestate {
+        Some(path) => match simulation::read_prestate(path) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to read prestate \"{}\": {}", path, e)));
+                std::process::exit(1);
+            }
+        },
+        None => simulation::Prestate::default(),
+    };
+    match simulation::simulate_call(&artifact, &calldata, value, &prestate) {
+        Ok(trace) => print!("{}", trace),
+        Err(e) => {
+            eprintln!("{}", Paint::red(CompilerError::SimulationError(e)));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Mutation tests an artifact against a file of call cases and prints the report
+fn mutate_artifact(artifact: &str, cases: &str) {
+    let artifact = match Artifact::import(artifact) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read artifact \"{}\": {}", artifact, e)));
+            std::process::exit(1);
+        }
+    };
+    let report = CallCase::load(Path::new(cases))
+        .and_then(|cases| mutation::mutation_test(&artifact, &cases));
+    match report {
+        Ok(r) => print!("{}", r),
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Exports an artifact for symbolic tools, or checks it with hevm
+fn symbolic_artifact(artifact: &str, output: Option<&str>, hevm: bool) {
+    let artifact = match Artifact::import(artifact) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read artifact \"{}\": {}", artifact, e)));
+            std::process::exit(1);
+        }
+    };
+    let target = symbolic::SymbolicTarget::new(&artifact);
+    if target.assertions.is_empty() {
+        tracing::warn!(target: "cli", "NO ASSERTIONS FOUND, ARE THEY WRAPPED IN __DEBUG?");
+    }
+    let json = match target.to_json() {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+    if let Some(out) = output {
+        if let Err(e) = std::fs::write(out, json) {
+            eprintln!("{}", Paint::red(format!("Failed to write \"{}\": {}", out, e)));
+            std::process::exit(1);
+        }
+    } else if !hevm {
+        println!("{}", json);
+    }
+    if !hevm {
+        return
+    }
+
+    let results = match symbolic::check_with_hevm(&artifact) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+    let mut failed = false;
+    for result in results {
+        let signature = result.entry_point.signature.unwrap_or_default();
+        if result.passed {
+            println!("{} {}", Paint::green("[PASS]"), signature);
+            continue
+        }
+        failed = true;
+        println!("{} {}", Paint::red("[FAIL]"), signature);
+        match result.counterexample {
+            Some(calldata) => println!("  Counterexample: {}", calldata),
+            None => print!("{}", result.output),
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Exports the control flow graph of an artifact
+fn export_cfg(artifact: &str, output: Option<&str>, json: bool) {
+    let artifact = match Artifact::import(artifact) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read artifact \"{}\": {}", artifact, e)));
+            std::process::exit(1);
+        }
+    };
+    let graph = ControlFlowGraph::new(&artifact);
+    let contents = match json {
+        true => match graph.to_json() {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        },
+        false => graph.to_dot(&artifact.file.path),
+    };
+    match output {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, contents) {
+                eprintln!("{}", Paint::red(format!("Failed to write \"{}\": {}", out, e)));
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", contents),
+    }
+}
+
+/// Prints or writes the macro invocation graph of a contract
+fn export_call_graph(path: &str, output: Option<&str>, dot: bool, json: bool, debug: bool) {
+    // A single path fetches a single source
+    let file = Compiler::fetch_sources(vec![path.into()])
+        .remove(0)
+        .map_err(Arc::new)
+        .and_then(|fs| Compiler::recurse_deps_with_remappings(fs, &Remapping::project()));
+    let file = match file {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+    let config = CodegenConfig { debug, ..Default::default() };
+    let graph = match CallGraph::from_file(&file, &Remapping::project(), &config) {
+        Ok(g) => g,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+    let contents = match (json, dot) {
+        (true, _) => match graph.to_json() {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        },
+        (false, true) => graph.to_dot(&file.path),
+        (false, false) => graph.render_tree(),
+    };
+    match output {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, contents) {
+                eprintln!("{}", Paint::red(format!("Failed to write \"{}\": {}", out, e)));
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", contents),
+    }
+}
+
+/// Prints the sizes of each contract across the retained builds
+fn compare_builds(dir: &str, baseline: Option<&str>, json: bool) {
+    let builds = match RetainedBuilds::load(Path::new(dir)) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to read builds in \"{}\": {}", dir, e)));
+            std::process::exit(1);
+        }
+    };
+    if builds.builds.is_empty() {
+        eprintln!("{}", Paint::red(format!("No retained builds in \"{}\"", dir)));
+        std::process::exit(1);
+    }
+    if let Some(b) = baseline.filter(|b| !builds.builds.contains_key(*b)) {
+        eprintln!("{}", Paint::red(format!("No retained \"{}\" build in \"{}\"", b, dir)));
+        std::process::exit(1);
+    }
+    let comparisons = builds.compare(baseline);
+    match json {
+        true => match retained::comparisons_to_json(&comparisons) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        },
+        false => print!("{}", retained::comparisons_to_text(&comparisons)),
+    }
+}
+
+/// Serves compile requests on the address until a shutdown is requested
+fn serve_daemon(compiler: Compiler, address: &str) {
+    let listener = match std::net::TcpListener::bind(address) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("{}", Paint::red(format!("Failed to listen on \"{}\": {}", address, e)));
+            std::process::exit(1);
+        }
+    };
+    println!("Listening for compile requests on {}", Paint::blue(address));
+    if let Err(e) = Daemon::new(compiler).serve(listener) {
+        eprintln!("{}", Paint::red(format!("Daemon failed: {}", e)));
+        std::process::exit(1);
+    }
+}
+
+/// Prints the shuffles of contracts that have shorter alternatives
+fn print_stack_hints(path: &str, json: bool) {
+    let files = match unpack_files(path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", Paint::red(CompilerError::FileUnpackError(e)));
+            std::process::exit(1);
+        }
+    };
+    let mut all_hints = BTreeMap::new();
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}", Paint::red(format!("Failed to read \"{}\": {}", file, e)));
+                std::process::exit(1);
+            }
+        };
+        let hints = scheduling::shuffle_hints(&source);
+        if !json {
+            for hint in &hints {
+                println!("{} {}", Paint::blue(&file), hint);
+            }
+        }
+        all_hints.insert(file, hints);
+    }
+    if json {
+        match scheduling::hints_to_json(&all_hints) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+    } else if all_hints.values().all(Vec::is_empty) {
+        println!("No shorter shuffles found");
+    }
 }
 
 /// Helper function to read an stdin input
@@ -97,14 +758,110 @@ fn main() {
         Compiler::init_tracing_subscriber(Some(vec![tracing::Level::DEBUG.into()]));
     }
 
+    if let Some(HuffCommand::Install { packages }) = &cli.command {
+        install_packages(packages);
+        return
+    }
+
+    if let Some(HuffCommand::Doc { path, output, json }) = &cli.command {
+        generate_docs(path, output, *json);
+        return
+    }
+
+    if let Some(HuffCommand::Wrapper { name, target, kind, gas, value, no_return, output }) =
+        &cli.command
+    {
+        let wrapper = CallWrapper {
+            name: name.clone(),
+            kind: *kind,
+            gas: gas.clone(),
+            value: value.clone(),
+            target: target.clone(),
+            forward_return: !no_return,
+        };
+        generate_wrapper(&wrapper, output);
+        return
+    }
+
+    if let Some(HuffCommand::Decode { artifact, data, function }) = &cli.command {
+        decode_data(artifact, data, function);
+        return
+    }
+
+    if let Some(HuffCommand::Simulate { artifact, data, value, prestate }) = &cli.command {
+        simulate_call(artifact, data, *value, prestate);
+        return
+    }
+
+    if let Some(HuffCommand::Mutate { artifact, cases }) = &cli.command {
+        mutate_artifact(artifact, cases);
+        return
+    }
+
+    if let Some(HuffCommand::Symbolic { artifact, output, hevm }) = &cli.command {
+        symbolic_artifact(artifact, output.as_deref(), *hevm);
+        return
+    }
+
+    if let Some(HuffCommand::Cfg { artifact, output, json }) = &cli.command {
+        export_cfg(artifact, output.as_deref(), *json);
+        return
+    }
+
+    if let Some(HuffCommand::Compare { dir, baseline, json }) = &cli.command {
+        compare_builds(dir, baseline.as_deref(), *json);
+        return
+    }
+
+    if let Some(HuffCommand::StackHints { path, json }) = &cli.command {
+        print_stack_hints(path, *json);
+        return
+    }
+
+    if let Some(HuffCommand::Callgraph { path, output, dot, json, debug }) = &cli.command {
+        export_call_graph(path, output.as_deref(), *dot, *json, *debug);
+        return
+    }
+
+    // Load the workspace members, if building a workspace
+    let workspace = cli.workspace.as_ref().map(|w| match Workspace::load(Path::new(w)) {
+        Ok(ws) => ws,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    });
+
+    // Load the network profile from the workspace manifest, or the current directory's
+    let network = cli.network.as_ref().map(|n| {
+        let manifest = cli.workspace.clone().unwrap_or_else(|| ".".to_string());
+        match NetworkProfile::load(Path::new(&manifest), n) {
+            Ok(profile) => profile,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+    });
+
     // Create compiler from the Huff Args
-    let sources: Arc<Vec<String>> = match cli.get_inputs() {
+    let inputs = match &workspace {
+        Some(ws) => ws.sources().map_err(|e| e.to_string()),
+        None => cli.get_inputs().map_err(|e| e.to_string()),
+    };
+    let sources: Arc<Vec<String>> = match inputs {
         Ok(s) => Arc::new(s),
+        // The daemon compiles the paths of its requests instead
+        Err(_) if matches!(cli.command, Some(HuffCommand::Daemon { .. })) => Arc::default(),
         Err(e) => {
-            eprintln!("{}", Paint::red(format!("{}", e)));
+            eprintln!("{}", Paint::red(e));
             std::process::exit(1);
         }
     };
+    let remappings = match &workspace {
+        Some(ws) => ws.remappings(),
+        None => Remapping::project(),
+    };
 
     // If constant overrides were passed, create a map of their names and values
     let constants: Option<BTreeMap<&str, Literal>> = cli.constants.as_ref().map(|_constants| {
@@ -119,6 +876,7 @@ fn main() {
                 if parts.len() != 2 ||
                     parts[0].chars().any(|c| !(c.is_alphabetic() || c == '_')) ||
                     !parts[1].starts_with("0x") ||
+                    parts[1].len() > 66 ||
                     parts[1][2..].chars().any(|c| {
                         !(c.is_numeric() ||
                             matches!(c, '\u{0041}'..='\u{0046}' | '\u{0061}'..='\u{0066}'))
@@ -133,6 +891,19 @@ fn main() {
             .collect()
     });
 
+    // Network profile constants are overridden by those passed on the command line
+    let constants = match &network {
+        Some(profile) => {
+            let mut overrides = profile.constant_overrides().unwrap_or_else(|e| {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            });
+            overrides.extend(constants.unwrap_or_default());
+            Some(overrides)
+        }
+        None => constants,
+    };
+
     let mut use_cache = true;
     if cli.interactive {
         // Don't accept configured inputs
@@ -145,13 +916,65 @@ fn main() {
         use_cache = false;
     }
 
+    // Raw bytecode files are exported alongside the artifacts
+    let mut additional_serializers: Vec<Arc<dyn Serializer>> = vec![];
+    if cli.bin {
+        additional_serializers.push(Arc::new(BinSerializer));
+    }
+    if cli.bin_runtime {
+        additional_serializers.push(Arc::new(BinRuntimeSerializer));
+    }
+    if !additional_serializers.is_empty() && !cli.interactive {
+        cli.artifacts = true;
+    }
+
+    // Retained builds are exported, to be compared later
+    if cli.retain && !cli.interactive {
+        cli.artifacts = true;
+    }
+
     let output = match (&cli.output, cli.artifacts) {
         (Some(o), true) => Some(o.clone()),
         (None, true) => Some(cli.outputdir.clone()),
         _ => None,
     };
 
-    let compiler: Compiler = Compiler {
+    // Grab the artifact serializer for the output format
+    let mut serializers = SerializerRegistry::default();
+    if cli.compact {
+        serializers.register(Arc::new(JsonSerializer { pretty: false }));
+    }
+    let serializer = match serializers.get(&cli.format) {
+        Some(s) => s,
+        None => {
+            eprintln!(
+                "Invalid artifact format: {}\nExpected one of: {}",
+                Paint::red(&cli.format),
+                serializers.names().join(", ")
+            );
+            std::process::exit(1);
+        }
+    };
+
+    // The requested checks and exports need their outputs, whatever the selection
+    let mut output_selection = cli
+        .output_selection
+        .clone()
+        .map_or_else(|| ArtifactOutput::DEFAULT.into(), BTreeSet::from_iter);
+    if cli.storage_layout.is_some() {
+        output_selection.insert(ArtifactOutput::StorageLayout);
+    }
+    if cli.abi_diff.is_some() || cli.interface || cli.interactive {
+        output_selection.insert(ArtifactOutput::Abi);
+    }
+    if cli.bytecode || cli.asm || cli.bin || cli.bin_runtime {
+        output_selection.insert(ArtifactOutput::Bytecode);
+    }
+    if cli.yul {
+        output_selection.insert(ArtifactOutput::Yul);
+    }
+
+    let mut compiler: Compiler = Compiler {
         sources: Arc::clone(&sources),
         output,
         construct_args: cli.inputs,
@@ -159,8 +982,47 @@ fn main() {
         optimize: cli.optimize,
         bytecode: cli.bytecode,
         cached: use_cache,
+        serializer: Some(Arc::clone(&serializer)),
+        additional_serializers,
+        simulate: cli.simulate,
+        size_budget: cli.size_budget,
+        remappings,
+        timings: Arc::default(),
+        warnings: Arc::default(),
+        cancellation: CancellationToken::default(),
+        builtins: BTreeMap::new(),
+        debug: cli.debug,
+        preserve_push_width: cli.preserve_push_width,
+        evm_version: cli
+            .evm_version
+            .or_else(|| network.as_ref().map(|n| n.evm_version))
+            .unwrap_or_default(),
+        check_stack: cli.check_stack,
+        metadata: cli.metadata,
+        bootstrap: cli.bootstrap.clone(),
+        output_selection,
+        allow_opcode_aliases: cli.allow_opcode_aliases,
+        max_expansion_depth: cli.max_expansion_depth,
+        max_expanded_size: cli.max_expanded_size,
     };
 
+    // Retained builds are exported to an output subdirectory named by their settings
+    if cli.retain {
+        cli.outputdir = retained::build_dir(&cli.outputdir, &compiler.build_settings());
+        if cli.output.is_none() && cli.artifacts {
+            compiler.output = Some(cli.outputdir.clone());
+        }
+    }
+
+    if let Some(HuffCommand::Daemon { address }) = &cli.command {
+        serve_daemon(compiler, address);
+        return
+    }
+
+    // Load previous artifacts before compiling since exporting may overwrite them
+    let previous_layout = cli.storage_layout.as_ref().map(|p| load_previous_artifact(p));
+    let previous_abi = cli.abi_diff.as_ref().map(|p| load_previous_artifact(p));
+
     // Create compiling spinner
     tracing::debug!(target: "cli", "[⠔] COMPILING");
     let mut sp: Option<Spinner> = None;
@@ -170,13 +1032,19 @@ fn main() {
     }
 
     let compile_res = compiler.execute();
+    let serializers = compiler.get_serializers();
 
     // Stop spinner animation if it exists
     if let Some(mut sp) = sp {
         sp.stop();
         println!(" ");
     }
-    match compile_res {
+    if cli.timings {
+        println!("{}", Paint::blue("Compilation timings:"));
+        print!("{}", compile_res.timings.report());
+    }
+    compile_res.warnings.iter().for_each(|w| eprintln!("{}", Paint::yellow(w)));
+    match compile_res.into_result() {
         Ok(mut artifacts) => {
             if artifacts.is_empty() {
                 let e = CompilerError::CodegenError(CodegenError {
@@ -198,12 +1066,67 @@ fn main() {
                             .collect::<Vec<Span>>(),
                     ),
                     token: None,
+                    expansion: vec![],
                 });
                 tracing::error!(target: "cli", "COMPILER ERRORED: {:?}", e);
                 eprintln!("{}", Paint::red(format!("{}", e)));
                 std::process::exit(1);
             }
 
+            if cli.asm {
+                for artifact in &artifacts {
+                    println!("{}", Paint::blue(format!("\"{}\" assembly:", artifact.file.path)));
+                    print!("{}", AsmListing::new(&artifact.runtime).render());
+                }
+            }
+
+            if cli.yul {
+                for artifact in &artifacts {
+                    println!("{}", Paint::blue(format!("\"{}\" yul:", artifact.file.path)));
+                    print!("{}", artifact.yul.as_deref().unwrap_or_default());
+                }
+            }
+
+            if let Some((previous_path, previous)) = &previous_layout {
+                let current = match_previous_artifact(&artifacts, previous);
+                let conflicts = current.storage_layout.compare(&previous.storage_layout);
+                if !conflicts.is_empty() {
+                    eprintln!(
+                        "{}",
+                        Paint::red(format!(
+                            "Incompatible storage layout for \"{}\":",
+                            current.file.path
+                        ))
+                    );
+                    conflicts.iter().for_each(|c| eprintln!("  {}", Paint::red(c)));
+                    std::process::exit(1);
+                }
+                println!("Storage layout compatible with {}", Paint::blue(previous_path));
+            }
+
+            if let Some((previous_path, previous)) = &previous_abi {
+                let current = match_previous_artifact(&artifacts, previous);
+                let changes = match (&current.abi, &previous.abi) {
+                    (Some(curr), Some(prev)) => curr.compare(prev),
+                    _ => {
+                        eprintln!(
+                            "{}",
+                            Paint::red(format!("Missing abi for \"{}\"", current.file.path))
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                if !changes.is_empty() {
+                    eprintln!(
+                        "{}",
+                        Paint::red(format!("Breaking abi changes for \"{}\":", current.file.path))
+                    );
+                    changes.iter().for_each(|c| eprintln!("  {}", Paint::red(c)));
+                    std::process::exit(1);
+                }
+                println!("Abi compatible with {}", Paint::blue(previous_path));
+            }
+
             if cli.interface {
                 tracing::info!(target: "cli", "GENERATING SOLIDITY INTERFACES FROM ARTIFACTS");
                 let interfaces = gen_sol_interfaces(&artifacts);
@@ -246,21 +1169,24 @@ fn main() {
                                         let arg_input = get_input(&format!(
                                             "Enter a {:?} for constructor param{}:",
                                             input.kind,
-                                            (!input.name.is_empty())
-                                                .then(|| format!(" \"{}\"", input.name))
-                                                .unwrap_or_default()
+                                            if !input.name.is_empty() {
+                                                format!(" \"{}\"", input.name)
+                                            } else {
+                                                Default::default()
+                                            }
                                         ));
-                                        let encoded =
-                                            Codegen::encode_constructor_args(vec![arg_input])
-                                                .iter()
-                                                .fold(String::default(), |acc, str| {
-                                                    let inner: Vec<u8> =
-                                                        ethers_core::abi::encode(&[str.clone()]);
-                                                    let hex_args: String =
-                                                        hex::encode(inner.as_slice());
-                                                    format!("{}{}", acc, hex_args)
-                                                });
-                                        appended_args.push_str(&encoded);
+                                        // Inputs are parsed as the param's type
+                                        let token =
+                                            match EToken::try_from_param(&arg_input, &input.kind) {
+                                                Ok(t) => t.0,
+                                                Err(e) => {
+                                                    eprintln!("{}", Paint::red(e));
+                                                    std::process::exit(1);
+                                                }
+                                            };
+                                        let inner: Vec<u8> =
+                                            ethers_core::abi::encode(std::slice::from_ref(&token));
+                                        appended_args.push_str(&hex::encode(inner.as_slice()));
                                     }
                                 }
                                 None => {
@@ -284,6 +1210,7 @@ fn main() {
                     Compiler::export_artifacts(
                         &artifacts,
                         &OutputLocation(cli.output.unwrap_or_else(|| cli.outputdir.clone())),
+                        &serializers,
                     );
                     tracing::info!(target: "cli", "RE-EXPORTED INTERACTIVE ARTIFACTS");
                 }
@@ -303,9 +1230,48 @@ fn main() {
     }
 }
 
+/// Loads a previous build's artifact, exiting if it can't be read
+fn load_previous_artifact(path: &str) -> (String, Artifact) {
+    tracing::info!(target: "cli", "LOADING PREVIOUS ARTIFACT \"{}\"", path);
+    match Artifact::import(path) {
+        Ok(a) => (path.to_string(), a),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                Paint::red(format!("Failed to read previous artifact \"{}\": {}", path, e))
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Finds the artifact compiled from the same file as a previous artifact, falling back to the
+/// only artifact if a single contract was compiled
+fn match_previous_artifact<'a>(
+    artifacts: &'a [Arc<Artifact>],
+    previous: &Artifact,
+) -> &'a Artifact {
+    match artifacts.iter().find(|a| a.file.path == previous.file.path).or_else(|| {
+        if artifacts.len() == 1 {
+            artifacts.first()
+        } else {
+            None
+        }
+    }) {
+        Some(a) => a,
+        None => {
+            eprintln!(
+                "{}",
+                Paint::red(format!("No compiled artifact matches \"{}\"", previous.file.path))
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
 impl Huff {
     /// Preprocesses input files for compiling
-    pub fn get_inputs(&self) -> Result<Vec<String>, CompilerError> {
+    pub fn get_inputs(&self) -> Result<Vec<String>, CompilerError<'_>> {
         match &self.path {
             Some(path) => {
                 tracing::debug!(target: "io", "FETCHING INPUT: {}", path);
@@ -0,0 +1,97 @@
+use huff_utils::prelude::{BuiltinFunctionKind, CancellationToken, EvmVersion};
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// Evaluates a custom builtin's 32 byte arguments into the 32 byte word it pushes
+pub type BuiltinEval = Arc<dyn Fn(&[[u8; 32]]) -> Result<[u8; 32], String> + Send + Sync>;
+
+/// A User Defined Compile Time Builtin
+///
+/// Arguments are resolved at compile time from literals and constants, and the result is pushed
+/// with the smallest PUSH instruction that fits it.
+#[derive(Clone)]
+pub struct CustomBuiltin {
+    /// The builtin name, including its `__` prefix
+    pub name: String,
+    /// The number of arguments the builtin takes
+    pub arity: usize,
+    /// The evaluation callback
+    pub eval: BuiltinEval,
+}
+
+impl CustomBuiltin {
+    /// Public associated function to instantiate a new custom builtin.
+    ///
+    /// Names must start with `__` to be lexed as builtin calls, and can't shadow a native builtin.
+    pub fn new<F>(name: &str, arity: usize, eval: F) -> Result<Self, String>
+    where
+        F: Fn(&[[u8; 32]]) -> Result<[u8; 32], String> + Send + Sync + 'static,
+    {
+        if !name.starts_with("__") || name.len() == 2 {
+            return Err(format!("Builtin \"{}\" must start with \"__\"", name))
+        }
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("Builtin \"{}\" must only contain alphanumerics and '_'", name))
+        }
+        if !matches!(BuiltinFunctionKind::from(name), BuiltinFunctionKind::Custom(_)) {
+            return Err(format!("Builtin \"{}\" shadows a native builtin", name))
+        }
+        Ok(CustomBuiltin { name: name.to_string(), arity, eval: Arc::new(eval) })
+    }
+}
+
+impl fmt::Debug for CustomBuiltin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomBuiltin")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The default constructor bootstrap, copying the runtime into memory and returning it
+pub const DEFAULT_BOOTSTRAP: &str = "{size}80{offset}3d393df3";
+
+/// The memory offset the constructor stages immutables from, past the two scratch space words
+/// that builtins like `__CONSTRUCTOR_ARG` copy into
+pub const IMMUTABLES_OFFSET: usize = 0x40;
+/// The default limit on the number of macros being expanded within each other, far past any
+/// nesting written by hand
+pub const DEFAULT_MAX_EXPANSION_DEPTH: usize = 128;
+
+/// The default limit on the size of expanded bytecode in bytes, far past any deployable contract
+pub const DEFAULT_MAX_EXPANDED_SIZE: usize = 0x100000;
+
+/// Code Generation Options
+#[derive(Debug, Clone, Default)]
+pub struct CodegenConfig {
+    /// Token checked before expanding each macro, to abort an in-flight compilation
+    pub cancellation: CancellationToken,
+    /// The custom builtins, keyed by name
+    pub builtins: BTreeMap<String, CustomBuiltin>,
+    /// Whether to expand `__DEBUG` macros, which are stripped from release builds
+    pub debug: bool,
+    /// Whether to push padded hex literals with the width they were written with, ie. `0x0001`
+    /// as `PUSH2 0x0001` instead of `PUSH1 0x01`
+    pub preserve_push_width: bool,
+    /// The evm version to target, failing on opcodes it doesn't support
+    pub evm_version: EvmVersion,
+    /// Whether to check that expanded macros match their `takes` and `returns`
+    pub check_stack: bool,
+    /// The constructor bootstrap template, defaulting to [`DEFAULT_BOOTSTRAP`]. `{size}` and
+    /// `{offset}` are replaced by pushes of the runtime's size and offset in the bytecode, and
+    /// `{args_size}` and `{args_offset}` by those of the constructor arguments after it.
+    pub bootstrap: Option<String>,
+    /// The limit on the number of macros being expanded within each other, defaulting to
+    /// [`DEFAULT_MAX_EXPANSION_DEPTH`]
+    pub max_expansion_depth: Option<usize>,
+    /// The limit on the size of the expanded bytecode in bytes, defaulting to
+    /// [`DEFAULT_MAX_EXPANDED_SIZE`]
+    pub max_expanded_size: Option<usize>,
+}
+
+impl CodegenConfig {
+    /// Registers a custom builtin, replacing any custom builtin of the same name
+    pub fn register_builtin(&mut self, builtin: CustomBuiltin) {
+        self.builtins.insert(builtin.name.clone(), builtin);
+    }
+}
@@ -1,6 +1,8 @@
 use huff_utils::prelude::*;
 use std::str::FromStr;
 
+use crate::irgen::prelude::push0_literal;
+
 // Arguments can be literals, labels, opcodes, or constants
 // !! IF THERE IS AMBIGUOUS NOMENCLATURE
 // !! (E.G. BOTH OPCODE AND LABEL ARE THE SAME STRING)
@@ -13,43 +15,28 @@ pub fn bubble_arg_call(
     bytes: &mut Vec<(usize, Bytes)>,
     macro_def: &MacroDefinition,
     contract: &Contract,
-    scope: &mut Vec<MacroDefinition>,
+    scope: &mut [MacroDefinition],
     offset: &mut usize,
     // mis: Parent macro invocations and their indices
     mis: &mut Vec<(usize, MacroInvocation)>,
     jump_table: &mut JumpTable,
+    evm_version: EvmVersion,
 ) -> Result<(), CodegenError> {
     let starting_offset = *offset;
 
     // Check Constant Definitions
-    if let Some(constant) =
-        contract.constants.borrow().iter().find(|const_def| const_def.name.eq(arg_name))
-    {
+    let constants = contract.constants.borrow();
+    if let Some(constant) = constants.iter().find(|const_def| const_def.name.eq(arg_name)) {
         tracing::info!(target: "codegen", "ARGCALL IS CONSTANT: {:?}", constant);
-        let push_bytes = match &constant.value {
-            ConstVal::Literal(l) => {
-                let hex_literal: String = bytes32_to_string(l, false);
-                format!("{:02x}{}", 95 + hex_literal.len() / 2, hex_literal)
-            }
-            ConstVal::FreeStoragePointer(fsp) => {
-                // If this is reached in codegen stage,
-                // `derive_storage_pointers`
-                // method was not called on the AST.
-                tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", fsp);
-                return Err(CodegenError {
-                    kind: CodegenErrorKind::StoragePointersNotDerived,
-                    span: AstSpan(vec![]),
-                    token: None,
-                })
-            }
-        };
-        *offset += push_bytes.len() / 2;
-        tracing::info!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-        bytes.push((starting_offset, Bytes(push_bytes)));
+        let push_bytes =
+            push0_literal(Bytes::push_word(&constant.evaluate(&constants)?), evm_version);
+        *offset += push_bytes.len();
+        tracing::info!(target: "codegen", "OFFSET: {}, PUSH BYTES: {}", offset, push_bytes);
+        bytes.push((starting_offset, push_bytes));
     } else if let Ok(o) = Opcode::from_str(arg_name) {
         // Check Opcode Definition
-        let b = Bytes(o.to_string());
-        *offset += b.0.len() / 2;
+        let b = Bytes::from(o);
+        *offset += b.len();
         tracing::info!(target: "codegen", "RECURSE_BYTECODE ARG CALL FOUND OPCODE: {:?}", b);
         bytes.push((starting_offset, b));
     } else if let Some(macro_invoc) = mis.last() {
@@ -58,7 +45,7 @@ pub fn bubble_arg_call(
         if let Some(pos) = macro_def
             .parameters
             .iter()
-            .position(|r| r.name.as_ref().map_or(false, |s| s.eq(arg_name)))
+            .position(|r| r.name.as_ref().is_some_and(|s| s.eq(arg_name)))
         {
             tracing::info!(target: "codegen", "GOT \"{}\" POS IN ARG LIST: {}", arg_name, pos);
 
@@ -68,18 +55,27 @@ pub fn bubble_arg_call(
                     MacroArg::Literal(l) => {
                         tracing::info!(target: "codegen", "GOT LITERAL {} ARG FROM MACRO INVOCATION", bytes32_to_string(l, false));
 
-                        let hex_literal: String = bytes32_to_string(l, false);
-                        let push_bytes =
-                            format!("{:02x}{}", 95 + hex_literal.len() / 2, hex_literal);
-                        let b = Bytes(push_bytes);
-                        *offset += b.0.len() / 2;
+                        let b = push0_literal(Bytes::push_word(l), evm_version);
+                        *offset += b.len();
                         bytes.push((starting_offset, b));
                     }
                     MacroArg::ArgCall(ac) => {
                         tracing::info!(target: "codegen", "GOT ARG CALL \"{}\" ARG FROM MACRO INVOCATION", ac);
                         tracing::debug!(target: "codegen", "~~~ BUBBLING UP ARG CALL");
                         let mut new_scope = Vec::from(&scope[..scope.len().saturating_sub(1)]);
-                        let bubbled_macro_invocation = new_scope.last().unwrap().clone();
+                        let bubbled_macro_invocation = match new_scope.last() {
+                            Some(m) => m.clone(),
+                            None => {
+                                return Err(CodegenError {
+                                    kind: CodegenErrorKind::MissingMacroInvocation(
+                                        macro_def.name.clone(),
+                                    ),
+                                    span: macro_def.span.clone(),
+                                    token: None,
+                                    expansion: vec![],
+                                })
+                            }
+                        };
                         tracing::debug!(target: "codegen", "BUBBLING UP WITH MACRO DEF: {}", bubbled_macro_invocation.name);
                         tracing::debug!(target: "codegen", "CURRENT MACRO DEF: {}", macro_def.name);
 
@@ -94,6 +90,7 @@ pub fn bubble_arg_call(
                                     ),
                                     span: bubbled_macro_invocation.span,
                                     token: None,
+                                    expansion: vec![],
                                 })
                             }
                         };
@@ -107,6 +104,7 @@ pub fn bubble_arg_call(
                                 offset,
                                 &mut Vec::from(&mis[..mis.len().saturating_sub(1)]),
                                 jump_table,
+                                evm_version,
                             )
                         } else {
                             bubble_arg_call(
@@ -118,6 +116,7 @@ pub fn bubble_arg_call(
                                 offset,
                                 mis,
                                 jump_table,
+                                evm_version,
                             )
                         }
                     }
@@ -127,7 +126,7 @@ pub fn bubble_arg_call(
                         tracing::debug!(target: "codegen", "At offset: {}", *offset);
 
                         // This should be equivalent to a label call.
-                        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+                        bytes.push((*offset, Bytes::push2_placeholder()));
                         jump_table.insert(
                             *offset,
                             vec![Jump {
@@ -156,7 +155,7 @@ pub fn bubble_arg_call(
             mis.last().map(|mi| mi.0).unwrap_or_else(|| 0),
             vec![Jump { label: arg_name.to_owned(), bytecode_index: 0, span: new_span }],
         );
-        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+        bytes.push((*offset, Bytes::push2_placeholder()));
         *offset += 3;
     }
 
@@ -1,13 +1,18 @@
 use huff_utils::prelude::{
-    bytes32_to_string, AstSpan, CodegenError, CodegenErrorKind, ConstVal, Contract,
+    str_to_bytes32, Argument, AstSpan, Bytes, CodegenError, CodegenErrorKind, ConstVal, Contract,
+    EvmVersion, Opcode,
 };
 
 /// Transforms a constant definition into it's respective bytecode
+///
+/// Literals are pushed with the smallest PUSH instruction that fits them, unless
+/// `preserve_width` is set, in which case the width the literal was written with is kept.
 pub fn constant_gen(
     name: &str,
     contract: &Contract,
     ir_byte_span: AstSpan,
-) -> Result<String, CodegenError> {
+    preserve_width: bool,
+) -> Result<Bytes, CodegenError> {
     // Get the first `ConstantDefinition` that matches the constant's name
     let constants = contract.constants.borrow();
     let constant = if let Some(m) = constants.iter().find(|const_def| const_def.name.eq(&name)) {
@@ -19,29 +24,104 @@ pub fn constant_gen(
             kind: CodegenErrorKind::MissingConstantDefinition(name.to_string()),
             span: ir_byte_span,
             token: None,
+            expansion: vec![],
         })
     };
 
     // Generate bytecode for the constant
-    // Should always be a `Literal` if storage pointers were derived in the AST
+    // Should always evaluate to a literal if storage pointers were derived in the AST
     // prior to generating the IR bytes.
     tracing::info!(target: "codegen", "FOUND CONSTANT DEFINITION: {}", constant.name);
-    let push_bytes = match &constant.value {
-        ConstVal::Literal(l) => {
-            let hex_literal: String = bytes32_to_string(l, false);
-            format!("{:02x}{}", 95 + hex_literal.len() / 2, hex_literal)
-        }
-        ConstVal::FreeStoragePointer(fsp) => {
-            // If this is reached in codegen stage, the `derive_storage_pointers`
-            // method was not called on the AST.
-            tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{:?}\"", fsp);
-            return Err(CodegenError {
-                kind: CodegenErrorKind::StoragePointersNotDerived,
-                span: constant.span.clone(),
+    let push_bytes = Bytes::push_word(&constant.evaluate(&constants)?);
+
+    // Only literals are written with a width to preserve
+    let push_bytes = match preserve_width && matches!(constant.value, ConstVal::Literal(_)) {
+        true => preserve_literal_width(push_bytes, &constant.span),
+        false => push_bytes,
+    };
+
+    Ok(push_bytes)
+}
+
+/// Evaluates a builtin function argument to its bytes32 value at compile time.
+/// The argument is either a prefixed hex literal or the name of a constant.
+pub fn constant_arg_value(
+    arg: &Argument,
+    contract: &Contract,
+    ir_byte_span: AstSpan,
+) -> Result<[u8; 32], CodegenError> {
+    let name = arg.name.as_deref().unwrap_or_default();
+    if let Some(hex_literal) = name.strip_prefix("0x") {
+        return Ok(str_to_bytes32(hex_literal))
+    }
+
+    let constants = contract.constants.borrow();
+    match constants.iter().find(|const_def| const_def.name.eq(name)) {
+        Some(constant) => constant.evaluate(&constants),
+        None => {
+            tracing::error!(target: "codegen", "MISSING CONSTANT DEFINITION \"{}\"", name);
+            Err(CodegenError {
+                kind: CodegenErrorKind::MissingConstantDefinition(name.to_string()),
+                span: ir_byte_span,
                 token: None,
+                expansion: vec![],
             })
         }
-    };
+    }
+}
 
-    Ok(push_bytes)
+/// The hex digits a literal was written with, ie. `0001` for `0x0001`.
+///
+/// Reads the source of the span's last token, returning `None` if it isn't a hex literal.
+pub fn written_literal(span: &AstSpan) -> Option<&str> {
+    let span = span.0.last()?;
+    let source = span.file.as_ref()?.source.as_ref()?;
+    let digits = source.get(span.start..span.end)?;
+    let prefixed = span.start >= 2 && source.get(span.start - 2..span.start) == Some("0x");
+    if !prefixed || digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None
+    }
+    Some(digits)
+}
+
+/// The number of bytes a hex literal was written with, ie. 2 for `0x0001`.
+pub fn written_literal_width(span: &AstSpan) -> Option<usize> {
+    written_literal(span).map(|digits| digits.len().div_ceil(2))
+}
+
+/// The number of bytes a builtin argument was written with, whether a hex literal or a constant
+/// defined as one.
+pub fn arg_literal_width(arg: &Argument, contract: &Contract) -> Option<usize> {
+    let name = arg.name.as_deref()?;
+    if name.starts_with("0x") {
+        return written_literal_width(&arg.span)
+    }
+    let constants = contract.constants.borrow();
+    constants
+        .iter()
+        .find(|c| c.name == name && matches!(c.value, ConstVal::Literal(_)))
+        .and_then(|c| written_literal_width(&c.span))
+}
+
+/// Widens a literal's push bytes to the width it was written with.
+///
+/// Push bytes are never narrowed, and are returned as is if the span isn't a hex literal.
+pub fn preserve_literal_width(push_bytes: Bytes, span: &AstSpan) -> Bytes {
+    match written_literal_width(span) {
+        Some(width) if width <= 32 && width >= push_bytes.len() => {
+            let mut data = vec![0u8; width + 1 - push_bytes.len()];
+            data.extend_from_slice(&push_bytes.0[1..]);
+            Bytes::push(&data)
+        }
+        _ => push_bytes,
+    }
+}
+
+/// Pushes a zero literal with `PUSH0` instead of `PUSH1 0x00` if the evm version supports it,
+/// saving a byte and a unit of gas.
+pub fn push0_literal(push_bytes: Bytes, evm_version: EvmVersion) -> Bytes {
+    match push_bytes.0 == [Opcode::Push1.byte(), 0] && evm_version.supports(Opcode::Push0) {
+        true => Bytes::from(Opcode::Push0),
+        false => push_bytes,
+    }
 }
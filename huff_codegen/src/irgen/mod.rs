@@ -7,7 +7,10 @@ pub mod statements;
 /// Argument Call Module
 pub mod arg_calls;
 
+/// Stack Height Analysis Module
+pub mod stack;
+
 /// Prelude wraps common utilities.
 pub mod prelude {
-    pub use super::{arg_calls::*, constants::*, statements::*};
+    pub use super::{arg_calls::*, constants::*, stack::*, statements::*};
 }
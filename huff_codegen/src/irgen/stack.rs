@@ -0,0 +1,287 @@
+use huff_utils::prelude::{
+    stack_effect, BuiltinFunctionKind, CodegenError, CodegenErrorKind, Contract, MacroDefinition,
+    Opcode, Statement, StatementType,
+};
+use std::collections::HashMap;
+
+/// The simulated stack of a macro body
+#[derive(Debug, Default)]
+struct StackState {
+    /// The number of items on the stack, or `None` if it can't be known at this point
+    height: Option<usize>,
+    /// The heights labels are jumped to with
+    label_heights: HashMap<String, usize>,
+    /// The label pushed by the previous statement, if any
+    pushed_label: Option<String>,
+    /// Whether to track the deepest stack, including within invoked macros, instead of failing on
+    /// underflows
+    track_depth: bool,
+    /// The most items that were on the stack
+    max_height: usize,
+    /// The macros being simulated, so that recursive invocations aren't simulated again
+    invoking: Vec<String>,
+}
+
+/// Checks a macro body against its `takes` and `returns` annotations.
+///
+/// The body is simulated from `takes` items on the stack, failing if a statement would pop more
+/// items than are on it, or if the end of the body is reached with other than `returns` items.
+/// After halting instructions and unconditional jumps the height is unknown until a label jumped
+/// to from a known height, and statements are not checked while it is.
+///
+/// Invoked macros are checked against their own annotations before they're relied on, so a wrong
+/// annotation is reported against the macro declaring it rather than its callers.
+pub fn check_stack_heights(
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+) -> Result<(), CodegenError> {
+    check_macro(macro_def, contract, &mut vec![macro_def.name.clone()])
+}
+
+/// Checks a macro body, and the macros it invokes that aren't in `checked`
+fn check_macro(
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+    checked: &mut Vec<String>,
+) -> Result<(), CodegenError> {
+    match stack_height_of(macro_def, contract, checked)? {
+        Some(height) if height != macro_def.returns => {
+            tracing::error!(
+                target: "codegen",
+                "MACRO \"{}\" RETURNS {} STACK ITEMS, NOT {}",
+                macro_def.name,
+                height,
+                macro_def.returns
+            );
+            Err(CodegenError {
+                kind: CodegenErrorKind::StackHeightMismatch(
+                    macro_def.name.clone(),
+                    macro_def.returns,
+                    height,
+                ),
+                span: macro_def.span.clone(),
+                token: None,
+                expansion: vec![],
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the number of items a macro body leaves on the stack.
+///
+/// The body is simulated from `takes` items on the stack like in
+/// [check_stack_heights](check_stack_heights), failing on underflows. The height is `None` if the
+/// end of the body can't be reached, or follows verbatim bytecode with an unknown stack effect.
+pub fn stack_height(
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+) -> Result<Option<usize>, CodegenError> {
+    stack_height_of(macro_def, contract, &mut vec![macro_def.name.clone()])
+}
+
+/// Returns the number of items a macro body leaves on the stack, checking the macros it invokes
+/// that aren't in `checked`
+fn stack_height_of(
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+    checked: &mut Vec<String>,
+) -> Result<Option<usize>, CodegenError> {
+    let mut state = StackState { height: Some(macro_def.takes), ..Default::default() };
+    simulate(&macro_def.statements, macro_def, contract, &mut state, checked)?;
+    Ok(state.height)
+}
+
+/// Returns the most items a macro body has on the stack.
+///
+/// The body is simulated from `takes` items on the stack like in
+/// [check_stack_heights](check_stack_heights), including the bodies of invoked macros, but
+/// underflows empty the stack rather than failing, as `takes` annotations aren't enforced.
+pub fn max_stack_depth(macro_def: &MacroDefinition, contract: &Contract) -> usize {
+    max_stack_depth_of(macro_def, contract, std::slice::from_ref(&macro_def.name))
+}
+
+/// Returns the most items a macro body has on the stack, when invoked within the given macros
+fn max_stack_depth_of(
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+    invoking: &[String],
+) -> usize {
+    let mut state = StackState {
+        height: Some(macro_def.takes),
+        track_depth: true,
+        max_height: macro_def.takes,
+        invoking: invoking.to_vec(),
+        ..Default::default()
+    };
+    let _ = simulate(&macro_def.statements, macro_def, contract, &mut state, &mut vec![]);
+    state.max_height
+}
+
+/// Simulates the stack effects of statements in order
+fn simulate(
+    statements: &[Statement],
+    macro_def: &MacroDefinition,
+    contract: &Contract,
+    state: &mut StackState,
+    checked: &mut Vec<String>,
+) -> Result<(), CodegenError> {
+    for statement in statements {
+        let pushed_label = state.pushed_label.take();
+        let (pops, pushes) = match &statement.ty {
+            StatementType::Label(l) => {
+                // Labels are entered by falling through or by a jump
+                state.height = state.height.or_else(|| state.label_heights.get(&l.name).copied());
+                simulate(&l.inner, macro_def, contract, state, checked)?;
+                continue
+            }
+            StatementType::Opcode(o) => {
+                let opcode = u8::from_str_radix(&o.string(), 16).unwrap_or_default();
+                let (pops, pushes) = stack_effect(opcode);
+                if matches!(o, Opcode::Jump | Opcode::Jumpi) {
+                    if let (Some(label), Some(height)) = (pushed_label, state.height) {
+                        state.label_heights.entry(label).or_insert(height.saturating_sub(pops));
+                    }
+                }
+                pop(state, pops, macro_def, statement)?;
+                if is_halting(o) {
+                    state.height = None;
+                    continue
+                }
+                (0, pushes)
+            }
+            StatementType::LabelCall(l) => {
+                state.pushed_label = Some(l.clone());
+                (0, 1)
+            }
+            StatementType::Literal(_) |
+            StatementType::StringLiteral(_) |
+            StatementType::Constant(_) |
+            StatementType::ArgCall(_) => (0, 1),
+            StatementType::MacroInvocation(mi) => {
+                let invoked = contract.macros.iter().find(|m| m.name == mi.macro_name);
+                if state.track_depth {
+                    if let (Some(m), Some(height)) = (invoked, state.height) {
+                        if !state.invoking.contains(&m.name) {
+                            state.invoking.push(m.name.clone());
+                            let depth = max_stack_depth_of(m, contract, &state.invoking);
+                            state.invoking.pop();
+                            let depth = height.saturating_sub(m.takes) + depth;
+                            state.max_height = state.max_height.max(depth);
+                        }
+                    }
+                } else if let Some(m) = invoked.filter(|m| !checked.contains(&m.name)) {
+                    checked.push(m.name.clone());
+                    check_macro(m, contract, checked)?;
+                }
+                match invoked {
+                    // Execution doesn't continue past inlined macros that always halt
+                    Some(m) if !m.outlined && halts(&m.statements, contract, &mut vec![]) => {
+                        pop(state, m.takes, macro_def, statement)?;
+                        state.height = None;
+                        continue
+                    }
+                    Some(m) => (m.takes, m.returns),
+                    None => {
+                        state.height = None;
+                        continue
+                    }
+                }
+            }
+            // Debug macros must leave the stack unchanged, verbatim bytecode has an unknown effect,
+            // the dispatcher keeps the selector it compares, setting an immutable consumes its
+            // value, and other builtins push a word
+            StatementType::BuiltinFunctionCall(bf) => match bf.kind {
+                BuiltinFunctionKind::Debug => {
+                    let name = bf.args.first().and_then(|a| a.name.as_deref()).unwrap_or_default();
+                    match contract.macros.iter().find(|m| m.name == name) {
+                        Some(m) => (m.takes, m.takes),
+                        None => (0, 0),
+                    }
+                }
+                BuiltinFunctionKind::Verbatim => {
+                    state.height = None;
+                    continue
+                }
+                BuiltinFunctionKind::Dispatch => (1, 1),
+                BuiltinFunctionKind::SetImmutable => (1, 0),
+                _ => (0, 1),
+            },
+            StatementType::Code(_) => {
+                state.height = None;
+                continue
+            }
+        };
+        pop(state, pops, macro_def, statement)?;
+        state.height = state.height.map(|h| h + pushes);
+        state.max_height = state.max_height.max(state.height.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Returns whether the end of a macro body is unreachable, ie. because it ends by returning or
+/// reverting, or by invoking a macro that does
+fn halts(statements: &[Statement], contract: &Contract, visited: &mut Vec<String>) -> bool {
+    let mut halted = false;
+    for statement in statements {
+        match &statement.ty {
+            StatementType::Label(l) => halted = halts(&l.inner, contract, visited),
+            StatementType::Opcode(o) => halted |= is_halting(o),
+            StatementType::MacroInvocation(mi) if !visited.contains(&mi.macro_name) => {
+                if let Some(m) = contract.macros.iter().find(|m| m.name == mi.macro_name) {
+                    visited.push(m.name.clone());
+                    halted |= !m.outlined && halts(&m.statements, contract, visited);
+                    visited.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+    halted
+}
+
+/// Whether execution never continues to the next instruction
+fn is_halting(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Stop |
+            Opcode::Jump |
+            Opcode::Return |
+            Opcode::Revert |
+            Opcode::Invalid |
+            Opcode::Selfdestruct
+    )
+}
+
+/// Pops items off the simulated stack, failing if there aren't enough
+fn pop(
+    state: &mut StackState,
+    pops: usize,
+    macro_def: &MacroDefinition,
+    statement: &Statement,
+) -> Result<(), CodegenError> {
+    let height = match state.height {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+    if height < pops && state.track_depth {
+        state.height = Some(0);
+        return Ok(())
+    }
+    if height < pops {
+        tracing::error!(
+            target: "codegen",
+            "STACK UNDERFLOW IN MACRO \"{}\": {}",
+            macro_def.name,
+            statement.ty
+        );
+        return Err(CodegenError {
+            kind: CodegenErrorKind::StackUnderflow(macro_def.name.clone()),
+            span: statement.span.clone(),
+            token: None,
+            expansion: vec![],
+        })
+    }
+    state.height = Some(height - pops);
+    Ok(())
+}
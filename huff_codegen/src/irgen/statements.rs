@@ -1,7 +1,148 @@
+use ethers_core::{
+    types::{Address, U256},
+    utils::{get_contract_address, get_create2_address_from_hash},
+};
 use huff_utils::prelude::*;
 use tiny_keccak::{Hasher, Keccak};
 
-use crate::Codegen;
+use crate::{
+    config::{CodegenConfig, IMMUTABLES_OFFSET},
+    irgen::prelude::{arg_literal_width, constant_arg_value, written_literal},
+    Codegen,
+};
+
+/// Adds the macro sizes of a recursed macro to the current sizes
+pub fn merge_macro_sizes(macro_sizes: &mut MacroSizes, other: MacroSizes) {
+    for (name, size) in other {
+        let entry = macro_sizes.entry(name).or_default();
+        entry.invocations += size.invocations;
+        entry.size += size.size;
+    }
+}
+
+/// Adds the labels of a recursed macro invocation to the current labels.
+///
+/// Each invocation resolves its own jumps against its own labels first, so its labels only need
+/// to be visible here to be referenced across macros. Labels defined by the current macro shadow
+/// the invocation's, and labels defined by several invocations are ambiguous, so they are only
+/// kept under names mangled with their offset, ie. `loop@42`, that jumps can't reference.
+pub fn merge_label_indices(
+    label_indices: &mut LabelIndices,
+    statements: &[Statement],
+    other: LabelIndices,
+) {
+    for (name, index) in other {
+        if name.contains('@') {
+            label_indices.insert(name, index);
+            continue
+        }
+        if defines_label(statements, &name) {
+            continue
+        }
+        let mangled = format!("{}@", name);
+        let ambiguous = !ambiguous_label_offsets(label_indices, &name).is_empty();
+        match label_indices.remove(&name) {
+            Some(existing) => {
+                tracing::warn!(target: "codegen", "AMBIGUOUS LABEL \"{}\"", name);
+                label_indices.insert(format!("{}{}", mangled, existing), existing);
+                label_indices.insert(format!("{}{}", mangled, index), index);
+            }
+            None if ambiguous => {
+                label_indices.insert(format!("{}{}", mangled, index), index);
+            }
+            None => {
+                label_indices.insert(name, index);
+            }
+        }
+    }
+}
+
+/// Returns the ascending offsets of the definitions of a label that is ambiguous, and so only
+/// kept under mangled names by [merge_label_indices](merge_label_indices)
+pub fn ambiguous_label_offsets(label_indices: &LabelIndices, name: &str) -> Vec<usize> {
+    let mangled = format!("{}@", name);
+    let mut offsets = label_indices
+        .range(mangled.clone()..)
+        .take_while(|(k, _)| k.starts_with(&mangled))
+        .map(|(_, index)| *index)
+        .collect::<Vec<usize>>();
+    offsets.sort_unstable();
+    offsets
+}
+
+/// Returns whether a label is defined in a macro body
+fn defines_label(statements: &[Statement], name: &str) -> bool {
+    statements.iter().any(|s| match &s.ty {
+        StatementType::Label(l) => l.name == name || defines_label(&l.inner, name),
+        _ => false,
+    })
+}
+
+/// Returns the name passed as the first argument of a builtin function call
+fn builtin_name_arg<'a>(
+    bf: &'a BuiltinFunctionCall,
+    builtin: &str,
+) -> Result<&'a str, CodegenError> {
+    bf.args.first().and_then(|a| a.name.as_deref()).ok_or_else(|| {
+        tracing::error!(target: "codegen", "MISSING NAME ARGUMENT PASSED TO {}", builtin);
+        CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(format!(
+                "Missing name argument passed to {}",
+                builtin
+            )),
+            span: bf.span.clone(),
+            token: None,
+            expansion: vec![],
+        }
+    })
+}
+
+/// Returns the chain of macros through which expanding statements requires expanding the named
+/// macro, through inlined macro invocations, `__codesize` calls, or `__DEBUG` calls
+fn expansion_path(
+    statements: &[Statement],
+    contract: &Contract,
+    name: &str,
+    visited: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    statements.iter().find_map(|s| {
+        let invoked = match &s.ty {
+            StatementType::Label(l) => return expansion_path(&l.inner, contract, name, visited),
+            // Outlined macros are jumped to rather than expanded
+            StatementType::MacroInvocation(mi) => match contract.find_macro_by_name(&mi.macro_name)
+            {
+                Some(m) if !m.outlined => m,
+                _ => return None,
+            },
+            StatementType::BuiltinFunctionCall(bf)
+                if matches!(bf.kind, BuiltinFunctionKind::Codesize | BuiltinFunctionKind::Debug) =>
+            {
+                match bf.args.first().and_then(|a| a.name.as_deref()) {
+                    Some(n) => contract.find_macro_by_name(n)?,
+                    None => return None,
+                }
+            }
+            _ => return None,
+        };
+        if invoked.name == name {
+            return Some(vec![invoked.name])
+        }
+        if visited.contains(&invoked.name) {
+            return None
+        }
+        visited.push(invoked.name.clone());
+        let mut path = expansion_path(&invoked.statements, contract, name, visited)?;
+        path.insert(0, invoked.name);
+        Some(path)
+    })
+}
+
+/// Returns the cycle of macros expanded since the named macro's expansion in the scope, back to
+/// the named macro, ie. `["A", "B", "A"]`
+fn scope_cycle(scope: &[MacroDefinition], name: &str) -> Vec<String> {
+    let start = scope.iter().position(|m| m.name == name).unwrap_or(scope.len());
+    scope[start..].iter().map(|m| m.name.clone()).chain(std::iter::once(name.to_string())).collect()
+}
 
 /// Generates the respective Bytecode for a given Statement
 #[allow(clippy::too_many_arguments)]
@@ -15,8 +156,12 @@ pub fn statement_gen(
     jump_table: &mut JumpTable,
     label_indices: &mut LabelIndices,
     table_instances: &mut Jumps,
+    immutable_references: &mut Jumps,
     utilized_tables: &mut Vec<TableDefinition>,
+    macro_sizes: &mut MacroSizes,
+    spans: &mut SourceSpans,
     starting_offset: usize,
+    config: &CodegenConfig,
 ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
     let mut bytes = vec![];
 
@@ -37,21 +182,41 @@ pub fn statement_gen(
                     kind: CodegenErrorKind::InvalidMacroInvocation(mi.macro_name.clone()),
                     span: mi.span.clone(),
                     token: None,
+                    expansion: vec![],
                 })
             };
 
             tracing::info!(target: "codegen", "FOUND INNER MACRO: {}", ir_macro.name);
 
+            // Arguments are substituted by position, so every parameter needs an argument
+            if mi.args.len() != ir_macro.parameters.len() {
+                tracing::error!(
+                    target: "codegen",
+                    "MACRO \"{}\" INVOKED WITH {} ARGS, EXPECTED {}",
+                    ir_macro.name,
+                    mi.args.len(),
+                    ir_macro.parameters.len()
+                );
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::InvalidMacroArgCount(
+                        ir_macro.name.clone(),
+                        ir_macro.parameters.len(),
+                        mi.args.len(),
+                    ),
+                    span: mi.span.clone(),
+                    token: None,
+                    expansion: vec![],
+                })
+            }
+
             // If invoked macro is a function (outlined), insert a jump to the function's code and a
             // jumpdest to return to. If it is inlined, insert the macro's code at the
             // current offset.
             if ir_macro.outlined {
                 // Get necessary swap ops to reorder stack
                 // PC of the return jumpdest should be below the function's stack inputs
-                let stack_swaps = (0..ir_macro.takes)
-                    .rev()
-                    .map(|i| format!("{:02x}", 0x90 + i))
-                    .collect::<Vec<_>>();
+                let stack_swaps =
+                    (0..ir_macro.takes).rev().map(|i| 0x90 + i as u8).collect::<Vec<_>>();
 
                 // Insert a jump to the outlined macro's code
                 jump_table.insert(
@@ -65,23 +230,40 @@ pub fn statement_gen(
 
                 // Store return JUMPDEST PC on the stack and re-order the stack so that
                 // the return JUMPDEST PC is below the function's stack inputs
-                bytes.push((
-                    *offset,
-                    Bytes(format!(
-                        "{}{:04x}{}",
-                        Opcode::Push2,
-                        *offset + stack_swaps.len() + 7,
-                        stack_swaps.join("")
-                    )),
-                ));
+                let return_pc = Codegen::push2_destination(
+                    &format!("return from {}", ir_macro.name),
+                    *offset + stack_swaps.len() + 7,
+                    &s.span,
+                )?;
+                let mut push_return = Bytes::push(&return_pc);
+                push_return.0.extend(stack_swaps.iter());
+                bytes.push((*offset, push_return));
                 // Insert jump to outlined macro + jumpdest to return to
+                let mut jump = Bytes::push2_placeholder();
+                jump.0.extend([Opcode::Jump.byte(), Opcode::Jumpdest.byte()]);
                 bytes.push((
                     *offset + stack_swaps.len() + 3, // PUSH2 + 2 bytes + stack_swaps.len()
-                    Bytes(format!("{}xxxx{}{}", Opcode::Push2, Opcode::Jump, Opcode::Jumpdest)),
+                    jump,
                 ));
                 // PUSH2 + 2 bytes + stack_swaps.len() + PUSH2 + 2 bytes + JUMP + JUMPDEST
                 *offset += stack_swaps.len() + 8;
             } else {
+                // An inlined macro that is already being expanded would never finish expanding
+                if scope.iter().any(|m| m.name == ir_macro.name) {
+                    let cycle = scope_cycle(scope, &ir_macro.name);
+                    tracing::error!(
+                        target: "codegen",
+                        "CIRCULAR MACRO INVOCATION \"{}\"",
+                        cycle.join(" -> ")
+                    );
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::CircularMacroInvocation(cycle),
+                        span: mi.span.clone(),
+                        token: None,
+                        expansion: vec![],
+                    })
+                }
+
                 // Recurse into macro invocation
                 scope.push(ir_macro.clone());
                 mis.push((*offset, mi.clone()));
@@ -92,6 +274,7 @@ pub fn statement_gen(
                     scope,
                     *offset,
                     mis,
+                    config,
                 ) {
                     Ok(r) => r,
                     Err(e) => {
@@ -100,7 +283,7 @@ pub fn statement_gen(
                             "FAILED TO RECURSE INTO MACRO \"{}\"",
                             ir_macro.name
                         );
-                        return Err(e)
+                        return Err(e.expanded_from(mi.span.clone()))
                     }
                 };
 
@@ -118,11 +301,18 @@ pub fn statement_gen(
                     jump_table.insert(new_index, new_jumps);
                 }
                 table_instances.extend(res.table_instances);
-                label_indices.extend(res.label_indices);
+                immutable_references.extend(res.immutable_references);
+                merge_label_indices(label_indices, &macro_def.statements, res.label_indices);
                 utilized_tables.extend(res.utilized_tables);
+                merge_macro_sizes(macro_sizes, res.macro_sizes);
+                spans.extend(res.spans);
 
                 // Increase offset by byte length of recursed macro
-                *offset += res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+                let macro_code_len = res.bytes.iter().map(|(_, b)| b.len()).sum::<usize>();
+                let macro_size = macro_sizes.entry(ir_macro.name.clone()).or_default();
+                macro_size.invocations += 1;
+                macro_size.size += macro_code_len;
+                *offset += macro_code_len;
                 // Add the macro's bytecode to the final result
                 bytes = [bytes, res.bytes].concat()
             }
@@ -131,7 +321,7 @@ pub fn statement_gen(
             // Add JUMPDEST opcode to final result and add to label_indices
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT LABEL: {:?}", label.name);
             label_indices.insert(label.name.clone(), *offset);
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
+            bytes.push((*offset, Bytes::from(Opcode::Jumpdest)));
             *offset += 1;
         }
         StatementType::LabelCall(label) => {
@@ -142,40 +332,76 @@ pub fn statement_gen(
                 *offset,
                 vec![Jump { label: label.to_string(), bytecode_index: 0, span: s.span.clone() }],
             );
-            bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+            bytes.push((*offset, Bytes::push2_placeholder()));
             *offset += 3;
         }
+        StatementType::StringLiteral(string) => {
+            // Strings are pushed left aligned like `__RIGHTPAD`, so must fit in a word
+            if string.len() > 32 {
+                tracing::error!(target: "codegen", "STRING LITERAL OF {} BYTES TOO LONG TO PUSH", string.len());
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::StringLiteralTooLong(string.len()),
+                    span: s.span.clone(),
+                    token: None,
+                    expansion: vec![],
+                })
+            }
+            let mut padded = [0u8; 32];
+            padded[..string.len()].copy_from_slice(string.as_bytes());
+
+            bytes.push((*offset, Bytes::push(&padded)));
+            *offset += 33;
+        }
         StatementType::BuiltinFunctionCall(bf) => {
             // Generate code for a `BuiltinFunctionCall`
             // __codesize, __tablesize, or __tablestart
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT BUILTIN FUNCTION CALL: {:?}", bf);
             match bf.kind {
                 BuiltinFunctionKind::Codesize => {
-                    let ir_macro = if let Some(m) =
-                        contract.find_macro_by_name(bf.args[0].name.as_ref().unwrap())
-                    {
+                    let name = builtin_name_arg(bf, "__codesize")?;
+                    let ir_macro = if let Some(m) = contract.find_macro_by_name(name) {
                         m
                     } else {
                         tracing::error!(
                             target: "codegen",
                             "MISSING MACRO PASSED TO __codesize \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
+                            name
                         );
                         return Err(CodegenError {
-                            kind: CodegenErrorKind::MissingMacroDefinition(
-                                bf.args[0].name.as_ref().unwrap().to_string(), /* yuck */
-                            ),
+                            kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
                         })
                     };
 
+                    // The size of a macro being expanded, or expanding to itself, depends on itself
+                    let cycle = match scope.iter().any(|m| m.name == ir_macro.name) {
+                        true => Some(scope_cycle(scope, &ir_macro.name)),
+                        false => expansion_path(&ir_macro.statements, contract, name, &mut vec![])
+                            .map(|path| [vec![name.to_string()], path].concat()),
+                    };
+                    if let Some(cycle) = cycle {
+                        tracing::error!(
+                            target: "codegen",
+                            "CIRCULAR MACRO PASSED TO __codesize \"{}\"",
+                            cycle.join(" -> ")
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::CircularMacroInvocation(cycle),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
                     let res: BytecodeRes = match Codegen::macro_to_bytecode(
                         ir_macro.clone(),
                         contract,
                         scope,
                         *offset,
                         mis,
+                        config,
                     ) {
                         Ok(r) => r,
                         Err(e) => {
@@ -184,55 +410,48 @@ pub fn statement_gen(
                                 "FAILED TO RECURSE INTO MACRO \"{}\"",
                                 ir_macro.name
                             );
-                            return Err(e)
+                            return Err(e.expanded_from(bf.span.clone()))
                         }
                     };
 
-                    let size = format_even_bytes(format!(
-                        "{:02x}",
-                        (res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2)
-                    ));
-                    let push_bytes = format!("{:02x}{}", 95 + size.len() / 2, size);
+                    let push_bytes = Bytes::push_usize(res.code_len());
 
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
                 }
                 BuiltinFunctionKind::Tablesize => {
-                    let ir_table = if let Some(t) =
-                        contract.find_table_by_name(bf.args[0].name.as_ref().unwrap())
-                    {
+                    let name = builtin_name_arg(bf, "__tablesize")?;
+                    let ir_table = if let Some(t) = contract.find_table_by_name(name) {
                         t
                     } else {
                         tracing::error!(
                             target: "codegen",
                             "MISSING TABLE PASSED TO __tablesize \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
+                            name
                         );
                         return Err(CodegenError {
-                            kind: CodegenErrorKind::InvalidMacroInvocation(
-                                bf.args[0].name.as_ref().unwrap().to_string(), /* yuck */
-                            ),
+                            kind: CodegenErrorKind::InvalidMacroInvocation(name.to_string()),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
                         })
                     };
 
-                    let size = bytes32_to_string(&ir_table.size, false);
-                    let push_bytes = format!("{:02x}{}", 95 + size.len() / 2, size);
+                    let push_bytes = Bytes::push_word(&ir_table.size);
 
                     if !utilized_tables.contains(&ir_table) {
                         utilized_tables.push(ir_table);
                     }
 
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
                 }
                 BuiltinFunctionKind::Tablestart => {
                     // Make sure the table exists
-                    if let Some(t) = contract.find_table_by_name(bf.args[0].name.as_ref().unwrap())
-                    {
+                    let name = builtin_name_arg(bf, "__tablestart")?;
+                    if let Some(t) = contract.find_table_by_name(name) {
                         table_instances.push(Jump {
-                            label: bf.args[0].name.as_ref().unwrap().to_owned(),
+                            label: name.to_owned(),
                             bytecode_index: *offset,
                             span: bf.span.clone(),
                         });
@@ -240,20 +459,19 @@ pub fn statement_gen(
                             utilized_tables.push(t);
                         }
 
-                        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+                        bytes.push((*offset, Bytes::push2_placeholder()));
                         *offset += 3;
                     } else {
                         tracing::error!(
                             target: "codegen",
                             "MISSING TABLE PASSED TO __tablestart \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
+                            name
                         );
                         return Err(CodegenError {
-                            kind: CodegenErrorKind::InvalidMacroInvocation(
-                                bf.args[0].name.as_ref().unwrap().to_string(),
-                            ),
+                            kind: CodegenErrorKind::InvalidMacroInvocation(name.to_string()),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
                         })
                     }
                 }
@@ -273,41 +491,24 @@ pub fn statement_gen(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
                         })
                     }
 
-                    if let Some(func) = contract
-                        .functions
-                        .iter()
-                        .find(|f| bf.args[0].name.as_ref().unwrap().eq(&f.name))
-                    {
-                        let sig = hex::encode(func.signature);
-                        let push_bytes = format!("{:02x}{}", 95 + sig.len() / 2, sig);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else if let Some(s) = &bf.args[0].name {
+                    let name = builtin_name_arg(bf, "__FUNC_SIG")?;
+                    if let Some(func) = contract.functions.iter().find(|f| name.eq(&f.name)) {
+                        let push_bytes = Bytes::push(&func.signature);
+                        *offset += push_bytes.len();
+                        bytes.push((starting_offset, push_bytes));
+                    } else {
                         let mut signature = [0u8; 4]; // Only keep first 4 bytes
                         let mut hasher = Keccak::v256();
-                        hasher.update(s.as_bytes());
+                        hasher.update(name.as_bytes());
                         hasher.finalize(&mut signature);
 
-                        let sig = hex::encode(signature);
-                        let push_bytes = format!("{:02x}{}", 95 + sig.len() / 2, sig);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else {
-                        tracing::error!(
-                            target: "codegen",
-                            "MISSING FUNCTION INTERFACE PASSED TO __SIG: \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
-                        );
-                        return Err(CodegenError {
-                            kind: CodegenErrorKind::MissingFunctionInterface(
-                                bf.args[0].name.as_ref().unwrap().to_string(),
-                            ),
-                            span: bf.span.clone(),
-                            token: None,
-                        })
+                        let push_bytes = Bytes::push(&signature);
+                        *offset += push_bytes.len();
+                        bytes.push((starting_offset, push_bytes));
                     }
                 }
                 BuiltinFunctionKind::EventHash => {
@@ -326,42 +527,577 @@ pub fn statement_gen(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
                         })
                     }
 
-                    if let Some(event) = contract
-                        .events
-                        .iter()
-                        .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
-                    {
-                        let hash = bytes32_to_string(&event.hash, false);
-                        let push_bytes = format!("{:02x}{}", 95 + hash.len() / 2, hash);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else if let Some(s) = &bf.args[0].name {
+                    let name = builtin_name_arg(bf, "__EVENT_HASH")?;
+                    if let Some(event) = contract.events.iter().find(|e| name.eq(&e.name)) {
+                        let push_bytes = Bytes::push_word(&event.hash);
+                        *offset += push_bytes.len();
+                        bytes.push((starting_offset, push_bytes));
+                    } else if !name.contains('(') {
+                        // Bare names must refer to a defined event, only signatures are hashed
+                        tracing::error!(
+                            target: "codegen",
+                            "MISSING EVENT PASSED TO __EVENT_HASH \"{}\"",
+                            name
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::MissingEventInterface(name.to_string()),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    } else {
                         let mut hash = [0u8; 32];
                         let mut hasher = Keccak::v256();
-                        hasher.update(s.as_bytes());
+                        hasher.update(name.as_bytes());
                         hasher.finalize(&mut hash);
 
-                        let hash = hex::encode(hash);
-                        let push_bytes = format!("{:02x}{}", 95 + hash.len() / 2, hash);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push_bytes = Bytes::push(&hash);
+                        *offset += push_bytes.len();
+                        bytes.push((starting_offset, push_bytes));
+                    }
+                }
+                BuiltinFunctionKind::Error => {
+                    let name = builtin_name_arg(bf, "__ERROR")?;
+                    let error = if let Some(e) = contract.errors.iter().find(|e| name.eq(&e.name)) {
+                        e
                     } else {
                         tracing::error!(
                             target: "codegen",
-                            "MISSING EVENT INTERFACE PASSED TO __EVENT_HASH: \"{}\"",
-                            bf.args[0].name.as_ref().unwrap()
+                            "MISSING ERROR PASSED TO __ERROR \"{}\"",
+                            name
                         );
                         return Err(CodegenError {
-                            kind: CodegenErrorKind::MissingEventInterface(
-                                bf.args[0].name.as_ref().unwrap().to_string(),
-                            ),
+                            kind: CodegenErrorKind::MissingErrorDefinition(name.to_string()),
                             span: bf.span.clone(),
                             token: None,
+                            expansion: vec![],
+                        })
+                    };
+
+                    // The selector is pushed in the high bytes, ready to be stored at offset 0
+                    let mut word = [0u8; 32];
+                    word[..4].copy_from_slice(&error.selector);
+                    let push_bytes = Bytes::push(&word);
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
+                }
+                BuiltinFunctionKind::CreateAddress | BuiltinFunctionKind::Create2Address => {
+                    let (name, arg_count) = match bf.kind {
+                        BuiltinFunctionKind::CreateAddress => ("__CREATE_ADDRESS", 2),
+                        _ => ("__CREATE2_ADDRESS", 3),
+                    };
+                    if bf.args.len() != arg_count {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to {}, should be {}: {}",
+                            name,
+                            arg_count,
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to {}, should be {}: {}",
+                                name,
+                                arg_count,
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
                         })
                     }
+
+                    // Every input must be known at compile time
+                    let values = bf
+                        .args
+                        .iter()
+                        .map(|a| constant_arg_value(a, contract, bf.span.clone()))
+                        .collect::<Result<Vec<[u8; 32]>, CodegenError>>()?;
+
+                    if values[0][..12].iter().any(|b| *b != 0) {
+                        tracing::error!(target: "codegen", "INVALID DEPLOYER PASSED TO {}", name);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Deployer passed to {} is larger than 20 bytes: {}",
+                                name,
+                                bytes32_to_string(&values[0], true)
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+                    let deployer = Address::from_slice(&values[0][12..]);
+
+                    let address = match bf.kind {
+                        BuiltinFunctionKind::CreateAddress => {
+                            get_contract_address(deployer, U256::from_big_endian(&values[1]))
+                        }
+                        _ => get_create2_address_from_hash(
+                            deployer,
+                            values[1].to_vec(),
+                            values[2].to_vec(),
+                        ),
+                    };
+
+                    let push_bytes = Bytes::push(address.as_bytes());
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
+                }
+                BuiltinFunctionKind::Mask | BuiltinFunctionKind::ShlConst => {
+                    let name = match bf.kind {
+                        BuiltinFunctionKind::Mask => "__MASK",
+                        _ => "__SHL_CONST",
+                    };
+                    if bf.args.len() != 2 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to {}, should be 2: {}",
+                            name,
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to {}, should be 2: {}",
+                                name,
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    // Every input must be known at compile time
+                    let values = bf
+                        .args
+                        .iter()
+                        .map(|a| {
+                            constant_arg_value(a, contract, bf.span.clone())
+                                .map(|v| U256::from_big_endian(&v))
+                        })
+                        .collect::<Result<Vec<U256>, CodegenError>>()?;
+
+                    let value = match bf.kind {
+                        BuiltinFunctionKind::Mask => {
+                            let (bits, shift) = (values[0], values[1]);
+                            if bits.is_zero() ||
+                                !matches!(bits.checked_add(shift), Some(w) if w <= U256::from(256))
+                            {
+                                tracing::error!(target: "codegen", "INVALID MASK PASSED TO __MASK: ({}, {})", bits, shift);
+                                return Err(CodegenError {
+                                    kind: CodegenErrorKind::InvalidArguments(format!(
+                                        "Mask of {} bits at offset {} does not fit in 256 bits",
+                                        bits, shift
+                                    )),
+                                    span: bf.span.clone(),
+                                    token: None,
+                                    expansion: vec![],
+                                })
+                            }
+                            let mask = match bits.as_usize() {
+                                256 => U256::MAX,
+                                b => (U256::one() << b) - 1,
+                            };
+                            mask << shift.as_usize()
+                        }
+                        // Bits shifted past 256 are dropped, as with the SHL opcode
+                        _ => match values[1] < U256::from(256) {
+                            true => values[0] << values[1].as_usize(),
+                            false => U256::zero(),
+                        },
+                    };
+
+                    let mut value_bytes = [0u8; 32];
+                    value.to_big_endian(&mut value_bytes);
+                    let push_bytes = Bytes::push_word(&value_bytes);
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
+                }
+                BuiltinFunctionKind::RightPad => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __RIGHTPAD, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __RIGHTPAD, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+                    let value = constant_arg_value(&bf.args[0], contract, bf.span.clone())?;
+
+                    // Leading zero bytes written in the literal are kept, ie. `0x00ff` pads to
+                    // `0x00ff00..00`
+                    let significant = 32 - value.iter().position(|b| *b != 0).unwrap_or(32);
+                    let width = arg_literal_width(&bf.args[0], contract)
+                        .map_or(significant, |w| w.clamp(significant, 32));
+                    let mut padded = [0u8; 32];
+                    padded[..width].copy_from_slice(&value[32 - width..]);
+
+                    let push_bytes = Bytes::push(&padded);
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
+                }
+                BuiltinFunctionKind::Verbatim => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    // Literals are emitted as written, and longer bytecode is passed as a string
+                    let arg = &bf.args[0];
+                    let code = written_literal(&arg.span)
+                        .or_else(|| arg.name.as_deref().map(|s| s.trim_start_matches("0x")))
+                        .unwrap_or_default()
+                        .to_lowercase();
+                    let verbatim = match Bytes::from_hex(&code) {
+                        Ok(b) if !b.is_empty() => b,
+                        _ => {
+                            tracing::error!(target: "codegen", "INVALID __VERBATIM BYTECODE: \"{}\"", code);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "Invalid bytecode passed to __VERBATIM: \"{}\"",
+                                    code
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                                expansion: vec![],
+                            })
+                        }
+                    };
+
+                    *offset += verbatim.len();
+                    bytes.push((starting_offset, verbatim));
+                }
+                BuiltinFunctionKind::Dispatch => {
+                    if !bf.args.is_empty() {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __DISPATCH, should be 0: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __DISPATCH, should be 0: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    // Labels are named after functions, so overloads can't be told apart
+                    let overloaded = contract.functions.iter().enumerate().find(|(i, f)| {
+                        contract.functions[..*i].iter().any(|other| other.name == f.name)
+                    });
+                    if let Some((_, func)) = overloaded {
+                        tracing::error!(
+                            target: "codegen",
+                            "OVERLOADED FUNCTION \"{}\" CAN'T BE DISPATCHED",
+                            func.name
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::OverloadedDispatch(func.name.clone()),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    // Compare the selector on the stack against each function's, jumping to the
+                    // label named after the function on a match and falling through otherwise
+                    for func in &contract.functions {
+                        let mut compare = Bytes::from(Opcode::Dup1);
+                        compare.0.extend(Bytes::push(&func.signature).0);
+                        compare.0.push(Opcode::Eq.byte());
+                        bytes.push((*offset, compare));
+                        *offset += 7;
+
+                        jump_table.insert(
+                            *offset,
+                            vec![Jump {
+                                label: func.name.clone(),
+                                bytecode_index: 0,
+                                span: bf.span.clone(),
+                            }],
+                        );
+                        let mut jump = Bytes::push2_placeholder();
+                        jump.0.push(Opcode::Jumpi.byte());
+                        bytes.push((*offset, jump));
+                        *offset += 4;
+                    }
+                }
+                BuiltinFunctionKind::ConstructorArg => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to __CONSTRUCTOR_ARG, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __CONSTRUCTOR_ARG, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+                    let index = U256::from_big_endian(&constant_arg_value(
+                        &bf.args[0],
+                        contract,
+                        bf.span.clone(),
+                    )?);
+
+                    // The arguments are abi encoded after the initcode, so when each is a single
+                    // word it sits a fixed distance back from the end of the code. Dynamic types
+                    // and multi word types would move the arguments after them by their length.
+                    let inputs = Codegen::constructor_types(contract).unwrap_or_default();
+                    if let Some(ty) = inputs.iter().find(|ty| ty.is_memory_type()) {
+                        tracing::error!(target: "codegen", "UNSUPPORTED CONSTRUCTOR ARGUMENT TYPE {}", ty);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Constructor argument type {} is not supported by __CONSTRUCTOR_ARG, which only reads single word types",
+                                ty
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+                    if index >= U256::from(inputs.len()) {
+                        tracing::error!(
+                            target: "codegen",
+                            "CONSTRUCTOR ARGUMENT {} OUT OF BOUNDS FOR {} INPUTS",
+                            index,
+                            inputs.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Constructor argument {} is out of bounds, the CONSTRUCTOR function takes {}",
+                                index,
+                                inputs.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+                    let distance = (inputs.len() - index.as_usize()) * 32;
+
+                    // Copy the argument's head into scratch space and load it
+                    let mut load = Bytes::push(&[0x20]);
+                    load.0.extend(Bytes::push_usize(distance).0);
+                    load.0.extend([Opcode::Codesize.byte(), Opcode::Sub.byte()]);
+                    load.0.extend(Bytes::push(&[0x00]).0);
+                    load.0.push(Opcode::Codecopy.byte());
+                    load.0.extend(Bytes::push(&[0x00]).0);
+                    load.0.push(Opcode::Mload.byte());
+                    *offset += load.len();
+                    bytes.push((starting_offset, load));
+                }
+                BuiltinFunctionKind::Immutable | BuiltinFunctionKind::SetImmutable => {
+                    let reads = bf.kind == BuiltinFunctionKind::Immutable;
+                    let builtin = if reads { "__IMMUTABLE" } else { "__SET_IMMUTABLE" };
+                    let name = builtin_name_arg(bf, builtin)?;
+                    let index = if let Some(i) = contract.find_immutable_index(name) {
+                        i
+                    } else {
+                        tracing::error!(
+                            target: "codegen",
+                            "MISSING IMMUTABLE PASSED TO {} \"{}\"",
+                            builtin,
+                            name
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::MissingImmutableDefinition(name.to_string()),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    };
+
+                    let push = if reads {
+                        // The constructor runs before its immutables are patched in, so would
+                        // only ever read the zeroed placeholder
+                        if scope.first().is_some_and(|m| m.name == "CONSTRUCTOR") {
+                            tracing::error!(
+                                target: "codegen",
+                                "IMMUTABLE \"{}\" READ IN CONSTRUCTOR",
+                                name
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::ImmutableReadInConstructor(
+                                    name.to_string(),
+                                ),
+                                span: bf.span.clone(),
+                                token: None,
+                                expansion: vec![],
+                            })
+                        }
+                        // Reserve a placeholder word for the constructor to fill in
+                        immutable_references.push(Jump {
+                            label: name.to_string(),
+                            bytecode_index: *offset + 1,
+                            span: bf.span.clone(),
+                        });
+                        Bytes::push(&[0u8; 32])
+                    } else {
+                        // The constructor stages each immutable in its own memory word, past the
+                        // scratch space
+                        let mut push = Bytes::push_usize(IMMUTABLES_OFFSET + index * 32);
+                        push.0.push(Opcode::Mstore.byte());
+                        push
+                    };
+                    *offset += push.len();
+                    bytes.push((starting_offset, push));
+                }
+                BuiltinFunctionKind::Debug => {
+                    let name = builtin_name_arg(bf, "__DEBUG")?;
+                    let ir_macro = if let Some(m) = contract.find_macro_by_name(name) {
+                        m
+                    } else {
+                        tracing::error!(
+                            target: "codegen",
+                            "MISSING MACRO PASSED TO __DEBUG \"{}\"",
+                            name
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    };
+
+                    // Stripping the macro from release builds must leave the same stack behind
+                    if ir_macro.takes != ir_macro.returns {
+                        tracing::error!(
+                            target: "codegen",
+                            "MACRO PASSED TO __DEBUG \"{}\" ISN'T STACK NEUTRAL",
+                            name
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Macro \"{}\" passed to __DEBUG must leave the stack unchanged",
+                                name
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    if config.debug {
+                        let invocation = Statement {
+                            ty: StatementType::MacroInvocation(MacroInvocation {
+                                macro_name: name.to_string(),
+                                args: vec![],
+                                span: bf.span.clone(),
+                            }),
+                            span: s.span.clone(),
+                        };
+                        bytes.extend(statement_gen(
+                            &invocation,
+                            contract,
+                            macro_def,
+                            scope,
+                            offset,
+                            mis,
+                            jump_table,
+                            label_indices,
+                            table_instances,
+                            immutable_references,
+                            utilized_tables,
+                            macro_sizes,
+                            spans,
+                            starting_offset,
+                            config,
+                        )?);
+                    }
+                }
+                BuiltinFunctionKind::Custom(ref name) => {
+                    let builtin = match config.builtins.get(name) {
+                        Some(b) => b,
+                        None => {
+                            tracing::error!(
+                                target: "codegen",
+                                "UNKNOWN BUILTIN FUNCTION \"{}\"",
+                                name
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::UnknownBuiltin(name.to_string()),
+                                span: bf.span.clone(),
+                                token: None,
+                                expansion: vec![],
+                            })
+                        }
+                    };
+                    if bf.args.len() != builtin.arity {
+                        tracing::error!(
+                            target: "codegen",
+                            "Incorrect number of arguments passed to {}, should be {}: {}",
+                            name,
+                            builtin.arity,
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to {}, should be {}: {}",
+                                name,
+                                builtin.arity,
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        })
+                    }
+
+                    // Every input must be known at compile time
+                    let values = bf
+                        .args
+                        .iter()
+                        .map(|a| constant_arg_value(a, contract, bf.span.clone()))
+                        .collect::<Result<Vec<[u8; 32]>, CodegenError>>()?;
+                    let value_bytes = (builtin.eval)(&values).map_err(|e| {
+                        tracing::error!(target: "codegen", "BUILTIN \"{}\" FAILED: {}", name, e);
+                        CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!("{}: {}", name, e)),
+                            span: bf.span.clone(),
+                            token: None,
+                            expansion: vec![],
+                        }
+                    })?;
+
+                    let push_bytes = Bytes::push_word(&value_bytes);
+                    *offset += push_bytes.len();
+                    bytes.push((starting_offset, push_bytes));
                 }
             }
         }
@@ -372,6 +1108,7 @@ pub fn statement_gen(
                 kind: CodegenErrorKind::InvalidMacroStatement,
                 span: s.span.clone(),
                 token: None,
+                expansion: vec![],
             })
         }
     }
@@ -2,7 +2,6 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 #![forbid(unsafe_code)]
-#![forbid(where_clauses_object_safety)]
 
 use huff_utils::{
     abi::*,
@@ -10,17 +9,33 @@ use huff_utils::{
     ast::*,
     bytecode::*,
     error::CodegenError,
-    evm::Opcode,
+    evm::{EvmVersion, Opcode},
     prelude::{
         bytes32_to_string, format_even_bytes, pad_n_bytes, CodegenErrorKind, FileSource, Span,
     },
+    recursion::with_stack,
+    serializer::{JsonSerializer, Serializer},
+    source_map::SourceSpans,
     types::EToken,
 };
-use std::{collections::HashMap, fs, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Write,
+    path::Path,
+    sync::Arc,
+};
 
 mod irgen;
 use crate::irgen::prelude::*;
 
+/// Code Generation Options Module
+pub mod config;
+use config::{
+    CodegenConfig, DEFAULT_BOOTSTRAP, DEFAULT_MAX_EXPANDED_SIZE, DEFAULT_MAX_EXPANSION_DEPTH,
+    IMMUTABLES_OFFSET,
+};
+
 /// ### Codegen
 ///
 /// Code Generation Manager responsible for generating bytecode from a
@@ -56,6 +71,26 @@ impl Codegen {
 
     /// Generates main bytecode from a Contract AST
     pub fn generate_main_bytecode(contract: &Contract) -> Result<String, CodegenError> {
+        Codegen::generate_main_bytecode_with_config(contract, &CodegenConfig::default())
+    }
+
+    /// Generates main bytecode from a Contract AST with custom builtins, aborting with a
+    /// `Cancelled` error before expanding a macro once the config's token is cancelled
+    pub fn generate_main_bytecode_with_config(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<String, CodegenError> {
+        Codegen::generate_main_bytecode_with_spans(contract, config).map(|(bytecode, _)| bytecode)
+    }
+
+    /// Generates main bytecode from a Contract AST, along with the source spans of the bytecode
+    pub fn generate_main_bytecode_with_spans(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<(String, SourceSpans), CodegenError> {
+        // Definitions are resolved by name, so must be unique across includes
+        Codegen::check_duplicate_definitions(contract)?;
+
         // Find the main macro
         let m_macro = Codegen::get_macro_by_name("MAIN", contract)?;
 
@@ -66,16 +101,362 @@ impl Codegen {
             &mut vec![m_macro],
             0,
             &mut Vec::default(),
+            config,
         )?;
 
         // Generate the fully baked bytecode
+        Codegen::gen_table_bytecode_with_spans(bytecode_res)
+    }
+
+    /// Generates the size each macro contributes to the main bytecode from a Contract AST
+    pub fn generate_main_macro_sizes(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<MacroSizes, CodegenError> {
+        let m_macro = Codegen::get_macro_by_name("MAIN", contract)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            contract,
+            &mut vec![m_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
+        Ok(bytecode_res.macro_sizes)
+    }
+
+    /// Generates the size each macro contributes to the constructor bytecode from a Contract AST
+    pub fn generate_constructor_macro_sizes(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<MacroSizes, CodegenError> {
+        let c_macro = match contract.find_macro_by_name("CONSTRUCTOR") {
+            Some(m) => m,
+            None => return Ok(MacroSizes::new()),
+        };
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            c_macro.clone(),
+            contract,
+            &mut vec![c_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
+        Ok(bytecode_res.macro_sizes)
+    }
+
+    /// Computes the deepest stack each macro of a Contract AST reaches, simulated from its
+    /// `takes` items, flagging those near the stack limit
+    pub fn max_stack_depths(contract: &Contract) -> StackDepths {
+        contract
+            .macros
+            .iter()
+            .map(|m| {
+                let depth = StackDepth::new(max_stack_depth(m, contract));
+                if depth.near_limit {
+                    tracing::warn!(
+                        target: "codegen",
+                        "MACRO \"{}\" REACHES A STACK DEPTH OF {}",
+                        m.name,
+                        depth.max
+                    );
+                }
+                (m.name.clone(), depth)
+            })
+            .collect()
+    }
+
+    /// Computes the number of items a macro of a Contract AST leaves on the stack, simulated from
+    /// its `takes` items, or `None` if the end of its body can't be reached
+    pub fn stack_height(
+        macro_name: &str,
+        contract: &Contract,
+    ) -> Result<Option<usize>, CodegenError> {
+        stack_height(&Codegen::get_macro_by_name(macro_name, contract)?, contract)
+    }
+
+    /// Estimates the static gas of a single expansion of each macro of a Contract AST, skipping
+    /// macros whose bytecode can't be generated on their own. Fns are estimated as if inlined.
+    pub fn generate_macro_gas(contract: &Contract, config: &CodegenConfig) -> MacroGasTable {
+        contract
+            .macros
+            .iter()
+            .filter_map(|m| {
+                let bytecode =
+                    Codegen::generate_macro_bytecode_with_config(&m.name, contract, config).ok()?;
+                Some((m.name.clone(), MacroGas::new(&bytecode)))
+            })
+            .collect()
+    }
+
+    /// Computes the opcode histogram and size breakdown of the main bytecode from a Contract AST
+    pub fn generate_main_stats(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<BytecodeStats, CodegenError> {
+        let m_macro = Codegen::get_macro_by_name("MAIN", contract)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            contract,
+            &mut vec![m_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
+        let code_len = bytecode_res.code_len();
+        let bytecode = Codegen::gen_table_bytecode(bytecode_res)?;
+        Ok(BytecodeStats::new(&bytecode, bytecode.len() / 2 - code_len))
+    }
+
+    /// Generates the offsets of the placeholder words that `__IMMUTABLE` reserves in the main
+    /// bytecode, keyed by immutable name
+    pub fn generate_main_immutable_references(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<BTreeMap<String, Vec<usize>>, CodegenError> {
+        let m_macro = Codegen::get_macro_by_name("MAIN", contract)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            contract,
+            &mut vec![m_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
+        let mut references: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for r in bytecode_res.immutable_references {
+            references.entry(r.label).or_default().push(r.bytecode_index);
+        }
+        Ok(references)
+    }
+
+    /// Builds a bootstrap template that copies the runtime after the immutables staged in memory
+    /// by `__SET_IMMUTABLE`, patches each of their references and returns the patched runtime.
+    ///
+    /// The constructor stages the immutables in memory from
+    /// [IMMUTABLES_OFFSET](config::IMMUTABLES_OFFSET), in declaration order, so must leave those
+    /// words untouched once set.
+    pub fn immutables_bootstrap(
+        contract: &Contract,
+        references: &BTreeMap<String, Vec<usize>>,
+    ) -> String {
+        let push = |value: usize| {
+            let value = format_even_bytes(format!("{:02x}", value));
+            format!("{:02x}{}", 95 + value.len() / 2, value)
+        };
+        let runtime_start = IMMUTABLES_OFFSET + contract.immutables.len() * 32;
+        let mut template = format!(
+            "{{size}}{}{{offset}}{}{}",
+            Opcode::Dup1,
+            push(runtime_start),
+            Opcode::Codecopy
+        );
+        for (name, offsets) in references {
+            let slot =
+                IMMUTABLES_OFFSET + contract.find_immutable_index(name).unwrap_or_default() * 32;
+            for offset in offsets {
+                template.push_str(&format!(
+                    "{}{}{}{}",
+                    push(slot),
+                    Opcode::Mload,
+                    push(runtime_start + offset),
+                    Opcode::Mstore
+                ));
+            }
+        }
+        template.push_str(&format!("{}{}", push(runtime_start), Opcode::Return));
+        template
+    }
+
+    /// Generates the bytecode of each test macro of a Contract AST, keyed by test name.
+    ///
+    /// Each test is compiled like `MAIN`, as the root of its own bytecode with the contract's
+    /// fns and tables appended, so test runners can execute it directly.
+    pub fn generate_tests(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<BTreeMap<String, String>, CodegenError> {
+        contract
+            .tests
+            .iter()
+            .map(|t| {
+                Ok((t.name.clone(), Codegen::generate_test_bytecode(&t.name, contract, config)?))
+            })
+            .collect()
+    }
+
+    /// Generates the bytecode of a single test macro from a Contract AST
+    pub fn generate_test_bytecode(
+        test_name: &str,
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<String, CodegenError> {
+        // Tests can invoke the contract's macros, but are never invoked by them
+        let mut with_tests = contract.clone();
+        with_tests.macros.append(&mut with_tests.tests);
+        Codegen::check_duplicate_definitions(&with_tests)?;
+        let tests = Contract { macros: contract.tests.clone(), ..Default::default() };
+        let t_macro = Codegen::get_macro_by_name(test_name, &tests)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            t_macro.clone(),
+            &with_tests,
+            &mut vec![t_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
         Codegen::gen_table_bytecode(bytecode_res)
     }
 
+    /// Compiles a macro of a Contract AST as the entry point of standalone runtime bytecode.
+    ///
+    /// A generated shim pushes the macro's `takes` from consecutive calldata words, with the first
+    /// word on top of the stack, and the contract's fns and tables are appended as for `MAIN`.
+    pub fn compile_macro(
+        macro_name: &str,
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<String, CodegenError> {
+        Codegen::check_duplicate_definitions(contract)?;
+        let m_macro = Codegen::get_macro_by_name(macro_name, contract)?;
+
+        let mut shim = Bytes::default();
+        for i in (0..m_macro.takes).rev() {
+            shim.0.extend(Bytes::push_usize(i * 32).0);
+            shim.0.push(Opcode::Calldataload.byte());
+        }
+        let mut bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            contract,
+            &mut vec![m_macro],
+            shim.len(),
+            &mut Vec::default(),
+            config,
+        )?;
+        bytecode_res.bytes.insert(0, (0, shim));
+        Codegen::gen_table_bytecode(bytecode_res)
+    }
+
+    /// Compiles each of the named macros of a Contract AST with
+    /// [compile_macro](Codegen::compile_macro), keyed by macro name.
+    pub fn compile_macros(
+        macro_names: &[&str],
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<BTreeMap<String, String>, CodegenError> {
+        macro_names
+            .iter()
+            .map(|name| Ok((name.to_string(), Codegen::compile_macro(name, contract, config)?)))
+            .collect()
+    }
+
+    /// Generates the bytecode a single macro inlines from a Contract AST.
+    ///
+    /// Outlined functions are inlined and jumps to labels outside of the macro are left
+    /// unfilled as `xxxx`, so the result is only meant for size and gas estimates.
+    pub fn generate_macro_bytecode(
+        macro_name: &str,
+        contract: &Contract,
+    ) -> Result<String, CodegenError> {
+        Codegen::generate_macro_bytecode_with_config(
+            macro_name,
+            contract,
+            &CodegenConfig::default(),
+        )
+    }
+
+    /// Generates the bytecode a single macro inlines from a Contract AST with the given codegen
+    /// options, like [generate_macro_bytecode](Codegen::generate_macro_bytecode)
+    pub fn generate_macro_bytecode_with_config(
+        macro_name: &str,
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<String, CodegenError> {
+        let mut inlined = contract.clone();
+        inlined.macros.iter_mut().for_each(|m| m.outlined = false);
+        let m_macro = Codegen::get_macro_by_name(macro_name, &inlined)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            &inlined,
+            &mut vec![m_macro],
+            0,
+            &mut Vec::default(),
+            config,
+        )?;
+        let code = bytecode_res.bytes.into_iter().flat_map(|(_, b)| b.0).collect::<Vec<u8>>();
+        let mut bytecode = hex::encode(&code);
+        for jump in bytecode_res.unmatched_jumps.iter().chain(&bytecode_res.table_instances) {
+            let i = jump.bytecode_index;
+            if code.get(i..i + 3) == Some(&[Opcode::Push2.byte(), 0, 0][..]) {
+                bytecode.replace_range(i * 2 + 2..i * 2 + 6, "xxxx");
+            }
+        }
+        Ok(bytecode)
+    }
+
+    /// Generates the bytes of a macro placed at an offset in other code, with its labels and
+    /// tables resolved to absolute offsets, and its outlined macros and tables appended after it.
+    ///
+    /// Returns the bytes along with the length of the macro's own code, before what's appended.
+    pub fn generate_macro_bytes_at(
+        macro_name: &str,
+        contract: &Contract,
+        offset: usize,
+        config: &CodegenConfig,
+    ) -> Result<(Vec<u8>, usize), CodegenError> {
+        Codegen::check_duplicate_definitions(contract)?;
+        let m_macro = Codegen::get_macro_by_name(macro_name, contract)?;
+        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+            m_macro.clone(),
+            contract,
+            &mut vec![m_macro],
+            offset,
+            &mut Vec::default(),
+            config,
+        )?;
+        // Outlined macros are appended from the first of their jump destinations
+        let body_len = contract
+            .macros
+            .iter()
+            .filter(|m| m.outlined)
+            .filter_map(|m| bytecode_res.label_indices.get(&format!("goto_{}", m.name)))
+            .min()
+            .map_or(bytecode_res.code_len(), |start| start - offset);
+        Codegen::gen_table_bytes(bytecode_res, offset).map(|bytes| (bytes, body_len))
+    }
+
     /// Generates constructor bytecode from a Contract AST
     pub fn generate_constructor_bytecode(contract: &Contract) -> Result<String, CodegenError> {
-        // Find the constructor macro
-        let c_macro = Codegen::get_macro_by_name("CONSTRUCTOR", contract)?;
+        Codegen::generate_constructor_bytecode_with_config(contract, &CodegenConfig::default())
+    }
+
+    /// Generates constructor bytecode from a Contract AST with custom builtins, aborting with a
+    /// `Cancelled` error before expanding a macro once the config's token is cancelled
+    pub fn generate_constructor_bytecode_with_config(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<String, CodegenError> {
+        Codegen::generate_constructor_bytecode_with_spans(contract, config)
+            .map(|(bytecode, _)| bytecode)
+    }
+
+    /// Generates constructor bytecode from a Contract AST, along with the source spans of the
+    /// bytecode
+    pub fn generate_constructor_bytecode_with_spans(
+        contract: &Contract,
+        config: &CodegenConfig,
+    ) -> Result<(String, SourceSpans), CodegenError> {
+        // Definitions are resolved by name, so must be unique across includes
+        Codegen::check_duplicate_definitions(contract)?;
+
+        // Contracts without a constructor macro deploy with the bootstrap code alone
+        let c_macro = match contract.find_macro_by_name("CONSTRUCTOR") {
+            Some(m) => m,
+            None => {
+                tracing::info!(target: "codegen", "NO \"CONSTRUCTOR\" MACRO, USING AN EMPTY CONSTRUCTOR");
+                return Ok((String::new(), vec![]))
+            }
+        };
 
         // For each MacroInvocation Statement, recurse into bytecode
         let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
@@ -84,9 +465,10 @@ impl Codegen {
             &mut vec![c_macro],
             0,
             &mut Vec::default(),
+            config,
         )?;
 
-        Codegen::gen_table_bytecode(bytecode_res)
+        Codegen::gen_table_bytecode_with_spans(bytecode_res)
     }
 
     /// Helper function to find a macro or generate a CodegenError
@@ -102,13 +484,45 @@ impl Codegen {
                 kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
                 span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
                 token: None,
+                expansion: vec![],
             })
         }
     }
 
-    /// Appends table bytecode to the end of the BytecodeRes output.
+    /// Appends table bytecode to the end of the BytecodeRes output, keeping the source spans of
+    /// the macro bytecode. Tables aren't generated from statements, so they are left unmapped.
+    pub(crate) fn gen_table_bytecode_with_spans(
+        mut res: BytecodeRes,
+    ) -> Result<(String, SourceSpans), CodegenError> {
+        let mut spans = std::mem::take(&mut res.spans);
+        let code_len = res.code_len();
+        spans.push((code_len, AstSpan(vec![])));
+        Codegen::gen_table_bytecode(res).map(|bytecode| (bytecode, spans))
+    }
+
+    /// Appends table bytecode to the end of the BytecodeRes output, encoded as hex.
     /// Fills table JUMPDEST placeholders.
     pub(crate) fn gen_table_bytecode(res: BytecodeRes) -> Result<String, CodegenError> {
+        Codegen::gen_table_bytes(res, 0).map(hex::encode)
+    }
+
+    /// Appends table bytes to the end of the BytecodeRes output, generated from the `base`
+    /// offset. Fills table JUMPDEST placeholders.
+    pub(crate) fn gen_table_bytes(res: BytecodeRes, base: usize) -> Result<Vec<u8>, CodegenError> {
+        // Jumps to labels defined by several invocations list the definitions they could target
+        let ambiguous = res.unmatched_jumps.iter().find_map(|uj| {
+            let offsets = ambiguous_label_offsets(&res.label_indices, &uj.label);
+            (!offsets.is_empty()).then_some((uj, offsets))
+        });
+        if let Some((uj, offsets)) = ambiguous {
+            tracing::error!(target: "codegen", "AMBIGUOUS JUMP LABEL \"{}\"", uj.label);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::AmbiguousJumpLabel(uj.label.clone(), offsets),
+                span: uj.span.clone(),
+                token: None,
+                expansion: vec![],
+            })
+        }
         if !res.unmatched_jumps.is_empty() {
             tracing::error!(
                 target: "codegen",
@@ -124,14 +538,16 @@ impl Codegen {
                         .collect::<Vec<Span>>(),
                 ),
                 token: None,
+                expansion: vec![],
             })
         }
 
         tracing::info!(target: "codegen", "GENERATING JUMPTABLE BYTECODE");
 
-        let mut bytecode = res.bytes.into_iter().map(|(_, b)| b.0).collect::<String>();
+        // Offsets are absolute, so the tables of code generated at an offset are placed after it
+        let mut bytecode = res.bytes.into_iter().flat_map(|(_, b)| b.0).collect::<Vec<u8>>();
         let mut table_offsets: HashMap<String, usize> = HashMap::new(); // table name -> bytecode offset
-        let mut table_offset = bytecode.len() / 2;
+        let mut table_offset = base + bytecode.len();
 
         res.utilized_tables.iter().try_for_each(|jt| {
             table_offsets.insert(jt.name.to_string(), table_offset);
@@ -140,14 +556,15 @@ impl Codegen {
                 Err(_) => return Err(CodegenError {
                     kind: CodegenErrorKind::UsizeConversion(format!("{:?}", jt.size)),
                     span: jt.span.clone(),
-                    token: None
+                    token: None,
+                    expansion: vec![],
                 })
             };
             table_offset += size;
 
             tracing::info!(target: "codegen", "GENERATING BYTECODE FOR TABLE: \"{}\"", jt.name);
 
-            let mut table_code = String::new();
+            let mut table_code: Vec<u8> = vec![];
             let collected = jt
                 .statements
                 .iter()
@@ -166,33 +583,36 @@ impl Codegen {
                                         kind: CodegenErrorKind::UnmatchedJumpLabel,
                                         span: s.span.clone(),
                                         token: None,
+                                        expansion: vec![],
                                     });
                                 }
                             };
-                            let hex = format_even_bytes(format!("{:02x}", offset));
-
-                            table_code = format!("{}{}", table_code, pad_n_bytes(
-                                hex.as_str(),
-                                if matches!(jt.kind, TableKind::JumpTablePacked) { 0x02 } else { 0x20 },
-                            ));
+                            match jt.kind {
+                                TableKind::JumpTablePacked => table_code
+                                    .extend(Codegen::push2_destination(label, *offset, &s.span)?),
+                                _ => {
+                                    table_code.extend([0u8; 24]);
+                                    table_code.extend((*offset as u64).to_be_bytes());
+                                }
+                            }
                         }
                         StatementType::Code(code) => {
-                            // Check if code length is even
-                            if code.len() % 2 != 0 {
-                                return Err(CodegenError {
-                                    kind: CodegenErrorKind::InvalidCodeLength(code.len()),
-                                    span: s.span.clone(),
-                                    token: None,
-                                });
-                            }
+                            // Code must be whole bytes
+                            let bytes = Bytes::from_hex(code).map_err(|_| CodegenError {
+                                kind: CodegenErrorKind::InvalidCodeLength(code.len()),
+                                span: s.span.clone(),
+                                token: None,
+                                expansion: vec![],
+                            })?;
 
-                            table_code = format!("{}{}", table_code, code);
+                            table_code.extend(bytes.0);
                         }
                         _ => {
                             return Err(CodegenError {
                                 kind: CodegenErrorKind::InvalidMacroStatement,
                                 span: jt.span.clone(),
-                                token: None
+                                token: None,
+                                expansion: vec![],
                             })
                         }
                     }
@@ -200,17 +620,15 @@ impl Codegen {
                 });
             collected?;
             tracing::info!(target: "codegen", "SUCCESSFULLY GENERATED BYTECODE FOR TABLE: \"{}\"", jt.name);
-            bytecode = format!("{}{}", bytecode, table_code);
+            bytecode.extend(table_code);
             Ok(())
         })?;
 
-        res.table_instances.iter().for_each(|jump| {
+        for jump in res.table_instances.iter() {
             if let Some(o) = table_offsets.get(&jump.label) {
-                let before = &bytecode[0..jump.bytecode_index * 2 + 2];
-                let after = &bytecode[jump.bytecode_index * 2 + 6..];
-
-                bytecode =
-                    format!("{}{}{}", before, pad_n_bytes(format!("{:02x}", o).as_str(), 2), after);
+                let offset = Codegen::push2_destination(&jump.label, *o, &jump.span)?;
+                let i = jump.bytecode_index - base;
+                bytecode[i + 1..i + 3].copy_from_slice(&offset);
                 tracing::info!(target: "codegen", "FILLED JUMPDEST FOR LABEL \"{}\"", jump.label);
             } else {
                 tracing::error!(
@@ -219,7 +637,7 @@ impl Codegen {
                     jump.label
                 );
             }
-        });
+        }
 
         Ok(bytecode)
     }
@@ -244,13 +662,61 @@ impl Codegen {
     /// * `scope` - Current scope of the recursion. Contains all macro definitions recursed so far.
     /// * `offset` - Current bytecode offset
     /// * `mis` - Vector of tuples containing parent macro invocations as well as their offsets.
+    /// * `config` - Custom builtins, and the token checked before expanding the macro
     pub(crate) fn macro_to_bytecode(
+        macro_def: MacroDefinition,
+        contract: &Contract,
+        scope: &mut Vec<MacroDefinition>,
+        offset: usize,
+        mis: &mut Vec<(usize, MacroInvocation)>,
+        config: &CodegenConfig,
+    ) -> Result<BytecodeRes, CodegenError> {
+        // Nested expansions grow the stack when it runs low, so any depth limit can be reached
+        with_stack(|| Codegen::expand_macro(macro_def, contract, scope, offset, mis, config))
+    }
+
+    /// Expands a MacroDefinition into Bytecode, on a stack with room for the expansion
+    fn expand_macro(
         macro_def: MacroDefinition,
         contract: &Contract,
         scope: &mut Vec<MacroDefinition>,
         mut offset: usize,
         mis: &mut Vec<(usize, MacroInvocation)>,
+        config: &CodegenConfig,
     ) -> Result<BytecodeRes, CodegenError> {
+        if config.cancellation.is_cancelled() {
+            tracing::warn!(
+                target: "codegen",
+                "CANCELLED BEFORE EXPANDING MACRO \"{}\"",
+                macro_def.name
+            );
+            return Err(CodegenError {
+                kind: CodegenErrorKind::Cancelled,
+                span: macro_def.span,
+                token: None,
+                expansion: vec![],
+            })
+        }
+
+        // Deep expansions are stopped before they can overflow the stack
+        let max_depth = config.max_expansion_depth.unwrap_or(DEFAULT_MAX_EXPANSION_DEPTH);
+        if scope.len() > max_depth {
+            tracing::error!(target: "codegen", "MACRO \"{}\" EXPANDED PAST DEPTH {}", macro_def.name, max_depth);
+            return Err(CodegenError::new(
+                CodegenErrorKind::MacroExpansionTooDeep(macro_def.name, max_depth),
+                macro_def.span,
+                None,
+            ))
+        }
+
+        // Check the macro only uses opcodes available in the target evm
+        Codegen::check_evm_version(&macro_def.statements, config.evm_version)?;
+
+        // Check the macro body against its takes and returns
+        if config.check_stack {
+            check_stack_heights(&macro_def, contract)?;
+        }
+
         // Get intermediate bytecode representation of the macro definition
         let mut bytes: Vec<(usize, Bytes)> = Vec::default();
         let ir_bytes = macro_def.to_irbytecode()?.0;
@@ -259,21 +725,43 @@ impl Codegen {
         let mut jump_table = JumpTable::new();
         let mut label_indices = LabelIndices::new();
         let mut table_instances = Jumps::new();
+        let mut immutable_references = Jumps::new();
         let mut utilized_tables: Vec<TableDefinition> = Vec::new();
+        let mut macro_sizes = MacroSizes::new();
+        let mut spans = SourceSpans::new();
 
         // Loop through all intermediate bytecode representations generated from the AST
-        for (_ir_bytes_index, ir_byte) in ir_bytes.into_iter().enumerate() {
+        let max_size = config.max_expanded_size.unwrap_or(DEFAULT_MAX_EXPANDED_SIZE);
+        for ir_byte in ir_bytes.into_iter() {
+            // Runaway expansions are stopped before their bytecode grows any further
+            if offset > max_size {
+                tracing::error!(target: "codegen", "MACRO \"{}\" EXPANDED PAST {} BYTES", macro_def.name, max_size);
+                return Err(CodegenError::new(
+                    CodegenErrorKind::ExpandedBytecodeTooLarge(macro_def.name, max_size),
+                    ir_byte.span,
+                    None,
+                ))
+            }
             let starting_offset = offset;
+            spans.push((starting_offset, ir_byte.span.clone()));
             match ir_byte.ty {
                 IRByteType::Bytes(b) => {
-                    offset += b.0.len() / 2;
+                    let b = match config.preserve_push_width {
+                        true => preserve_literal_width(b, &ir_byte.span),
+                        false => b,
+                    };
+                    let b = push0_literal(b, config.evm_version);
+                    offset += b.len();
                     bytes.push((starting_offset, b));
                 }
                 IRByteType::Constant(name) => {
-                    let push_bytes = constant_gen(&name, contract, ir_byte.span)?;
-                    offset += push_bytes.len() / 2;
-                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    let push_bytes = push0_literal(
+                        constant_gen(&name, contract, ir_byte.span, config.preserve_push_width)?,
+                        config.evm_version,
+                    );
+                    offset += push_bytes.len();
+                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH BYTES: {}", offset, push_bytes);
+                    bytes.push((starting_offset, push_bytes));
                 }
                 IRByteType::Statement(s) => {
                     let mut push_bytes = statement_gen(
@@ -286,8 +774,12 @@ impl Codegen {
                         &mut jump_table,
                         &mut label_indices,
                         &mut table_instances,
+                        &mut immutable_references,
                         &mut utilized_tables,
+                        &mut macro_sizes,
+                        &mut spans,
                         starting_offset,
+                        config,
                     )?;
                     bytes.append(&mut push_bytes);
                 }
@@ -303,6 +795,7 @@ impl Codegen {
                         &mut offset,
                         mis,
                         &mut jump_table,
+                        config.evm_version,
                     )?
                 }
             }
@@ -324,7 +817,11 @@ impl Codegen {
                 &mut jump_table,
                 &mut label_indices,
                 &mut table_instances,
+                &mut immutable_references,
+                &mut macro_sizes,
+                &mut spans,
                 bytes,
+                config,
             )?;
         } else {
             // If the scope length is > 1, we're processing a child macro. Since we're done
@@ -335,7 +832,60 @@ impl Codegen {
         // Fill JUMPDEST placeholders
         let (bytes, unmatched_jumps) = Codegen::fill_unmatched(bytes, &jump_table, &label_indices)?;
 
-        Ok(BytecodeRes { bytes, label_indices, unmatched_jumps, table_instances, utilized_tables })
+        Ok(BytecodeRes {
+            bytes,
+            label_indices,
+            unmatched_jumps,
+            table_instances,
+            immutable_references,
+            utilized_tables,
+            macro_sizes,
+            spans,
+        })
+    }
+
+    /// Checks that no macro, constant, or table is defined more than once across a contract and
+    /// its includes, naming both definitions if one is.
+    pub fn check_duplicate_definitions(contract: &Contract) -> Result<(), CodegenError> {
+        match contract.duplicate_definition() {
+            Some((kind, name, first, second)) => {
+                tracing::error!(target: "codegen", "DUPLICATE {} DEFINITION \"{}\"", kind, name);
+                Err(CodegenError {
+                    kind: CodegenErrorKind::DuplicateDefinition(kind.to_string(), name),
+                    span: AstSpan(first.0.into_iter().chain(second.0).collect()),
+                    token: None,
+                    expansion: vec![],
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the opcodes of a macro body, including those in its labels, are available in
+    /// the target evm version.
+    pub fn check_evm_version(
+        statements: &[Statement],
+        evm_version: EvmVersion,
+    ) -> Result<(), CodegenError> {
+        for statement in statements {
+            match &statement.ty {
+                StatementType::Opcode(o) if !evm_version.supports(*o) => {
+                    tracing::error!(target: "codegen", "OPCODE {:?} UNSUPPORTED BY {}", o, evm_version);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::UnsupportedOpcode(
+                            format!("{:?}", o).to_lowercase(),
+                            evm_version,
+                        ),
+                        span: statement.span.clone(),
+                        token: None,
+                        expansion: vec![],
+                    })
+                }
+                StatementType::Label(l) => Codegen::check_evm_version(&l.inner, evm_version)?,
+                _ => {}
+            }
+        }
+        Ok(())
     }
 
     /// Helper associated function to fill unmatched jump dests.
@@ -356,8 +906,9 @@ impl Codegen {
         label_indices: &LabelIndices,
     ) -> Result<(Vec<(usize, Bytes)>, Vec<Jump>), CodegenError> {
         let mut unmatched_jumps = Jumps::default();
-        let bytes =
-            bytes.into_iter().fold(Vec::default(), |mut acc, (code_index, mut formatted_bytes)| {
+        let bytes = bytes.into_iter().try_fold(
+            Vec::default(),
+            |mut acc, (code_index, mut formatted_bytes)| {
                 // Check if a jump table exists at `code_index` (starting offset of `b`)
                 if let Some(jt) = jump_table.get(&code_index) {
                     // Loop through jumps inside of the found JumpTable
@@ -366,16 +917,15 @@ impl Codegen {
                         // unmatched jumps and define its `bytecode_index`
                         // at `code_index`
                         if let Some(jump_index) = label_indices.get(jump.label.as_str()) {
-                            // Format the jump index as a 2 byte hex number
-                            let jump_value = format!("{:04x}", jump_index);
-
-                            // Get the bytes before & after the placeholder
-                            let before = &formatted_bytes.0[0..jump.bytecode_index + 2];
-                            let after = &formatted_bytes.0[jump.bytecode_index + 6..];
+                            // Format the jump index as a 2 byte destination
+                            let jump_value =
+                                Codegen::push2_destination(&jump.label, *jump_index, &jump.span)?;
 
                             // Check if a jump dest placeholder is present
-                            if !&formatted_bytes.0[jump.bytecode_index + 2..jump.bytecode_index + 6]
-                                .eq("xxxx")
+                            let placeholder = jump.bytecode_index + 1..jump.bytecode_index + 3;
+                            if formatted_bytes.0.get(jump.bytecode_index) !=
+                                Some(&Opcode::Push2.byte()) ||
+                                formatted_bytes.0.get(placeholder.clone()) != Some(&[0, 0][..])
                             {
                                 tracing::error!(
                                     target: "codegen",
@@ -384,8 +934,10 @@ impl Codegen {
                                 );
                             }
 
-                            // Replace the "xxxx" placeholder with the jump value
-                            formatted_bytes = Bytes(format!("{}{}{}", before, jump_value, after));
+                            // Replace the placeholder with the jump value
+                            if let Some(operand) = formatted_bytes.0.get_mut(placeholder) {
+                                operand.copy_from_slice(&jump_value);
+                            }
                         } else {
                             // The jump did not have a corresponding label index. Add it to the
                             // unmatched jumps vec.
@@ -399,12 +951,39 @@ impl Codegen {
                 }
 
                 acc.push((code_index, formatted_bytes));
-                acc
-            });
+                Ok(acc)
+            },
+        )?;
 
         Ok((bytes, unmatched_jumps))
     }
 
+    /// Encodes a jump destination as the 2 byte operand of a `PUSH2`.
+    ///
+    /// Jumps are pushed before the code after them is generated, so destinations past 0xffff
+    /// fail instead of widening the push and moving every offset after it.
+    pub(crate) fn push2_destination(
+        label: &str,
+        destination: usize,
+        span: &AstSpan,
+    ) -> Result<[u8; 2], CodegenError> {
+        if destination > 0xffff {
+            tracing::error!(
+                target: "codegen",
+                "JUMP DESTINATION 0x{:x} OF \"{}\" EXCEEDS PUSH2",
+                destination,
+                label
+            );
+            return Err(CodegenError {
+                kind: CodegenErrorKind::JumpDestinationTooLarge(label.to_string(), destination),
+                span: span.clone(),
+                token: None,
+                expansion: vec![],
+            })
+        }
+        Ok((destination as u16).to_be_bytes())
+    }
+
     /// Helper associated function to append functions to the end of the bytecode.
     ///
     /// ## Overview
@@ -423,15 +1002,26 @@ impl Codegen {
         jump_table: &mut JumpTable,
         label_indices: &mut LabelIndices,
         table_instances: &mut Jumps,
+        immutable_references: &mut Jumps,
+        macro_sizes: &mut MacroSizes,
+        spans: &mut SourceSpans,
         mut bytes: Vec<(usize, Bytes)>,
+        config: &CodegenConfig,
     ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
         for macro_def in contract.macros.iter().filter(|m| m.outlined) {
             // Push the function to the scope
             scope.push(macro_def.clone());
+            spans.push((*offset, macro_def.span.clone()));
 
             // Add 1 to starting offset to account for the JUMPDEST opcode
-            let mut res =
-                Codegen::macro_to_bytecode(macro_def.clone(), contract, scope, *offset + 1, mis)?;
+            let mut res = Codegen::macro_to_bytecode(
+                macro_def.clone(),
+                contract,
+                scope,
+                *offset + 1,
+                mis,
+                config,
+            )?;
 
             for j in res.unmatched_jumps.iter_mut() {
                 let new_index = j.bytecode_index;
@@ -445,26 +1035,31 @@ impl Codegen {
                 jump_table.insert(new_index, new_jumps);
             }
             table_instances.extend(res.table_instances);
+            immutable_references.extend(res.immutable_references);
             label_indices.extend(res.label_indices);
+            merge_macro_sizes(macro_sizes, res.macro_sizes);
+            spans.extend(res.spans);
 
-            let macro_code_len = res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+            let macro_code_len = res.bytes.iter().map(|(_, b)| b.len()).sum::<usize>();
+            spans.push((*offset + macro_code_len + 1, macro_def.span.clone()));
 
             // Get necessary swap ops to reorder stack
             // PC of the return jumpdest should be above the function's outputs on the stack
-            let stack_swaps =
-                (0..macro_def.returns).map(|i| format!("{:02x}", 0x90 + i)).collect::<Vec<_>>();
+            let stack_swaps = (0..macro_def.returns).map(|i| 0x90 + i as u8).collect::<Vec<_>>();
 
             // Insert JUMPDEST, stack swaps, and final JUMP back to the location of invocation.
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
-            res.bytes.push((
-                *offset + macro_code_len + 1,
-                Bytes(format!("{}{}", stack_swaps.join(""), Opcode::Jump)),
-            ));
+            bytes.push((*offset, Bytes::from(Opcode::Jumpdest)));
+            let mut jump_back = Bytes(stack_swaps.clone());
+            jump_back.0.push(Opcode::Jump.byte());
+            res.bytes.push((*offset + macro_code_len + 1, jump_back));
             bytes = [bytes, res.bytes].concat();
             // Add the jumpdest to the beginning of the outlined macro.
             label_indices.insert(format!("goto_{}", macro_def.name.clone()), *offset);
             *offset += macro_code_len + stack_swaps.len() + 2; // JUMPDEST + MACRO_CODE_LEN +
                                                                // stack_swaps.len() + JUMP
+            let fn_size = macro_sizes.entry(macro_def.name.clone()).or_default();
+            fn_size.invocations += 1;
+            fn_size.size += macro_code_len + stack_swaps.len() + 2;
         }
         Ok(bytes)
     }
@@ -483,109 +1078,222 @@ impl Codegen {
         main_bytecode: &str,
         constructor_bytecode: &str,
     ) -> Result<Artifact, CodegenError> {
-        let mut artifact: &mut Artifact = if let Some(art) = &mut self.artifact {
-            art
-        } else {
-            self.artifact = Some(Artifact::default());
-            self.artifact.as_mut().unwrap()
-        };
+        self.churn_with_config(
+            file,
+            args,
+            main_bytecode,
+            constructor_bytecode,
+            &CodegenConfig::default(),
+        )
+    }
+
+    /// Generate a codegen artifact with the config's constructor bootstrap
+    pub fn churn_with_config(
+        &mut self,
+        file: Arc<FileSource>,
+        args: Vec<ethers_core::abi::token::Token>,
+        main_bytecode: &str,
+        constructor_bytecode: &str,
+        config: &CodegenConfig,
+    ) -> Result<Artifact, CodegenError> {
+        let artifact: &mut Artifact = self.artifact.get_or_insert_with(Artifact::default);
 
         let contract_length = main_bytecode.len() / 2;
         let constructor_length = constructor_bytecode.len() / 2;
 
         let encoded: Vec<Vec<u8>> =
-            args.iter().map(|tok| ethers_core::abi::encode(&[tok.clone()])).collect();
+            args.iter().map(|tok| ethers_core::abi::encode(std::slice::from_ref(tok))).collect();
         let hex_args: Vec<String> = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
         let constructor_args = hex_args.join("");
-
-        // Constructor size optimizations
-        let mut bootstrap_code_size = 9;
-        let contract_size = if contract_length < 256 {
-            format!("60{}", pad_n_bytes(format!("{:x}", contract_length).as_str(), 1))
-        } else {
-            bootstrap_code_size += 1;
-
-            format!("61{}", pad_n_bytes(format!("{:x}", contract_length).as_str(), 2))
-        };
-        let contract_code_offset = if (bootstrap_code_size + constructor_length) < 256 {
-            format!(
-                "60{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 1)
-            )
-        } else {
-            bootstrap_code_size += 1;
-
-            format!(
-                "61{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 2)
-            )
-        };
+        let args_length = constructor_args.len() / 2;
 
         // Generate the final bytecode
-        let bootstrap_code = format!("{}80{}3d393df3", contract_size, contract_code_offset);
+        let template = config.bootstrap.as_deref().unwrap_or(DEFAULT_BOOTSTRAP);
+        let bootstrap_code =
+            Codegen::gen_bootstrap(template, constructor_length, contract_length, args_length)?;
         let constructor_code = format!("{}{}", constructor_bytecode, bootstrap_code);
         artifact.bytecode =
             format!("{}{}{}", constructor_code, main_bytecode, constructor_args).to_lowercase();
         artifact.runtime = main_bytecode.to_string().to_lowercase();
+        artifact.sizes = BytecodeSizes {
+            runtime: artifact.runtime.len() / 2,
+            initcode: artifact.bytecode.len() / 2,
+        };
         artifact.file = file;
         Ok(artifact.clone())
     }
 
+    /// Fills a bootstrap template with pushes of the runtime's size and offset, and of the
+    /// constructor arguments' size and offset after the runtime.
+    ///
+    /// Each push uses the fewest bytes that fit its value, ie. `PUSH3` for runtimes larger than
+    /// 0xffff bytes. The offsets include the bootstrap itself, so widening their pushes can move
+    /// them.
+    pub fn gen_bootstrap(
+        template: &str,
+        constructor_length: usize,
+        contract_length: usize,
+        args_length: usize,
+    ) -> Result<String, CodegenError> {
+        let placeholders = ["{size}", "{offset}", "{args_size}", "{args_offset}"];
+        let fixed = placeholders.iter().fold(template.to_string(), |t, p| t.replace(p, ""));
+        if !fixed.len().is_multiple_of(2) || !fixed.chars().all(|c| c.is_ascii_hexdigit()) {
+            tracing::error!(target: "codegen", "INVALID BOOTSTRAP TEMPLATE \"{}\"", template);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::InvalidArguments(format!(
+                    "Invalid bootstrap template \"{}\", expected hex with {}",
+                    template,
+                    placeholders.join(", ")
+                )),
+                span: AstSpan(vec![]),
+                token: None,
+                expansion: vec![],
+            })
+        }
+
+        let width =
+            |value: usize| (usize::BITS - value.leading_zeros()).div_ceil(8).max(1) as usize;
+        let push = |value: usize, width: usize| {
+            format!("{:x}{}", 0x5f + width, pad_n_bytes(&format!("{:x}", value), width))
+        };
+        let size = push(contract_length, width(contract_length));
+        let args_size = push(args_length, width(args_length));
+        let count = |placeholder: &str| template.matches(placeholder).count();
+        let sizes_length =
+            count("{size}") * size.len() / 2 + count("{args_size}") * args_size.len() / 2;
+
+        // Widen the offset pushes until they fit the offsets they move
+        let (mut offset_width, mut args_offset_width) = (1, 1);
+        let (offset, args_offset) = loop {
+            let bootstrap_length = fixed.len() / 2 +
+                sizes_length +
+                count("{offset}") * (1 + offset_width) +
+                count("{args_offset}") * (1 + args_offset_width);
+            let offset = constructor_length + bootstrap_length;
+            let args_offset = offset + contract_length;
+            if width(offset) <= offset_width && width(args_offset) <= args_offset_width {
+                break (push(offset, offset_width), push(args_offset, args_offset_width))
+            }
+            offset_width = offset_width.max(width(offset));
+            args_offset_width = args_offset_width.max(width(args_offset));
+        };
+        Ok(template
+            .replace("{args_size}", &args_size)
+            .replace("{args_offset}", &args_offset)
+            .replace("{size}", &size)
+            .replace("{offset}", &offset))
+    }
+
     /// Encode constructor arguments as ethers_core::abi::token::Token
-    pub fn encode_constructor_args(args: Vec<String>) -> Vec<ethers_core::abi::token::Token> {
-        let tokens: Vec<ethers_core::abi::token::Token> =
-            args.iter().map(|tok| EToken::try_from(tok.clone()).unwrap().0).collect();
-        tokens
+    pub fn encode_constructor_args(
+        args: Vec<String>,
+    ) -> Result<Vec<ethers_core::abi::token::Token>, CodegenError> {
+        args.iter()
+            .map(|tok| {
+                EToken::try_from(tok.clone()).map(|t| t.0).map_err(|e| {
+                    tracing::error!(target: "codegen", "INVALID CONSTRUCTOR ARGUMENT \"{}\"", tok);
+                    CodegenError {
+                        kind: CodegenErrorKind::InvalidArguments(format!(
+                            "Invalid constructor argument \"{}\": {}",
+                            tok, e
+                        )),
+                        span: AstSpan(vec![]),
+                        token: None,
+                        expansion: vec![],
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Encode constructor arguments as ethers_core::abi::token::Token, parsing each argument as
+    /// the constructor parameter of the same position
+    pub fn encode_typed_constructor_args(
+        args: Vec<String>,
+        types: &[FunctionParamType],
+    ) -> Result<Vec<ethers_core::abi::token::Token>, CodegenError> {
+        let invalid = |msg: String| CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(msg),
+            span: AstSpan(vec![]),
+            token: None,
+            expansion: vec![],
+        };
+        if args.len() != types.len() {
+            tracing::error!(target: "codegen", "INVALID CONSTRUCTOR ARGUMENT COUNT {}", args.len());
+            return Err(invalid(format!(
+                "Expected {} constructor arguments, found {}",
+                types.len(),
+                args.len()
+            )))
+        }
+        args.iter()
+            .zip(types)
+            .enumerate()
+            .map(|(i, (arg, ty))| {
+                EToken::try_from_param(arg, ty).map(|t| t.0).map_err(|e| {
+                    tracing::error!(target: "codegen", "INVALID CONSTRUCTOR ARGUMENT \"{}\"", arg);
+                    invalid(format!("Invalid constructor argument {} of type {}: {}", i, ty, e))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the constructor parameter types declared by a `CONSTRUCTOR` function interface
+    pub fn constructor_types(contract: &Contract) -> Option<Vec<FunctionParamType>> {
+        let constructor = contract.functions.iter().find(|f| f.name == "CONSTRUCTOR")?;
+        constructor
+            .inputs
+            .iter()
+            .map(|arg| {
+                let ty = arg.arg_type.as_deref()?;
+                FunctionParamType::convert_string_to_type(ty).ok()
+            })
+            .collect()
     }
 
     /// Export
     ///
-    /// Writes a Codegen Artifact out to the specified file.
+    /// Writes a Codegen Artifact out to the specified file, creating any missing parent
+    /// directories.
     ///
     /// # Arguments
     ///
     /// * `out` - Output location to write the serialized json artifact to.
     pub fn export(output: String, art: &Artifact) -> Result<(), CodegenError> {
-        let serialized_artifact = serde_json::to_string_pretty(art).unwrap();
-        // Try to create the parent directory
         let file_path = Path::new(&output);
-        if let Some(p) = file_path.parent() {
-            if let Err(e) = fs::create_dir_all(p) {
-                return Err(CodegenError {
-                    kind: CodegenErrorKind::IOError(e.to_string()),
-                    span: AstSpan(vec![Span {
-                        start: 0,
-                        end: 0,
-                        file: Some(Arc::new(FileSource {
-                            id: uuid::Uuid::new_v4(),
-                            path: output,
-                            source: None,
-                            access: None,
-                            dependencies: None,
-                        })),
-                    }]),
-                    token: None,
-                })
-            }
-        }
-        if let Err(e) = fs::write(file_path, serialized_artifact) {
-            return Err(CodegenError {
-                kind: CodegenErrorKind::IOError(e.to_string()),
-                span: AstSpan(vec![Span {
-                    start: 0,
-                    end: 0,
-                    file: Some(Arc::new(FileSource {
-                        id: uuid::Uuid::new_v4(),
-                        path: output,
-                        source: None,
-                        access: None,
-                        dependencies: None,
-                    })),
-                }]),
-                token: None,
-            })
+        let mut file = file_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::File::create(file_path))
+            .map_err(|e| export_error(CodegenErrorKind::IOError(e.to_string()), Some(&output)))?;
+        Codegen::write_artifact(&mut file, art).map_err(|e| export_error(e.kind, Some(&output)))
+    }
+
+    /// Writes a Codegen Artifact as json to the given writer, such as an in-memory buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the serialized json artifact.
+    /// * `art` - The artifact to serialize.
+    pub fn write_artifact<W: Write>(writer: &mut W, art: &Artifact) -> Result<(), CodegenError> {
+        let serialized_artifact = JsonSerializer::default()
+            .serialize(art)
+            .map_err(|e| export_error(CodegenErrorKind::SerializationError(e.to_string()), None))?;
+        writer
+            .write_all(serialized_artifact.as_bytes())
+            .and_then(|_| writer.flush())
+            .map_err(|e| export_error(CodegenErrorKind::IOError(e.to_string()), None))
+    }
+
+    /// Exports the Codegen's own artifact to the specified file.
+    ///
+    /// Errors with [MissingArtifact](CodegenErrorKind::MissingArtifact) if nothing has been
+    /// generated yet.
+    pub fn export_artifact(&self, output: String) -> Result<(), CodegenError> {
+        match &self.artifact {
+            Some(art) => Codegen::export(output, art),
+            None => Err(export_error(CodegenErrorKind::MissingArtifact, Some(&output))),
         }
-        Ok(())
     }
 
     /// Abi Generation
@@ -601,16 +1309,9 @@ impl Codegen {
         let abi: Abi = ast.into();
 
         // Set the abi on self
-        let art: &Artifact = match &mut self.artifact {
-            Some(artifact) => {
-                artifact.abi = Some(abi.clone());
-                artifact
-            }
-            None => {
-                self.artifact = Some(Artifact { abi: Some(abi.clone()), ..Default::default() });
-                self.artifact.as_ref().unwrap()
-            }
-        };
+        let art: &mut Artifact = self.artifact.get_or_insert_with(Artifact::default);
+        art.abi = Some(abi.clone());
+        art.method_identifiers = abi.method_identifiers();
 
         // If an output's specified, write the artifact out
         if let Some(o) = output {
@@ -622,3 +1323,22 @@ impl Codegen {
         Ok(abi)
     }
 }
+
+/// Builds an export error, spanning the output file if there is one.
+fn export_error(kind: CodegenErrorKind, output: Option<&str>) -> CodegenError {
+    let file = output.map(|path| {
+        Arc::new(FileSource {
+            id: uuid::Uuid::new_v4(),
+            path: path.to_string(),
+            source: None,
+            access: None,
+            dependencies: None,
+        })
+    });
+    CodegenError {
+        kind,
+        span: AstSpan(vec![Span { start: 0, end: 0, file }]),
+        token: None,
+        expansion: vec![],
+    }
+}
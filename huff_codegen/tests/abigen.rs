@@ -21,7 +21,10 @@ fn constructs_valid_abi() {
         constants: Rc::new(RefCell::new(vec![])),
         functions: vec![],
         events: vec![],
+        errors: vec![],
         tables: vec![],
+        tests: vec![],
+        immutables: vec![],
     };
 
     // Generate the abi from the contract
@@ -33,6 +36,7 @@ fn constructs_valid_abi() {
             constructor: Some(Constructor { inputs: vec![] }),
             functions: BTreeMap::new(),
             events: BTreeMap::new(),
+            errors: BTreeMap::new(),
             receive: false,
             fallback: false
         }
@@ -57,7 +61,10 @@ fn missing_constructor_fails() {
         constants: Rc::new(RefCell::new(vec![])),
         functions: vec![],
         events: vec![],
+        errors: vec![],
         tables: vec![],
+        tests: vec![],
+        immutables: vec![],
     };
 
     // Generate the abi from the contract
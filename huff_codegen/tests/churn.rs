@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
 use ethers_core::abi::Token;
-use huff_codegen::Codegen;
-use huff_utils::prelude::FileSource;
+use huff_codegen::{
+    config::{CodegenConfig, DEFAULT_BOOTSTRAP},
+    Codegen,
+};
+use huff_utils::prelude::{CodegenErrorKind, FileSource};
 
 #[test]
 fn churns_into_bytecode() {
@@ -47,3 +50,75 @@ fn churns_constructor_args() {
     assert!(churn_res.is_ok());
     assert_ne!(churn_res.unwrap().bytecode, "336000556101ac806100116000396000f360003560E01c8063a9059cbb1461004857806340c10f19146100de57806370a082311461014e57806318160ddd1461016b578063095ea7b314610177578063dd62ed3e1461018e575b600435336024358160016000526000602001526040600020548082116100d8578190038260016000526000602001526040600020558281906001600052600060200152604060002054018360016000526000602001526040600020556000527fDDF252AD1BE2C89B69C2B068FC378DAA952BA7F163C4A11628F55A4DF523B3EF60206000a3600160005260206000f35b60006000fd5b60005433146100ed5760006000fd5b600435600060243582819060016000526000602001526040600020540183600160005260006020015260406000205580600254016002556000527fDDF252AD1BE2C89B69C2B068FC378DAA952BA7F163C4A11628F55A4DF523B3EF60206000a35b600435600160005260006020015260406000205460005260206000f35b60025460005260206000f35b602435600435336000526000602001526040600020555b60243560043560005260006020015260406000205460005260206000f3".to_lowercase());
 }
+
+#[test]
+fn widens_bootstrap_pushes() {
+    // Runtimes larger than 0xffff bytes have their size pushed with PUSH3
+    let main_bytecode = "00".repeat(0x10000);
+    let artifact =
+        Codegen::new().churn(Arc::new(FileSource::default()), vec![], &main_bytecode, "").unwrap();
+    assert_eq!(&artifact.bytecode[..22], "6201000080600b3d393df3");
+
+    // The offset is widened once the constructor and bootstrap pass 0xff bytes
+    let constructor_bytecode = "5b".repeat(0xf7);
+    let artifact = Codegen::new()
+        .churn(Arc::new(FileSource::default()), vec![], "00", &constructor_bytecode)
+        .unwrap();
+    assert_eq!(&artifact.bytecode[0xf7 * 2..], "6001806101013d393df300");
+}
+
+#[test]
+fn bootstrap_pushes_at_boundaries() {
+    // (constructor length, runtime length, expected bootstrap)
+    let cases = [
+        (0, 0xff, "60ff8060093d393df3"),
+        (0, 0x100, "61010080600a3d393df3"),
+        (0, 0xffff, "61ffff80600a3d393df3"),
+        (0, 0x10000, "6201000080600b3d393df3"),
+        (0xf6, 1, "60018060ff3d393df3"),
+        (0xf7, 1, "6001806101013d393df3"),
+    ];
+    for (constructor_length, contract_length, expected) in cases {
+        let bootstrap =
+            Codegen::gen_bootstrap(DEFAULT_BOOTSTRAP, constructor_length, contract_length, 0)
+                .unwrap();
+        assert_eq!(bootstrap, expected);
+    }
+
+    // The arguments offset is widened on its own, shifting the runtime offset by one byte
+    let bootstrap = Codegen::gen_bootstrap("{args_offset}{offset}", 0xf0, 0x0d, 0).unwrap();
+    assert_eq!(bootstrap, "61010260f5");
+}
+
+#[test]
+fn churns_custom_bootstrap() {
+    // Reverts if called with value, then returns the runtime
+    let config = CodegenConfig {
+        bootstrap: Some("341558600801573d3dfd5b{size}80{offset}3d393df3".to_string()),
+        ..Default::default()
+    };
+    let artifact = Codegen::new()
+        .churn_with_config(Arc::new(FileSource::default()), vec![], "6001", "33600055", &config)
+        .unwrap();
+    assert_eq!(artifact.bytecode, "33600055341558600801573d3dfd5b60028060183d393df36001");
+
+    // Copies the constructor arguments after the runtime into memory and stores the first one
+    let config = CodegenConfig {
+        bootstrap: Some("{args_size}{args_offset}3d393d513d55{size}80{offset}3d393df3".to_string()),
+        ..Default::default()
+    };
+    let args = vec![Token::Uint(5.into())];
+    let artifact = Codegen::new()
+        .churn_with_config(Arc::new(FileSource::default()), args, "6001", "", &config)
+        .unwrap();
+    assert_eq!(artifact.bytecode, format!("602060153d393d513d5560028060133d393df36001{:064x}", 5));
+
+    // Templates must be hex around the placeholders
+    for template in ["{size}80{offset}3d393df", "{size}80{offset}zz", "{runtime}"] {
+        let config = CodegenConfig { bootstrap: Some(template.to_string()), ..Default::default() };
+        let err = Codegen::new()
+            .churn_with_config(Arc::new(FileSource::default()), vec![], "6001", "", &config)
+            .unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+    }
+}
@@ -13,7 +13,7 @@ fn encode_simple_constructor_args() {
     let expected_bytes32: Vec<u8> =
         str_to_vec("87674fa174add091f082eab424cc60625118fa4c553592a4e54a76fb9e8512f6").unwrap();
     // Bogus constructors args
-    let args: Vec<String> = vec![
+    let args: Vec<String> = [
         "Hello",
         "10000",
         "false",
@@ -26,7 +26,7 @@ fn encode_simple_constructor_args() {
     .map(|s| s.to_string())
     .collect();
 
-    let results = Codegen::encode_constructor_args(args);
+    let results = Codegen::encode_constructor_args(args).unwrap();
     assert_eq!(results[0], Token::String("Hello".to_string()));
     assert_eq!(results[1], Token::Uint(U256::from_dec_str("10000").unwrap()));
     assert_eq!(results[2], Token::Bool(false));
@@ -44,7 +44,7 @@ fn encode_array_constructor_args() {
     let _expected_bytes32: Vec<u8> =
         str_to_vec("87674fa174add091f082eab424cc60625118fa4c553592a4e54a76fb9e8512f6").unwrap();
     // Bogus constructors args
-    let args: Vec<String> = vec![
+    let args: Vec<String> = [
         "[100, 200, 300]",
         "[0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087, 0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087]",
         "[true, false, false]",
@@ -56,7 +56,7 @@ fn encode_array_constructor_args() {
     .map(|s| s.to_string())
     .collect();
 
-    let results = Codegen::encode_constructor_args(args);
+    let results = Codegen::encode_constructor_args(args).unwrap();
 
     assert_eq!(
         results[0],
@@ -95,7 +95,7 @@ fn encode_missing_brackets_array_constructor_args() {
     let _expected_bytes32: Vec<u8> =
         str_to_vec("87674fa174add091f082eab424cc60625118fa4c553592a4e54a76fb9e8512f6").unwrap();
     // Bogus constructors args
-    let args: Vec<String> = vec![
+    let args: Vec<String> = [
         "  100,  200,  300   ",
         " 0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087,    0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087",
         "true,  false,   false",
@@ -107,7 +107,7 @@ fn encode_missing_brackets_array_constructor_args() {
     .map(|s| s.to_string())
     .collect();
 
-    let results = Codegen::encode_constructor_args(args);
+    let results = Codegen::encode_constructor_args(args).unwrap();
 
     assert_eq!(
         results[0],
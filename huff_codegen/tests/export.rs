@@ -0,0 +1,67 @@
+use std::{fs, io, sync::Arc};
+
+use huff_codegen::Codegen;
+use huff_utils::prelude::*;
+
+/// A writer that always fails
+struct BrokenWriter;
+
+impl io::Write for BrokenWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("broken pipe"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn writes_artifact_to_memory() {
+    let artifact = Artifact { runtime: "6001".to_string(), ..Default::default() };
+    let mut buffer = Vec::new();
+    Codegen::write_artifact(&mut buffer, &artifact).unwrap();
+
+    let written: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    assert_eq!(written["runtime"], "6001");
+}
+
+#[test]
+fn surfaces_write_errors() {
+    let err = Codegen::write_artifact(&mut BrokenWriter, &Artifact::default()).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::IOError("broken pipe".to_string()));
+}
+
+#[test]
+fn exports_into_missing_directories() {
+    let dir = std::env::temp_dir().join(format!("huff-export-{}", uuid::Uuid::new_v4()));
+    let output = dir.join("nested").join("artifact.json");
+
+    let mut cg = Codegen::new();
+    cg.churn(Arc::new(FileSource::default()), vec![], "6001", "").unwrap();
+    cg.export_artifact(output.to_string_lossy().to_string()).unwrap();
+
+    let written: Artifact = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(written.runtime, "6001");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn fails_to_export_without_artifact() {
+    let err = Codegen::new().export_artifact("./unused.json".to_string()).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingArtifact);
+    assert_eq!(err.span.0[0].file.as_ref().unwrap().path, "./unused.json");
+}
+
+#[test]
+fn surfaces_io_errors_with_the_output_path() {
+    // A directory can't be overwritten by the artifact file
+    let dir = std::env::temp_dir().join(format!("huff-export-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.to_string_lossy().to_string();
+
+    let err = Codegen::export(output.clone(), &Artifact::default()).unwrap_err();
+    assert!(matches!(err.kind, CodegenErrorKind::IOError(_)));
+    assert_eq!(err.span.0[0].file.as_ref().unwrap().path, output);
+    fs::remove_dir_all(dir).unwrap();
+}
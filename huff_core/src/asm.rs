@@ -0,0 +1,160 @@
+use huff_utils::prelude::{opcode_mnemonic, stack_effect, static_gas, Instruction, Instructions};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The opcodes ending a basic block: STOP, JUMP, JUMPI, RETURN, REVERT, INVALID and SELFDESTRUCT
+const TERMINATORS: [u8; 7] = [0x00, 0x56, 0x57, 0xf3, 0xfd, 0xfe, 0xff];
+
+/// The JUMPDEST opcode, starting a basic block
+const JUMPDEST: u8 = 0x5b;
+
+/// An Instruction of an Assembly Listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmInstruction {
+    /// The byte offset of the instruction
+    pub offset: usize,
+    /// The opcode byte
+    pub opcode: u8,
+    /// The uppercase opcode mnemonic
+    pub mnemonic: String,
+    /// The hex encoded push data, for PUSH instructions
+    pub data: Option<String>,
+    /// The static gas cost of the instruction
+    pub gas: u64,
+    /// The number of items on the stack after the instruction, or `None` if it can't be known
+    pub depth: Option<usize>,
+}
+
+impl AsmInstruction {
+    /// The instruction as written in assembly, ie. `PUSH1 0x80`
+    pub fn text(&self) -> String {
+        match &self.data {
+            Some(data) => format!("{} 0x{}", self.mnemonic, data),
+            None => self.mnemonic.clone(),
+        }
+    }
+}
+
+/// A Basic Block of an Assembly Listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmBlock {
+    /// The byte offset of the first instruction
+    pub offset: usize,
+    /// The instructions of the block
+    pub instructions: Vec<AsmInstruction>,
+}
+
+impl AsmBlock {
+    /// The static gas of executing the block straight through
+    pub fn gas(&self) -> u64 {
+        self.instructions.iter().map(|i| i.gas).sum()
+    }
+}
+
+/// An Assembly Listing of Bytecode, Annotated With Static Gas
+///
+/// Blocks start at each JUMPDEST and after each instruction that halts or jumps. Dynamic costs,
+/// ie. memory expansion or cold access, are not included.
+///
+/// Stack depths are simulated from an empty stack. After halting instructions and unconditional
+/// jumps the depth is unknown until a JUMPDEST jumped to from a known depth, with the destination
+/// pushed right before the jump.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AsmListing {
+    /// The basic blocks of the bytecode
+    pub blocks: Vec<AsmBlock>,
+}
+
+impl AsmListing {
+    /// Disassembles a hex encoded bytecode string into basic blocks
+    pub fn new(bytecode: &str) -> Self {
+        let mut blocks: Vec<AsmBlock> = vec![];
+        let mut ends_block = true;
+        let mut depth = Some(0);
+        let mut jump_depths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut pushed: Option<usize> = None;
+        for Instruction { pc: offset, opcode, data } in Instructions::new(bytecode) {
+            let data = (0x60..=0x7f).contains(&opcode).then(|| data.to_string());
+            let mnemonic = opcode_mnemonic(opcode)
+                .map(|o| o.to_uppercase())
+                .unwrap_or_else(|| format!("UNKNOWN(0x{:02x})", opcode));
+
+            if ends_block || opcode == JUMPDEST {
+                blocks.push(AsmBlock { offset, instructions: vec![] });
+            }
+            ends_block = TERMINATORS.contains(&opcode);
+
+            if opcode == JUMPDEST {
+                depth = depth.or_else(|| jump_depths.get(&offset).copied());
+            }
+            let (pops, pushes) = stack_effect(opcode);
+            depth = depth.and_then(|d| d.checked_sub(pops));
+            if let (0x56 | 0x57, Some(destination), Some(d)) = (opcode, pushed, depth) {
+                jump_depths.entry(destination).or_insert(d);
+            }
+            // Conditional jumps fall through to the next instruction
+            depth = match ends_block && opcode != 0x57 {
+                true => None,
+                false => depth.map(|d| d + pushes),
+            };
+            pushed = data.as_deref().and_then(|d| usize::from_str_radix(d, 16).ok());
+            if let Some(block) = blocks.last_mut() {
+                block.instructions.push(AsmInstruction {
+                    offset,
+                    opcode,
+                    mnemonic,
+                    data,
+                    gas: static_gas(opcode),
+                    depth,
+                });
+            }
+        }
+        AsmListing { blocks }
+    }
+
+    /// The static gas of every instruction
+    pub fn gas(&self) -> u64 {
+        self.blocks.iter().map(AsmBlock::gas).sum()
+    }
+
+    /// Renders the listing with the stack depth after each instruction, its static gas, and the
+    /// cumulative gas of each instruction within its block. Unknown depths are shown as `?`.
+    pub fn render(&self) -> String {
+        let texts = self
+            .blocks
+            .iter()
+            .map(|b| b.instructions.iter().map(AsmInstruction::text).collect::<Vec<String>>())
+            .collect::<Vec<Vec<String>>>();
+        let width = texts.iter().flatten().map(String::len).max().unwrap_or_default().max(11);
+
+        let mut listing = format!(
+            "{:<6}  {:<width$} {:>5} {:>6} {:>10}\n",
+            "offset",
+            "instruction",
+            "stack",
+            "gas",
+            "block gas",
+            width = width
+        );
+        for (block, texts) in self.blocks.iter().zip(texts) {
+            let _ = writeln!(listing, "; block 0x{:04x}: {} gas", block.offset, block.gas());
+            let mut cumulative = 0;
+            for (instruction, text) in block.instructions.iter().zip(texts) {
+                cumulative += instruction.gas;
+                let depth =
+                    instruction.depth.map(|d| d.to_string()).unwrap_or_else(|| "?".to_string());
+                let _ = writeln!(
+                    listing,
+                    "0x{:04x}  {:<width$} {:>5} {:>6} {:>10}",
+                    instruction.offset,
+                    text,
+                    depth,
+                    instruction.gas,
+                    cumulative,
+                    width = width
+                );
+            }
+        }
+        let _ = writeln!(listing, "; total: {} gas", self.gas());
+        listing
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use huff_utils::prelude::{Artifact, FileSource, OutputLocation};
+use huff_utils::prelude::{Artifact, BuildSettings, FileSource, OutputLocation};
 use walkdir::WalkDir;
 
 /// Parallelized Artifact Cachcing
@@ -8,11 +8,12 @@ pub fn get_cached_artifacts(
     files: &[Arc<FileSource>],
     out: &OutputLocation,
     constructor_args: String,
+    settings: &BuildSettings,
 ) -> Option<Vec<Arc<Artifact>>> {
     // Check if the file artifacts are already generated the the default "./artifacts/" directory or
     // the specified output dir
     let artifacts: Vec<(Arc<FileSource>, Artifact)> =
-        resolve_existing_artifacts(files, out, constructor_args)?;
+        resolve_existing_artifacts(files, out, constructor_args, settings)?;
 
     // Return the artifacts if cached
     Some(artifacts.into_iter().map(|(_, artifact)| Arc::new(artifact)).collect())
@@ -23,6 +24,7 @@ pub fn resolve_existing_artifacts(
     files: &[Arc<FileSource>],
     output: &OutputLocation,
     constructor_args: String,
+    settings: &BuildSettings,
 ) -> Option<Vec<(Arc<FileSource>, Artifact)>> {
     let mut artifacts: Vec<(Arc<FileSource>, Artifact)> = Vec::new();
 
@@ -31,14 +33,15 @@ pub fn resolve_existing_artifacts(
         files.iter().map(|f| (f.path.clone().to_lowercase(), Arc::clone(f))).collect();
 
     // If outputdir is not specified, use the default "./artifacts/" directory
-    let output_dir = (!output.0.is_empty()).then_some(&*output.0).unwrap_or("./artifacts");
+    let output_dir = if !output.0.is_empty() { &*output.0 } else { "./artifacts" };
 
     // For each file, check if the artifact file exists at the location
     tracing::debug!(target: "core", "Traversing output directory {}", output_dir);
-    for entry in WalkDir::new(&output_dir)
+    for entry in WalkDir::new(output_dir)
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| !e.file_type().is_dir())
+        .filter(|e| e.path().extension().map(|ext| ext == "json").unwrap_or(false))
     {
         // Are we expecting this file to be compiled
         let formatted_path = entry
@@ -46,12 +49,15 @@ pub fn resolve_existing_artifacts(
             .display()
             .to_string()
             .replace(".json", "")
-            .replace(&output_dir, ".")
+            .replace(output_dir, ".")
             .to_lowercase();
         let expected = file_sources.remove(&formatted_path);
 
         // Try to read the file into an artifact
-        match serde_json::from_str::<Artifact>(&std::fs::read_to_string(entry.path()).unwrap()) {
+        let artifact = std::fs::read_to_string(entry.path())
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Artifact>(&s).map_err(|e| e.to_string()));
+        match artifact {
             Ok(artifact) => {
                 // If we expected compilation, the sources must match
                 match expected {
@@ -60,6 +66,10 @@ pub fn resolve_existing_artifacts(
                             tracing::warn!(target: "core", "Mismatched Constructor Args for Cached Artifact \"{}\"", artifact.file.path);
                             return None
                         }
+                        if &artifact.settings != settings {
+                            tracing::warn!(target: "core", "Mismatched Build Settings for Cached Artifact \"{}\"", artifact.file.path);
+                            return None
+                        }
                         if artifact.file.source != expected_fs.source {
                             tracing::warn!(target: "core", "Cache Resolution Failed: \"{}\" Artifact Outdated", artifact.file.path);
                             return None
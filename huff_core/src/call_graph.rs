@@ -0,0 +1,214 @@
+use crate::{hover::flattened_contract, index::walk_statements};
+use huff_codegen::{config::CodegenConfig, Codegen};
+use huff_utils::prelude::{
+    BuiltinFunctionKind, CodegenError, CompilerError, Contract, FileSource, MacroSize, MacroSizes,
+    Remapping, SerializationError, StatementType,
+};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// The macros compilation starts from, which are expanded once rather than invoked
+const ROOTS: [&str; 2] = ["MAIN", "CONSTRUCTOR"];
+
+/// A Macro or Fn of a Call Graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MacroNode {
+    /// The macro name
+    pub name: String,
+    /// Whether the macro is a fn, called rather than inlined
+    pub outlined: bool,
+    /// The number of times the macro is expanded. A fn's body is appended once to each bytecode
+    pub invocations: usize,
+    /// The bytes all of the macro's expansions contribute, including nested macros
+    pub size: usize,
+}
+
+/// An Edge From a Macro to a Macro it Invokes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallEdge {
+    /// The invoking macro
+    pub caller: String,
+    /// The invoked macro
+    pub callee: String,
+    /// The number of invocations written in the caller's body
+    pub count: usize,
+}
+
+/// A Macro Invocation Graph
+///
+/// Edges count the invocations written in each macro body, while the expansions and sizes of each
+/// macro are those of compiling `MAIN` and `CONSTRUCTOR`, so a macro invoked twice by a macro
+/// that is itself invoked twice is expanded four times.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CallGraph {
+    /// The macros, in definition order
+    pub nodes: Vec<MacroNode>,
+    /// The invocations, grouped by caller in definition order
+    pub edges: Vec<CallEdge>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of a contract. `__DEBUG` invocations are only included in debug
+    /// builds, as they're stripped otherwise.
+    pub fn new(contract: &Contract, config: &CodegenConfig) -> Result<Self, CodegenError> {
+        let mut sizes = MacroSizes::new();
+        for root in ROOTS.iter().filter(|r| contract.find_macro_by_name(r).is_some()) {
+            let (root_sizes, bytecode) = match *root {
+                "MAIN" => (
+                    Codegen::generate_main_macro_sizes(contract, config)?,
+                    Codegen::generate_main_bytecode_with_config(contract, config)?,
+                ),
+                _ => (
+                    Codegen::generate_constructor_macro_sizes(contract, config)?,
+                    Codegen::generate_constructor_bytecode_with_config(contract, config)?,
+                ),
+            };
+            for (name, size) in root_sizes {
+                let entry = sizes.entry(name).or_default();
+                entry.invocations += size.invocations;
+                entry.size += size.size;
+            }
+            sizes.insert(root.to_string(), MacroSize { invocations: 1, size: bytecode.len() / 2 });
+        }
+
+        let mut edges: Vec<CallEdge> = vec![];
+        for m in &contract.macros {
+            walk_statements(&m.statements, &mut |s| {
+                let callee = match &s.ty {
+                    StatementType::MacroInvocation(mi) => &mi.macro_name,
+                    StatementType::BuiltinFunctionCall(bf)
+                        if bf.kind == BuiltinFunctionKind::Debug && config.debug =>
+                    {
+                        match bf.args.first().and_then(|a| a.name.as_ref()) {
+                            Some(name) => name,
+                            None => return,
+                        }
+                    }
+                    _ => return,
+                };
+                match edges.iter_mut().find(|e| e.caller == m.name && &e.callee == callee) {
+                    Some(edge) => edge.count += 1,
+                    None => edges.push(CallEdge {
+                        caller: m.name.clone(),
+                        callee: callee.clone(),
+                        count: 1,
+                    }),
+                }
+            });
+        }
+
+        let nodes = contract
+            .macros
+            .iter()
+            .map(|m| {
+                let size = sizes.get(&m.name).copied().unwrap_or_default();
+                MacroNode {
+                    name: m.name.clone(),
+                    outlined: m.outlined,
+                    invocations: size.invocations,
+                    size: size.size,
+                }
+            })
+            .collect();
+        Ok(CallGraph { nodes, edges })
+    }
+
+    /// Builds the call graph of a file with its dependencies resolved
+    pub fn from_file(
+        file: &FileSource,
+        remappings: &[Remapping],
+        config: &CodegenConfig,
+    ) -> Result<Self, String> {
+        let (contract, _) = flattened_contract(file, remappings)
+            .ok_or_else(|| format!("Failed to parse \"{}\"", file.path))?;
+        CallGraph::new(&contract, config).map_err(|e| CompilerError::CodegenError(e).to_string())
+    }
+
+    /// The macros that are never expanded, excluding `MAIN` and `CONSTRUCTOR`
+    pub fn unused(&self) -> Vec<&MacroNode> {
+        self.nodes.iter().filter(|n| n.invocations == 0 && !ROOTS.contains(&&*n.name)).collect()
+    }
+
+    /// Renders the graph as a tree from `MAIN` and `CONSTRUCTOR`, followed by the unused macros.
+    ///
+    /// Each invocation shows the number of times it's written in the caller, and the expansions
+    /// and bytes of the invoked macro across the whole contract. Recursive invocations aren't
+    /// expanded again.
+    pub fn render_tree(&self) -> String {
+        let mut tree = String::new();
+        for root in self.nodes.iter().filter(|n| ROOTS.contains(&&*n.name)) {
+            let _ = writeln!(tree, "{} ({} bytes)", root.name, root.size);
+            self.render_invocations(&root.name, "", &mut vec![root.name.clone()], &mut tree);
+        }
+        let unused = self.unused();
+        if !unused.is_empty() {
+            let names = unused.iter().map(|n| n.name.as_str()).collect::<Vec<&str>>();
+            let _ = writeln!(tree, "Unused: {}", names.join(", "));
+        }
+        tree
+    }
+
+    /// Renders the invocations of a macro as the branches of a tree
+    fn render_invocations(
+        &self,
+        caller: &str,
+        prefix: &str,
+        path: &mut Vec<String>,
+        tree: &mut String,
+    ) {
+        let edges = self.edges.iter().filter(|e| e.caller == caller).collect::<Vec<&CallEdge>>();
+        for (i, edge) in edges.iter().enumerate() {
+            let last = i + 1 == edges.len();
+            let (branch, indent) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+            let node = self.nodes.iter().find(|n| n.name == edge.callee);
+            let summary = match node {
+                Some(n) if n.outlined => format!("fn, {} copies, {} bytes", n.invocations, n.size),
+                Some(n) => format!("{} expansions, {} bytes", n.invocations, n.size),
+                None => "undefined".to_string(),
+            };
+            let _ =
+                writeln!(tree, "{}{}{} x{} ({})", prefix, branch, edge.callee, edge.count, summary);
+            if node.is_some() && !path.contains(&edge.callee) {
+                path.push(edge.callee.clone());
+                self.render_invocations(&edge.callee, &format!("{}{}", prefix, indent), path, tree);
+                path.pop();
+            }
+        }
+    }
+
+    /// Renders the graph in the graphviz DOT language. Fns are drawn as ellipses, and unused
+    /// macros are dashed.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", name.replace('"', "\\\""));
+        dot.push_str("    node [shape=box];\n");
+        for node in &self.nodes {
+            let label = match node.invocations {
+                0 if !ROOTS.contains(&&*node.name) => "unused".to_string(),
+                _ if ROOTS.contains(&&*node.name) => format!("{} bytes", node.size),
+                n => format!("{} expansions, {} bytes", n, node.size),
+            };
+            let mut attributes = format!("label=\"{}\\n{}\"", node.name, label);
+            if node.outlined {
+                attributes.push_str(" shape=ellipse");
+            }
+            if node.invocations == 0 && !ROOTS.contains(&&*node.name) {
+                attributes.push_str(" style=dashed");
+            }
+            let _ = writeln!(dot, "    \"{}\" [{}];", node.name, attributes);
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"x{}\"];",
+                edge.caller, edge.callee, edge.count
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serializes the graph as pretty printed json
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{asm::AsmListing, simulation::locate, symbolic::SymbolicTarget};
+use huff_utils::prelude::{Artifact, SerializationError};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// The Kind of a Control Flow Edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    /// An unconditional jump
+    Jump,
+    /// A conditional jump that is taken
+    Branch,
+    /// A dispatcher jump taken when the selector matches
+    Dispatch,
+    /// Continuing to the next block, ie. when a conditional jump isn't taken
+    Fallthrough,
+}
+
+/// A Control Flow Edge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CfgEdge {
+    /// The offset of the block the edge leaves
+    pub from: usize,
+    /// The offset of the block the edge enters
+    pub to: usize,
+    /// The kind of edge
+    pub kind: EdgeKind,
+    /// The hex encoded function selector of a dispatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    /// The function signature of a dispatch, if the selector is in the abi
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl CfgEdge {
+    /// Public associated function to instantiate an edge that isn't a dispatch
+    pub fn new(from: usize, to: usize, kind: EdgeKind) -> Self {
+        CfgEdge { from, to, kind, selector: None, signature: None }
+    }
+}
+
+/// A Basic Block of a Control Flow Graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CfgBlock {
+    /// The byte offset of the first instruction
+    pub offset: usize,
+    /// The instructions of the block as written in assembly, ie. `PUSH1 0x80`
+    pub instructions: Vec<String>,
+    /// The macro or fn the block's first instruction past its jumpdest was generated from, as a
+    /// label's jumpdest belongs to the macro defining the label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<String>,
+    /// Whether the block ends with a jump whose destination isn't pushed right before it
+    pub unresolved_jump: bool,
+}
+
+/// A Control Flow Graph of an Artifact's Runtime
+///
+/// Jump destinations are resolved from the push preceding each jump, which is how Huff compiles
+/// label calls. Jumps to computed destinations are marked unresolved rather than guessed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ControlFlowGraph {
+    /// The basic blocks, in bytecode order
+    pub blocks: Vec<CfgBlock>,
+    /// The edges between blocks
+    pub edges: Vec<CfgEdge>,
+}
+
+impl ControlFlowGraph {
+    /// Builds the control flow graph of an artifact's runtime
+    pub fn new(artifact: &Artifact) -> Self {
+        let listing = AsmListing::new(&artifact.runtime);
+        let locations = locate(artifact);
+        let entry_points = SymbolicTarget::new(artifact).entry_points;
+        let is_jumpdest = |offset: usize| {
+            listing.blocks.iter().any(|b| b.offset == offset && b.instructions[0].opcode == 0x5b)
+        };
+
+        let mut graph = ControlFlowGraph::default();
+        for (i, block) in listing.blocks.iter().enumerate() {
+            let next = listing.blocks.get(i + 1).map(|b| b.offset);
+            let last = block.instructions.last();
+            let pushed = block
+                .instructions
+                .iter()
+                .rev()
+                .nth(1)
+                .and_then(|p| p.data.as_deref())
+                .and_then(|d| usize::from_str_radix(d, 16).ok())
+                .filter(|d| is_jumpdest(*d));
+
+            let opcode = last.map(|l| l.opcode).unwrap_or_default();
+            match (opcode, pushed) {
+                (0x56, Some(to)) => {
+                    graph.edges.push(CfgEdge::new(block.offset, to, EdgeKind::Jump))
+                }
+                (0x57, Some(to)) => {
+                    // Dispatches compare a pushed selector, ie. `dup1 __FUNC_SIG(f) eq f jumpi`
+                    let dispatch = entry_points.iter().find(|e| {
+                        e.pc == to &&
+                            block.instructions.iter().any(|i| {
+                                i.data.as_deref().is_some_and(|d| {
+                                    d.len() <= 8 && format!("0x{:0>8}", d) == e.selector
+                                })
+                            })
+                    });
+                    match dispatch {
+                        Some(e) => graph.edges.push(CfgEdge {
+                            from: block.offset,
+                            to,
+                            kind: EdgeKind::Dispatch,
+                            selector: Some(e.selector.clone()),
+                            signature: e.signature.clone(),
+                        }),
+                        None => graph.edges.push(CfgEdge::new(block.offset, to, EdgeKind::Branch)),
+                    }
+                }
+                _ => {}
+            }
+            let falls_through = !matches!(opcode, 0x00 | 0x56 | 0xf3 | 0xfd | 0xfe | 0xff);
+            if let Some(next) = next.filter(|_| falls_through) {
+                graph.edges.push(CfgEdge::new(block.offset, next, EdgeKind::Fallthrough));
+            }
+
+            graph.blocks.push(CfgBlock {
+                offset: block.offset,
+                instructions: block.instructions.iter().map(|i| i.text()).collect(),
+                definition: block
+                    .instructions
+                    .iter()
+                    .filter(|i| i.opcode != 0x5b)
+                    .chain(block.instructions.first())
+                    .find_map(|i| locations.get(&i.offset))
+                    .and_then(|l| l.definition.clone()),
+                unresolved_jump: matches!(opcode, 0x56 | 0x57) && pushed.is_none(),
+            });
+        }
+        graph
+    }
+
+    /// Serializes the graph as pretty printed json
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Renders the graph in the graphviz DOT language.
+    ///
+    /// Taken branches are green and the fallthroughs of conditional jumps red. Dispatches are
+    /// labelled with their function, and blocks ending in unresolved jumps are dashed.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", name.replace('"', "\\\""));
+        dot.push_str("    node [shape=box fontname=\"monospace\"];\n");
+        for block in &self.blocks {
+            let header = match &block.definition {
+                Some(d) => format!("0x{:04x} {}", block.offset, d),
+                None => format!("0x{:04x}", block.offset),
+            };
+            let style = if block.unresolved_jump { " style=dashed" } else { "" };
+            let _ = writeln!(
+                dot,
+                "    b{} [label=\"{}\\l{}\\l\"{}];",
+                block.offset,
+                header,
+                block.instructions.join("\\l"),
+                style
+            );
+        }
+        for edge in &self.edges {
+            let conditional = self.edges.iter().any(|e| {
+                e.from == edge.from && matches!(e.kind, EdgeKind::Branch | EdgeKind::Dispatch)
+            });
+            let attributes = match edge.kind {
+                EdgeKind::Jump => String::new(),
+                EdgeKind::Branch => " [color=green]".to_string(),
+                EdgeKind::Dispatch => format!(
+                    " [color=green label=\"{}\"]",
+                    edge.signature.as_ref().or(edge.selector.as_ref()).cloned().unwrap_or_default()
+                ),
+                EdgeKind::Fallthrough if conditional => " [color=red]".to_string(),
+                EdgeKind::Fallthrough => " [style=dashed]".to_string(),
+            };
+            let _ = writeln!(dot, "    b{} -> b{}{};", edge.from, edge.to, attributes);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    docs::{constant_signature, macro_signature, DocumentedContract},
+    index::flatten_dependencies,
+};
+use huff_lexer::Lexer;
+use huff_utils::prelude::{
+    FileSource, FullFileSource, Remapping, Token, TokenKind, OPCODES, OPCODES_MAP,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// The builtin functions available in macro bodies, with their signature and documentation
+const BUILTINS: [(&str, &str, &str); 17] = [
+    ("__tablesize", "__tablesize(TABLE)", "Pushes the size of a jump table or code table"),
+    ("__tablestart", "__tablestart(TABLE)", "Pushes the offset of a jump table or code table"),
+    ("__codesize", "__codesize(MACRO)", "Pushes the size of a macro's bytecode"),
+    ("__FUNC_SIG", "__FUNC_SIG(function)", "Pushes the 4 byte selector of a function"),
+    ("__EVENT_HASH", "__EVENT_HASH(event)", "Pushes the 32 byte topic of an event"),
+    ("__ERROR", "__ERROR(error)", "Pushes the left aligned 4 byte selector of a custom error"),
+    (
+        "__CREATE_ADDRESS",
+        "__CREATE_ADDRESS(deployer, nonce)",
+        "Pushes the address of a contract deployed with CREATE",
+    ),
+    (
+        "__CREATE2_ADDRESS",
+        "__CREATE2_ADDRESS(deployer, salt, init_code_hash)",
+        "Pushes the address of a contract deployed with CREATE2",
+    ),
+    ("__MASK", "__MASK(bits, offset)", "Pushes a mask of `bits` set bits shifted left by `offset`"),
+    ("__SHL_CONST", "__SHL_CONST(value, shift)", "Pushes a constant shifted left at compile time"),
+    ("__RIGHTPAD", "__RIGHTPAD(literal)", "Pushes a literal right padded to 32 bytes"),
+    ("__VERBATIM", "__VERBATIM(bytecode)", "Inserts raw bytecode as written"),
+    ("__DISPATCH", "__DISPATCH()", "Jumps to the label named after the selector's function"),
+    (
+        "__CONSTRUCTOR_ARG",
+        "__CONSTRUCTOR_ARG(index)",
+        "Pushes a single word constructor argument appended to the initcode",
+    ),
+    ("__IMMUTABLE", "__IMMUTABLE(NAME)", "Pushes an immutable set by the constructor"),
+    ("__SET_IMMUTABLE", "__SET_IMMUTABLE(NAME)", "Sets an immutable from the constructor"),
+    ("__DEBUG", "__DEBUG(MACRO)", "Expands a stack neutral macro in debug builds only"),
+];
+
+/// The Kind of a Completion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    /// A macro or fn defined in the project
+    Macro,
+    /// A constant defined in the project
+    Constant,
+    /// A builtin function
+    Builtin,
+    /// An EVM opcode
+    Opcode,
+}
+
+/// A Completion Suggestion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompletionItem {
+    /// The completed text
+    pub label: String,
+    /// The completion kind
+    pub kind: CompletionKind,
+    /// A short description, ie. the definition signature or opcode byte
+    pub detail: String,
+    /// The doc comment of the completed definition
+    pub docs: String,
+}
+
+/// Returns the completions for the identifier being written at an offset in a file.
+///
+/// Suggestions depend on the cursor context: constants inside `[]`, builtins after `__`, and
+/// macros and opcodes elsewhere in macro bodies. They are ranked by how closely they match the
+/// written prefix, project definitions before opcodes.
+pub fn completions(
+    files: &[Arc<FileSource>],
+    remappings: &[Remapping],
+    file: &str,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let sources = flatten_dependencies(files);
+    let source = match sources.iter().find(|f| f.path == file).and_then(|f| f.source.as_deref()) {
+        Some(s) if s.is_char_boundary(offset) => s,
+        _ => return vec![],
+    };
+    let prefix_start = source[..offset]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let prefix = &source[prefix_start..offset];
+
+    let kinds = match completion_context(source, prefix_start) {
+        Some(TokenKind::OpenBracket) => vec![CompletionKind::Constant],
+        Some(_) if prefix.starts_with("__") => vec![CompletionKind::Builtin],
+        Some(_) => vec![CompletionKind::Macro, CompletionKind::Opcode],
+        None => return vec![],
+    };
+
+    let mut items = vec![];
+    if kinds.contains(&CompletionKind::Macro) || kinds.contains(&CompletionKind::Constant) {
+        for f in &sources {
+            let documented = match DocumentedContract::parse(f, remappings) {
+                Some(d) => d,
+                None => continue,
+            };
+            if kinds.contains(&CompletionKind::Macro) {
+                items.extend(documented.contract.macros.iter().map(|m| CompletionItem {
+                    label: m.name.clone(),
+                    kind: CompletionKind::Macro,
+                    detail: macro_signature(m),
+                    docs: documented.docs(&m.span),
+                }));
+            }
+            if kinds.contains(&CompletionKind::Constant) {
+                items.extend(documented.contract.constants.borrow().iter().map(|c| {
+                    CompletionItem {
+                        label: c.name.clone(),
+                        kind: CompletionKind::Constant,
+                        detail: constant_signature(c),
+                        docs: documented.docs(&c.span),
+                    }
+                }));
+            }
+        }
+    }
+    if kinds.contains(&CompletionKind::Builtin) {
+        items.extend(BUILTINS.iter().map(|(name, signature, docs)| CompletionItem {
+            label: name.to_string(),
+            kind: CompletionKind::Builtin,
+            detail: signature.to_string(),
+            docs: docs.to_string(),
+        }));
+    }
+    if kinds.contains(&CompletionKind::Opcode) {
+        items.extend(OPCODES.iter().map(|o| CompletionItem {
+            label: o.to_string(),
+            kind: CompletionKind::Opcode,
+            detail: format!("0x{}", OPCODES_MAP[*o]),
+            docs: String::new(),
+        }));
+    }
+
+    // Keep prefix matches, exact case first, then by kind, length, and name
+    let lower = prefix.to_lowercase();
+    items.retain(|i| i.label.to_lowercase().starts_with(&lower));
+    items.sort_by(|a, b| {
+        (!a.label.starts_with(prefix), a.kind, a.label.len(), &a.label).cmp(&(
+            !b.label.starts_with(prefix),
+            b.kind,
+            b.label.len(),
+            &b.label,
+        ))
+    });
+    items.dedup_by(|a, b| a.label == b.label && a.kind == b.kind);
+    items
+}
+
+/// Returns the token preceding an offset if it lies in a macro body, or None otherwise
+fn completion_context(source: &str, offset: usize) -> Option<TokenKind> {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source)
+        .map_while(Result::ok)
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment(_)))
+        .take_while(|t| t.span.end <= offset)
+        .collect::<Vec<Token>>();
+
+    let mut in_macro = false;
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::Define => in_macro = false,
+            TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
+                in_macro = i.checked_sub(1).map(|p| &tokens[p].kind) == Some(&TokenKind::Define);
+            }
+            TokenKind::OpenBrace => depth += 1,
+            TokenKind::CloseBrace => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    match tokens.last() {
+        // Parameters and builtin arguments are not completed
+        Some(Token { kind: TokenKind::LeftAngle | TokenKind::OpenParen, .. }) => None,
+        Some(t) if in_macro && depth > 0 => Some(t.kind.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,398 @@
+use crate::{
+    index::{Location, SymbolIndex},
+    Compiler,
+};
+use huff_utils::prelude::{Artifact, CompilerError, CompilerWarning, FileSource, Span};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Error, ErrorKind, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// The address the daemon listens on by default
+pub const DEFAULT_DAEMON_ADDRESS: &str = "127.0.0.1:4545";
+
+/// The JSON-RPC error code of a request that isn't valid json
+pub const PARSE_ERROR: i64 = -32700;
+
+/// The JSON-RPC error code of an unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The JSON-RPC error code of invalid method parameters
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// The JSON-RPC error code of a request whose files failed to parse
+pub const COMPILE_ERROR: i64 = -32000;
+
+/// The artifact of a compiled file, kept while its flattened source is unchanged
+#[derive(Debug, Clone)]
+struct CachedArtifact {
+    source: String,
+    artifact: Arc<Artifact>,
+    warnings: Vec<Diagnostic>,
+}
+
+/// The artifacts and diagnostics of the requested files
+#[derive(Debug, Default)]
+struct Build {
+    artifacts: Vec<Arc<Artifact>>,
+    diagnostics: Vec<Diagnostic>,
+    cached: Vec<String>,
+}
+
+/// The result of a compile request
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct DaemonCompileResult {
+    /// The artifacts of the files that compiled
+    pub artifacts: Vec<Arc<Artifact>>,
+    /// The errors of the files that failed to compile
+    pub errors: Vec<String>,
+    /// The warnings raised while compiling
+    pub warnings: Vec<String>,
+    /// The paths of the files whose artifacts were reused from a previous request
+    pub cached: Vec<String>,
+}
+
+/// The Severity of a Diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file failed to compile
+    Error,
+    /// The file compiled with a non-fatal issue
+    Warning,
+}
+
+/// A Compiler Error or Warning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// The diagnostic severity
+    pub severity: Severity,
+    /// The path of the requested file
+    pub file: String,
+    /// The diagnostic message
+    pub message: String,
+    /// The source range the diagnostic points at, which may be in an imported file
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    /// Creates the diagnostic of a file's compiler error
+    pub fn error(file: &str, error: &CompilerError) -> Self {
+        let span = match error {
+            CompilerError::LexicalError(le) => Some(le.span.clone()),
+            CompilerError::ParserError(pe) => pe.spans.0.first().cloned(),
+            CompilerError::CodegenError(ce) => ce.span.0.first().cloned(),
+            _ => None,
+        };
+        Self {
+            severity: Severity::Error,
+            file: file.to_string(),
+            message: error.to_string().trim().to_string(),
+            location: span.filter(|s| *s != Span::EOF).map(|s| Location {
+                file: s.file.as_ref().map_or_else(|| file.to_string(), |f| f.path.clone()),
+                start: s.start,
+                end: s.end,
+            }),
+        }
+    }
+
+    /// Creates the diagnostic of a warning raised while compiling a file, possibly in its imports
+    pub fn warning(file: &str, warning: &CompilerWarning) -> Self {
+        Self {
+            severity: Severity::Warning,
+            file: file.to_string(),
+            message: warning.to_string(),
+            location: None,
+        }
+    }
+}
+
+/// The Selectors of a Compiled Contract
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Selectors {
+    /// The 4 byte selector of each function, by canonical signature
+    pub functions: BTreeMap<String, String>,
+    /// The topic of each event, by canonical signature
+    pub events: BTreeMap<String, String>,
+    /// The 4 byte selector of each custom error, by canonical signature
+    pub errors: BTreeMap<String, String>,
+}
+
+/// ## Daemon
+///
+/// A long lived compiler serving line delimited JSON-RPC 2.0 requests over a local socket.
+///
+/// The daemon compiles with the options of its [Compiler], and keeps the artifact of each
+/// compiled file in memory, reusing it while the file's flattened source (including its imports)
+/// is unchanged. Connections are served concurrently, and share the kept artifacts. Requests can
+/// also be passed to [handle](Daemon::handle) directly, to serve them over another transport.
+///
+/// #### Methods
+///
+/// Every method but `shutdown` takes a `paths` parameter of the files or directories to use.
+///
+/// - `compile` returns a [DaemonCompileResult].
+/// - `diagnostics` returns the [Diagnostic]s of compiling the files.
+/// - `selectors` returns the [Selectors] of each compiled file, by path.
+/// - `ast` returns the [SymbolIndex] of the files' parsed ASTs, or a [COMPILE_ERROR] if a file
+///   fails to parse.
+/// - `shutdown` stops the daemon once the response is written.
+#[derive(Debug, Default)]
+pub struct Daemon<'a> {
+    /// The compiler serving the requests
+    pub compiler: Compiler<'a>,
+    /// The cached artifacts, by file path
+    cache: Mutex<BTreeMap<String, CachedArtifact>>,
+    /// Whether a shutdown was requested
+    shutdown: AtomicBool,
+}
+
+impl<'a> Daemon<'a> {
+    /// Creates a daemon compiling with the given compiler's options
+    pub fn new(compiler: Compiler<'a>) -> Self {
+        Self { compiler, cache: Mutex::default(), shutdown: AtomicBool::new(false) }
+    }
+
+    /// Serves connections on the listener, each on its own thread, until a shutdown is requested
+    ///
+    /// The daemon compiles any file its clients ask for, so the listener must be bound to a
+    /// loopback address. A connection failing only drops that connection.
+    pub fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        let address = listener.local_addr()?;
+        if !address.ip().is_loopback() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The daemon only listens on loopback addresses, not {}", address),
+            ))
+        }
+
+        // Open connections are closed on shutdown, so their threads finish
+        let connections: Mutex<Vec<TcpStream>> = Mutex::default();
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                if self.shutdown.load(Ordering::SeqCst) {
+                    break
+                }
+                let stream = match stream {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!(target: "core", "FAILED TO ACCEPT DAEMON CONNECTION: {}", e);
+                        continue
+                    }
+                };
+                if let (Ok(clone), Ok(mut open)) = (stream.try_clone(), connections.lock()) {
+                    open.push(clone);
+                }
+                scope.spawn(move || {
+                    if let Err(e) = self.serve_connection(stream, address) {
+                        tracing::warn!(target: "core", "DROPPED DAEMON CONNECTION: {}", e);
+                    }
+                });
+            }
+            if let Ok(open) = connections.lock() {
+                open.iter().for_each(|s| {
+                    let _ = s.shutdown(Shutdown::Both);
+                });
+            }
+        });
+        Ok(())
+    }
+
+    /// Answers the requests of a connection until it closes or a shutdown is requested
+    fn serve_connection(&self, mut stream: TcpStream, address: SocketAddr) -> std::io::Result<()> {
+        let reader = BufReader::new(stream.try_clone()?);
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue
+            }
+            writeln!(stream, "{}", self.handle(&line))?;
+            if self.shutdown.load(Ordering::SeqCst) {
+                // Wake the listener blocked on accepting a connection, so it sees the shutdown
+                TcpStream::connect(address)?;
+                break
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a JSON-RPC request, returning the serialized response
+    pub fn handle(&self, request: &str) -> String {
+        let request: Value = match serde_json::from_str(request) {
+            Ok(r) => r,
+            Err(e) => return rpc_error(Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let paths = request
+            .get("params")
+            .and_then(|p| p.get("paths"))
+            .and_then(|p| serde_json::from_value::<Vec<String>>(p.clone()).ok());
+        let result = match (request.get("method").and_then(Value::as_str), paths) {
+            (Some("shutdown"), _) => {
+                self.shutdown.store(true, Ordering::SeqCst);
+                Value::Null
+            }
+            (Some(m @ ("compile" | "diagnostics" | "selectors" | "ast")), None) => {
+                let message = format!("Expected a `paths` array of strings for \"{}\"", m);
+                return rpc_error(id, INVALID_PARAMS, &message)
+            }
+            (Some("compile"), Some(paths)) => json!(self.compile(&paths)),
+            (Some("diagnostics"), Some(paths)) => json!(self.diagnostics(&paths)),
+            (Some("selectors"), Some(paths)) => json!(self.selectors(&paths)),
+            (Some("ast"), Some(paths)) => match self.ast(&paths) {
+                Ok(index) => json!(index),
+                Err(e) => return rpc_error(id, COMPILE_ERROR, &e),
+            },
+            (Some(m), _) => {
+                return rpc_error(id, METHOD_NOT_FOUND, &format!("Unknown method \"{}\"", m))
+            }
+            (None, _) => return rpc_error(id, METHOD_NOT_FOUND, "Missing method"),
+        };
+        json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string()
+    }
+
+    /// Compiles the files at the paths, reusing the cached artifacts of unchanged files
+    ///
+    /// If the compiler has an output location, the artifacts are exported to it.
+    pub fn compile(&self, paths: &[String]) -> DaemonCompileResult {
+        let build = self.build(paths);
+        if self.compiler.output.is_some() {
+            Compiler::export_artifacts(
+                &build.artifacts,
+                &self.compiler.get_outputs(),
+                &self.compiler.get_serializers(),
+            );
+        }
+        let (errors, warnings): (Vec<Diagnostic>, Vec<Diagnostic>) =
+            build.diagnostics.into_iter().partition(|d| d.severity == Severity::Error);
+        DaemonCompileResult {
+            artifacts: build.artifacts,
+            errors: errors.into_iter().map(|d| d.message).collect(),
+            warnings: warnings.into_iter().map(|d| d.message).collect(),
+            cached: build.cached,
+        }
+    }
+
+    /// Compiles the files at the paths, returning their errors and warnings
+    pub fn diagnostics(&self, paths: &[String]) -> Vec<Diagnostic> {
+        self.build(paths).diagnostics
+    }
+
+    /// Compiles the files at the paths, returning the selectors of each compiled file
+    pub fn selectors(&self, paths: &[String]) -> BTreeMap<String, Selectors> {
+        self.build(paths)
+            .artifacts
+            .iter()
+            .map(|a| {
+                let selectors = a.abi.as_ref().map_or_else(Selectors::default, |abi| Selectors {
+                    functions: abi.method_identifiers(),
+                    events: abi.event_topics(),
+                    errors: abi.error_selectors(),
+                });
+                (a.file.path.clone(), selectors)
+            })
+            .collect()
+    }
+
+    /// Parses the files at the paths and their imports into a [SymbolIndex]
+    pub fn ast(&self, paths: &[String]) -> Result<SymbolIndex, String> {
+        let (files, diagnostics) = self.resolve(paths);
+        if let Some(d) = diagnostics.into_iter().next() {
+            return Err(d.message)
+        }
+        SymbolIndex::build(&files, &self.compiler.remappings).map_err(|e| e.to_string())
+    }
+
+    /// Reads the files at the paths and resolves their imports
+    fn resolve(&self, paths: &[String]) -> (Vec<Arc<FileSource>>, Vec<Diagnostic>) {
+        let mut files = vec![];
+        let mut diagnostics = vec![];
+        for path in paths {
+            let file_paths = match Compiler::transform_paths(&vec![path.clone()]) {
+                Ok(p) => p,
+                Err(e) => {
+                    diagnostics.push(Diagnostic::error(path, &e));
+                    continue
+                }
+            };
+            let fetched = Compiler::fetch_sources(file_paths.clone());
+            for (file_path, fetched) in file_paths.iter().zip(fetched) {
+                match fetched.map_err(Arc::new).and_then(|f| {
+                    Compiler::recurse_deps_with_remappings(f, &self.compiler.remappings)
+                }) {
+                    Ok(f) => files.push(f),
+                    Err(e) => diagnostics.push(Diagnostic::error(&file_path.to_string_lossy(), &e)),
+                }
+            }
+        }
+        (files, diagnostics)
+    }
+
+    /// Compiles the files at the paths, reusing the cached artifacts of unchanged files
+    fn build(&self, paths: &[String]) -> Build {
+        let (files, diagnostics) = self.resolve(paths);
+        let mut build = Build { diagnostics, ..Default::default() };
+
+        // Only compile the files whose flattened source changed since their last compilation
+        let mut stale: Vec<(Arc<FileSource>, String)> = vec![];
+        for file in files {
+            let source = FileSource::fully_flatten(Arc::clone(&file)).0;
+            match self.cache.lock().ok().and_then(|cache| cache.get(&file.path).cloned()) {
+                Some(cached) if cached.source == source => {
+                    build.artifacts.push(Arc::clone(&cached.artifact));
+                    build.diagnostics.extend(cached.warnings.iter().cloned());
+                    build.cached.push(file.path.clone());
+                }
+                _ => stale.push((file, source)),
+            }
+        }
+        tracing::info!(target: "core", "DAEMON COMPILING {} STALE FILES", stale.len());
+
+        // Each file is compiled with its own warnings, so those raised in its imports, or by other
+        // requests compiling at the same time, are attributed to the right file
+        let compiled = stale
+            .into_par_iter()
+            .map(|(file, source)| {
+                let compiler = Compiler { warnings: Arc::default(), ..self.compiler.clone() };
+                let artifact = compiler.gen_artifact(Arc::clone(&file));
+                let warnings: Vec<Diagnostic> = compiler
+                    .warnings
+                    .lock()
+                    .map(|w| w.iter().map(|w| Diagnostic::warning(&file.path, w)).collect())
+                    .unwrap_or_default();
+                (file, source, artifact, warnings)
+            })
+            .collect::<Vec<_>>();
+
+        for (file, source, artifact, warnings) in compiled {
+            match artifact {
+                Ok(a) => {
+                    let artifact = Arc::new(a);
+                    build.diagnostics.extend(warnings.iter().cloned());
+                    build.artifacts.push(Arc::clone(&artifact));
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.insert(
+                            file.path.clone(),
+                            CachedArtifact { source, artifact, warnings },
+                        );
+                    }
+                }
+                Err(e) => build.diagnostics.push(Diagnostic::error(&file.path, &e)),
+            }
+        }
+        build
+    }
+}
+
+/// Serializes a JSON-RPC error response
+fn rpc_error(id: Value, code: i64, message: &str) -> String {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }).to_string()
+}
@@ -0,0 +1,264 @@
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::{
+    ast::{ErrorDefinition, Event, Function},
+    prelude::{
+        bytes32_to_string, Argument, AstSpan, CompilerError, ConstVal, ConstantDefinition,
+        Contract, FileSource, FullFileSource, FunctionType, MacroDefinition, Remapping,
+        SerializationError, Span, Token, TokenKind,
+    },
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A Documented Definition
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemDoc {
+    /// The definition name
+    pub name: String,
+    /// The definition signature, as written in source
+    pub signature: String,
+    /// The doc comment preceding the definition
+    pub docs: String,
+}
+
+/// The Documentation of a Huff Source File
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ContractDocs {
+    /// The documented file path
+    pub file: String,
+    /// Macro and fn definitions
+    pub macros: Vec<ItemDoc>,
+    /// Function interfaces
+    pub functions: Vec<ItemDoc>,
+    /// Event interfaces
+    pub events: Vec<ItemDoc>,
+    /// Custom error definitions
+    pub errors: Vec<ItemDoc>,
+    /// Constant definitions
+    pub constants: Vec<ItemDoc>,
+}
+
+impl ContractDocs {
+    /// Generates the documentation for a source file's own definitions.
+    ///
+    /// Doc comments are `///` line comments or `/** */` block comments directly preceding a
+    /// `#define`.
+    pub fn generate<'a>(file: &str, source: &'a str) -> Result<Self, CompilerError<'a>> {
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(full_source)
+            .collect::<Result<Vec<Token>, _>>()
+            .map_err(CompilerError::LexicalError)?;
+        let docs = doc_comments(&tokens);
+
+        let mut parser = Parser::new(tokens, Some(file.to_string()));
+        let contract: Contract = parser.parse().map_err(CompilerError::ParserError)?;
+
+        // Definitions are matched to their doc comment by the start of their `#define`
+        let find_docs = |span: &[Span]| {
+            span.first().and_then(|s| docs.get(&s.start)).cloned().unwrap_or_default()
+        };
+
+        let macros = contract
+            .macros
+            .iter()
+            .map(|m| ItemDoc {
+                name: m.name.clone(),
+                signature: macro_signature(m),
+                docs: find_docs(&m.span.0),
+            })
+            .collect();
+        let functions = contract
+            .functions
+            .iter()
+            .map(|f| ItemDoc {
+                name: f.name.clone(),
+                signature: function_signature(f),
+                docs: find_docs(&f.span.0),
+            })
+            .collect();
+        let events = contract
+            .events
+            .iter()
+            .map(|e| ItemDoc {
+                name: e.name.clone(),
+                signature: event_signature(e),
+                docs: find_docs(&e.span.0),
+            })
+            .collect();
+        let errors = contract
+            .errors
+            .iter()
+            .map(|e| ItemDoc {
+                name: e.name.clone(),
+                signature: error_signature(e),
+                docs: find_docs(&e.span.0),
+            })
+            .collect();
+        let constants = contract
+            .constants
+            .borrow()
+            .iter()
+            .map(|c| ItemDoc {
+                name: c.name.clone(),
+                signature: constant_signature(c),
+                docs: find_docs(&c.span.0),
+            })
+            .collect();
+
+        Ok(ContractDocs { file: file.to_string(), macros, functions, events, errors, constants })
+    }
+
+    /// Renders the documentation as a markdown reference
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n", self.file);
+        let sections = [
+            ("Macros", &self.macros),
+            ("Functions", &self.functions),
+            ("Events", &self.events),
+            ("Errors", &self.errors),
+            ("Constants", &self.constants),
+        ];
+        for (title, items) in sections.into_iter().filter(|(_, items)| !items.is_empty()) {
+            let _ = write!(md, "\n## {}\n", title);
+            for item in items {
+                let _ = write!(md, "\n### `{}`\n\n```huff\n{}\n```\n", item.name, item.signature);
+                if !item.docs.is_empty() {
+                    let _ = write!(md, "\n{}\n", item.docs);
+                }
+            }
+        }
+        md
+    }
+
+    /// Renders the documentation as json
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// A Parsed Source File With Its Doc Comments
+pub(crate) struct DocumentedContract {
+    /// The parsed contract
+    pub contract: Contract,
+    /// The doc comments, keyed by the start of the `#define` they precede
+    pub docs: BTreeMap<usize, String>,
+}
+
+impl DocumentedContract {
+    /// Parses a file, returning None if it does not lex or parse
+    pub(crate) fn parse(file: &FileSource, remappings: &[Remapping]) -> Option<Self> {
+        let source = file.source.as_deref()?;
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(full_source).collect::<Result<Vec<Token>, _>>().ok()?;
+        let docs = doc_comments(&tokens);
+        let mut parser = Parser::new(tokens, Some(file.path.clone()));
+        parser.remappings = remappings.to_vec();
+        let contract = parser.parse().ok()?;
+        Some(DocumentedContract { contract, docs })
+    }
+
+    /// Returns the doc comment of the definition with the given span
+    pub(crate) fn docs(&self, span: &AstSpan) -> String {
+        span.0.first().and_then(|s| self.docs.get(&s.start)).cloned().unwrap_or_default()
+    }
+}
+
+/// Formats a macro's signature, as written in source
+pub(crate) fn macro_signature(m: &MacroDefinition) -> String {
+    format!(
+        "#define {} {}({}) = takes ({}) returns ({})",
+        if m.outlined { "fn" } else { "macro" },
+        m.name,
+        m.parameters.iter().filter_map(|p| p.name.clone()).collect::<Vec<String>>().join(", "),
+        m.takes,
+        m.returns
+    )
+}
+
+/// Formats a function's signature
+pub(crate) fn function_signature(f: &Function) -> String {
+    format!(
+        "#define function {}({}) {} returns ({})",
+        f.name,
+        format_args(&f.inputs),
+        match f.fn_type {
+            FunctionType::View => "view",
+            FunctionType::Payable => "payable",
+            FunctionType::NonPayable => "nonpayable",
+            FunctionType::Pure => "pure",
+        },
+        format_args(&f.outputs)
+    )
+}
+
+/// Formats an event's signature
+pub(crate) fn event_signature(e: &Event) -> String {
+    format!("#define event {}({})", e.name, format_args(&e.parameters))
+}
+
+/// Formats a custom error's signature
+pub(crate) fn error_signature(e: &ErrorDefinition) -> String {
+    format!("#define error {}({})", e.name, format_args(&e.parameters))
+}
+
+/// Formats a constant's signature
+pub(crate) fn constant_signature(c: &ConstantDefinition) -> String {
+    format!(
+        "#define constant {} = {}",
+        c.name,
+        match &c.value {
+            ConstVal::Literal(l) => bytes32_to_string(l, true),
+            ConstVal::FreeStoragePointer(_) => "FREE_STORAGE_POINTER()".to_string(),
+            ConstVal::Expression(expr) => expr.to_string(),
+        }
+    )
+}
+
+/// Formats a list of typed arguments
+fn format_args(args: &[Argument]) -> String {
+    args.iter()
+        .map(|a| {
+            let mut arg = a.arg_type.clone().unwrap_or_default();
+            if a.indexed {
+                arg.push_str(" indexed");
+            }
+            if let Some(name) = &a.name {
+                arg = format!("{} {}", arg, name);
+            }
+            arg
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Maps the start of each `#define` to the doc comment directly preceding it
+pub(crate) fn doc_comments(tokens: &[Token]) -> BTreeMap<usize, String> {
+    let mut docs = BTreeMap::new();
+    let mut pending: Vec<String> = vec![];
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Comment(c) if c.starts_with("///") => {
+                pending.push(c.trim_start_matches('/').trim().to_string());
+            }
+            TokenKind::Comment(c) if c.starts_with("/**") => {
+                pending.extend(
+                    c.trim_start_matches("/**")
+                        .trim_end_matches("*/")
+                        .lines()
+                        .map(|l| l.trim().trim_start_matches('*').trim().to_string())
+                        .filter(|l| !l.is_empty()),
+                );
+            }
+            TokenKind::Whitespace => {}
+            TokenKind::Define => {
+                if !pending.is_empty() {
+                    docs.insert(token.span.start, pending.join("\n"));
+                }
+                pending.clear();
+            }
+            _ => pending.clear(),
+        }
+    }
+    docs
+}
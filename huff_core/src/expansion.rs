@@ -0,0 +1,139 @@
+use crate::{
+    hover::flattened_contract,
+    index::{flatten_dependencies, walk_statements, Location},
+};
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{
+    FileSource, FullFileSource, Instructions, MacroDefinition, Remapping, Statement, StatementType,
+    Token, OPCODES, OPCODES_MAP,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// The name of the synthetic macro wrapping a previewed invocation
+const PREVIEW_MACRO: &str = "__EXPANSION_PREVIEW";
+
+/// The Expansion of a Macro
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Expansion {
+    /// The expanded macro name
+    pub name: String,
+    /// The source range of the previewed invocation or definition
+    pub range: Location,
+    /// The expanded bytecode, with unresolved jump destinations as `xxxx`
+    pub bytecode: String,
+    /// The expanded size in bytes
+    pub size: usize,
+    /// The disassembled instructions of the expansion
+    pub instructions: Vec<String>,
+}
+
+impl Expansion {
+    /// Expands the macro invocation or definition at an offset in a file.
+    ///
+    /// Invocations are expanded with their arguments. Arguments that are only known in an
+    /// enclosing invocation, like a definition's own parameters, expand as unresolved jumps.
+    pub fn at(
+        files: &[Arc<FileSource>],
+        remappings: &[Remapping],
+        file: &str,
+        offset: usize,
+    ) -> Option<Self> {
+        let sources = flatten_dependencies(files);
+        let current = sources.iter().find(|f| f.path == file)?;
+        let source = current.source.as_deref()?;
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let tokens = Lexer::new(full_source).collect::<Result<Vec<Token>, _>>().ok()?;
+        let mut parser = Parser::new(tokens, Some(file.to_string()));
+        parser.remappings = remappings.to_vec();
+        let parsed = parser.parse().ok()?;
+
+        let (mut contract, _) = flattened_contract(current, remappings)?;
+        let location = |start: usize, end: usize| Location { file: file.to_string(), start, end };
+
+        // Prefer the invocation under the cursor, falling back to the definition name
+        let mut invocation: Option<&Statement> = None;
+        for m in &parsed.macros {
+            walk_statements(&m.statements, &mut |s| {
+                if matches!(s.ty, StatementType::MacroInvocation(_)) &&
+                    statement_range(s)
+                        .is_some_and(|(start, end)| start <= offset && offset <= end)
+                {
+                    invocation = Some(s);
+                }
+            });
+        }
+        let (name, range) = match invocation {
+            Some(s) => {
+                let name = match &s.ty {
+                    StatementType::MacroInvocation(mi) => mi.macro_name.clone(),
+                    _ => return None,
+                };
+                let (start, end) = statement_range(s)?;
+                contract.macros.push(MacroDefinition::new(
+                    PREVIEW_MACRO.to_string(),
+                    vec![],
+                    vec![s.clone()],
+                    0,
+                    0,
+                    vec![],
+                    false,
+                ));
+                (name, location(start, end))
+            }
+            None => {
+                let m = parsed.macros.iter().find(|m| {
+                    m.span.0.iter().any(|s| {
+                        source.get(s.start..s.end) == Some(m.name.as_str()) &&
+                            s.start <= offset &&
+                            offset <= s.end
+                    })
+                })?;
+                let span = m.span.0.iter().find(|s| source.get(s.start..s.end) == Some(&m.name))?;
+                (m.name.clone(), location(span.start, span.end))
+            }
+        };
+
+        let expanded = if invocation.is_some() { PREVIEW_MACRO } else { name.as_str() };
+        let bytecode = Codegen::generate_macro_bytecode(expanded, &contract).ok()?;
+        Some(Expansion {
+            name,
+            range,
+            size: bytecode.len() / 2,
+            instructions: disassemble(&bytecode),
+            bytecode,
+        })
+    }
+
+    /// Summarizes the expansion for an inlay hint or code lens
+    pub fn summary(&self) -> String {
+        format!("{} expands to {} bytes: {}", self.name, self.size, self.instructions.join(" "))
+    }
+}
+
+/// Returns the source range covered by a statement
+fn statement_range(s: &Statement) -> Option<(usize, usize)> {
+    Some((s.span.0.first()?.start, s.span.0.last()?.end))
+}
+
+/// Disassembles a bytecode string into instructions, rendering unresolved push data as `????`
+fn disassemble(bytecode: &str) -> Vec<String> {
+    Instructions::new(bytecode)
+        .map(|i| {
+            let mnemonic = OPCODES
+                .iter()
+                .find(|o| OPCODES_MAP[**o].string() == format!("{:02x}", i.opcode))
+                .map(|o| o.to_uppercase())
+                .unwrap_or_else(|| format!("UNKNOWN(0x{:02x})", i.opcode));
+            match i.opcode {
+                0x60..=0x7f if i.data.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    format!("{} 0x{}", mnemonic, i.data)
+                }
+                0x60..=0x7f => format!("{} ????", mnemonic),
+                _ => mnemonic,
+            }
+        })
+        .collect()
+}
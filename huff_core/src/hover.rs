@@ -0,0 +1,249 @@
+use crate::{
+    docs::{
+        constant_signature, error_signature, event_signature, function_signature, macro_signature,
+        DocumentedContract,
+    },
+    index::{flatten_dependencies, Location, SymbolIndex, SymbolKind},
+};
+use ethers_core::{types::U256, utils::hex};
+use huff_codegen::Codegen;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{
+    bytes32_to_string, estimate_static_gas, ConstVal, Contract, FileSource, FullFileSource,
+    Remapping, TableKind, Token,
+};
+use serde::Serialize;
+use std::{fmt::Write, sync::Arc};
+
+/// The Computed Values of a Hovered Symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HoverInfo {
+    /// A constant's resolved value, and its slot if it is a free storage pointer
+    Constant {
+        /// The resolved value
+        value: Option<String>,
+        /// The assigned storage slot
+        slot: Option<usize>,
+    },
+    /// A function's selector
+    Function {
+        /// The 4 byte selector
+        selector: String,
+    },
+    /// An event's topic
+    Event {
+        /// The 32 byte topic0
+        topic: String,
+    },
+    /// A custom error's selector
+    Error {
+        /// The 4 byte selector
+        selector: String,
+    },
+    /// A macro's stack effect and estimated cost
+    Macro {
+        /// The number of stack items taken
+        takes: usize,
+        /// The number of stack items returned
+        returns: usize,
+        /// The estimated bytecode size
+        size: Option<usize>,
+        /// The estimated static gas of executing the macro straight through
+        gas: Option<u64>,
+    },
+}
+
+/// Hover Information for a Symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hover {
+    /// The symbol name
+    pub name: String,
+    /// The symbol kind
+    pub kind: SymbolKind,
+    /// The hovered source range
+    pub range: Location,
+    /// The definition signature, as written in source
+    pub signature: String,
+    /// The doc comment preceding the definition
+    pub docs: String,
+    /// The computed values, for constants, functions, events, and macros
+    pub info: Option<HoverInfo>,
+}
+
+impl Hover {
+    /// Resolves the hover information of the symbol at an offset in a file.
+    ///
+    /// Values are computed on the file's flattened contract, so free storage pointer slots match
+    /// the slots assigned when compiling that file. Macro size and gas are estimated on the
+    /// macro's own bytecode, with its arguments expanded as unresolved jumps.
+    pub fn at(
+        files: &[Arc<FileSource>],
+        remappings: &[Remapping],
+        file: &str,
+        offset: usize,
+    ) -> Option<Self> {
+        let index = SymbolIndex::build(files, remappings).ok()?;
+        let symbol = index.symbol_at(file, offset)?;
+        let range = std::iter::once(&symbol.definition)
+            .chain(symbol.references.iter())
+            .find(|l| l.contains(file, offset))?
+            .clone();
+
+        let sources = flatten_dependencies(files);
+        let defining = sources.iter().find(|f| f.path == symbol.definition.file)?;
+        let documented = DocumentedContract::parse(defining, remappings)?;
+        let name = symbol.name.as_str();
+        let (signature, docs) = match symbol.kind {
+            SymbolKind::Macro => {
+                let m = documented.contract.macros.iter().find(|m| m.name == name)?;
+                (macro_signature(m), documented.docs(&m.span))
+            }
+            SymbolKind::Constant => {
+                let constants = documented.contract.constants.borrow();
+                let c = constants.iter().find(|c| c.name == name)?;
+                (constant_signature(c), documented.docs(&c.span))
+            }
+            SymbolKind::Function => {
+                let f = documented.contract.functions.iter().find(|f| f.name == name)?;
+                (function_signature(f), documented.docs(&f.span))
+            }
+            SymbolKind::Event => {
+                let e = documented.contract.events.iter().find(|e| e.name == name)?;
+                (event_signature(e), documented.docs(&e.span))
+            }
+            SymbolKind::Error => {
+                let e = documented.contract.errors.iter().find(|e| e.name == name)?;
+                (error_signature(e), documented.docs(&e.span))
+            }
+            SymbolKind::Table => {
+                let t = documented.contract.tables.iter().find(|t| t.name == name)?;
+                let keyword = match t.kind {
+                    TableKind::JumpTable => "jumptable",
+                    TableKind::JumpTablePacked => "jumptable__packed",
+                    TableKind::CodeTable => "table",
+                };
+                (format!("#define {} {}", keyword, name), documented.docs(&t.span))
+            }
+            SymbolKind::Immutable => {
+                let i = documented.contract.immutables.iter().find(|i| i.name == name)?;
+                (format!("#define immutable {}", name), documented.docs(&i.span))
+            }
+            SymbolKind::Label => (format!("{}:", name), String::new()),
+            SymbolKind::Parameter => (format!("<{}>", name), String::new()),
+        };
+
+        let current = sources.iter().find(|f| f.path == file)?;
+        let info = flattened_contract(current, remappings)
+            .and_then(|(contract, pointers)| hover_info(&contract, &pointers, symbol.kind, name));
+
+        Some(Hover { name: symbol.name.clone(), kind: symbol.kind, range, signature, docs, info })
+    }
+
+    /// Renders the hover information as markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("```huff\n{}\n```\n", self.signature);
+        if !self.docs.is_empty() {
+            let _ = write!(md, "\n{}\n", self.docs);
+        }
+        let mut details = vec![];
+        match &self.info {
+            Some(HoverInfo::Constant { value, slot }) => {
+                if let Some(value) = value {
+                    details.push(format!("Value: `{}`", value));
+                }
+                if let Some(slot) = slot {
+                    details.push(format!("Slot: `{}`", slot));
+                }
+            }
+            Some(HoverInfo::Function { selector }) | Some(HoverInfo::Error { selector }) => {
+                details.push(format!("Selector: `{}`", selector))
+            }
+            Some(HoverInfo::Event { topic }) => details.push(format!("Topic: `{}`", topic)),
+            Some(HoverInfo::Macro { takes, returns, size, gas }) => {
+                details.push(format!("Stack: takes `{}`, returns `{}`", takes, returns));
+                if let Some(size) = size {
+                    details.push(format!("Size: `{}` bytes", size));
+                }
+                if let Some(gas) = gas {
+                    details.push(format!("Static Gas: `{}`", gas));
+                }
+            }
+            None => {}
+        }
+        if !details.is_empty() {
+            let _ = write!(md, "\n{}\n", details.join("  \n"));
+        }
+        md
+    }
+}
+
+/// Parses a file flattened with its dependencies, deriving its storage pointers and returning
+/// the names of the free storage pointer constants
+pub(crate) fn flattened_contract(
+    file: &FileSource,
+    remappings: &[Remapping],
+) -> Option<(Contract, Vec<String>)> {
+    let flattened = FileSource::fully_flatten(Arc::new(file.clone()));
+    let full_source = FullFileSource { source: &flattened.0, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source).collect::<Result<Vec<Token>, _>>().ok()?;
+    let mut parser = Parser::new(tokens, Some(file.path.clone()));
+    parser.remappings = remappings.to_vec();
+    let mut contract = parser.parse().ok()?;
+    let pointers = contract.free_storage_pointers();
+    contract.derive_storage_pointers();
+    Some((contract, pointers))
+}
+
+/// Computes the values of a symbol on a flattened contract
+fn hover_info(
+    contract: &Contract,
+    pointers: &[String],
+    kind: SymbolKind,
+    name: &str,
+) -> Option<HoverInfo> {
+    match kind {
+        SymbolKind::Constant => {
+            let constants = contract.constants.borrow();
+            let c = constants.iter().find(|c| c.name == name)?;
+            Some(match &c.value {
+                ConstVal::Literal(l) => HoverInfo::Constant {
+                    value: Some(bytes32_to_string(l, true)),
+                    slot: pointers
+                        .iter()
+                        .any(|p| p == name)
+                        .then(|| U256::from_big_endian(l).as_usize()),
+                },
+                ConstVal::FreeStoragePointer(_) => HoverInfo::Constant { value: None, slot: None },
+                ConstVal::Expression(_) => HoverInfo::Constant {
+                    value: c.evaluate(&constants).ok().map(|l| bytes32_to_string(&l, true)),
+                    slot: None,
+                },
+            })
+        }
+        SymbolKind::Function => {
+            let f = contract.functions.iter().find(|f| f.name == name)?;
+            Some(HoverInfo::Function { selector: format!("0x{}", hex::encode(f.signature)) })
+        }
+        SymbolKind::Event => {
+            let e = contract.events.iter().find(|e| e.name == name)?;
+            Some(HoverInfo::Event { topic: format!("0x{}", hex::encode(e.hash)) })
+        }
+        SymbolKind::Error => {
+            let e = contract.errors.iter().find(|e| e.name == name)?;
+            Some(HoverInfo::Error { selector: format!("0x{}", hex::encode(e.selector)) })
+        }
+        SymbolKind::Macro => {
+            let m = contract.macros.iter().find(|m| m.name == name)?;
+            let bytecode = Codegen::generate_macro_bytecode(name, contract).ok();
+            Some(HoverInfo::Macro {
+                takes: m.takes,
+                returns: m.returns,
+                size: bytecode.as_ref().map(|b| b.len() / 2),
+                gas: bytecode.as_deref().map(|b| estimate_static_gas(b).0),
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,401 @@
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{
+    AstSpan, BuiltinFunctionKind, CompilerError, Contract, FileSource, FullFileSource, MacroArg,
+    MacroDefinition, Remapping, RenameError, SerializationError, Span, Statement, StatementType,
+    Token, TokenKind, OPCODES_MAP, OPCODE_ALIASES,
+};
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+
+/// The keywords a symbol can't be renamed to
+const KEYWORDS: [TokenKind; 16] = [
+    TokenKind::Macro,
+    TokenKind::Fn,
+    TokenKind::Function,
+    TokenKind::Event,
+    TokenKind::Error,
+    TokenKind::Constant,
+    TokenKind::View,
+    TokenKind::Pure,
+    TokenKind::Payable,
+    TokenKind::NonPayable,
+    TokenKind::Indexed,
+    TokenKind::Takes,
+    TokenKind::Returns,
+    TokenKind::JumpTable,
+    TokenKind::JumpTablePacked,
+    TokenKind::CodeTable,
+];
+
+/// The Kind of an Indexed Symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    /// A macro or fn definition
+    Macro,
+    /// A function interface
+    Function,
+    /// An event interface
+    Event,
+    /// A custom error definition
+    Error,
+    /// A constant definition
+    Constant,
+    /// An immutable definition
+    Immutable,
+    /// A jump table or code table
+    Table,
+    /// A jump label, scoped to its macro
+    Label,
+    /// A macro parameter, scoped to its macro
+    Parameter,
+}
+
+/// A Source Location
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Location {
+    /// The file path
+    pub file: String,
+    /// The start offset in the file
+    pub start: usize,
+    /// The end offset in the file
+    pub end: usize,
+}
+
+impl Location {
+    /// Checks if the location contains an offset in the given file
+    pub fn contains(&self, file: &str, offset: usize) -> bool {
+        self.file == file && self.start <= offset && offset <= self.end
+    }
+}
+
+/// An Indexed Symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    /// The symbol name
+    pub name: String,
+    /// The symbol kind
+    pub kind: SymbolKind,
+    /// The enclosing macro, for labels and parameters
+    pub scope: Option<String>,
+    /// The location of the symbol's name in its definition
+    pub definition: Location,
+    /// The locations of every reference to the symbol
+    pub references: Vec<Location>,
+}
+
+/// A Text Replacement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    /// The replaced source range
+    pub location: Location,
+    /// The replacement text
+    pub new_text: String,
+}
+
+/// A Parsed Source File
+struct IndexedFile<'a> {
+    path: String,
+    source: &'a str,
+    contract: Contract,
+}
+
+/// A Project-Wide Definition and Reference Index
+///
+/// Built from the parsed ASTs of a set of files and every file they import, so editors can
+/// resolve go-to-definition and find-references queries.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolIndex {
+    /// The indexed symbols
+    pub symbols: Vec<Symbol>,
+}
+
+impl SymbolIndex {
+    /// Builds the index for the given files and their dependencies.
+    ///
+    /// Files should have their dependencies resolved, ie. using
+    /// [recurse_deps](crate::Compiler::recurse_deps).
+    pub fn build<'a>(
+        files: &'a [Arc<FileSource>],
+        remappings: &[Remapping],
+    ) -> Result<Self, CompilerError<'a>> {
+        let mut indexed = vec![];
+        for file in flatten_dependencies(files) {
+            let source = match &file.source {
+                Some(s) => s.as_str(),
+                None => continue,
+            };
+            let full_source = FullFileSource { source, file: None, spans: vec![] };
+            let tokens = Lexer::new(full_source)
+                .collect::<Result<Vec<Token>, _>>()
+                .map_err(CompilerError::LexicalError)?;
+            let mut parser = Parser::new(tokens, Some(file.path.clone()));
+            parser.remappings = remappings.to_vec();
+            let contract = parser.parse().map_err(CompilerError::ParserError)?;
+            indexed.push(IndexedFile { path: file.path.clone(), source, contract });
+        }
+
+        let mut index = SymbolIndex::default();
+        let mut lookup: HashMap<(SymbolKind, Option<String>, String), usize> = HashMap::new();
+
+        // Index every definition before resolving references, since references may precede
+        // their definition or live in another file
+        for file in &indexed {
+            let contract = &file.contract;
+            let mut definitions = vec![];
+            for m in contract.macros.iter().chain(&contract.tests) {
+                definitions.push((SymbolKind::Macro, None, &m.name, &m.span));
+                for p in &m.parameters {
+                    if let Some(name) = &p.name {
+                        definitions.push((SymbolKind::Parameter, Some(&m.name), name, &p.span));
+                    }
+                }
+                walk_statements(&m.statements, &mut |s| {
+                    if let StatementType::Label(l) = &s.ty {
+                        definitions.push((SymbolKind::Label, Some(&m.name), &l.name, &s.span));
+                    }
+                });
+            }
+            for f in &contract.functions {
+                definitions.push((SymbolKind::Function, None, &f.name, &f.span));
+            }
+            for e in &contract.events {
+                definitions.push((SymbolKind::Event, None, &e.name, &e.span));
+            }
+            for e in &contract.errors {
+                definitions.push((SymbolKind::Error, None, &e.name, &e.span));
+            }
+            for t in &contract.tables {
+                definitions.push((SymbolKind::Table, None, &t.name, &t.span));
+            }
+            let constants = contract.constants.borrow();
+            for c in constants.iter() {
+                definitions.push((SymbolKind::Constant, None, &c.name, &c.span));
+            }
+            for i in &contract.immutables {
+                definitions.push((SymbolKind::Immutable, None, &i.name, &i.span));
+            }
+
+            for (kind, scope, name, span) in definitions {
+                let definition = match file.name_location(name, &span.0) {
+                    Some(l) => l,
+                    None => continue,
+                };
+                let key = (kind, scope.cloned(), name.clone());
+                if lookup.contains_key(&key) {
+                    tracing::warn!(target: "index", "DUPLICATE DEFINITION OF \"{}\"", name);
+                    continue
+                }
+                lookup.insert(key, index.symbols.len());
+                index.symbols.push(Symbol {
+                    name: name.clone(),
+                    kind,
+                    scope: scope.cloned(),
+                    definition,
+                    references: vec![],
+                });
+            }
+        }
+
+        // Resolve the references in every macro body
+        for file in &indexed {
+            for m in file.contract.macros.iter().chain(&file.contract.tests) {
+                let mut references = vec![];
+                walk_statements(&m.statements, &mut |s| file.references(m, s, &mut references));
+                for (kind, scope, name, location) in references {
+                    match lookup.get(&(kind, scope, name)) {
+                        Some(i) => index.symbols[*i].references.push(location),
+                        None => {
+                            tracing::debug!(target: "index", "UNRESOLVED REFERENCE AT {:?}", location)
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Returns the symbol defined or referenced at an offset in a file
+    pub fn symbol_at(&self, file: &str, offset: usize) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| {
+            s.definition.contains(file, offset) ||
+                s.references.iter().any(|r| r.contains(file, offset))
+        })
+    }
+
+    /// Returns the definition of the symbol at an offset in a file
+    pub fn definition_at(&self, file: &str, offset: usize) -> Option<&Location> {
+        self.symbol_at(file, offset).map(|s| &s.definition)
+    }
+
+    /// Returns the global symbol with the given kind and name
+    pub fn find(&self, kind: SymbolKind, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| s.kind == kind && s.scope.is_none() && s.name == name)
+    }
+
+    /// Produces the edits renaming the symbol at an offset in a file.
+    ///
+    /// Edits cover the definition and every reference, including macro invocation arguments,
+    /// across all indexed files, ordered by file and offset.
+    pub fn rename(
+        &self,
+        file: &str,
+        offset: usize,
+        new_name: &str,
+    ) -> Result<Vec<TextEdit>, RenameError> {
+        let symbol = self
+            .symbol_at(file, offset)
+            .ok_or_else(|| RenameError::NoSymbol(file.to_string(), offset))?;
+
+        let mut chars = new_name.chars();
+        let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_') &&
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_') &&
+            !OPCODES_MAP.contains_key(new_name) &&
+            !OPCODE_ALIASES.contains_key(new_name) &&
+            !BuiltinFunctionKind::NAMES.contains(&new_name) &&
+            !KEYWORDS.iter().any(|k| k.to_string() == new_name);
+        if !valid {
+            return Err(RenameError::InvalidName(new_name.to_string()))
+        }
+        if symbol.name != new_name &&
+            self.symbols
+                .iter()
+                .any(|s| s.kind == symbol.kind && s.scope == symbol.scope && s.name == new_name)
+        {
+            return Err(RenameError::NameConflict(new_name.to_string()))
+        }
+
+        let mut edits = std::iter::once(&symbol.definition)
+            .chain(symbol.references.iter())
+            .map(|l| TextEdit { location: l.clone(), new_text: new_name.to_string() })
+            .collect::<Vec<TextEdit>>();
+        edits.sort_by(|a, b| a.location.cmp(&b.location));
+        edits.dedup();
+        Ok(edits)
+    }
+
+    /// Serializes the index as json
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl<'a> IndexedFile<'a> {
+    /// Finds the location of a name among the spans of a definition or statement
+    fn name_location(&self, name: &str, spans: &[Span]) -> Option<Location> {
+        spans.iter().find(|s| self.source.get(s.start..s.end) == Some(name)).map(|s| Location {
+            file: self.path.clone(),
+            start: s.start,
+            end: s.end,
+        })
+    }
+
+    /// Collects the references made by a single statement in a macro body
+    fn references(
+        &self,
+        m: &MacroDefinition,
+        s: &Statement,
+        references: &mut Vec<(SymbolKind, Option<String>, String, Location)>,
+    ) {
+        let scope = Some(m.name.clone());
+        let mut push = |kind: SymbolKind, name: &str, span: &AstSpan| {
+            if let Some(l) = self.name_location(name, &span.0) {
+                let scope = match kind {
+                    SymbolKind::Label | SymbolKind::Parameter => scope.clone(),
+                    _ => None,
+                };
+                references.push((kind, scope, name.to_string(), l));
+            }
+        };
+        match &s.ty {
+            StatementType::MacroInvocation(mi) => {
+                let name_span = AstSpan(s.span.0.first().cloned().into_iter().collect());
+                push(SymbolKind::Macro, &mi.macro_name, &name_span);
+                // Invocation arguments are only spanned as part of the invocation
+                let arg_spans = AstSpan(s.span.0.get(1..).unwrap_or_default().to_vec());
+                for arg in &mi.args {
+                    match arg {
+                        MacroArg::Ident(name) => push(SymbolKind::Label, name, &arg_spans),
+                        MacroArg::ArgCall(name) => push(SymbolKind::Parameter, name, &arg_spans),
+                        MacroArg::Literal(_) => {}
+                    }
+                }
+            }
+            StatementType::Constant(name) => push(SymbolKind::Constant, name, &s.span),
+            StatementType::ArgCall(name) => push(SymbolKind::Parameter, name, &s.span),
+            StatementType::LabelCall(name) => push(SymbolKind::Label, name, &s.span),
+            StatementType::BuiltinFunctionCall(bf) => {
+                let kind = match bf.kind {
+                    BuiltinFunctionKind::Codesize | BuiltinFunctionKind::Debug => SymbolKind::Macro,
+                    BuiltinFunctionKind::Tablesize | BuiltinFunctionKind::Tablestart => {
+                        SymbolKind::Table
+                    }
+                    BuiltinFunctionKind::FunctionSignature | BuiltinFunctionKind::Dispatch => {
+                        SymbolKind::Function
+                    }
+                    BuiltinFunctionKind::EventHash => SymbolKind::Event,
+                    BuiltinFunctionKind::Error => SymbolKind::Error,
+                    BuiltinFunctionKind::Immutable | BuiltinFunctionKind::SetImmutable => {
+                        SymbolKind::Immutable
+                    }
+                    BuiltinFunctionKind::CreateAddress |
+                    BuiltinFunctionKind::Create2Address |
+                    BuiltinFunctionKind::Mask |
+                    BuiltinFunctionKind::ShlConst |
+                    BuiltinFunctionKind::RightPad |
+                    BuiltinFunctionKind::Verbatim |
+                    BuiltinFunctionKind::ConstructorArg |
+                    BuiltinFunctionKind::Custom(_) => SymbolKind::Constant,
+                };
+                for arg in &bf.args {
+                    if let Some(name) = &arg.name {
+                        push(kind, name, &arg.span);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Applies the edits targeting a file to its source
+pub fn apply_edits(file: &str, source: &str, edits: &[TextEdit]) -> String {
+    let mut edits = edits.iter().filter(|e| e.location.file == file).collect::<Vec<&TextEdit>>();
+    edits.sort_by_key(|e| std::cmp::Reverse(e.location.start));
+    let mut edited = source.to_string();
+    for edit in edits {
+        edited.replace_range(edit.location.start..edit.location.end, &edit.new_text);
+    }
+    edited
+}
+
+/// Visits every statement, including those nested in labels
+pub(crate) fn walk_statements<'s>(
+    statements: &'s [Statement],
+    visit: &mut impl FnMut(&'s Statement),
+) {
+    for s in statements {
+        visit(s);
+        if let StatementType::Label(l) = &s.ty {
+            walk_statements(&l.inner, visit);
+        }
+    }
+}
+
+/// Collects the given files and all of their dependencies, without duplicates
+pub(crate) fn flatten_dependencies(files: &[Arc<FileSource>]) -> Vec<&FileSource> {
+    let mut flattened: Vec<&FileSource> = vec![];
+    let mut stack = files.iter().rev().map(|f| f.as_ref()).collect::<Vec<&FileSource>>();
+    while let Some(file) = stack.pop() {
+        if flattened.iter().any(|f| f.path == file.path) {
+            continue
+        }
+        if let Some(deps) = &file.dependencies {
+            stack.extend(deps.iter().rev().map(|f| f.as_ref()));
+        }
+        flattened.push(file);
+    }
+    flattened
+}
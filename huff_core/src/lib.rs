@@ -2,27 +2,96 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 #![forbid(unsafe_code)]
-#![forbid(where_clauses_object_safety)]
 
+use asm::AsmListing;
 use ethers_core::utils::hex;
-use huff_codegen::*;
+use huff_codegen::{
+    config::{CodegenConfig, CustomBuiltin},
+    *,
+};
 use huff_lexer::*;
 use huff_parser::*;
 use huff_utils::prelude::*;
 use rayon::prelude::*;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ffi::OsString,
     fs,
     path::{Path, PathBuf},
-    sync::Arc,
-    time::SystemTime,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
+use result::CompileResult;
+use timings::{Stage, Timings};
 use tracing_subscriber::{filter::Directive, EnvFilter};
 use uuid::Uuid;
+use yul::YulObject;
 
 pub(crate) mod cache;
 
+/// Constructor and Call Simulation Module
+pub mod simulation;
+
+/// Mutation Testing Module
+pub mod mutation;
+
+/// Symbolic Execution Harness Module
+pub mod symbolic;
+
+/// Control Flow Graph Module
+pub mod cfg;
+
+/// Macro Call Graph Module
+pub mod call_graph;
+
+/// Experimental Stack Scheduling Module
+pub mod scheduling;
+
+/// Package Management Module
+pub mod packages;
+
+/// Workspace Module
+pub mod workspace;
+
+/// Documentation Generation Module
+pub mod docs;
+
+/// Symbol Index Module
+pub mod index;
+
+/// Signature Help Module
+pub mod signature;
+
+/// Completion Module
+pub mod completion;
+
+/// Hover Information Module
+pub mod hover;
+
+/// Macro Expansion Preview Module
+pub mod expansion;
+
+/// Compilation Timings Module
+pub mod timings;
+
+/// Compilation Result Module
+pub mod result;
+
+/// Gas Annotated Assembly Listing Module
+pub mod asm;
+
+/// Compilation Daemon Module
+pub mod daemon;
+
+/// Retained Builds Module
+pub mod retained;
+
+/// Experimental Yul Backend Module
+pub mod yul;
+
+/// Huff Snippet Compilation Module
+pub mod snippet;
+
 /// ## The Core Huff Compiler
 ///
 /// #### Usage
@@ -64,6 +133,44 @@ pub struct Compiler<'a> {
     pub bytecode: bool,
     /// Whether to check cached artifacts
     pub cached: bool,
+    /// The artifact serializer, defaulting to json
+    pub serializer: Option<Arc<dyn Serializer>>,
+    /// Serializers for additional artifact outputs
+    pub additional_serializers: Vec<Arc<dyn Serializer>>,
+    /// Whether to simulate the constructor
+    pub simulate: bool,
+    /// The maximum runtime bytecode size in bytes
+    pub size_budget: Option<usize>,
+    /// Import path remappings
+    pub remappings: Vec<Remapping>,
+    /// The stage durations of each compiled contract
+    pub timings: Arc<Mutex<Timings>>,
+    /// The non-fatal diagnostics raised while compiling
+    pub warnings: Arc<Mutex<Vec<CompilerWarning>>>,
+    /// Cancels an in-flight compilation, checked between stages and before expanding each macro
+    pub cancellation: CancellationToken,
+    /// User defined compile time builtins, keyed by name
+    pub builtins: BTreeMap<String, CustomBuiltin>,
+    /// Whether to compile a debug build, expanding `__DEBUG` macros
+    pub debug: bool,
+    /// Whether to push padded hex literals with the width they were written with
+    pub preserve_push_width: bool,
+    /// The evm version to target, failing on opcodes it doesn't support
+    pub evm_version: EvmVersion,
+    /// Whether to check that expanded macros match their `takes` and `returns`
+    pub check_stack: bool,
+    /// Whether to append a metadata trailer to the runtime bytecode
+    pub metadata: bool,
+    /// The constructor bootstrap template, replacing the default one
+    pub bootstrap: Option<String>,
+    /// The outputs to include in artifacts, skipping the analyses of the others
+    pub output_selection: BTreeSet<ArtifactOutput>,
+    /// Whether to accept historical opcode aliases, ie. `sha3`, without warning
+    pub allow_opcode_aliases: bool,
+    /// The limit on the number of macros being expanded within each other
+    pub max_expansion_depth: Option<usize>,
+    /// The limit on the size of the expanded bytecode in bytes
+    pub max_expanded_size: Option<usize>,
 }
 
 impl<'a> Compiler<'a> {
@@ -87,9 +194,33 @@ impl<'a> Compiler<'a> {
             optimize: false,
             bytecode: false,
             cached,
+            serializer: None,
+            additional_serializers: vec![],
+            simulate: false,
+            size_budget: None,
+            remappings: Remapping::project(),
+            timings: Arc::default(),
+            warnings: Arc::default(),
+            cancellation: CancellationToken::default(),
+            builtins: BTreeMap::new(),
+            debug: false,
+            preserve_push_width: false,
+            evm_version: EvmVersion::default(),
+            check_stack: false,
+            metadata: false,
+            bootstrap: None,
+            output_selection: ArtifactOutput::DEFAULT.into(),
+            allow_opcode_aliases: false,
+            max_expansion_depth: None,
+            max_expanded_size: None,
         }
     }
 
+    /// Whether an output is selected for the artifacts
+    fn selects(&self, output: ArtifactOutput) -> bool {
+        self.output_selection.contains(&output)
+    }
+
     /// Tracing
     ///
     /// Creates a new tracing subscriber to span the compilation process.
@@ -116,29 +247,45 @@ impl<'a> Compiler<'a> {
     /// 2. Fetch file sources in parallel with [fetch_sources](Compiler::fetch_sources).
     /// 3. Recurse file dependencies in parallel with [recurse_deps](Compiler::recurse_deps).
     /// 4. For each top-level file [Parallelized], generate the artifact using
-    /// [gen_artifact](Compiler::gen_artifact).
-    /// 5. Return the compiling error(s) or successfully generated artifacts.
-    pub fn execute(&self) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'a>>> {
+    ///    [gen_artifact](Compiler::gen_artifact).
+    /// 5. Export the generated artifacts and return them with the errors of the files that
+    ///    failed to compile, the warnings, and the timings.
+    ///
+    /// Once the [cancellation](Compiler::cancellation) token is cancelled, the result only
+    /// holds a [CompilerError::Cancelled] error and no artifacts are exported.
+    pub fn execute(&self) -> CompileResult<'a> {
+        let mut result = CompileResult::default();
+        if let Err(e) = self.compile(&mut result) {
+            if *e == CompilerError::Cancelled {
+                result.errors.clear();
+            }
+            result.artifacts.clear();
+            result.errors.push(e);
+        }
+        if let Ok(mut warnings) = self.warnings.lock() {
+            result.warnings = std::mem::take(&mut *warnings);
+        }
+        if let Ok(mut timings) = self.timings.lock() {
+            result.timings = std::mem::take(&mut *timings);
+        }
+        result
+    }
+
+    /// Compiles every source into the result, collecting per file errors and returning the
+    /// errors that abort the whole compilation
+    fn compile(&self, result: &mut CompileResult<'a>) -> Result<(), Arc<CompilerError<'a>>> {
         // Grab the input files
         let file_paths: Vec<PathBuf> = Compiler::transform_paths(&self.sources)?;
 
-        // Parallel file fetching
-        let files: Vec<Result<Arc<FileSource>, CompilerError>> =
-            Compiler::fetch_sources(file_paths);
-
-        // Unwrap errors
-        let mut errors =
-            files.iter().filter_map(|rfs| rfs.as_ref().err()).collect::<Vec<&CompilerError>>();
-        if !errors.is_empty() {
-            let error = errors.remove(0);
-            return Err(Arc::new(error.clone()))
+        // Parallel file fetching, keeping the files that could be read
+        let mut files: Vec<Arc<FileSource>> = vec![];
+        for fetched in Compiler::fetch_sources(file_paths) {
+            match fetched {
+                Ok(f) => files.push(f),
+                Err(e) => result.errors.push(Arc::new(e)),
+            }
         }
-
-        // Unpack files into their file sources
-        let files = files
-            .iter()
-            .filter_map(|fs| fs.as_ref().map(Arc::clone).ok())
-            .collect::<Vec<Arc<FileSource>>>();
+        self.check_cancelled().map_err(Arc::new)?;
 
         // Grab the output
         let output = self.get_outputs();
@@ -150,74 +297,99 @@ impl<'a> Compiler<'a> {
         //     || cache::get_cached_artifacts(cloned_files, ol)
         // });
 
-        let mut artifacts: Vec<Arc<Artifact>> = vec![];
-
         // Get our constructor arguments as a hex encoded string to compare to the cache
         let inputs = self.get_constructor_args();
-        let encoded_inputs = Codegen::encode_constructor_args(inputs);
-        let encoded: Vec<Vec<u8>> =
-            encoded_inputs.iter().map(|tok| ethers_core::abi::encode(&[tok.clone()])).collect();
+        let encoded_inputs = Codegen::encode_constructor_args(inputs)
+            .map_err(|e| Arc::new(CompilerError::CodegenError(e)))?;
+        let encoded: Vec<Vec<u8>> = encoded_inputs
+            .iter()
+            .map(|tok| ethers_core::abi::encode(std::slice::from_ref(tok)))
+            .collect();
         let constructor_args = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
 
         // Get Cached or Generate Artifacts
         tracing::debug!(target: "core", "Output directory: {}", output.0);
-        match cache::get_cached_artifacts(&files, &output, constructor_args) {
-            Some(arts) => artifacts = arts,
+        let cached = match self.reads_cache() {
+            true => cache::get_cached_artifacts(
+                &files,
+                &output,
+                constructor_args,
+                &self.build_settings(),
+            ),
+            false => None,
+        };
+        match cached {
+            Some(arts) => {
+                // Warnings raised while compiling are raised again for the cached artifacts
+                arts.iter().for_each(|a| self.check_size_limits(a));
+                self.replay_alias_warnings(&arts);
+                result.artifacts = arts;
+            }
             None => {
                 // Parallel Dependency Resolution
                 let recursed_file_sources: Vec<Result<Arc<FileSource>, Arc<CompilerError<'a>>>> =
-                    files.into_par_iter().map(Compiler::recurse_deps).collect();
-
-                // Collect Recurse Deps errors and try to resolve to the first one
-                let mut errors = recursed_file_sources
-                    .iter()
-                    .filter_map(|rfs| rfs.as_ref().err())
-                    .collect::<Vec<&Arc<CompilerError>>>();
-                if !errors.is_empty() {
-                    let error = errors.remove(0);
-                    return Err(Arc::clone(error))
-                }
+                    files
+                        .into_par_iter()
+                        .map(|f| {
+                            let start = Instant::now();
+                            let path = f.path.clone();
+                            let recursed =
+                                Compiler::recurse_deps_with_remappings(f, &self.remappings);
+                            self.record_timing(&path, Stage::Imports, start.elapsed());
+                            recursed
+                        })
+                        .collect();
 
-                // Unpack recursed dependencies into FileSources
-                let files = recursed_file_sources
-                    .into_iter()
-                    .filter_map(|fs| fs.ok())
-                    .collect::<Vec<Arc<FileSource>>>();
+                // Unpack recursed dependencies into FileSources, keeping the errors of the files
+                // whose dependencies failed to resolve
+                let mut files: Vec<Arc<FileSource>> = vec![];
+                for recursed in recursed_file_sources {
+                    match recursed {
+                        Ok(f) => files.push(f),
+                        Err(e) => result.errors.push(e),
+                    }
+                }
                 tracing::info!(target: "core", "COMPILER RECURSED {} FILE DEPENDENCIES", files.len());
+                self.check_cancelled().map_err(Arc::new)?;
 
                 // Parallel Compilation
                 let potential_artifacts: Vec<Result<Artifact, CompilerError<'a>>> =
                     files.into_par_iter().map(|f| self.gen_artifact(f)).collect();
 
-                let mut gen_errors: Vec<CompilerError<'a>> = vec![];
-
-                // Output errors + return OR print # of successfully compiled files
+                // A cancelled compilation fails as a whole, not per file
+                self.check_cancelled().map_err(Arc::new)?;
                 for r in potential_artifacts {
                     match r {
-                        Ok(a) => artifacts.push(Arc::new(a)),
-                        Err(ce) => gen_errors.push(ce),
+                        Ok(a) => result.artifacts.push(Arc::new(a)),
+                        Err(ce) => result.errors.push(Arc::new(ce)),
                     }
                 }
-
-                if !gen_errors.is_empty() {
-                    tracing::error!(target: "core", "{} FILES FAILED TO COMPILE", gen_errors.len());
-                    return Err(Arc::new(CompilerError::FailedCompiles(gen_errors)))
+                if !result.is_ok() {
+                    let failed = result.errors.len();
+                    tracing::error!(target: "core", "{} FILES FAILED TO COMPILE", failed);
                 }
 
-                // Export
-                Compiler::export_artifacts(&artifacts, &output);
+                // Export the artifacts of the files that compiled
+                let exported =
+                    Compiler::export_artifacts(&result.artifacts, &output, &self.get_serializers());
+                for (path, duration) in exported {
+                    self.record_timing(&path, Stage::Export, duration);
+                }
             }
         }
 
-        Ok(artifacts)
+        Ok(())
     }
 
     /// Artifact Generation
     ///
     /// Compiles a FileSource into an Artifact.
     pub fn gen_artifact(&self, file: Arc<FileSource>) -> Result<Artifact, CompilerError<'a>> {
+        self.check_cancelled()?;
+
         // Fully Flatten a file into a source string containing source code of file and all
         // its dependencies
+        let lex_start = Instant::now();
         let flattened = FileSource::fully_flatten(Arc::clone(&file));
         tracing::info!(target: "core", "FLATTENED SOURCE FILE \"{}\"", file.path);
         let full_source = FullFileSource {
@@ -229,28 +401,50 @@ impl<'a> Compiler<'a> {
 
         // Perform Lexical Analysis
         // Create a new lexer from the FileSource, flattening dependencies
-        let lexer: Lexer = Lexer::new(full_source);
+        let mut lexer: Lexer = Lexer::new(full_source);
+        lexer.custom_builtins = self.builtins.keys().cloned().collect();
 
         // Grab the tokens from the lexer
-        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let tokens = lexer
+            .by_ref()
+            .collect::<Result<Vec<Token>, _>>()
+            .map_err(|e| CompilerError::LexicalError(e.into_owned()))?;
+        if !self.allow_opcode_aliases {
+            self.record_alias_warnings(&file, &lexer.aliases);
+        }
         tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
         tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
+        self.record_timing(&file.path, Stage::Lex, lex_start.elapsed());
 
         // Parser incantation
+        self.check_cancelled()?;
+        let parse_start = Instant::now();
+        let comments = trailing_comments(&tokens);
         let mut parser = Parser::new(tokens, Some(file.path.clone()));
+        parser.remappings = self.remappings.clone();
 
         // Parse into an AST
         let parse_res = parser.parse().map_err(CompilerError::ParserError);
         let mut contract = parse_res?;
+        let mut storage_pointers = contract.free_storage_pointers();
+        storage_pointers.extend(contract.literal_storage_slots());
         contract.derive_storage_pointers();
         contract.add_override_constants(&self.constant_overrides);
+        let storage_layout =
+            StorageLayout::new(&storage_pointers, &contract.constants.borrow(), &comments);
         tracing::info!(target: "core", "PARSED CONTRACT [{}]", file.path);
+        self.record_timing(&file.path, Stage::Parse, parse_start.elapsed());
+        self.check_cancelled()?;
+        let codegen_start = Instant::now();
 
         // Primary Bytecode Generation
         // See huffc: https://github.com/huff-language/huffc/blob/2e5287afbfdf9cc977b204a4fd1e89c27375b040/src/compiler/processor.ts
         let mut cg = Codegen::new();
-        let main_bytecode = match Codegen::generate_main_bytecode(&contract) {
+        let config = self.codegen_config();
+        let main_res = Codegen::generate_main_bytecode_with_spans(&contract, &config);
+        let (mut main_bytecode, mut main_spans) = match main_res {
             Ok(mb) => mb,
+            Err(_) if self.cancellation.is_cancelled() => return Err(CompilerError::Cancelled),
             Err(mut e) => {
                 tracing::error!(target: "codegen", "FAILED TO GENERATE MAIN BYTECODE FOR CONTRACT");
                 // Add File Source to Span
@@ -259,7 +453,7 @@ impl<'a> Compiler<'a> {
                         .0
                         .into_iter()
                         .map(|mut s| {
-                            s.file = Some(Arc::clone(&file));
+                            s.file.get_or_insert_with(|| Arc::clone(&file));
                             s
                         })
                         .collect::<Vec<Span>>(),
@@ -269,58 +463,224 @@ impl<'a> Compiler<'a> {
             }
         };
         tracing::info!(target: "core", "MAIN BYTECODE GENERATED [{}]", main_bytecode);
-        let inputs = self.get_constructor_args();
-        let constructor_bytecode = match Codegen::generate_constructor_bytecode(&contract) {
-            Ok(mb) => mb,
+
+        // Immutables are patched into the runtime by the bootstrap, so can't use a custom one
+        let mut config = config;
+        let immutable_references = match contract.immutables.is_empty() {
+            true => BTreeMap::new(),
+            false => {
+                if self.bootstrap.is_some() {
+                    tracing::error!(target: "core", "CUSTOM BOOTSTRAP WITH IMMUTABLES");
+                    return Err(CompilerError::CodegenError(CodegenError {
+                        kind: CodegenErrorKind::InvalidArguments(
+                            "A custom bootstrap can't be used with immutables".to_string(),
+                        ),
+                        span: contract.immutables[0].span.clone(),
+                        token: None,
+                        expansion: vec![],
+                    }))
+                }
+                let references = Codegen::generate_main_immutable_references(&contract, &config)
+                    .map_err(CompilerError::CodegenError)?;
+                config.bootstrap = Some(Codegen::immutables_bootstrap(&contract, &references));
+                references
+            }
+        };
+
+        // Tests are compiled separately, so they never change the contract's bytecode
+        let tests_res = match self.selects(ArtifactOutput::Tests) {
+            true => Codegen::generate_tests(&contract, &config),
+            false => Ok(BTreeMap::new()),
+        };
+        let tests = match tests_res {
+            Ok(tests) => tests,
+            Err(_) if self.cancellation.is_cancelled() => return Err(CompilerError::Cancelled),
             Err(mut e) => {
-                // Return any errors except if the inputs is empty and the constructor definition is
-                // missing
-                if e.kind != CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()) ||
-                    !inputs.is_empty()
-                {
-                    // Add File Source to Span
-                    let mut errs = e
-                        .span
-                        .0
-                        .into_iter()
-                        .map(|mut s| {
-                            s.file = Some(Arc::clone(&file));
-                            s
-                        })
-                        .collect::<Vec<Span>>();
-                    errs.dedup();
-                    e.span = AstSpan(errs);
-                    tracing::error!(target: "codegen", "Constructor inputs provided, but contract missing \"CONSTRUCTOR\" macro!");
-                    return Err(CompilerError::CodegenError(e))
+                tracing::error!(target: "codegen", "FAILED TO GENERATE TEST BYTECODE FOR CONTRACT");
+                for s in e.span.0.iter_mut() {
+                    s.file.get_or_insert_with(|| Arc::clone(&file));
                 }
+                return Err(CompilerError::CodegenError(e))
+            }
+        };
 
-                // If the kind is a missing constructor we can ignore it
-                tracing::warn!(target: "codegen", "Contract has no \"CONSTRUCTOR\" macro definition!");
-                "".to_string()
+        // The metadata trailer follows the runtime bytecode, and isn't mapped to any source
+        let metadata = self.metadata.then(|| Metadata::new(&flattened.0));
+        if let Some(m) = &metadata {
+            main_spans.push((main_bytecode.len() / 2, AstSpan(vec![])));
+            main_bytecode.push_str(&m.trailer);
+        }
+        if let Some(budget) = self.size_budget {
+            let size = main_bytecode.len() / 2;
+            if size > budget {
+                tracing::error!(target: "core", "RUNTIME SIZE {} EXCEEDS BUDGET {}", size, budget);
+                let mut report = Codegen::generate_main_macro_sizes(&contract, &config)
+                    .map_err(CompilerError::CodegenError)?
+                    .into_iter()
+                    .collect::<Vec<(String, MacroSize)>>();
+                report.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(&b.0)));
+                return Err(CompilerError::SizeBudgetError(SizeBudgetError {
+                    file: file.path.clone(),
+                    size,
+                    budget,
+                    report,
+                }))
+            }
+        }
+        let inputs = self.get_constructor_args();
+        // Constructor arguments can't be used without a constructor macro to read them
+        if !inputs.is_empty() && contract.find_macro_by_name("CONSTRUCTOR").is_none() {
+            tracing::error!(target: "codegen", "Constructor inputs provided, but contract missing \"CONSTRUCTOR\" macro!");
+            return Err(CompilerError::CodegenError(CodegenError::new(
+                CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()),
+                AstSpan(vec![Span { start: 0, end: 0, file: Some(Arc::clone(&file)) }]),
+                None,
+            )))
+        }
+        let constructor_res =
+            Codegen::generate_constructor_bytecode_with_spans(&contract, &config);
+        let (constructor_bytecode, constructor_spans) = match constructor_res {
+            Ok(mb) => mb,
+            Err(_) if self.cancellation.is_cancelled() => return Err(CompilerError::Cancelled),
+            Err(mut e) => {
+                tracing::error!(target: "codegen", "FAILED TO GENERATE CONSTRUCTOR BYTECODE FOR CONTRACT");
+                // Add File Source to Span
+                let mut errs = e
+                    .span
+                    .0
+                    .into_iter()
+                    .map(|mut s| {
+                        s.file.get_or_insert_with(|| Arc::clone(&file));
+                        s
+                    })
+                    .collect::<Vec<Span>>();
+                errs.dedup();
+                e.span = AstSpan(errs);
+                return Err(CompilerError::CodegenError(e))
             }
         };
 
         // Encode Constructor Arguments
         tracing::info!(target: "core", "CONSTRUCTOR BYTECODE GENERATED [{}]", constructor_bytecode);
         tracing::info!(target: "core", "ENCODING {} INPUTS", inputs.len());
-        let encoded_inputs = Codegen::encode_constructor_args(inputs);
+        // Arguments are parsed as the declared constructor types when there is an interface, and
+        // builds without arguments leave them out like before
+        let encoded_inputs = match Codegen::constructor_types(&contract) {
+            Some(types) if !inputs.is_empty() => {
+                Codegen::encode_typed_constructor_args(inputs, &types)
+            }
+            _ => Codegen::encode_constructor_args(inputs),
+        }
+        .map_err(CompilerError::CodegenError)?;
         tracing::info!(target: "core", "ENCODED {} INPUTS", encoded_inputs.len());
+        let encoded_len = encoded_inputs
+            .iter()
+            .map(|tok| ethers_core::abi::encode(std::slice::from_ref(tok)).len() * 2)
+            .sum::<usize>();
 
         // Generate Artifact with ABI
-        let churn_res = cg.churn(file, encoded_inputs, &main_bytecode, &constructor_bytecode);
+        self.check_cancelled()?;
+        let churn_res = cg.churn_with_config(
+            file,
+            encoded_inputs,
+            &main_bytecode,
+            &constructor_bytecode,
+            &config,
+        );
         match churn_res {
             Ok(mut artifact) => {
+                artifact.settings = self.build_settings();
+                if self.selects(ArtifactOutput::StorageLayout) {
+                    artifact.storage_layout = storage_layout;
+                }
+                if self.selects(ArtifactOutput::MacroGas) {
+                    artifact.macro_gas = Codegen::generate_macro_gas(&contract, &config);
+                }
+                if self.selects(ArtifactOutput::Stats) {
+                    artifact.stats = Codegen::generate_main_stats(&contract, &config).ok();
+                }
+                artifact.tests = tests;
+                artifact.metadata = metadata;
+                artifact.immutable_references = immutable_references;
+                self.check_size_limits(&artifact);
+                // The deployment source map covers the constructor and bootstrap code, which
+                // precede the runtime bytecode and constructor arguments
+                let deploy_len = artifact.bytecode.len() - artifact.runtime.len() - encoded_len;
+                if self.selects(ArtifactOutput::SourceMap) {
+                    artifact.source_map = Some(SourceMap::new(
+                        &artifact.bytecode[..deploy_len],
+                        &constructor_spans,
+                        &artifact.runtime,
+                        &main_spans,
+                    ));
+                }
+                if self.selects(ArtifactOutput::Yul) {
+                    let yul = YulObject::new(
+                        &artifact.file.path,
+                        &contract,
+                        (&constructor_bytecode, &constructor_spans),
+                        (&artifact.runtime, &main_spans),
+                        &artifact.immutable_references,
+                    );
+                    artifact.yul = Some(yul.render());
+                }
                 // Then we can have the code gen output the artifact
-                let abiout = cg.abi_gen(contract, None);
+                let abiout = match self.selects(ArtifactOutput::Abi) ||
+                    self.selects(ArtifactOutput::MethodIdentifiers)
+                {
+                    true => cg.abi_gen(contract, None).map(Some),
+                    false => Ok(None),
+                };
                 match abiout {
-                    Ok(abi) => {
+                    Ok(Some(abi)) => {
                         tracing::info!(target: "core", "GENERATED ABI");
-                        artifact.abi = Some(abi)
+                        if self.selects(ArtifactOutput::MethodIdentifiers) {
+                            artifact.method_identifiers = abi.method_identifiers();
+                        }
+                        if self.selects(ArtifactOutput::Abi) {
+                            artifact.abi = Some(abi)
+                        }
                     }
+                    Ok(None) => {}
                     Err(e) => {
-                        tracing::error!(target: "core", "ARTIFACT GENERATION FAILED: {:?}", e)
+                        tracing::error!(target: "core", "ARTIFACT GENERATION FAILED: {:?}", e);
+                        self.record_warning(
+                            &artifact.file.path,
+                            CompilerWarningKind::AbiGenerationFailure(e.kind),
+                        );
                     }
                 }
+                if self.simulate {
+                    let simulation =
+                        simulation::simulate_constructor(&artifact.file.path, &artifact.bytecode)
+                            .map_err(CompilerError::SimulationError)?;
+                    // The constructor patches the immutables' placeholder words, so they differ
+                    let mut simulated = simulation.runtime.clone();
+                    for offset in artifact.immutable_references.values().flatten() {
+                        if simulated.len() >= (offset + 32) * 2 {
+                            simulated
+                                .replace_range(offset * 2..(offset + 32) * 2, &"00".repeat(32));
+                        }
+                    }
+                    if simulated != artifact.runtime {
+                        tracing::warn!(target: "core", "SIMULATED RUNTIME DIFFERS FROM GENERATED RUNTIME FOR \"{}\"", artifact.file.path);
+                        self.record_warning(
+                            &artifact.file.path,
+                            CompilerWarningKind::SimulatedRuntimeMismatch,
+                        );
+                    }
+                    artifact.simulation = Some(simulation);
+                }
+                if self.selects(ArtifactOutput::Asm) {
+                    artifact.asm = Some(AsmListing::new(&artifact.runtime).render());
+                }
+                if !self.selects(ArtifactOutput::Bytecode) {
+                    artifact.bytecode.clear();
+                    artifact.runtime.clear();
+                    artifact.immutable_references.clear();
+                    artifact.sizes = BytecodeSizes::default();
+                }
+                self.record_timing(&artifact.file.path, Stage::Codegen, codegen_start.elapsed());
                 Ok(artifact)
             }
             Err(e) => {
@@ -353,8 +713,16 @@ impl<'a> Compiler<'a> {
             .collect()
     }
 
-    /// Recurses file dependencies
+    /// Recurses file dependencies, using the project remappings
     pub fn recurse_deps(fs: Arc<FileSource>) -> Result<Arc<FileSource>, Arc<CompilerError<'a>>> {
+        Compiler::recurse_deps_with_remappings(fs, &Remapping::project())
+    }
+
+    /// Recurses file dependencies, resolving imports with the given remappings
+    pub fn recurse_deps_with_remappings(
+        fs: Arc<FileSource>,
+        remappings: &[Remapping],
+    ) -> Result<Arc<FileSource>, Arc<CompilerError<'a>>> {
         let mut new_fs = FileSource { path: fs.path.clone(), ..Default::default() };
         let file_source = if let Some(s) = &fs.source {
             s.clone()
@@ -378,21 +746,34 @@ impl<'a> Compiler<'a> {
         let localized_imports: Vec<String> = imports
             .iter()
             .map(|import| {
-                FileSource::localize_file(&fs.path, import).unwrap_or_default().replacen(
-                    "contracts/contracts",
-                    "contracts",
-                    1,
-                )
+                FileSource::resolve_import(&fs.path, import, remappings)
+                    .unwrap_or_default()
+                    .replacen("contracts/contracts", "contracts", 1)
             })
             .collect();
         if !localized_imports.is_empty() {
             tracing::info!(target: "core", "LOCALIZED IMPORTS {:?}", localized_imports);
         }
+        // Standard library imports are served from the sources embedded in the compiler
+        let (std_imports, localized_imports): (Vec<String>, Vec<String>) =
+            localized_imports.into_iter().partition(|import| is_std_import(import));
+        let mut file_sources = vec![];
+        for import in std_imports {
+            match std_file_source(&import) {
+                Some(fs) => file_sources.push(fs),
+                None => {
+                    tracing::error!(target: "core", "UNKNOWN STD IMPORT: \"{}\"", import);
+                    let e = CompilerError::FileUnpackError(UnpackError::MissingFile(import));
+                    return Err(Arc::new(e))
+                }
+            }
+        }
+
         let import_bufs: Vec<PathBuf> = Compiler::transform_paths(&localized_imports)?;
         let potentials: Result<Vec<Arc<FileSource>>, CompilerError> =
             Compiler::fetch_sources(import_bufs).into_iter().collect();
-        let mut file_sources = match potentials {
-            Ok(p) => p,
+        match potentials {
+            Ok(mut p) => file_sources.append(&mut p),
             Err(e) => return Err(Arc::new(e)),
         };
         if !file_sources.is_empty() {
@@ -402,7 +783,10 @@ impl<'a> Compiler<'a> {
         // Now that we have all the file sources, we have to recurse and get their source
         file_sources = file_sources
             .into_par_iter()
-            .map(|inner_fs| match Compiler::recurse_deps(Arc::clone(&inner_fs)) {
+            .map(|inner_fs| match Compiler::recurse_deps_with_remappings(
+                Arc::clone(&inner_fs),
+                remappings,
+            ) {
                 Ok(new_fs) => new_fs,
                 Err(e) => {
                     tracing::error!(target: "core", "NESTED DEPENDENCY RESOLUTION FAILED: \"{:?}\"", e);
@@ -420,12 +804,21 @@ impl<'a> Compiler<'a> {
     /// Export Artifacts
     ///
     /// 1. Cleans any previous artifacts in the output directory.
-    /// 2. Exports artifacts in parallel as serialized json `Artifact` objects.
-    pub fn export_artifacts(artifacts: &Vec<Arc<Artifact>>, output: &OutputLocation) {
+    /// 2. Exports artifacts in parallel, once for each of the given `Serializer`s.
+    ///
+    /// If the output location is a file, the first serializer writes to it and any others write
+    /// alongside it using their own file extension.
+    ///
+    /// Returns the time spent exporting each artifact, by file path.
+    pub fn export_artifacts(
+        artifacts: &Vec<Arc<Artifact>>,
+        output: &OutputLocation,
+        serializers: &[Arc<dyn Serializer>],
+    ) -> Vec<(String, Duration)> {
         // Exit if empty output location
         if output.0.is_empty() {
             tracing::warn!(target: "core", "Exiting artifact export with empty output location!");
-            return
+            return vec![]
         }
 
         // Clean the Output Directory
@@ -438,22 +831,204 @@ impl<'a> Compiler<'a> {
         let is_file = std::path::PathBuf::from(&output.0).extension().is_some();
 
         // Export the artifacts with parallelized io
-        artifacts.into_par_iter().for_each(|a| {
-            // If it's a file type, we just export to `output.0`
-            let json_out = match is_file {
-                true => output.0.clone(),
-                false => format!(
-                    "{}/{}.json",
-                    output.0,
-                    a.file.path.to_uppercase().replacen("./", "", 1)
+        artifacts
+            .into_par_iter()
+            .map(|a| {
+                let start = Instant::now();
+                for (i, serializer) in serializers.iter().enumerate() {
+                    // If it's a file type, we just export to `output.0`
+                    let out = match (is_file, i) {
+                        (true, 0) => output.0.clone(),
+                        (true, _) => Path::new(&output.0)
+                            .with_extension(serializer.extension())
+                            .to_string_lossy()
+                            .to_string(),
+                        (false, _) => format!(
+                            "{}/{}.{}",
+                            output.0,
+                            a.file.path.to_uppercase().replacen("./", "", 1),
+                            serializer.extension()
+                        ),
+                    };
+
+                    if let Err(e) = a.export_with(&out, serializer.as_ref()) {
+                        tracing::error!(target: "core", "ARTIFACT EXPORT FAILED!\nError: {:?}", e);
+                    }
+                    tracing::info!(target: "core", "EXPORTED {} ARTIFACT TO \"{}\"", serializer.name(), out);
+                }
+                (a.file.path.clone(), start.elapsed())
+            })
+            .collect()
+    }
+
+    /// Adds the duration of a stage to a file's timings
+    fn record_timing(&self, file: &str, stage: Stage, duration: Duration) {
+        if let Ok(mut timings) = self.timings.lock() {
+            timings.record(file, stage, duration);
+        }
+    }
+
+    /// Registers a custom builtin, replacing any custom builtin of the same name
+    pub fn register_builtin(&mut self, builtin: CustomBuiltin) {
+        self.builtins.insert(builtin.name.clone(), builtin);
+    }
+
+    /// The settings that change the compiled output, compared with cached artifacts' settings
+    pub fn build_settings(&self) -> BuildSettings {
+        BuildSettings {
+            debug: self.debug,
+            preserve_push_width: self.preserve_push_width,
+            evm_version: self.evm_version,
+            metadata: self.metadata,
+            constant_overrides: self
+                .constant_overrides
+                .iter()
+                .flatten()
+                .map(|(name, value)| (name.to_string(), bytes32_to_string(value, true)))
+                .collect(),
+            bootstrap: self.bootstrap.clone(),
+            output_selection: self.output_selection.clone(),
+            max_expansion_depth: self.max_expansion_depth,
+            max_expanded_size: self.max_expanded_size,
+        }
+    }
+
+    /// The codegen options of the compilation
+    fn codegen_config(&self) -> CodegenConfig {
+        CodegenConfig {
+            cancellation: self.cancellation.clone(),
+            builtins: self.builtins.clone(),
+            debug: self.debug,
+            preserve_push_width: self.preserve_push_width,
+            evm_version: self.evm_version,
+            check_stack: self.check_stack,
+            bootstrap: self.bootstrap.clone(),
+            max_expansion_depth: self.max_expansion_depth,
+            max_expanded_size: self.max_expanded_size,
+        }
+    }
+
+    /// Warns if an artifact's bytecode exceeds the EIP-170 or EIP-3860 size limits.
+    ///
+    /// Deployments fail past the limits, but the bytecode is still usable, ie. on chains without
+    /// them.
+    fn check_size_limits(&self, artifact: &Artifact) {
+        if artifact.sizes.runtime > MAX_RUNTIME_SIZE {
+            tracing::warn!(target: "core", "RUNTIME SIZE {} EXCEEDS EIP-170 LIMIT", artifact.sizes.runtime);
+            self.record_warning(
+                &artifact.file.path,
+                CompilerWarningKind::RuntimeSizeLimit(artifact.sizes.runtime),
+            );
+        }
+        if artifact.sizes.initcode > MAX_INITCODE_SIZE {
+            tracing::warn!(target: "core", "INITCODE SIZE {} EXCEEDS EIP-3860 LIMIT", artifact.sizes.initcode);
+            self.record_warning(
+                &artifact.file.path,
+                CompilerWarningKind::InitcodeSizeLimit(artifact.sizes.initcode),
+            );
+        }
+    }
+
+    /// Adds a non-fatal diagnostic to the compilation's warnings
+    fn record_warning(&self, file: &str, kind: CompilerWarningKind) {
+        if let Ok(mut warnings) = self.warnings.lock() {
+            warnings.push(CompilerWarning { file: file.to_string(), kind });
+        }
+    }
+
+    /// Records a warning for each historical opcode alias lexed from a file or its imports
+    fn record_alias_warnings(&self, file: &FileSource, aliases: &[(String, Span)]) {
+        for (alias, span) in aliases {
+            tracing::warn!(target: "core", "OPCODE ALIAS \"{}\" USED IN \"{}\"", alias, file.path);
+            let path = span.file.as_ref().map_or(&file.path, |f| &f.path);
+            self.record_warning(
+                path,
+                CompilerWarningKind::OpcodeAlias(
+                    alias.clone(),
+                    OPCODE_ALIASES[alias.as_str()].to_string(),
                 ),
+            );
+        }
+    }
+
+    /// Records the opcode alias warnings of cached artifacts, which are raised while lexing, by
+    /// lexing their sources again
+    fn replay_alias_warnings(&self, artifacts: &[Arc<Artifact>]) {
+        if self.allow_opcode_aliases {
+            return
+        }
+        for artifact in artifacts {
+            let flattened = FileSource::fully_flatten(Arc::clone(&artifact.file));
+            let full_source = FullFileSource {
+                source: &flattened.0,
+                file: Some(Arc::clone(&artifact.file)),
+                spans: flattened.1,
             };
+            let mut lexer = Lexer::new(full_source);
+            lexer.by_ref().for_each(drop);
+            self.record_alias_warnings(&artifact.file, &lexer.aliases);
+        }
+    }
 
-            if let Err(e) = a.export(&json_out) {
-                tracing::error!(target: "core", "ARTIFACT EXPORT FAILED!\nError: {:?}", e);
-            }
-            tracing::info!(target: "core", "EXPORTED ARTIFACT TO \"{}\"", json_out);
-        });
+    /// Whether artifacts can be read from the cache.
+    ///
+    /// Cached artifacts are json, and only reused when their sources, constructor arguments and
+    /// [build settings](Compiler::build_settings) match, so the options outside of those must
+    /// leave the output unchanged. Every field is destructured so that new options have to be
+    /// considered here.
+    fn reads_cache(&self) -> bool {
+        let Compiler {
+            // Compared with those of the cached artifacts
+            sources: _,
+            construct_args: _,
+            // Compared with the cached artifacts' settings
+            constant_overrides: _,
+            debug: _,
+            preserve_push_width: _,
+            evm_version: _,
+            metadata: _,
+            bootstrap: _,
+            output_selection: _,
+            max_expansion_depth: _,
+            max_expanded_size: _,
+            // Read from the project, like the sources
+            output: _,
+            remappings: _,
+            // Only change what is logged or tracked
+            bytecode: _,
+            timings: _,
+            warnings: _,
+            cancellation: _,
+            // Alias warnings are replayed from the cached sources
+            allow_opcode_aliases: _,
+            // Doesn't change the output, as there are no optimizations yet
+            optimize: _,
+            cached,
+            serializer,
+            additional_serializers,
+            simulate,
+            size_budget,
+            builtins,
+            check_stack,
+        } = self;
+        // Constructors are simulated, and size budgets and stack heights checked while compiling,
+        // and custom builtins can't be compared, so those builds aren't cached
+        *cached &&
+            serializer.as_ref().is_none_or(|s| s.name() == "json") &&
+            additional_serializers.is_empty() &&
+            !simulate &&
+            size_budget.is_none() &&
+            builtins.is_empty() &&
+            !check_stack
+    }
+
+    /// Returns [CompilerError::Cancelled] if the compilation has been cancelled
+    fn check_cancelled(&self) -> Result<(), CompilerError<'a>> {
+        if self.cancellation.is_cancelled() {
+            tracing::warn!(target: "core", "COMPILATION CANCELLED");
+            return Err(CompilerError::Cancelled)
+        }
+        Ok(())
     }
 
     /// Transforms File Strings into PathBufs
@@ -488,6 +1063,21 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// Derives the artifact serializer
+    pub fn get_serializer(&self) -> Arc<dyn Serializer> {
+        match &self.serializer {
+            Some(s) => Arc::clone(s),
+            None => Arc::new(JsonSerializer::default()),
+        }
+    }
+
+    /// Derives all artifact serializers, starting with the primary serializer
+    pub fn get_serializers(&self) -> Vec<Arc<dyn Serializer>> {
+        let mut serializers = vec![self.get_serializer()];
+        serializers.extend(self.additional_serializers.iter().map(Arc::clone));
+        serializers
+    }
+
     /// Derives an output location
     pub fn get_outputs(&self) -> OutputLocation {
         match &self.output {
@@ -0,0 +1,244 @@
+use crate::simulation::{locate, simulate_call_outcome, Prestate, TraceLocation, TraceOutcome};
+use ethers_core::utils::hex;
+use huff_utils::prelude::{Artifact, Instruction, Instructions};
+use rayon::prelude::*;
+use revm::interpreter::opcode::OpCode;
+use serde::Deserialize;
+use std::{fmt, fs, path::Path};
+
+/// Opcodes swapped with their counterpart, ie. a `lt` that should have been a `gt`
+const SWAPS: [(u8, u8); 6] = [
+    (0x10, 0x11), // LT, GT
+    (0x12, 0x13), // SLT, SGT
+    (0x01, 0x03), // ADD, SUB
+    (0x02, 0x04), // MUL, DIV
+    (0x16, 0x17), // AND, OR
+    (0x1b, 0x1c), // SHL, SHR
+];
+
+/// A Call Test Case
+///
+/// Cases are read from a json array, ie. `[{"calldata": "0x70a08231...", "output": "0x..."}]`.
+/// A case passes if the call returns, or reverts if `reverts` is set, with the expected output.
+/// Any output is expected if it isn't set.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct CallCase {
+    /// The hex calldata
+    #[serde(default)]
+    pub calldata: String,
+    /// The value sent with the call, in wei
+    #[serde(default)]
+    pub value: u128,
+    /// The expected hex return or revert data
+    pub output: Option<String>,
+    /// Whether the call is expected to revert
+    #[serde(default)]
+    pub reverts: bool,
+}
+
+impl CallCase {
+    /// Reads the cases of a json file, checking their calldata is valid hex
+    pub fn load(path: &Path) -> Result<Vec<CallCase>, String> {
+        let cases = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Vec<CallCase>>(&s).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Failed to read cases \"{}\": {}", path.display(), e))?;
+        for case in &cases {
+            hex::decode(case.calldata.trim_start_matches("0x"))
+                .map_err(|e| format!("Invalid calldata \"{}\": {}", case.calldata, e))?;
+        }
+        Ok(cases)
+    }
+
+    /// Whether the case passes against an artifact
+    pub fn passes(&self, artifact: &Artifact) -> bool {
+        let calldata = hex::decode(self.calldata.trim_start_matches("0x")).unwrap_or_default();
+        let outcome = simulate_call_outcome(artifact, &calldata, self.value, &Prestate::default());
+        let (reverted, output) = match outcome {
+            Ok(TraceOutcome::Returned(output)) => (false, output),
+            Ok(TraceOutcome::Reverted(output)) => (true, output),
+            _ => return false,
+        };
+        reverted == self.reverts &&
+            self.output
+                .as_ref()
+                .is_none_or(|o| o.trim_start_matches("0x").eq_ignore_ascii_case(&output))
+    }
+}
+
+/// A Mutated Instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    /// The program counter of the mutated instruction in the runtime
+    pub pc: usize,
+    /// The original instruction, ie. `LT` or `PUSH1 0x05`
+    pub original: String,
+    /// The mutated instruction
+    pub mutated: String,
+    /// Where the instruction was generated from
+    pub location: TraceLocation,
+    /// The hex encoded mutated instruction, the same length as the original
+    bytes: String,
+}
+
+impl Mutant {
+    /// Applies the mutation to an artifact's runtime, and the copy of it in the bytecode
+    pub fn apply(&self, artifact: &Artifact) -> Artifact {
+        let mut mutated = artifact.clone();
+        let start = self.pc * 2;
+        let end = start + self.bytes.len();
+        mutated.runtime.replace_range(start..end, &self.bytes);
+
+        // The runtime follows the constructor and bootstrap code, and only constructor arguments
+        // follow it
+        if let Some(i) = artifact.bytecode.rfind(&artifact.runtime) {
+            mutated.bytecode.replace_range(i + start..i + end, &self.bytes);
+        }
+        mutated
+    }
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>5} {} -> {}  {} {}:{} | {}",
+            self.pc,
+            self.original,
+            self.mutated,
+            self.location.definition.as_deref().unwrap_or("-"),
+            self.location.file,
+            self.location.line,
+            self.location.code
+        )
+    }
+}
+
+/// Generates the mutants of an artifact's runtime.
+///
+/// Comparison, arithmetic, and bitwise opcodes are swapped with their counterpart, and pushed
+/// constants are moved off by one. Jump destinations, and instructions that weren't generated
+/// from source, ie. tables, are left alone.
+pub fn mutants(artifact: &Artifact) -> Vec<Mutant> {
+    let locations = locate(artifact);
+    let mut mutants = vec![];
+    let mut instructions = Instructions::new(&artifact.runtime).peekable();
+    while let Some(Instruction { pc, opcode, data }) = instructions.next() {
+        let width = if (0x60..=0x7f).contains(&opcode) { (opcode - 0x5f) as usize } else { 0 };
+        // Push data cut short by the end of the code isn't mutated
+        let location = match locations.get(&pc) {
+            Some(l) if data.len() == width * 2 => l,
+            _ => continue,
+        };
+        let mut mutant = |original: String, mutated: String, bytes: Vec<u8>| {
+            mutants.push(Mutant {
+                pc,
+                original,
+                mutated,
+                location: location.clone(),
+                bytes: hex::encode(bytes),
+            })
+        };
+
+        let swapped = SWAPS.iter().find_map(|(a, b)| match opcode {
+            o if o == *a => Some(*b),
+            o if o == *b => Some(*a),
+            _ => None,
+        });
+        if let Some(swapped) = swapped {
+            mutant(name(opcode), name(swapped), vec![swapped]);
+        }
+
+        let jumps = matches!(instructions.peek(), Some(i) if matches!(i.opcode, 0x56 | 0x57));
+        if width > 0 && !jumps {
+            let data = &hex::decode(data).unwrap_or_default()[..];
+            let push = |data: &[u8]| format!("{} 0x{}", name(opcode), hex::encode(data));
+            if let Some(incremented) = step(data, true) {
+                mutant(push(data), push(&incremented), [&[opcode], &incremented[..]].concat());
+            }
+            if let Some(decremented) = step(data, false) {
+                mutant(push(data), push(&decremented), [&[opcode], &decremented[..]].concat());
+            }
+        }
+    }
+    mutants
+}
+
+/// Adds or subtracts one from a big endian value, if it doesn't overflow its width
+fn step(data: &[u8], increment: bool) -> Option<Vec<u8>> {
+    let mut stepped = data.to_vec();
+    for byte in stepped.iter_mut().rev() {
+        let (b, carry) = if increment { byte.overflowing_add(1) } else { byte.overflowing_sub(1) };
+        *byte = b;
+        if !carry {
+            return Some(stepped)
+        }
+    }
+    None
+}
+
+/// The name of an opcode byte
+fn name(opcode: u8) -> String {
+    OpCode::name_by_op(opcode).to_string()
+}
+
+/// The Result of Mutation Testing an Artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationReport {
+    /// The number of mutants tested
+    pub mutants: usize,
+    /// The mutants every case still passed against
+    pub survivors: Vec<Mutant>,
+}
+
+impl MutationReport {
+    /// The share of mutants killed, from 0 to 1
+    pub fn score(&self) -> f64 {
+        match self.mutants {
+            0 => 1.0,
+            n => (n - self.survivors.len()) as f64 / n as f64,
+        }
+    }
+}
+
+impl fmt::Display for MutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}/{} mutants killed ({:.2}%)",
+            self.mutants - self.survivors.len(),
+            self.mutants,
+            self.score() * 100.0
+        )?;
+        if !self.survivors.is_empty() {
+            writeln!(f, "Surviving mutants:")?;
+            self.survivors.iter().try_for_each(|m| writeln!(f, "{}", m))?;
+        }
+        Ok(())
+    }
+}
+
+/// Mutation Testing
+///
+/// Runs the cases against every mutant of an artifact, reporting the mutants no case failed
+/// against. The cases must all pass against the unmutated artifact.
+pub fn mutation_test(artifact: &Artifact, cases: &[CallCase]) -> Result<MutationReport, String> {
+    if cases.is_empty() {
+        return Err("No cases to run against the mutants".to_string())
+    }
+    if let Some(i) = cases.iter().position(|c| !c.passes(artifact)) {
+        return Err(format!("Case {} fails against the unmutated \"{}\"", i, artifact.file.path))
+    }
+
+    let mutants = mutants(artifact);
+    tracing::info!(target: "core", "TESTING {} MUTANTS OF \"{}\"", mutants.len(), artifact.file.path);
+    let survivors = mutants
+        .par_iter()
+        .filter(|m| {
+            let mutated = m.apply(artifact);
+            cases.iter().all(|c| c.passes(&mutated))
+        })
+        .cloned()
+        .collect::<Vec<Mutant>>();
+    Ok(MutationReport { mutants: mutants.len(), survivors })
+}
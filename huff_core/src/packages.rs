@@ -0,0 +1,115 @@
+use huff_utils::prelude::{PackageError, Remapping};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// The directory packages are vendored into
+pub const LIB_DIR: &str = "lib";
+
+/// A Package Spec, of the form `owner/repo[@tag]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// The github repository owner
+    pub owner: String,
+    /// The repository name
+    pub repo: String,
+    /// The tag or branch to install, defaulting to the repository's default branch
+    pub tag: Option<String>,
+}
+
+impl FromStr for PackageSpec {
+    type Err = PackageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, tag) = match s.split_once('@') {
+            Some((p, t)) if !t.is_empty() => (p, Some(t.to_string())),
+            Some(_) => return Err(PackageError::InvalidSpec(s.to_string())),
+            None => (s, None),
+        };
+        // Dot-only components like `..` would vendor the package outside the lib directory
+        let valid = |p: &str| {
+            !p.chars().all(|c| c == '.') &&
+                p.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        };
+        match path.split_once('/') {
+            Some((owner, repo)) if valid(owner) && valid(repo) => {
+                Ok(PackageSpec { owner: owner.to_string(), repo: repo.to_string(), tag })
+            }
+            _ => Err(PackageError::InvalidSpec(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for PackageSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.tag {
+            Some(t) => write!(f, "{}/{}@{}", self.owner, self.repo, t),
+            None => write!(f, "{}/{}", self.owner, self.repo),
+        }
+    }
+}
+
+impl PackageSpec {
+    /// The github url of the package
+    pub fn url(&self) -> String {
+        format!("https://github.com/{}/{}", self.owner, self.repo)
+    }
+}
+
+/// Installs a package from github into `lib_dir`, returning its remapping
+pub fn install(spec: &PackageSpec, lib_dir: &Path) -> Result<Remapping, PackageError> {
+    install_from(spec, &spec.url(), lib_dir)
+}
+
+/// Installs a package from a git url into `lib_dir`, returning its remapping.
+///
+/// The repository is shallow cloned into `lib_dir/<repo>` and its `.git` directory removed, so
+/// the package is vendored as plain files rather than as a submodule.
+pub fn install_from(
+    spec: &PackageSpec,
+    url: &str,
+    lib_dir: &Path,
+) -> Result<Remapping, PackageError> {
+    let dest = lib_dir.join(&spec.repo);
+    if dest.exists() {
+        return Err(PackageError::AlreadyInstalled(dest.to_string_lossy().to_string()))
+    }
+    std::fs::create_dir_all(lib_dir).map_err(|e| PackageError::Io(e.to_string()))?;
+
+    tracing::info!(target: "packages", "INSTALLING \"{}\" FROM {}", spec, url);
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(tag) = &spec.tag {
+        cmd.args(["--branch", tag]);
+    }
+    let output =
+        cmd.arg(url).arg(&dest).output().map_err(|e| PackageError::GitFailed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(PackageError::GitFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+    std::fs::remove_dir_all(dest.join(".git")).map_err(|e| PackageError::Io(e.to_string()))?;
+
+    Ok(package_remapping(&spec.repo, &dest))
+}
+
+/// Generates the remapping for a vendored package, pointing at its `src` directory if present
+pub fn package_remapping(name: &str, dest: &Path) -> Remapping {
+    let root: PathBuf = match dest.join("src").is_dir() {
+        true => dest.join("src"),
+        false => dest.to_path_buf(),
+    };
+    Remapping { prefix: format!("{}/", name), path: format!("{}/", root.to_string_lossy()) }
+}
+
+/// Adds remappings to a remappings file, replacing any existing remappings with the same prefix
+pub fn write_remappings(path: &Path, new: Vec<Remapping>) -> Result<(), PackageError> {
+    let mut remappings = Remapping::read(path);
+    Remapping::merge(&mut remappings, new);
+    let contents = remappings.iter().map(|r| format!("{}\n", r)).collect::<String>();
+    std::fs::write(path, contents).map_err(|e| PackageError::Io(e.to_string()))
+}
@@ -0,0 +1,40 @@
+use crate::timings::Timings;
+use huff_utils::prelude::{Artifact, CompilerError, CompilerWarning};
+use std::sync::Arc;
+
+/// The Result of a Compilation
+///
+/// Files are compiled independently, so a multi-contract build can produce artifacts for some
+/// files alongside errors for others.
+#[derive(Debug, Clone, Default)]
+pub struct CompileResult<'a> {
+    /// The generated or cached artifacts of the files that compiled
+    pub artifacts: Vec<Arc<Artifact>>,
+    /// Non-fatal diagnostics raised while compiling
+    pub warnings: Vec<CompilerWarning>,
+    /// The errors of the files that failed to compile
+    pub errors: Vec<Arc<CompilerError<'a>>>,
+    /// The stage durations of each compiled contract
+    pub timings: Timings,
+}
+
+impl<'a> CompileResult<'a> {
+    /// Whether every file compiled
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Collapses the result into its artifacts, or its errors if any file failed to compile.
+    ///
+    /// A single error is returned as is, while several are wrapped in
+    /// [FailedCompiles](CompilerError::FailedCompiles).
+    pub fn into_result(self) -> Result<Vec<Arc<Artifact>>, Arc<CompilerError<'a>>> {
+        match self.errors.len() {
+            0 => Ok(self.artifacts),
+            1 => Err(Arc::clone(&self.errors[0])),
+            _ => Err(Arc::new(CompilerError::FailedCompiles(
+                self.errors.iter().map(|e| (**e).clone()).collect(),
+            ))),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use huff_utils::prelude::{Artifact, BuildSettings, EvmVersion, SerializationError};
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+use walkdir::WalkDir;
+
+/// The output directory of a build with the settings, within the given output directory
+///
+/// Each build is retained in a subdirectory named by its [BuildSettings::key].
+pub fn build_dir(output_dir: &str, settings: &BuildSettings) -> String {
+    Path::new(output_dir).join(settings.key()).to_string_lossy().to_string()
+}
+
+/// Whether a directory name is a [BuildSettings::key], ie. `shanghai-1f2e3d4c`
+pub fn is_build_key(name: &str) -> bool {
+    match name.rsplit_once('-') {
+        Some((evm_version, hash)) => {
+            EvmVersion::from_str(evm_version).is_ok() &&
+                hash.len() == 8 &&
+                hash.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// The Sizes of a Contract in a Retained Build
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildSizes {
+    /// The settings key of the build
+    pub build: String,
+    /// The size of the runtime bytecode in bytes
+    pub runtime: usize,
+    /// The size of the deployment bytecode in bytes
+    pub initcode: usize,
+    /// Whether the runtime bytecode matches the baseline build's
+    #[serde(rename = "sameRuntime")]
+    pub same_runtime: bool,
+}
+
+/// A Contract Compared Across Retained Builds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContractComparison {
+    /// The path of the compiled file
+    pub contract: String,
+    /// The contract's sizes in each build that compiled it, starting with the baseline
+    pub builds: Vec<BuildSizes>,
+}
+
+/// The Builds Retained in an Output Directory
+///
+/// Each build is exported to a subdirectory of the output directory named by its
+/// [BuildSettings::key], so builds with different settings don't overwrite each other.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RetainedBuilds {
+    /// The artifacts of each build, by settings key
+    pub builds: BTreeMap<String, Vec<Artifact>>,
+}
+
+impl RetainedBuilds {
+    /// Loads the json artifacts of every build retained in the output directory, ignoring
+    /// subdirectories not named by build settings
+    pub fn load(output_dir: &Path) -> std::io::Result<Self> {
+        let mut builds = BTreeMap::new();
+        for entry in fs::read_dir(output_dir)? {
+            let entry = entry?;
+            let key = entry.file_name().to_string_lossy().to_string();
+            if !is_build_key(&key) || !entry.file_type()?.is_dir() {
+                continue
+            }
+            let mut artifacts = vec![];
+            for file in WalkDir::new(entry.path())
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.path().extension().map(|ext| ext == "json").unwrap_or(false))
+            {
+                artifacts.push(Artifact::import(&file.path().to_string_lossy())?);
+            }
+            artifacts.sort_by(|a, b| a.file.path.cmp(&b.file.path));
+            builds.insert(key, artifacts);
+        }
+        Ok(Self { builds })
+    }
+
+    /// Compares the sizes of each contract across the builds, against the baseline build, or the
+    /// first build by settings without one
+    pub fn compare(&self, baseline: Option<&str>) -> Vec<ContractComparison> {
+        let mut order: Vec<&String> = self.builds.keys().collect();
+        if let Some(i) = baseline.and_then(|b| order.iter().position(|k| *k == b)) {
+            let baseline = order.remove(i);
+            order.insert(0, baseline);
+        }
+
+        let mut contracts: BTreeMap<&str, Vec<(&String, &Artifact)>> = BTreeMap::new();
+        for key in order {
+            for artifact in &self.builds[key] {
+                contracts.entry(&artifact.file.path).or_default().push((key, artifact));
+            }
+        }
+        contracts
+            .into_iter()
+            .map(|(contract, builds)| {
+                let baseline_runtime = &builds[0].1.runtime;
+                ContractComparison {
+                    contract: contract.to_string(),
+                    builds: builds
+                        .iter()
+                        .map(|(key, a)| BuildSizes {
+                            build: key.to_string(),
+                            runtime: a.runtime.len() / 2,
+                            initcode: a.bytecode.len() / 2,
+                            same_runtime: a.runtime == *baseline_runtime,
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Serializes comparisons as json
+pub fn comparisons_to_json(
+    comparisons: &[ContractComparison],
+) -> Result<String, SerializationError> {
+    Ok(serde_json::to_string_pretty(comparisons)?)
+}
+
+/// Renders comparisons as text, with the size differences from the baseline
+pub fn comparisons_to_text(comparisons: &[ContractComparison]) -> String {
+    let mut out = String::new();
+    for comparison in comparisons {
+        out.push_str(&format!("{}\n", comparison.contract));
+        let width = comparison.builds.iter().map(|b| b.build.len()).max().unwrap_or_default();
+        let baseline = &comparison.builds[0];
+        for (i, build) in comparison.builds.iter().enumerate() {
+            let delta = |size: usize, base: usize| match i {
+                0 => String::new(),
+                _ => format!(" ({:+})", size as i64 - base as i64),
+            };
+            out.push_str(&format!(
+                "  {:width$}  runtime {} bytes{}, initcode {} bytes{}{}\n",
+                build.build,
+                build.runtime,
+                delta(build.runtime, baseline.runtime),
+                build.initcode,
+                delta(build.initcode, baseline.initcode),
+                if i > 0 && build.same_runtime { ", same runtime" } else { "" },
+                width = width
+            ));
+        }
+    }
+    out
+}
@@ -0,0 +1,248 @@
+use huff_lexer::Lexer;
+use huff_utils::prelude::{FullFileSource, SerializationError, TokenKind};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt,
+};
+
+/// The longest shuffle searched for, as the search grows exponentially with its length
+pub const MAX_SHUFFLE_LENGTH: usize = 6;
+
+/// The most stacks a search visits before giving up, bounding its memory on deep stacks
+pub const MAX_SHUFFLE_NODES: usize = 100_000;
+
+/// A Stack Shuffling Instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackOp {
+    /// Duplicates the nth item, ie. `dup2`
+    Dup(usize),
+    /// Swaps the top item with the item n below it, ie. `swap1`
+    Swap(usize),
+    /// Removes the top item
+    Pop,
+}
+
+impl StackOp {
+    /// The shuffling instruction of an opcode byte, if it is one
+    pub fn from_opcode(opcode: u8) -> Option<Self> {
+        match opcode {
+            0x80..=0x8f => Some(StackOp::Dup((opcode - 0x7f) as usize)),
+            0x90..=0x9f => Some(StackOp::Swap((opcode - 0x8f) as usize)),
+            0x50 => Some(StackOp::Pop),
+            _ => None,
+        }
+    }
+
+    /// Applies the instruction to a stack written top first, returning `None` if the stack is
+    /// too shallow
+    pub fn apply(&self, stack: &[String]) -> Option<Vec<String>> {
+        let mut stack = stack.to_vec();
+        match *self {
+            StackOp::Dup(n) => stack.insert(0, stack.get(n.checked_sub(1)?)?.clone()),
+            StackOp::Swap(n) if n < stack.len() => stack.swap(0, n),
+            StackOp::Swap(_) => return None,
+            StackOp::Pop if !stack.is_empty() => {
+                stack.remove(0);
+            }
+            StackOp::Pop => return None,
+        }
+        Some(stack)
+    }
+}
+
+impl fmt::Display for StackOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackOp::Dup(n) => write!(f, "dup{}", n),
+            StackOp::Swap(n) => write!(f, "swap{}", n),
+            StackOp::Pop => write!(f, "pop"),
+        }
+    }
+}
+
+/// Instructions are serialized as their mnemonics, ie. `dup2`
+impl Serialize for StackOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A Hand Written Shuffle With a Shorter Alternative
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShuffleHint {
+    /// The macro or fn the shuffle is written in
+    #[serde(rename = "macro")]
+    pub macro_name: String,
+    /// The 1-indexed line of the shuffle's first instruction
+    pub line: usize,
+    /// The stack commented before the shuffle, top first
+    pub from: Vec<String>,
+    /// The stack commented after the shuffle, top first
+    pub to: Vec<String>,
+    /// The shuffle as written
+    pub written: Vec<StackOp>,
+    /// The shortest shuffle found
+    pub suggested: Vec<StackOp>,
+}
+
+impl fmt::Display for ShuffleHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |ops: &[StackOp]| ops.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        write!(
+            f,
+            "line {} in {}: [{}] -> [{}] with \"{}\" instead of \"{}\"",
+            self.line,
+            self.macro_name,
+            self.from.join(", "),
+            self.to.join(", "),
+            join(&self.suggested).join(" "),
+            join(&self.written).join(" ")
+        )
+    }
+}
+
+/// Searches for the shortest sequence of dups, swaps and pops turning one stack into another,
+/// both written top first, of up to `max_length` instructions.
+///
+/// The search is breadth first, so its cost grows exponentially with `max_length` and the depth
+/// of the stack. It gives up after visiting [`MAX_SHUFFLE_NODES`] stacks, returning `None` as if
+/// no shuffle was found.
+pub fn shortest_shuffle(from: &[String], to: &[String], max_length: usize) -> Option<Vec<StackOp>> {
+    // Dups can't produce items that aren't already on the stack
+    let reachable = |stack: &[String]| to.iter().all(|item| stack.contains(item));
+    if !reachable(from) {
+        return None
+    }
+
+    let mut visited = HashSet::from([from.to_vec()]);
+    let mut queue = VecDeque::from([(from.to_vec(), vec![])]);
+    while let Some((stack, ops)) = queue.pop_front() {
+        if stack == to {
+            return Some(ops)
+        }
+        if ops.len() == max_length {
+            continue
+        }
+        let candidates = (1..=stack.len().min(16))
+            .map(StackOp::Dup)
+            .chain((1..stack.len().min(17)).map(StackOp::Swap))
+            .chain(std::iter::once(StackOp::Pop));
+        for op in candidates {
+            let next = match op.apply(&stack) {
+                Some(next) => next,
+                None => continue,
+            };
+            // Each pop removes a single item, so surplus items must be popped in the steps left
+            let steps_left = max_length - ops.len() - 1;
+            if next.len() > to.len() + steps_left || !reachable(&next) {
+                continue
+            }
+            if visited.len() >= MAX_SHUFFLE_NODES {
+                return None
+            }
+            if visited.insert(next.clone()) {
+                queue.push_back((next, [ops.clone(), vec![op]].concat()));
+            }
+        }
+    }
+    None
+}
+
+/// Parses a stack comment, ie. `// [a, b, c]` with the top of the stack first
+pub fn parse_stack_comment(comment: &str) -> Option<Vec<String>> {
+    let start = comment.find('[')?;
+    let mut depth = 0;
+    let mut items = vec![];
+    let mut item = String::new();
+    for c in comment[start + 1..].chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' if depth > 0 => depth -= 1,
+            ']' => {
+                items.push(item.trim().to_string());
+                return match items.as_slice() {
+                    [only] if only.is_empty() => Some(vec![]),
+                    _ => Some(items),
+                }
+            }
+            ',' if depth == 0 => {
+                items.push(std::mem::take(&mut item).trim().to_string());
+                continue
+            }
+            _ => {}
+        }
+        item.push(c);
+    }
+    None
+}
+
+/// Finds the hand written shuffles of a source file that have a shorter alternative.
+///
+/// This is experimental. Shuffles are runs of dups, swaps and pops with a stack comment right
+/// before and after them, ie. on the lines of the statements they follow. Comments are trusted,
+/// but shuffles that don't produce the stack commented after them are skipped.
+pub fn shuffle_hints(source: &str) -> Vec<ShuffleHint> {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut hints = vec![];
+    let mut macro_name = String::new();
+    let mut defining = false;
+    let mut before: Option<Vec<String>> = None;
+    let mut run: Vec<(StackOp, usize)> = vec![];
+    for token in Lexer::new(full_source).filter_map(Result::ok) {
+        match &token.kind {
+            TokenKind::Whitespace => continue,
+            TokenKind::Macro | TokenKind::Fn | TokenKind::Test => defining = true,
+            TokenKind::Ident(name) if defining => {
+                macro_name = name.clone();
+                defining = false;
+            }
+            TokenKind::Comment(comment) => {
+                let after = match parse_stack_comment(comment) {
+                    Some(stack) => stack,
+                    None => continue,
+                };
+                if let (Some(from), false) = (&before, run.is_empty()) {
+                    let written = run.iter().map(|(op, _)| *op).collect::<Vec<StackOp>>();
+                    let produced =
+                        written.iter().try_fold(from.clone(), |stack, op| op.apply(&stack));
+                    let max_length = (written.len() - 1).min(MAX_SHUFFLE_LENGTH);
+                    if produced.as_ref() == Some(&after) {
+                        if let Some(suggested) = shortest_shuffle(from, &after, max_length) {
+                            hints.push(ShuffleHint {
+                                macro_name: macro_name.clone(),
+                                line: source[..run[0].1].matches('\n').count() + 1,
+                                from: from.clone(),
+                                to: after.clone(),
+                                written,
+                                suggested,
+                            });
+                        }
+                    }
+                }
+                before = Some(after);
+                run.clear();
+                continue
+            }
+            TokenKind::Opcode(o) => {
+                let opcode = o.byte();
+                if let Some(op) = StackOp::from_opcode(opcode) {
+                    run.push((op, token.span.start));
+                    continue
+                }
+            }
+            _ => {}
+        }
+        // Other statements change the stack, so the last comment no longer describes it
+        before = None;
+        run.clear();
+    }
+    hints
+}
+
+/// Serializes the shuffle hints of each file as pretty printed json
+pub fn hints_to_json(
+    hints: &BTreeMap<String, Vec<ShuffleHint>>,
+) -> Result<String, SerializationError> {
+    Ok(serde_json::to_string_pretty(hints)?)
+}
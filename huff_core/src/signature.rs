@@ -0,0 +1,100 @@
+use crate::{
+    docs::{macro_signature, DocumentedContract},
+    index::flatten_dependencies,
+};
+use huff_lexer::Lexer;
+use huff_utils::prelude::{FileSource, FullFileSource, Remapping, Token, TokenKind};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// The Signature of an Invoked Macro
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureHelp {
+    /// The macro name
+    pub name: String,
+    /// The macro signature, as written in source
+    pub label: String,
+    /// The macro parameter names
+    pub parameters: Vec<String>,
+    /// The number of stack items the macro takes
+    pub takes: usize,
+    /// The number of stack items the macro returns
+    pub returns: usize,
+    /// The doc comment preceding the macro definition
+    pub docs: String,
+    /// The index of the argument under the cursor
+    pub active_parameter: usize,
+}
+
+impl SignatureHelp {
+    /// Resolves the signature of the macro invocation enclosing an offset in a file.
+    ///
+    /// The file must be one of the given files or their dependencies. The invocation may be
+    /// incomplete, and files that fail to parse are skipped when resolving the definition.
+    pub fn at(
+        files: &[Arc<FileSource>],
+        remappings: &[Remapping],
+        file: &str,
+        offset: usize,
+    ) -> Option<Self> {
+        let sources = flatten_dependencies(files);
+        let source = sources.iter().find(|f| f.path == file)?.source.as_deref()?;
+        let (name, active_parameter) = enclosing_invocation(source, offset)?;
+
+        sources.iter().find_map(|f| {
+            let documented = DocumentedContract::parse(f, remappings)?;
+            let m = documented.contract.macros.iter().find(|m| m.name == name)?;
+            Some(SignatureHelp {
+                name: m.name.clone(),
+                label: macro_signature(m),
+                parameters: m.parameters.iter().filter_map(|p| p.name.clone()).collect(),
+                takes: m.takes,
+                returns: m.returns,
+                docs: documented.docs(&m.span),
+                active_parameter,
+            })
+        })
+    }
+}
+
+/// Finds the innermost unclosed macro invocation before an offset, returning the macro name and
+/// the index of the argument being written
+fn enclosing_invocation(source: &str, offset: usize) -> Option<(String, usize)> {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source)
+        .map_while(Result::ok)
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment(_)))
+        .take_while(|t| t.span.end <= offset)
+        .collect::<Vec<Token>>();
+
+    // Open parentheses, with the invoked macro and argument index for invocations
+    let mut parens: Vec<Option<(String, usize)>> = vec![];
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::Define => {
+                parens.clear();
+                depth = 0;
+            }
+            TokenKind::OpenBrace => depth += 1,
+            TokenKind::CloseBrace => depth = depth.saturating_sub(1),
+            TokenKind::OpenParen => {
+                let invoked = match i.checked_sub(1).map(|p| &tokens[p].kind) {
+                    Some(TokenKind::Ident(name)) if depth > 0 => Some((name.clone(), 0)),
+                    _ => None,
+                };
+                parens.push(invoked);
+            }
+            TokenKind::CloseParen => {
+                parens.pop();
+            }
+            TokenKind::Comma => {
+                if let Some(Some((_, arg))) = parens.last_mut() {
+                    *arg += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    parens.into_iter().rev().flatten().next()
+}
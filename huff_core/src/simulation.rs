@@ -0,0 +1,427 @@
+use ethers_core::utils::hex;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{
+    Artifact, ConstructorSimulation, FullFileSource, SimulationError, SimulationErrorKind, Token,
+};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    inspector_handle_register,
+    interpreter::{opcode::OpCode, Interpreter},
+    primitives::{
+        keccak256, AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output, TxKind, B256,
+        U256,
+    },
+    Database, DatabaseCommit, Evm, EvmContext, Inspector,
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt, ops::Range, str::FromStr};
+
+/// The gas limit constructors are simulated with
+pub const SIMULATION_GAS_LIMIT: u64 = 30_000_000;
+
+/// The address simulated deployments and calls are sent from, which isn't the zero address so
+/// constructors storing the `caller` can be told apart from ones storing nothing
+pub const SIMULATION_SENDER: Address = Address::with_last_byte(1);
+
+/// Constructor Simulation
+///
+/// Executes the deployment bytecode as a create transaction against an empty state, returning
+/// the deployed runtime bytecode and the storage slots the constructor wrote.
+pub fn simulate_constructor(
+    file: &str,
+    bytecode: &str,
+) -> Result<ConstructorSimulation, SimulationError> {
+    deploy(&mut CacheDB::new(EmptyDB::default()), file, bytecode).map(|(simulation, _)| simulation)
+}
+
+/// Deploys the bytecode with a create transaction, committing the created account to the state
+fn deploy(
+    db: &mut CacheDB<EmptyDB>,
+    file: &str,
+    bytecode: &str,
+) -> Result<(ConstructorSimulation, Address), SimulationError> {
+    let error = |kind: SimulationErrorKind| SimulationError { kind, file: file.to_string() };
+    let initcode =
+        hex::decode(bytecode).map_err(|e| error(SimulationErrorKind::EvmError(e.to_string())))?;
+
+    let mut evm = Evm::builder()
+        .with_db(&mut *db)
+        .modify_tx_env(|tx| {
+            tx.caller = SIMULATION_SENDER;
+            tx.transact_to = TxKind::Create;
+            tx.data = Bytes::from(initcode);
+            tx.gas_limit = SIMULATION_GAS_LIMIT;
+        })
+        .build();
+    let res =
+        evm.transact().map_err(|e| error(SimulationErrorKind::EvmError(format!("{:?}", e))))?;
+    drop(evm);
+
+    match res.result {
+        ExecutionResult::Success {
+            gas_used,
+            output: Output::Create(runtime, Some(address)),
+            ..
+        } => {
+            let storage = res
+                .state
+                .get(&address)
+                .map(|account| {
+                    account
+                        .storage
+                        .iter()
+                        .filter(|(_, slot)| slot.is_changed())
+                        .map(|(k, slot)| {
+                            (format!("{:#066x}", k), format!("{:#x}", slot.present_value))
+                        })
+                        .collect::<BTreeMap<String, String>>()
+                })
+                .unwrap_or_default();
+            db.commit(res.state);
+            tracing::info!(target: "core", "SIMULATED CONSTRUCTOR FOR \"{}\" USING {} GAS", file, gas_used);
+            Ok((
+                ConstructorSimulation { runtime: hex::encode(runtime), storage, gas_used },
+                address,
+            ))
+        }
+        ExecutionResult::Success { .. } => {
+            Err(error(SimulationErrorKind::EvmError("Create returned call output".to_string())))
+        }
+        ExecutionResult::Revert { output, .. } => {
+            tracing::error!(target: "core", "CONSTRUCTOR REVERTED FOR \"{}\"", file);
+            Err(error(SimulationErrorKind::Reverted(hex::encode(output))))
+        }
+        ExecutionResult::Halt { reason, .. } => {
+            tracing::error!(target: "core", "CONSTRUCTOR HALTED FOR \"{}\"", file);
+            Err(error(SimulationErrorKind::Halted(format!("{:?}", reason))))
+        }
+    }
+}
+
+/// Local State to Simulate Calls Against
+///
+/// Accounts keyed by their hex address, in the format of geth's `prestateTracer`:
+///
+/// ```json
+/// {
+///   "0x000000000000000000000000000000000000beef": {
+///     "balance": "0x2a",
+///     "code": "0x60005460005260206000f3",
+///     "storage": { "0x00": "0x01" }
+///   }
+/// }
+/// ```
+///
+/// Chain state can't be forked, as there's no rpc client to fetch it with, so any accounts a call
+/// depends on must be given here.
+pub type Prestate = BTreeMap<String, PrestateAccount>;
+
+/// An Account of a [Prestate]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PrestateAccount {
+    /// The hex balance in wei
+    pub balance: Option<String>,
+    /// The account nonce
+    pub nonce: u64,
+    /// The hex runtime bytecode
+    pub code: Option<String>,
+    /// The hex storage values, keyed by hex slot
+    pub storage: BTreeMap<String, String>,
+}
+
+/// Reads a [Prestate] from a json file
+pub fn read_prestate(path: &str) -> Result<Prestate, String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+}
+
+/// Call Simulation
+///
+/// Deploys an artifact against the given prestate, then calls it with the given calldata and
+/// value, tracing every executed instruction. Instructions of the artifact's runtime are annotated
+/// with the definition and source line they were generated from, using its source map.
+///
+/// Prestate accounts are inserted after the artifact is deployed and the caller is funded, so
+/// they replace either if they share an address.
+pub fn simulate_call(
+    artifact: &Artifact,
+    calldata: &[u8],
+    value: u128,
+    prestate: &Prestate,
+) -> Result<CallTrace, SimulationError> {
+    call(artifact, calldata, value, prestate, true)
+}
+
+/// Simulates a call like [simulate_call], without tracing it
+pub fn simulate_call_outcome(
+    artifact: &Artifact,
+    calldata: &[u8],
+    value: u128,
+    prestate: &Prestate,
+) -> Result<TraceOutcome, SimulationError> {
+    call(artifact, calldata, value, prestate, false).map(|trace| trace.outcome)
+}
+
+/// Locates the instructions of an artifact's runtime in its source, by program counter.
+///
+/// Instructions are located in the definition whose span contains their own.
+pub fn locate(artifact: &Artifact) -> BTreeMap<usize, TraceLocation> {
+    let mut definitions: BTreeMap<String, Vec<(String, Range<usize>)>> = BTreeMap::new();
+    artifact
+        .source_map
+        .as_ref()
+        .map(|map| {
+            map.decode(&map.runtime, &artifact.runtime)
+                .into_iter()
+                .filter_map(|(pc, loc)| {
+                    let source = artifact.file.find(&loc.file)?.source.as_ref()?;
+                    let definitions = definitions
+                        .entry(loc.file.clone())
+                        .or_insert_with(|| definition_spans(source));
+                    let definition = definitions
+                        .iter()
+                        .find(|(_, span)| span.contains(&loc.start))
+                        .map(|(name, _)| name.clone());
+                    Some((pc, TraceLocation::new(loc.file, source, loc.start, definition)?))
+                })
+                .collect::<BTreeMap<usize, TraceLocation>>()
+        })
+        .unwrap_or_default()
+}
+
+/// Deploys an artifact and calls it, recording each executed instruction if tracing
+fn call(
+    artifact: &Artifact,
+    calldata: &[u8],
+    value: u128,
+    prestate: &Prestate,
+    trace: bool,
+) -> Result<CallTrace, SimulationError> {
+    let file = &artifact.file.path;
+    let error = |kind: SimulationErrorKind| SimulationError { kind, file: file.to_string() };
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    let (_, address) = deploy(&mut db, file, &artifact.bytecode)?;
+    let code_hash =
+        db.basic(address).ok().flatten().map(|account| account.code_hash).unwrap_or(keccak256([]));
+
+    // The caller is funded with the value sent
+    db.insert_account_info(
+        SIMULATION_SENDER,
+        AccountInfo { balance: U256::from(value), ..Default::default() },
+    );
+    insert_prestate(&mut db, prestate)
+        .map_err(|e| error(SimulationErrorKind::InvalidPrestate(e)))?;
+
+    let locations = if trace { locate(artifact) } else { BTreeMap::new() };
+    let mut tracer = Tracer { trace, code_hash, locations, steps: vec![] };
+    let mut evm = Evm::builder()
+        .with_db(&mut db)
+        .with_external_context(&mut tracer)
+        .modify_tx_env(|tx| {
+            tx.caller = SIMULATION_SENDER;
+            tx.transact_to = TxKind::Call(address);
+            tx.data = Bytes::copy_from_slice(calldata);
+            tx.value = U256::from(value);
+            tx.gas_limit = SIMULATION_GAS_LIMIT;
+        })
+        .append_handler_register(inspector_handle_register)
+        .build();
+    let res =
+        evm.transact().map_err(|e| error(SimulationErrorKind::EvmError(format!("{:?}", e))))?;
+    drop(evm);
+
+    let (outcome, gas_used) = match res.result {
+        ExecutionResult::Success { gas_used, output, .. } => {
+            (TraceOutcome::Returned(hex::encode(output.into_data())), gas_used)
+        }
+        ExecutionResult::Revert { gas_used, output } => {
+            (TraceOutcome::Reverted(hex::encode(output)), gas_used)
+        }
+        ExecutionResult::Halt { reason, gas_used } => {
+            (TraceOutcome::Halted(format!("{:?}", reason)), gas_used)
+        }
+    };
+    tracing::info!(target: "core", "SIMULATED CALL TO \"{}\" USING {} GAS", file, gas_used);
+    Ok(CallTrace { address: format!("{:#x}", address), steps: tracer.steps, outcome, gas_used })
+}
+
+/// Inserts the accounts of a prestate into the simulated state
+fn insert_prestate(db: &mut CacheDB<EmptyDB>, prestate: &Prestate) -> Result<(), String> {
+    let word = |s: &str| U256::from_str(s).map_err(|e| format!("Invalid word \"{}\": {}", s, e));
+    for (address, account) in prestate {
+        let address = Address::from_str(address)
+            .map_err(|e| format!("Invalid address \"{}\": {}", address, e))?;
+        let code = account
+            .code
+            .as_deref()
+            .map(|c| hex::decode(c.trim_start_matches("0x")))
+            .transpose()
+            .map_err(|e| format!("Invalid code for {:#x}: {}", address, e))?
+            .unwrap_or_default();
+        let info = AccountInfo {
+            balance: account.balance.as_deref().map(word).transpose()?.unwrap_or_default(),
+            nonce: account.nonce,
+            code_hash: keccak256(&code),
+            code: Some(Bytecode::new_raw(Bytes::from(code))),
+        };
+        db.insert_account_info(address, info);
+        for (slot, value) in &account.storage {
+            db.insert_account_storage(address, word(slot)?, word(value)?)
+                .map_err(|e| format!("{:?}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the names of the macros and functions defined in a source, with the byte range of
+/// their definitions. Sources that fail to parse have none.
+fn definition_spans(source: &str) -> Vec<(String, Range<usize>)> {
+    let lexer = Lexer::new(FullFileSource { source, file: None, spans: vec![] });
+    let contract = match lexer.collect::<Result<Vec<Token>, _>>() {
+        Ok(tokens) => Parser::new(tokens, None).parse().ok(),
+        Err(_) => None,
+    };
+    contract
+        .into_iter()
+        .flat_map(|c| c.macros)
+        .filter_map(|m| {
+            let start = m.span.0.iter().map(|s| s.start).min()?;
+            let end = m.span.0.iter().map(|s| s.end).max()?;
+            Some((m.name, start..end))
+        })
+        .collect()
+}
+
+/// The Trace of a Simulated Call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTrace {
+    /// The address the artifact was deployed at
+    pub address: String,
+    /// The executed instructions, in order
+    pub steps: Vec<TraceStep>,
+    /// How the call ended
+    pub outcome: TraceOutcome,
+    /// The gas used by the call, including the intrinsic gas
+    pub gas_used: u64,
+}
+
+/// How a Simulated Call Ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceOutcome {
+    /// The call returned the given hex encoded output
+    Returned(String),
+    /// The call reverted with the given hex encoded output
+    Reverted(String),
+    /// The call halted for the given reason
+    Halted(String),
+}
+
+/// An Executed Instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The call depth, starting at 1 for the simulated call
+    pub depth: u64,
+    /// The address of the executing account
+    pub address: String,
+    /// The program counter
+    pub pc: usize,
+    /// The opcode name
+    pub opcode: String,
+    /// The gas remaining before the instruction executed
+    pub gas: u64,
+    /// Where the instruction was generated from, if it's in the artifact's runtime
+    pub location: Option<TraceLocation>,
+}
+
+/// The Huff Source an Instruction Was Generated From
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceLocation {
+    /// The macro or function defining the instruction
+    pub definition: Option<String>,
+    /// The path of the source file
+    pub file: String,
+    /// The 1-indexed line number
+    pub line: usize,
+    /// The trimmed source line
+    pub code: String,
+}
+
+impl TraceLocation {
+    /// Resolves the line of a byte offset into a source
+    fn new(file: String, source: &str, offset: usize, definition: Option<String>) -> Option<Self> {
+        let preceding = source.get(..offset)?;
+        let line_start = preceding.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len());
+
+        Some(TraceLocation {
+            definition,
+            file,
+            line: preceding.matches('\n').count() + 1,
+            code: source[line_start..line_end].trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CallTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut frame: Option<(u64, &str)> = None;
+        for step in &self.steps {
+            if frame != Some((step.depth, &step.address)) {
+                writeln!(f, "[{}] {}", step.depth, step.address)?;
+                frame = Some((step.depth, &step.address));
+            }
+            let indent = "  ".repeat(step.depth as usize);
+            write!(f, "{}{:>5} {:<14} {:>10}", indent, step.pc, step.opcode, step.gas)?;
+            match &step.location {
+                Some(loc) => writeln!(
+                    f,
+                    "  {} {}:{} | {}",
+                    loc.definition.as_deref().unwrap_or("-"),
+                    loc.file,
+                    loc.line,
+                    loc.code
+                )?,
+                None => writeln!(f)?,
+            }
+        }
+        match &self.outcome {
+            TraceOutcome::Returned(output) => write!(f, "Returned 0x{}", output)?,
+            TraceOutcome::Reverted(output) => write!(f, "Reverted with 0x{}", output)?,
+            TraceOutcome::Halted(reason) => write!(f, "Halted: {}", reason)?,
+        }
+        writeln!(f, " using {} gas", self.gas_used)
+    }
+}
+
+/// Records each executed instruction, locating those of the traced runtime
+struct Tracer {
+    trace: bool,
+    code_hash: B256,
+    locations: BTreeMap<usize, TraceLocation>,
+    steps: Vec<TraceStep>,
+}
+
+impl<DB: Database> Inspector<DB> for Tracer {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if !self.trace {
+            return
+        }
+        let pc = interp.program_counter();
+        let location = match interp.contract.hash == Some(self.code_hash) {
+            true => self.locations.get(&pc).cloned(),
+            false => None,
+        };
+        self.steps.push(TraceStep {
+            depth: context.journaled_state.depth(),
+            address: format!("{:#x}", interp.contract.target_address),
+            pc,
+            opcode: OpCode::name_by_op(interp.current_opcode()).to_string(),
+            gas: interp.gas.remaining(),
+            location,
+        });
+    }
+}
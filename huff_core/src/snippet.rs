@@ -0,0 +1,93 @@
+use huff_codegen::{config::CodegenConfig, Codegen};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::{CompilerError, FullFileSource, Token};
+
+/// The name of the synthetic macro wrapping a snippet's body
+const SNIPPET_MACRO: &str = "__SNIPPET";
+
+/// A Compiled Huff Snippet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snippet {
+    /// The generated bytes, with outlined macros and tables appended after the body
+    pub bytes: Vec<u8>,
+    /// The number of bytes of the body, before the appended outlined macros and tables. A body
+    /// that doesn't halt or jump away falls through into them, so snippets continuing into the
+    /// embedding code must end in a jump past `bytes`, unless nothing is appended.
+    pub body_len: usize,
+    /// The number of stack items the snippet was declared to take
+    pub takes: usize,
+    /// The number of items the snippet leaves on the stack less those it takes, or `None` if it
+    /// can't be known, ie. because the snippet always halts
+    pub stack_delta: Option<isize>,
+    /// The most items on the stack while the snippet runs, including those it takes
+    pub max_stack_depth: usize,
+}
+
+/// Compiles Standalone Huff Snippets
+///
+/// For other code generators to embed hand written Huff in their own bytecode. A snippet is a
+/// macro body compiled on its own, without a `MAIN` or `CONSTRUCTOR`, from a declared number of
+/// items on the stack.
+///
+/// ```rust
+/// use huff_core::snippet::SnippetCompiler;
+///
+/// let compiler = SnippetCompiler {
+///     definitions: "#define macro DOUBLE() = takes (1) returns (1) { dup1 add }".to_string(),
+///     ..Default::default()
+/// };
+/// let snippet = compiler.compile("DOUBLE() 0x01 add", 1).unwrap();
+/// assert_eq!(snippet.bytes, vec![0x80, 0x01, 0x60, 0x01, 0x01]);
+/// assert_eq!(snippet.stack_delta, Some(0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SnippetCompiler {
+    /// Huff source defining the constants, macros, and tables snippets can use. Imports are not
+    /// resolved, so included files must be inlined.
+    pub definitions: String,
+    /// The offset the snippet is placed at in the embedding bytecode, which its jump
+    /// destinations are resolved relative to
+    pub offset: usize,
+    /// The code generation options, ie. the targeted evm version
+    pub config: CodegenConfig,
+}
+
+impl SnippetCompiler {
+    /// Compiles a macro body expecting `takes` items on the stack.
+    ///
+    /// Fails if the body pops more items than it takes, or jumps to a label it doesn't define.
+    pub fn compile(&self, body: &str, takes: usize) -> Result<Snippet, CompilerError<'static>> {
+        let source = format!(
+            "{}\n#define macro {}() = takes ({}) returns (0) {{\n{}\n}}\n",
+            self.definitions, SNIPPET_MACRO, takes, body
+        );
+        let full_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let tokens = Lexer::new(full_source)
+            .collect::<Result<Vec<Token>, _>>()
+            .map_err(|e| CompilerError::LexicalError(e.into_owned()))?;
+        let mut contract = Parser::new(tokens, None).parse().map_err(CompilerError::ParserError)?;
+        contract.derive_storage_pointers_from(&[SNIPPET_MACRO]);
+
+        let height =
+            Codegen::stack_height(SNIPPET_MACRO, &contract).map_err(CompilerError::CodegenError)?;
+        let max_stack_depth = Codegen::max_stack_depths(&contract)
+            .get(SNIPPET_MACRO)
+            .map_or(takes, |depth| depth.max);
+        // The snippet returns whatever it leaves on the stack, so it passes stack checks
+        if let Some(m) = contract.macros.iter_mut().find(|m| m.name == SNIPPET_MACRO) {
+            m.returns = height.unwrap_or_default();
+        }
+
+        let (bytes, body_len) =
+            Codegen::generate_macro_bytes_at(SNIPPET_MACRO, &contract, self.offset, &self.config)
+                .map_err(CompilerError::CodegenError)?;
+        Ok(Snippet {
+            bytes,
+            body_len,
+            takes,
+            stack_delta: height.map(|h| h as isize - takes as isize),
+            max_stack_depth,
+        })
+    }
+}
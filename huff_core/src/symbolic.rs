@@ -0,0 +1,184 @@
+use crate::simulation::{locate, simulate_call_outcome, Prestate, TraceOutcome};
+use ethers_core::utils::{hex, keccak256};
+use huff_utils::prelude::{Artifact, Instruction, Instructions, SerializationError};
+use serde::Serialize;
+use std::process::Command;
+
+/// The revert data of a failed `Panic(uint256)` assertion, as `std/assert.huff` reverts with
+pub const ASSERTION_PANIC: &str =
+    "4e487b710000000000000000000000000000000000000000000000000000000000000001";
+
+/// A Symbolic Execution Target
+///
+/// The label resolved runtime of an artifact, with the entry points of its dispatcher and the
+/// reverts of its assertion macros, for symbolic tools like hevm or halmos to explore.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolicTarget {
+    /// The hex encoded runtime bytecode, with its macros expanded and labels resolved
+    pub runtime: String,
+    /// The functions the dispatcher jumps to
+    #[serde(rename = "entryPoints")]
+    pub entry_points: Vec<EntryPoint>,
+    /// The reverts of assertion macros, which a symbolic tool should prove unreachable
+    pub assertions: Vec<AssertionSite>,
+}
+
+/// A Function Entry Point
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryPoint {
+    /// The function signature, if the selector is in the abi
+    pub signature: Option<String>,
+    /// The hex encoded function selector
+    pub selector: String,
+    /// The program counter the dispatcher jumps to
+    pub pc: usize,
+}
+
+/// The Revert of an Assertion Macro
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct AssertionSite {
+    /// The program counter of the revert
+    pub pc: usize,
+    /// The assertion macro
+    #[serde(rename = "macro")]
+    pub macro_name: String,
+    /// The path of the source file
+    pub file: String,
+    /// The 1-indexed line number
+    pub line: usize,
+}
+
+/// The Result of Checking an Entry Point Symbolically
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolicResult {
+    /// The checked entry point
+    pub entry_point: EntryPoint,
+    /// Whether the symbolic tool found no way to fail an assertion
+    pub passed: bool,
+    /// The calldata of a call failing an assertion, confirmed by simulating it, if one was found
+    pub counterexample: Option<String>,
+    /// The output of the symbolic tool
+    pub output: String,
+}
+
+impl SymbolicTarget {
+    /// Builds the symbolic target of an artifact.
+    ///
+    /// Entry points are found by the dispatcher pattern comparing the selector with `eq` and
+    /// jumping on a match, ie. `dup1 __FUNC_SIG(transfer) eq transfer jumpi`. Assertions are the
+    /// reverts of macros whose name starts with `ASSERT`, like those of `std/assert.huff`.
+    pub fn new(artifact: &Artifact) -> Self {
+        let instructions = Instructions::new(&artifact.runtime).collect::<Vec<Instruction>>();
+
+        let functions = artifact.abi.as_ref().map(|abi| abi.functions.values().collect::<Vec<_>>());
+        let entry_points = instructions
+            .windows(5)
+            .filter_map(|w| {
+                // The selector may be duplicated in between the push and the comparison
+                let (push, rest) = match w[1].opcode {
+                    0x80..=0x8f => (w[0], &w[2..5]),
+                    _ => (w[0], &w[1..4]),
+                };
+                let is_selector = (0x60..=0x63).contains(&push.opcode);
+                let dispatches = rest[0].opcode == 0x14 &&
+                    (0x60..=0x7f).contains(&rest[1].opcode) &&
+                    rest[2].opcode == 0x57;
+                if !is_selector || !dispatches {
+                    return None
+                }
+                let data = hex::decode(push.data).ok()?;
+                let selector = [vec![0; 4 - data.len()], data].concat();
+                let pc = usize::from_str_radix(rest[1].data, 16).ok()?;
+                let signature = functions.iter().flatten().find_map(|f| {
+                    let signature = f.signature();
+                    (keccak256(signature.as_bytes())[..4] == selector[..]).then_some(signature)
+                });
+                Some(EntryPoint { signature, selector: format!("0x{}", hex::encode(selector)), pc })
+            })
+            .collect();
+
+        let locations = locate(artifact);
+        let assertions = instructions
+            .iter()
+            .filter(|i| i.opcode == 0xfd)
+            .filter_map(|Instruction { pc, .. }| {
+                let location = locations.get(pc)?;
+                let definition =
+                    location.definition.as_ref().filter(|d| d.starts_with("ASSERT"))?;
+                Some(AssertionSite {
+                    pc: *pc,
+                    macro_name: definition.clone(),
+                    file: location.file.clone(),
+                    line: location.line,
+                })
+            })
+            .collect();
+
+        SymbolicTarget { runtime: artifact.runtime.clone(), entry_points, assertions }
+    }
+
+    /// Serializes the target as pretty printed json
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Checks each entry point of an artifact for calldata failing an assertion with `hevm symbolic`,
+/// which must be installed.
+///
+/// Counterexamples hevm reports are simulated, and only kept if the call does revert with the
+/// assertion panic.
+pub fn check_with_hevm(artifact: &Artifact) -> Result<Vec<SymbolicResult>, String> {
+    let target = SymbolicTarget::new(artifact);
+    target
+        .entry_points
+        .iter()
+        .filter(|e| e.signature.is_some())
+        .map(|entry_point| {
+            let signature = entry_point.signature.clone().unwrap_or_default();
+            tracing::info!(target: "core", "CHECKING \"{}\" SYMBOLICALLY", signature);
+            let out = Command::new("hevm")
+                .args(["symbolic", "--code", &target.runtime, "--sig", &signature])
+                .output()
+                .map_err(|e| format!("Failed to run hevm: {}", e))?;
+            let output = format!(
+                "{}{}",
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr)
+            );
+            Ok(SymbolicResult::from_hevm(artifact, entry_point, out.status.success(), output))
+        })
+        .collect()
+}
+
+impl SymbolicResult {
+    /// Reads the result of checking an entry point from the output of `hevm symbolic`.
+    ///
+    /// The counterexample is the first hex word of at least a selector after the
+    /// `Counterexample` heading that fails an assertion when the artifact is called with it.
+    pub fn from_hevm(
+        artifact: &Artifact,
+        entry_point: &EntryPoint,
+        passed: bool,
+        output: String,
+    ) -> Self {
+        let counterexample = output
+            .split("Counterexample")
+            .skip(1)
+            .flat_map(|s| s.split(|c: char| !c.is_ascii_hexdigit() && c != 'x'))
+            .filter_map(|w| w.strip_prefix("0x"))
+            .filter(|w| w.len() >= 8 && w.len() % 2 == 0)
+            .find(|calldata| fails_assertion(artifact, calldata))
+            .map(|calldata| format!("0x{}", calldata));
+        SymbolicResult { entry_point: entry_point.clone(), passed, counterexample, output }
+    }
+}
+
+/// Whether calling the artifact with the hex calldata reverts with the assertion panic
+pub fn fails_assertion(artifact: &Artifact, calldata: &str) -> bool {
+    let calldata = hex::decode(calldata.trim_start_matches("0x")).unwrap_or_default();
+    matches!(
+        simulate_call_outcome(artifact, &calldata, 0, &Prestate::default()),
+        Ok(TraceOutcome::Reverted(output)) if output == ASSERTION_PANIC
+    )
+}
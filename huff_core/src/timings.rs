@@ -0,0 +1,91 @@
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+/// A Compilation Stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// Lexical analysis
+    Lex,
+    /// Parsing and storage pointer derivation
+    Parse,
+    /// Import resolution
+    Imports,
+    /// Bytecode and abi generation
+    Codegen,
+    /// Artifact export
+    Export,
+}
+
+impl Stage {
+    /// Every stage, in report order
+    pub const ALL: [Stage; 5] =
+        [Stage::Lex, Stage::Parse, Stage::Imports, Stage::Codegen, Stage::Export];
+
+    /// The name of the stage
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Lex => "lex",
+            Stage::Parse => "parse",
+            Stage::Imports => "imports",
+            Stage::Codegen => "codegen",
+            Stage::Export => "export",
+        }
+    }
+}
+
+/// Stage Durations of Each Compiled Contract
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Timings {
+    /// The durations of each contract's stages, keyed by file path
+    pub contracts: BTreeMap<String, BTreeMap<Stage, Duration>>,
+}
+
+impl Timings {
+    /// Adds a stage duration to a contract
+    pub fn record(&mut self, file: &str, stage: Stage, duration: Duration) {
+        *self.contracts.entry(file.to_string()).or_default().entry(stage).or_default() += duration;
+    }
+
+    /// The summed duration of each stage across all contracts
+    pub fn totals(&self) -> BTreeMap<Stage, Duration> {
+        let mut totals: BTreeMap<Stage, Duration> = BTreeMap::new();
+        for (stage, duration) in self.contracts.values().flatten() {
+            *totals.entry(*stage).or_default() += *duration;
+        }
+        totals
+    }
+
+    /// Renders a table of stage durations per contract with a totals row.
+    ///
+    /// Stages that did not run, ie. when artifacts are cached, are shown as `-`.
+    pub fn report(&self) -> String {
+        if self.contracts.is_empty() {
+            return "No stages were timed, artifacts were cached\n".to_string()
+        }
+
+        let mut rows = self.contracts.iter().collect::<Vec<(&String, &BTreeMap<Stage, _>)>>();
+        let totals = self.totals();
+        let total_label = "total".to_string();
+        rows.push((&total_label, &totals));
+
+        let width = rows.iter().map(|(f, _)| f.len()).max().unwrap_or_default().max(8);
+        let mut report = format!("{:<width$}", "contract", width = width);
+        for stage in Stage::ALL {
+            let _ = write!(report, " {:>10}", stage.name());
+        }
+        let _ = writeln!(report, " {:>10}", "total");
+        for (file, stages) in rows {
+            let _ = write!(report, "{:<width$}", file, width = width);
+            for stage in Stage::ALL {
+                let cell = stages.get(&stage).map(format_duration).unwrap_or_else(|| "-".into());
+                let _ = write!(report, " {:>10}", cell);
+            }
+            let _ = writeln!(report, " {:>10}", format_duration(&stages.values().sum()));
+        }
+        report
+    }
+}
+
+/// Formats a duration in milliseconds
+fn format_duration(duration: &Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,218 @@
+use huff_utils::prelude::{
+    str_to_bytes32, unpack_files, EvmVersion, Literal, Remapping, WorkspaceError, REMAPPINGS_FILE,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The workspace and package manifest file name
+pub const MANIFEST_FILE: &str = "huff.toml";
+
+/// The default package source directory
+pub const DEFAULT_SRC: &str = "contracts";
+
+/// The workspace manifest
+#[derive(Debug, Deserialize)]
+struct WorkspaceManifest {
+    workspace: WorkspaceSection,
+}
+
+/// The `[workspace]` manifest section
+#[derive(Debug, Deserialize)]
+struct WorkspaceSection {
+    members: Vec<String>,
+}
+
+/// A member package manifest
+#[derive(Debug, Default, Deserialize)]
+struct PackageManifest {
+    #[serde(default)]
+    package: PackageSection,
+}
+
+/// The `[package]` manifest section
+#[derive(Debug, Default, Deserialize)]
+struct PackageSection {
+    name: Option<String>,
+    src: Option<String>,
+}
+
+/// The network profiles of a manifest
+#[derive(Debug, Default, Deserialize)]
+struct NetworksManifest {
+    #[serde(default)]
+    networks: BTreeMap<String, NetworkProfile>,
+}
+
+/// A Workspace Member Package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// The package name, used as its import prefix
+    pub name: String,
+    /// The package directory
+    pub path: PathBuf,
+    /// The package source directory
+    pub src: PathBuf,
+}
+
+/// A Workspace of Huff Packages
+///
+/// A `huff.toml` with a `[workspace]` section lists member package directories:
+///
+/// ```toml
+/// [workspace]
+/// members = ["token", "vault"]
+/// ```
+///
+/// Each member may have its own `huff.toml` with a `[package]` section setting its `name`
+/// (defaulting to the directory name) and `src` directory (defaulting to `contracts`).
+/// Members are compiled together and can import each other's sources as `<name>/<file>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    /// The workspace root directory
+    pub root: PathBuf,
+    /// The member packages
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Loads a workspace from a manifest path or a directory containing a `huff.toml`
+    pub fn load(path: &Path) -> Result<Self, WorkspaceError> {
+        let manifest_path = match path.is_dir() {
+            true => path.join(MANIFEST_FILE),
+            false => path.to_path_buf(),
+        };
+        let root = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let manifest: WorkspaceManifest = read_manifest(&manifest_path)?;
+
+        let mut members: Vec<WorkspaceMember> = vec![];
+        for member in manifest.workspace.members {
+            let path = root.join(&member);
+            if !path.is_dir() {
+                return Err(WorkspaceError::MissingMember(path.to_string_lossy().to_string()))
+            }
+            let package_manifest = path.join(MANIFEST_FILE);
+            let package = match package_manifest.exists() {
+                true => read_manifest::<PackageManifest>(&package_manifest)?.package,
+                false => PackageSection::default(),
+            };
+            let name = package.name.unwrap_or_else(|| {
+                path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(member)
+            });
+            if members.iter().any(|m| m.name == name) {
+                return Err(WorkspaceError::DuplicateMember(name))
+            }
+            let src = path.join(package.src.as_deref().unwrap_or(DEFAULT_SRC));
+            tracing::info!(target: "workspace", "FOUND MEMBER \"{}\" AT {:?}", name, path);
+            members.push(WorkspaceMember { name, path, src });
+        }
+
+        Ok(Workspace { root, members })
+    }
+
+    /// Returns the huff source files of every member
+    pub fn sources(&self) -> Result<Vec<String>, WorkspaceError> {
+        let mut sources = vec![];
+        for member in &self.members {
+            let src = member.src.to_string_lossy().to_string();
+            let mut files =
+                unpack_files(&src).map_err(|_| WorkspaceError::MissingMember(src.clone()))?;
+            files.sort();
+            sources.append(&mut files);
+        }
+        Ok(sources)
+    }
+
+    /// Returns the remappings for importing members by name, along with the remappings in the
+    /// workspace root's `remappings.txt`. Member names take precedence.
+    pub fn remappings(&self) -> Vec<Remapping> {
+        let mut remappings = Remapping::read(self.root.join(REMAPPINGS_FILE))
+            .into_iter()
+            .map(|r| Remapping {
+                path: format!("{}", self.root.join(&r.path).to_string_lossy()),
+                prefix: r.prefix,
+            })
+            .collect::<Vec<Remapping>>();
+        Remapping::merge(
+            &mut remappings,
+            self.members
+                .iter()
+                .map(|m| Remapping {
+                    prefix: format!("{}/", m.name),
+                    path: format!("{}/", m.src.to_string_lossy()),
+                })
+                .collect(),
+        );
+        remappings
+    }
+}
+
+/// A Network Profile
+///
+/// Named `[networks.<name>]` sections of a `huff.toml` configure how contracts are built for each
+/// network:
+///
+/// ```toml
+/// [networks.optimism]
+/// evm_version = "london"
+///
+/// [networks.optimism.constants]
+/// OWNER = "0x4200000000000000000000000000000000000006"
+/// ```
+///
+/// The `evm_version` defaults to `london`, and contracts using opcodes introduced after it fail
+/// to compile.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct NetworkProfile {
+    /// The evm version the network supports
+    #[serde(default)]
+    pub evm_version: EvmVersion,
+    /// Constant overrides, keyed by constant name
+    #[serde(default)]
+    pub constants: BTreeMap<String, String>,
+}
+
+impl NetworkProfile {
+    /// Loads a network profile by name from a manifest path or a directory containing a
+    /// `huff.toml`
+    pub fn load(path: &Path, name: &str) -> Result<Self, WorkspaceError> {
+        let manifest_path = match path.is_dir() {
+            true => path.join(MANIFEST_FILE),
+            false => path.to_path_buf(),
+        };
+        let mut manifest: NetworksManifest = read_manifest(&manifest_path)?;
+        manifest
+            .networks
+            .remove(name)
+            .ok_or_else(|| WorkspaceError::MissingNetwork(name.to_string()))
+    }
+
+    /// Returns the constant overrides as literals, which must be `0x` prefixed hex of at most 32
+    /// bytes
+    pub fn constant_overrides(&self) -> Result<BTreeMap<&str, Literal>, WorkspaceError> {
+        self.constants
+            .iter()
+            .map(|(name, value)| {
+                let digits = value.strip_prefix("0x").unwrap_or_default();
+                if digits.is_empty() ||
+                    digits.len() > 64 ||
+                    !digits.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(WorkspaceError::InvalidConstantOverride(name.clone(), value.clone()))
+                }
+                Ok((name.as_str(), str_to_bytes32(digits)))
+            })
+            .collect()
+    }
+}
+
+/// Reads and deserializes a toml manifest
+fn read_manifest<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, WorkspaceError> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|_| WorkspaceError::MissingManifest(path.to_string_lossy().to_string()))?;
+    toml::from_str(&source).map_err(|e| {
+        WorkspaceError::InvalidManifest(path.to_string_lossy().to_string(), e.to_string())
+    })
+}
@@ -0,0 +1,149 @@
+use crate::asm::AsmListing;
+use huff_codegen::config::IMMUTABLES_OFFSET;
+use huff_utils::prelude::{Contract, SourceLocation, SourceMap, SourceSpans};
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+/// A Verbatim Block of a Yul Object
+///
+/// Each basic block of the expanded bytecode is emitted as its own `verbatim_0i_0o` call, so
+/// the Yul stays readable and each block keeps the source location it was generated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerbatimBlock {
+    /// The byte offset of the block in its code
+    pub offset: usize,
+    /// The hex encoded bytes of the block
+    pub code: String,
+    /// The source location of the block's first instruction, if it was generated from source
+    pub location: Option<SourceLocation>,
+}
+
+/// A Yul Object Embedding a Contract's Expanded Bytecode
+///
+/// Experimental. The constructor and runtime are lowered into verbatim blocks, and the
+/// bootstrap copying and returning the runtime is written in Yul, patching the immutables the
+/// constructor staged in memory like the Huff bootstrap does.
+///
+/// Verbatim blocks jump to absolute offsets, so the object must be compiled without the
+/// optimizer, which would move code around them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct YulObject {
+    /// The object name, ie. `Token`, with the runtime in a `Token_deployed` sub object
+    pub name: String,
+    /// The source file paths, indexed by the file index of each block's location
+    pub sources: Vec<String>,
+    /// The verbatim blocks of the constructor
+    pub constructor: Vec<VerbatimBlock>,
+    /// The verbatim blocks of the runtime
+    pub runtime: Vec<VerbatimBlock>,
+    /// The memory offset the runtime is copied to, after the staged immutables
+    pub runtime_start: usize,
+    /// The memory word of each immutable and the runtime offsets of its placeholder words
+    pub immutables: Vec<(usize, Vec<usize>)>,
+}
+
+impl YulObject {
+    /// Lowers the hex encoded constructor and runtime bytecode of a contract, along with the
+    /// source spans of their bytes, into a Yul object named after the file
+    pub fn new(
+        path: &str,
+        contract: &Contract,
+        constructor: (&str, &SourceSpans),
+        runtime: (&str, &SourceSpans),
+        immutable_references: &BTreeMap<String, Vec<usize>>,
+    ) -> Self {
+        let name = Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().replace('"', ""))
+            .unwrap_or_default();
+        let map = SourceMap::new(constructor.0, constructor.1, runtime.0, runtime.1);
+        let immutables = immutable_references
+            .iter()
+            .map(|(name, offsets)| {
+                let index = contract.find_immutable_index(name).unwrap_or_default();
+                (IMMUTABLES_OFFSET + index * 32, offsets.clone())
+            })
+            .collect();
+        YulObject {
+            name,
+            constructor: verbatim_blocks(constructor.0, &map.decode(&map.bytecode, constructor.0)),
+            runtime: verbatim_blocks(runtime.0, &map.decode(&map.runtime, runtime.0)),
+            sources: map.sources,
+            runtime_start: IMMUTABLES_OFFSET + contract.immutables.len() * 32,
+            immutables,
+        }
+    }
+
+    /// Renders the object in Yul object syntax, with solc's `@use-src` and `@src` annotations
+    pub fn render(&self) -> String {
+        let deployed = format!("{}_deployed", self.name);
+        let mut yul = String::new();
+        if !self.sources.is_empty() {
+            let sources = self
+                .sources
+                .iter()
+                .enumerate()
+                .map(|(i, s)| format!("{}:\"{}\"", i, s))
+                .collect::<Vec<String>>();
+            let _ = writeln!(yul, "/// @use-src {}", sources.join(", "));
+        }
+        let _ = writeln!(yul, "object \"{}\" {{", self.name);
+        let _ = writeln!(yul, "    code {{");
+        self.render_blocks(&mut yul, &self.constructor, 2);
+        let start = self.runtime_start;
+        let _ = writeln!(
+            yul,
+            "        datacopy({:#x}, dataoffset(\"{}\"), datasize(\"{}\"))",
+            start, deployed, deployed
+        );
+        for (slot, offsets) in &self.immutables {
+            for offset in offsets {
+                let _ = writeln!(yul, "        mstore({:#x}, mload({:#x}))", start + offset, slot);
+            }
+        }
+        let _ = writeln!(yul, "        return({:#x}, datasize(\"{}\"))", start, deployed);
+        let _ = writeln!(yul, "    }}");
+        let _ = writeln!(yul, "    object \"{}\" {{", deployed);
+        let _ = writeln!(yul, "        code {{");
+        self.render_blocks(&mut yul, &self.runtime, 3);
+        let _ = writeln!(yul, "        }}");
+        let _ = writeln!(yul, "    }}");
+        let _ = writeln!(yul, "}}");
+        yul
+    }
+
+    /// Renders verbatim blocks at an indentation level, each preceded by its source location
+    fn render_blocks(&self, yul: &mut String, blocks: &[VerbatimBlock], level: usize) {
+        let indent = "    ".repeat(level);
+        for block in blocks {
+            let src = match &block.location {
+                Some(l) => {
+                    let file = self.sources.iter().position(|s| *s == l.file).unwrap_or_default();
+                    format!("{}:{}:{}", file, l.start, l.start + l.length)
+                }
+                None => "-1:-1:-1".to_string(),
+            };
+            let _ = writeln!(yul, "{}/// @src {}", indent, src);
+            let _ = writeln!(yul, "{}verbatim_0i_0o(hex\"{}\")", indent, block.code);
+        }
+    }
+}
+
+/// Splits hex encoded bytecode into a verbatim block per basic block
+fn verbatim_blocks(
+    bytecode: &str,
+    locations: &BTreeMap<usize, SourceLocation>,
+) -> Vec<VerbatimBlock> {
+    let offsets = AsmListing::new(bytecode).blocks.iter().map(|b| b.offset).collect::<Vec<usize>>();
+    offsets
+        .iter()
+        .enumerate()
+        .map(|(i, offset)| {
+            let end = offsets.get(i + 1).map_or(bytecode.len(), |next| next * 2);
+            VerbatimBlock {
+                offset: *offset,
+                code: bytecode[offset * 2..end].to_string(),
+                location: locations.get(offset).cloned(),
+            }
+        })
+        .collect()
+}
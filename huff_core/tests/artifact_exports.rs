@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_export_raw_bytecode_files() {
+    let artifact = Artifact {
+        file: Arc::new(FileSource {
+            path: "./contracts/Example.huff".to_string(),
+            ..Default::default()
+        }),
+        bytecode: "600580600a3d393df3".to_string(),
+        runtime: "6001".to_string(),
+        ..Default::default()
+    };
+    let out_dir = std::env::temp_dir().join(format!("huff-exports-{}", uuid::Uuid::new_v4()));
+    let output = OutputLocation(out_dir.to_string_lossy().to_string());
+
+    Compiler::export_artifacts(
+        &vec![Arc::new(artifact.clone())],
+        &output,
+        &[
+            Arc::new(JsonSerializer::default()),
+            Arc::new(BinSerializer),
+            Arc::new(BinRuntimeSerializer),
+        ],
+    );
+
+    let base = out_dir.join("CONTRACTS/EXAMPLE.HUFF");
+    let read = |ext: &str| std::fs::read_to_string(base.with_extension(format!("HUFF.{}", ext)));
+    assert_eq!(serde_json::from_str::<Artifact>(&read("json").unwrap()).unwrap(), artifact);
+    assert_eq!(read("bin").unwrap(), "600580600a3d393df3");
+    assert_eq!(read("bin-runtime").unwrap(), "6001");
+
+    let _ = std::fs::remove_dir_all(out_dir);
+}
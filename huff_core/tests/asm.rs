@@ -0,0 +1,77 @@
+use huff_core::asm::*;
+
+// PUSH1 0x00 CALLDATALOAD PUSH2 0x000c JUMPI
+// PUSH1 0x00 PUSH1 0x00 REVERT
+// JUMPDEST PUSH1 0x01 PUSH1 0x00 SSTORE STOP
+const BYTECODE: &str = "60003561000c57600060 00fd5b600160005500";
+
+#[test]
+fn test_asm_listing_blocks() {
+    let listing = AsmListing::new(&BYTECODE.replace(' ', ""));
+    assert_eq!(listing.blocks.len(), 3);
+
+    let offsets = listing.blocks.iter().map(|b| b.offset).collect::<Vec<usize>>();
+    assert_eq!(offsets, vec![0x00, 0x07, 0x0c]);
+    let gas = listing.blocks.iter().map(AsmBlock::gas).collect::<Vec<u64>>();
+    assert_eq!(gas, vec![19, 6, 107]);
+    assert_eq!(listing.gas(), 132);
+
+    let jumpi = &listing.blocks[0].instructions[2];
+    assert_eq!(jumpi.offset, 3);
+    assert_eq!(jumpi.mnemonic, "PUSH2");
+    assert_eq!(jumpi.data, Some("000c".to_string()));
+    assert_eq!(jumpi.text(), "PUSH2 0x000c");
+    assert_eq!(listing.blocks[2].instructions[0].mnemonic, "JUMPDEST");
+}
+
+#[test]
+fn test_asm_listing_render() {
+    let listing = AsmListing::new(&format!("0x{}", BYTECODE.replace(' ', "")));
+    let render = listing.render();
+    let lines = render.lines().collect::<Vec<&str>>();
+    assert!(lines[0].starts_with("offset") && lines[0].ends_with("block gas"));
+    assert_eq!(lines[1], "; block 0x0000: 19 gas");
+    assert!(lines[2].starts_with("0x0000  PUSH1 0x00"));
+    assert!(lines[5].starts_with("0x0006  JUMPI") && lines[5].ends_with("10         19"));
+    assert_eq!(lines[6], "; block 0x0007: 6 gas");
+    assert!(lines[14].starts_with("0x0011  SSTORE") && lines[14].ends_with("100        107"));
+    assert_eq!(lines.last(), Some(&"; total: 132 gas"));
+}
+
+#[test]
+fn test_asm_listing_unknown_and_truncated() {
+    let listing = AsmListing::new("0c61ff");
+    let instructions = &listing.blocks[0].instructions;
+    assert_eq!(instructions[0].mnemonic, "UNKNOWN(0x0c)");
+    assert_eq!(instructions[0].gas, 0);
+    assert_eq!(instructions[1].data, Some("ff".to_string()));
+
+    assert_eq!(AsmListing::new(""), AsmListing::default());
+}
+
+#[test]
+fn test_asm_listing_stack_depths() {
+    let listing = AsmListing::new(&BYTECODE.replace(' ', ""));
+    let depths = listing
+        .blocks
+        .iter()
+        .map(|b| b.instructions.iter().map(|i| i.depth).collect::<Vec<Option<usize>>>())
+        .collect::<Vec<Vec<Option<usize>>>>();
+    assert_eq!(
+        depths,
+        vec![
+            vec![Some(1), Some(1), Some(2), Some(0)],
+            vec![Some(1), Some(2), None],
+            // The jumpdest is entered with the depth of the jump to it
+            vec![Some(0), Some(1), Some(2), Some(0), None],
+        ]
+    );
+
+    // The depth is unknown until a jump from a known depth
+    let listing = AsmListing::new("00600160010156");
+    assert!(listing.blocks[1].instructions.iter().all(|i| i.depth.is_none()));
+
+    let render = listing.render();
+    assert!(render.lines().next().unwrap().contains("instruction stack    gas"));
+    assert!(render.lines().any(|l| l.starts_with("0x0001  PUSH1 0x01      ?")));
+}
@@ -1,4 +1,10 @@
-use huff_codegen::*;
+mod common;
+
+use common::{compiler, file};
+use huff_codegen::{
+    config::{CodegenConfig, CustomBuiltin},
+    *,
+};
 use huff_lexer::*;
 use huff_parser::*;
 use huff_utils::prelude::*;
@@ -118,7 +124,7 @@ fn test_tablesize_builtin() {
 
     // Have the Codegen create the constructor bytecode
     let mbytes = Codegen::generate_main_bytecode(&contract).unwrap();
-    assert_eq!(mbytes, String::from("6008608061002c60003960205b60006000f35b60006000f35b60006000f35b60006000f3000c00120018001e000000000000000000000000000000000000000000000000000000000000000c00000000000000000000000000000000000000000000000000000000000000120000000000000000000000000000000000000000000000000000000000000018000000000000000000000000000000000000000000000000000000000000001eDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF"));
+    assert_eq!(mbytes, String::from("6008608061002c60003960205b60006000f35b60006000f35b60006000f35b60006000f3000c00120018001e000000000000000000000000000000000000000000000000000000000000000c00000000000000000000000000000000000000000000000000000000000000120000000000000000000000000000000000000000000000000000000000000018000000000000000000000000000000000000000000000000000000000000001edeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"));
 }
 
 #[test]
@@ -477,3 +483,515 @@ fn test_event_hash_builtin() {
         String::from("7fbeabacc8ffedac16e9a60acdb2ca743d80c2ebb44977a93fa8e483c74d2b35a87fbeabacc8ffedac16e9a60acdb2ca743d80c2ebb44977a93fa8e483c74d2b35a87fbeabacc8ffedac16e9a60acdb2ca743d80c2ebb44977a93fa8e483c74d2b35a8600055")
     );
 }
+
+#[test]
+fn test_error_builtin() {
+    let source: &str = r#"
+        #define error Unauthorized()
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __ERROR(Unauthorized)
+            0x00 mstore
+            0x04 0x00 revert
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+
+    let cbytes = Codegen::generate_main_bytecode(&contract).unwrap();
+    // `Unauthorized()` selector = 0x82b42900, left aligned in a 32 byte word
+    assert_eq!(cbytes, format!("7f82b42900{}60005260046000fd", "00".repeat(28)));
+}
+
+#[test]
+fn test_create_address_builtins() {
+    let source: &str = r#"
+        #define constant DEPLOYER = 0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0
+        #define constant FACTORY = 0xdeadbeef00000000000000000000000000000000
+        #define constant SALT = 0x00
+        #define constant INITCODE_HASH = 0xbc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __CREATE_ADDRESS(DEPLOYER, 0x00)
+            __CREATE_ADDRESS(DEPLOYER, 0x01)
+            __CREATE2_ADDRESS(FACTORY, SALT, INITCODE_HASH)
+            __CREATE2_ADDRESS(0x00, 0x00, INITCODE_HASH)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Have the Codegen create the main bytecode
+    let mbytes = Codegen::generate_main_bytecode(&contract).unwrap();
+    assert_eq!(&mbytes[2..42], "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+    assert_eq!(&mbytes[44..84], "343c43a37d37dff08ae8c4a11544c718abb4fcf8");
+    assert_eq!(&mbytes[86..126], "b928f69bb1d91cd65274e3c79d8986362984fda3");
+    assert_eq!(&mbytes[128..168], "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38");
+    assert_eq!(mbytes.len(), 168);
+}
+
+#[test]
+fn test_create_address_builtin_errors() {
+    let cases = [
+        // Incorrect number of arguments
+        ("__CREATE_ADDRESS(0x01)", None),
+        // The deployer must fit in 20 bytes
+        ("__CREATE_ADDRESS(0x010000000000000000000000000000000000000000, 0x00)", None),
+        // All inputs must be constants
+        ("__CREATE2_ADDRESS(0x01, SALT, 0x00)", Some("SALT")),
+    ];
+
+    for (body, missing_constant) in cases {
+        let source = format!("#define macro MAIN() = takes(0) returns (0) {{ {} }}", body);
+
+        // Parse tokens
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let mut contract = parser.parse().unwrap();
+        contract.derive_storage_pointers();
+
+        let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+        match missing_constant {
+            Some(c) => {
+                assert_eq!(err.kind, CodegenErrorKind::MissingConstantDefinition(c.to_string()))
+            }
+            None => assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_))),
+        }
+    }
+}
+
+#[test]
+fn test_bit_manipulation_builtins() {
+    let source: &str = r#"
+        #define constant BALANCE_BITS = 0x60
+        #define constant FLAG = 0x01
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __MASK(8, 0)
+            __MASK(BALANCE_BITS, 160)
+            __MASK(256, 0)
+            __SHL_CONST(FLAG, 255)
+            __SHL_CONST(0xff, 0x08)
+            __SHL_CONST(FLAG, 256)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Each builtin folds to a single push
+    let mbytes = Codegen::generate_main_bytecode(&contract).unwrap();
+    assert_eq!(
+        mbytes,
+        [
+            "60ff",
+            "7fffffffffffffffffffffffff0000000000000000000000000000000000000000",
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "7f8000000000000000000000000000000000000000000000000000000000000000",
+            "61ff00",
+            "6000",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_bit_manipulation_builtin_errors() {
+    let cases = [
+        // Incorrect number of arguments
+        ("__SHL_CONST(0x01)", None),
+        // The mask must fit in 256 bits
+        ("__MASK(8, 249)", None),
+        ("__MASK(0, 0)", None),
+        // All inputs must be constants
+        ("__MASK(BITS, 0)", Some("BITS")),
+    ];
+
+    for (body, missing_constant) in cases {
+        let source = format!("#define macro MAIN() = takes(0) returns (0) {{ {} }}", body);
+
+        // Parse tokens
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let mut contract = parser.parse().unwrap();
+        contract.derive_storage_pointers();
+
+        let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+        match missing_constant {
+            Some(c) => {
+                assert_eq!(err.kind, CodegenErrorKind::MissingConstantDefinition(c.to_string()))
+            }
+            None => assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_))),
+        }
+    }
+}
+
+#[test]
+fn test_rightpad_builtin() {
+    let source = r#"
+        #define constant MESSAGE = 0x00ff
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __RIGHTPAD(0xdeadbeef)
+            __RIGHTPAD(MESSAGE)
+            __RIGHTPAD(0x00)
+        }
+    "#;
+    let file = std::sync::Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "RightPad.huff".to_string(),
+        ..Default::default()
+    });
+    let compiler =
+        huff_core::Compiler::new(std::sync::Arc::new(vec![]), None, None, None, false, false);
+    let artifact = compiler.gen_artifact(file).unwrap();
+
+    // Literals are padded from the width they were written with
+    assert_eq!(
+        artifact.runtime,
+        [
+            format!("7fdeadbeef{}", "00".repeat(28)),
+            format!("7f00ff{}", "00".repeat(30)),
+            format!("7f{}", "00".repeat(32)),
+        ]
+        .concat()
+    );
+
+    // Without the source, leading zero bytes can't be recovered
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    let mbytes = Codegen::generate_main_bytecode(&contract).unwrap();
+    assert_eq!(&mbytes[66..132], format!("7fff{}", "00".repeat(31)));
+
+    // Only a single argument is accepted
+    let source = "#define macro MAIN() = takes(0) returns (0) { __RIGHTPAD(0x01, 0x02) }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+}
+
+#[test]
+fn test_verbatim_builtin() {
+    let long = "5b".repeat(40);
+    let source = format!(
+        r#"
+        #define macro MAIN() = takes(0) returns (0) {{
+            __VERBATIM(0x60FF57)
+            __VERBATIM(0x0000)
+            __VERBATIM("0x{}")
+            done jump
+            done:
+        }}
+    "#,
+        long
+    );
+    let file = std::sync::Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "Verbatim.huff".to_string(),
+        ..Default::default()
+    });
+    let compiler =
+        huff_core::Compiler::new(std::sync::Arc::new(vec![]), None, None, None, false, false);
+    let artifact = compiler.gen_artifact(file).unwrap();
+
+    // Bytes are emitted as written, without a push, and offset the labels after them
+    assert_eq!(artifact.runtime, format!("60ff570000{}610031565b", long));
+
+    // Only whole bytes of hex are accepted
+    for arg in ["0x60f", "\"0xzz\"", "\"\"", "0x01, 0x02"] {
+        let source =
+            format!("#define macro MAIN() = takes(0) returns (0) {{ __VERBATIM({}) }}", arg);
+        let file = std::sync::Arc::new(FileSource {
+            source: Some(source),
+            path: "Verbatim.huff".to_string(),
+            ..Default::default()
+        });
+        let err = compiler.gen_artifact(file).unwrap_err();
+        assert!(matches!(
+            err,
+            CompilerError::CodegenError(CodegenError {
+                kind: CodegenErrorKind::InvalidArguments(_),
+                ..
+            })
+        ));
+    }
+}
+
+#[test]
+fn test_dispatch_builtin() {
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function owner() view returns (address)
+
+        #define macro MAIN() = takes(0) returns (0) {
+            0x00 calldataload 0xe0 shr
+            __DISPATCH()
+            0x00 dup1 revert
+            transfer:
+                stop
+            owner:
+                stop
+        }
+    "#;
+    let compiler = compiler();
+    let artifact = compiler.gen_artifact(file("Dispatch.huff", source, None)).unwrap();
+
+    // Each selector is compared in definition order, falling through to the revert
+    assert_eq!(
+        artifact.runtime,
+        "60003560e01c8063a9059cbb146100205780638da5cb5b1461002257600080fd5b005b00"
+    );
+
+    // Functions without a matching label are unmatched jumps
+    let source = r#"
+        #define function owner() view returns (address)
+        #define macro MAIN() = takes(0) returns (0) { 0x00 calldataload 0xe0 shr __DISPATCH() }
+    "#;
+    assert!(compiler.gen_artifact(file("Dispatch.huff", source, None)).is_err());
+
+    // Overloads share a label, so can't be dispatched to
+    let source = r#"
+        #define function transfer(address,uint256) nonpayable returns ()
+        #define function transfer(address,address,uint256) nonpayable returns ()
+        #define macro MAIN() = takes(0) returns (0) {
+            0x00 calldataload 0xe0 shr __DISPATCH()
+            transfer:
+                stop
+        }
+    "#;
+    let err = compiler.gen_artifact(file("Dispatch.huff", source, None)).unwrap_err();
+    assert!(matches!(
+        err,
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::OverloadedDispatch(name),
+            ..
+        }) if name == "transfer"
+    ));
+
+    // The dispatcher takes no arguments
+    let source = "#define macro MAIN() = takes(1) returns (1) { __DISPATCH(0x01) }";
+    let err = compiler.gen_artifact(file("Dispatch.huff", source, None)).unwrap_err();
+    assert!(matches!(
+        err,
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(_),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_constructor_arg_builtin() {
+    let source = r#"
+        #define function CONSTRUCTOR(uint256, address) nonpayable returns ()
+
+        #define macro CONSTRUCTOR() = takes(0) returns (0) {
+            __CONSTRUCTOR_ARG(0) 0x00 sstore
+            __CONSTRUCTOR_ARG(0x01) 0x01 sstore
+        }
+
+        #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Each argument is copied from its distance back from the end of the code
+    let constructor_bytecode = Codegen::generate_constructor_bytecode(&contract).unwrap();
+    assert_eq!(
+        constructor_bytecode,
+        "602060403803600039600051600055602060203803600039600051600155"
+    );
+
+    // Indexes must be within the CONSTRUCTOR function's inputs, which must all be single words
+    for source in [
+        "#define macro CONSTRUCTOR() = takes(0) returns (0) { __CONSTRUCTOR_ARG(0) pop }",
+        r#"
+        #define function CONSTRUCTOR(uint256) nonpayable returns ()
+        #define macro CONSTRUCTOR() = takes(0) returns (0) { __CONSTRUCTOR_ARG(1) pop }
+        "#,
+        r#"
+        #define function CONSTRUCTOR(uint256[2], uint256) nonpayable returns ()
+        #define macro CONSTRUCTOR() = takes(0) returns (0) { __CONSTRUCTOR_ARG(1) pop }
+        "#,
+        r#"
+        #define function CONSTRUCTOR(uint256, string) nonpayable returns ()
+        #define macro CONSTRUCTOR() = takes(0) returns (0) { __CONSTRUCTOR_ARG(0) pop }
+        "#,
+    ] {
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(full_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None);
+        let contract = parser.parse().unwrap();
+        let err = Codegen::generate_constructor_bytecode(&contract).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+    }
+}
+
+/// Packs two words into the low and high 128 bits of a single word
+fn pack_builtin() -> CustomBuiltin {
+    CustomBuiltin::new("__PACK", 2, |args| {
+        if args.iter().any(|a| a[..16] != [0u8; 16]) {
+            return Err("inputs must fit in 128 bits".to_string())
+        }
+        let mut packed = [0u8; 32];
+        packed[..16].copy_from_slice(&args[1][16..]);
+        packed[16..].copy_from_slice(&args[0][16..]);
+        Ok(packed)
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_custom_builtin() {
+    let source = r#"
+        #define constant HIGH = 0x02
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __PACK(0x01, HIGH)
+            __PACK(0xff, 0x00)
+        }
+    "#;
+
+    // Parse tokens, lexing the custom builtin's calls
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    lexer.custom_builtins = vec!["__PACK".to_string()];
+    let tokens = lexer.map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Builtins that aren't registered with codegen fail to generate
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::UnknownBuiltin("__PACK".to_string()));
+
+    // Registered builtins fold to the smallest push of their result
+    let mut config = CodegenConfig::default();
+    config.register_builtin(pack_builtin());
+    let mbytes = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap();
+    assert_eq!(mbytes, format!("7002{}01{}", "00".repeat(15), "60ff"));
+
+    // The arity is checked and evaluation errors are surfaced
+    for body in ["__PACK(0x01)", "__PACK(0x0100000000000000000000000000000000, 0x00)"] {
+        let source = format!("#define macro MAIN() = takes(0) returns (0) {{ {} }}", body);
+        let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+        let mut lexer = Lexer::new(flattened_source);
+        lexer.custom_builtins = vec!["__PACK".to_string()];
+        let tokens = lexer.map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let contract = Parser::new(tokens, None).parse().unwrap();
+        let err = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+    }
+}
+
+#[test]
+fn test_custom_builtin_names() {
+    assert!(CustomBuiltin::new("PACK", 1, |a| Ok(a[0])).is_err());
+    assert!(CustomBuiltin::new("__", 1, |a| Ok(a[0])).is_err());
+    assert!(CustomBuiltin::new("__PA-CK", 1, |a| Ok(a[0])).is_err());
+    assert!(CustomBuiltin::new("__MASK", 1, |a| Ok(a[0])).is_err());
+    assert_eq!(pack_builtin().arity, 2);
+}
+
+#[test]
+fn test_compiler_custom_builtin() {
+    let source = "#define macro MAIN() = takes(0) returns (0) { __PACK(0x01, 0x02) }";
+    let mut compiler = compiler();
+    compiler.register_builtin(pack_builtin());
+    let artifact = compiler.gen_artifact(file("", source, None)).unwrap();
+    assert_eq!(artifact.runtime, format!("7002{}01", "00".repeat(15)));
+}
+
+#[test]
+fn test_unregistered_builtin_name_is_macro_call() {
+    let source = r#"
+        #define macro __HELPER() = takes(0) returns (0) {
+            0x01
+        }
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __HELPER()
+        }
+    "#;
+    let artifact = compiler().gen_artifact(file("", source, None)).unwrap();
+    assert_eq!(artifact.runtime, "6001");
+}
+
+#[test]
+fn test_debug_builtin() {
+    let source = r#"
+        #define macro PEEK() = takes(1) returns (1) {
+            dup1 pop
+            peeked:
+        }
+
+        #define macro MAIN() = takes(0) returns (0) {
+            0x01 __DEBUG(PEEK) __DEBUG(PEEK) pop
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Debug macros are stripped from release builds
+    let mbytes = Codegen::generate_main_bytecode(&contract).unwrap();
+    assert_eq!(mbytes, "600150");
+
+    // And expanded like invocations in debug builds
+    let config = CodegenConfig { debug: true, ..Default::default() };
+    let mbytes = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap();
+    assert_eq!(mbytes, "600180505b80505b50");
+
+    // Macros that change the stack can't be stripped, even from release builds
+    let source = r#"
+        #define macro PUSH() = takes(0) returns (1) { 0x01 }
+        #define macro MAIN() = takes(0) returns (0) { __DEBUG(PUSH) }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Artifact {
+    compiler().gen_artifact(file("", source, None)).unwrap()
+}
+
+#[test]
+fn test_bytecode_stats() {
+    let source = r#"
+    #define function owner() view returns (address)
+    #define function total() view returns (uint256)
+
+    #define jumptable__packed TABLE {
+        owner total
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        __tablestart(TABLE) pop
+        0x00 calldataload 0xe0 shr
+        __DISPATCH()
+        0x00 dup1 revert
+        owner:
+            stop
+        total:
+            stop
+    }
+    "#;
+
+    let artifact = compile(source);
+    let stats = artifact.stats.unwrap();
+    assert_eq!(stats.opcodes["push1"], 3);
+    assert_eq!(stats.opcodes["push2"], 3);
+    assert_eq!(stats.opcodes["dup1"], 3);
+    assert_eq!(stats.opcodes["jumpdest"], 2);
+    assert_eq!(stats.opcodes.values().sum::<usize>(), stats.opcode_bytes);
+    assert_eq!(stats.opcode_bytes, 23);
+    assert_eq!(stats.push_data_bytes, 17);
+    assert_eq!(stats.table_bytes, 4);
+    // Two selector comparisons of 11 bytes each
+    assert_eq!(stats.dispatcher_bytes, 22);
+    assert_eq!(
+        stats.opcode_bytes + stats.push_data_bytes + stats.table_bytes,
+        artifact.runtime.len() / 2
+    );
+}
+
+#[test]
+fn test_bytecode_stats_without_tables() {
+    // A comparison without a DUP1, then truncated push data
+    let stats = BytecodeStats::new("63123456781461001057000c7f0102", 0);
+    assert_eq!(stats.dispatcher_bytes, 10);
+    assert_eq!(stats.opcodes["unknown(0x0c)"], 1);
+    assert_eq!(stats.opcode_bytes, 7);
+    assert_eq!(stats.push_data_bytes, 8);
+    assert_eq!(stats.table_bytes, 0);
+}
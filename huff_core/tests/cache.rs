@@ -0,0 +1,193 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use huff_codegen::config::CustomBuiltin;
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define function get() view returns (uint256)
+    #define constant VALUE = 0x01
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [VALUE] 0x00 mstore
+        0x20 0x00 return
+    }
+"#;
+
+/// Writes the source to a uniquely named directory, relative like the paths the cache matches
+fn project(name: &str) -> (String, String, String) {
+    let root = format!("./{}-{}", name, uuid::Uuid::new_v4());
+    std::fs::create_dir_all(format!("{}/contracts", root)).unwrap();
+    let path = format!("{}/contracts/A.huff", root);
+    std::fs::write(&path, SOURCE).unwrap();
+    let output = format!("{}/artifacts", root);
+    (root, path, output)
+}
+
+fn compiler<'a>(path: &str, output: &str) -> Compiler<'a> {
+    Compiler::new(
+        Arc::new(vec![path.to_string()]),
+        Some(output.to_string()),
+        None,
+        None,
+        false,
+        true,
+    )
+}
+
+#[test]
+fn test_simulated_build_is_not_cached() {
+    let (root, path, output) = project("huff-cache-simulate");
+    compiler(&path, &output).execute().into_result().unwrap();
+
+    // The constructor is simulated even though a cached artifact exists
+    let simulated = Compiler { simulate: true, ..compiler(&path, &output) };
+    let artifacts = simulated.execute().into_result().unwrap();
+    let simulation = artifacts[0].simulation.as_ref().unwrap();
+    assert_eq!(simulation.runtime, artifacts[0].runtime);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_budgeted_build_is_not_cached() {
+    let (root, path, output) = project("huff-cache-budget");
+    compiler(&path, &output).execute().into_result().unwrap();
+
+    // The budget is checked even though a cached artifact exists
+    let budgeted = Compiler { size_budget: Some(3), ..compiler(&path, &output) };
+    let result = budgeted.execute();
+    assert!(result.artifacts.is_empty());
+    assert!(matches!(
+        &*result.errors[0],
+        CompilerError::SizeBudgetError(SizeBudgetError { size: 10, budget: 3, .. })
+    ));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_cache_matches_build_settings() {
+    let (root, path, output) = project("huff-cache-settings");
+    compiler(&path, &output).execute().into_result().unwrap();
+
+    // Debug builds compile again rather than reading the release artifact
+    let debug = Compiler { debug: true, ..compiler(&path, &output) };
+    let result = debug.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert!(result.artifacts[0].settings.debug);
+
+    // As do builds overriding constants
+    let overrides = BTreeMap::from([("VALUE", str_to_bytes32("02"))]);
+    let overridden = Compiler { constant_overrides: Some(overrides), ..compiler(&path, &output) };
+    let result = overridden.execute();
+    assert_eq!(result.artifacts[0].runtime, "600260005260206000f3");
+
+    // And builds preserving push widths
+    let padded = Compiler { preserve_push_width: true, ..compiler(&path, &output) };
+    let result = padded.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert!(result.artifacts[0].settings.preserve_push_width);
+
+    // And builds targeting another evm version
+    let petersburg = Compiler { evm_version: EvmVersion::Petersburg, ..compiler(&path, &output) };
+    let result = petersburg.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert_eq!(result.artifacts[0].settings.evm_version, EvmVersion::Petersburg);
+
+    // And builds appending metadata
+    let metadata = Compiler { metadata: true, ..compiler(&path, &output) };
+    let result = metadata.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert!(result.artifacts[0].metadata.is_some());
+
+    // And builds with a custom bootstrap
+    let bootstrap = "{size}80{offset}5f395ff3".to_string();
+    let custom = Compiler { bootstrap: Some(bootstrap.clone()), ..compiler(&path, &output) };
+    let result = custom.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert_eq!(result.artifacts[0].settings.bootstrap, Some(bootstrap));
+
+    // And builds narrowing their outputs, which are read back by the same selection
+    let selection = BTreeSet::from([ArtifactOutput::Abi]);
+    let narrowed = Compiler { output_selection: selection.clone(), ..compiler(&path, &output) };
+    let result = narrowed.execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert!(result.artifacts[0].runtime.is_empty());
+    let narrowed = Compiler { output_selection: selection.clone(), ..compiler(&path, &output) };
+    let result = narrowed.execute();
+    assert!(result.timings.contracts.is_empty());
+    assert_eq!(result.artifacts[0].settings.output_selection, selection);
+
+    // A plain build isn't served the overridden artifact, but caches its own
+    let result = compiler(&path, &output).execute();
+    assert!(!result.timings.contracts.is_empty());
+    assert_eq!(result.artifacts[0].runtime, "600160005260206000f3");
+    assert!(result.artifacts[0].settings.is_default());
+
+    let result = compiler(&path, &output).execute();
+    assert!(result.timings.contracts.is_empty());
+    assert_eq!(result.artifacts[0].runtime, "600160005260206000f3");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_cached_build_warns_on_size_limits() {
+    let (root, path, output) = project("huff-cache-sizes");
+    let source = format!(
+        "#define table T {{ 0x{} }}\n{}",
+        "ab".repeat(MAX_RUNTIME_SIZE),
+        "#define macro MAIN() = takes(0) returns (0) { __tablesize(T) }\n"
+    );
+    std::fs::write(&path, source).unwrap();
+    let result = compiler(&path, &output).execute();
+    assert!(matches!(result.warnings[0].kind, CompilerWarningKind::RuntimeSizeLimit(_)));
+
+    // The warning is raised again when the artifact is read from the cache
+    let result = compiler(&path, &output).execute();
+    assert!(result.timings.contracts.is_empty());
+    assert!(matches!(result.warnings[0].kind, CompilerWarningKind::RuntimeSizeLimit(_)));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_cached_build_warns_of_aliases() {
+    let (root, path, output) = project("huff-cache-aliases");
+    std::fs::write(&path, "#define macro MAIN() = takes(0) returns (0) { 0x00 0x00 sha3 }")
+        .unwrap();
+    let result = compiler(&path, &output).execute();
+    assert_eq!(result.warnings.len(), 1);
+
+    // The cached build raises the same warning
+    let result = compiler(&path, &output).execute();
+    assert!(result.timings.contracts.is_empty());
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(
+        result.warnings[0].kind,
+        CompilerWarningKind::OpcodeAlias("sha3".to_string(), "keccak256".to_string())
+    );
+
+    // Unless aliases are allowed
+    let allowed = Compiler { allow_opcode_aliases: true, ..compiler(&path, &output) };
+    assert!(allowed.execute().warnings.is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_custom_builtin_build_is_not_cached() {
+    let (root, path, output) = project("huff-cache-builtins");
+    compiler(&path, &output).execute().into_result().unwrap();
+
+    let mut custom = compiler(&path, &output);
+    custom.register_builtin(CustomBuiltin::new("__ZERO", 0, |_| Ok([0u8; 32])).unwrap());
+    let result = custom.execute();
+    assert!(!result.timings.contracts.is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,99 @@
+use huff_codegen::config::CodegenConfig;
+use huff_core::call_graph::*;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro INNER() = takes (0) returns (1) { 0x01 }
+    #define macro MIDDLE() = takes (0) returns (2) { INNER() INNER() }
+    #define fn HELPER() = takes (0) returns (0) { 0x02 pop }
+    #define macro CHECK() = takes (0) returns (0) { 0x03 pop }
+    #define macro UNUSED() = takes (0) returns (0) { 0x04 pop }
+
+    #define macro CONSTRUCTOR() = takes (0) returns (0) {
+        INNER() pop
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        MIDDLE() MIDDLE() add add add pop
+        HELPER()
+        __DEBUG(CHECK)
+    }
+"#;
+
+fn graph(debug: bool) -> CallGraph {
+    let flattened_source = FullFileSource { source: SOURCE, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+    CallGraph::new(&contract, &CodegenConfig { debug, ..Default::default() }).unwrap()
+}
+
+#[test]
+fn test_call_graph() {
+    let graph = graph(false);
+    // HELPER's body is appended to both the constructor and runtime bytecode
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|n| (n.name.as_str(), n.outlined, n.invocations, n.size))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        vec![
+            ("INNER", false, 5, 10),
+            ("MIDDLE", false, 2, 8),
+            ("HELPER", true, 2, 10),
+            ("CHECK", false, 0, 0),
+            ("UNUSED", false, 0, 0),
+            ("CONSTRUCTOR", false, 1, 8),
+            ("MAIN", false, 1, 25),
+        ]
+    );
+
+    // Edges count the invocations written in each body, and __DEBUG is stripped
+    let edges = graph
+        .edges
+        .iter()
+        .map(|e| (e.caller.as_str(), e.callee.as_str(), e.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            ("MIDDLE", "INNER", 2),
+            ("CONSTRUCTOR", "INNER", 1),
+            ("MAIN", "MIDDLE", 2),
+            ("MAIN", "HELPER", 1)
+        ]
+    );
+    let unused = graph.unused().iter().map(|n| n.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(unused, vec!["CHECK", "UNUSED"]);
+
+    assert_eq!(
+        graph.render_tree(),
+        "\
+CONSTRUCTOR (8 bytes)
+└─ INNER x1 (5 expansions, 10 bytes)
+MAIN (25 bytes)
+├─ MIDDLE x2 (2 expansions, 8 bytes)
+│  └─ INNER x2 (5 expansions, 10 bytes)
+└─ HELPER x1 (fn, 2 copies, 10 bytes)
+Unused: CHECK, UNUSED
+"
+    );
+
+    let dot = graph.to_dot("Graph.huff");
+    assert!(dot.starts_with("digraph \"Graph.huff\" {\n"));
+    assert!(
+        dot.contains("    \"HELPER\" [label=\"HELPER\\n2 expansions, 10 bytes\" shape=ellipse];\n")
+    );
+    assert!(dot.contains("    \"UNUSED\" [label=\"UNUSED\\nunused\" style=dashed];\n"));
+    assert!(dot.contains("    \"MAIN\" -> \"MIDDLE\" [label=\"x2\"];\n"));
+}
+
+#[test]
+fn test_debug_call_graph() {
+    let graph = graph(true);
+    assert!(graph.edges.iter().any(|e| e.caller == "MAIN" && e.callee == "CHECK" && e.count == 1));
+    assert_eq!(graph.unused().iter().map(|n| n.name.as_str()).collect::<Vec<_>>(), vec!["UNUSED"]);
+}
@@ -0,0 +1,59 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+use std::str::FromStr;
+
+fn compile(wrapper: &CallWrapper) -> String {
+    let source = format!(
+        "#define constant TARGET = FREE_STORAGE_POINTER()\n{}\n{}",
+        wrapper, "#define macro MAIN() = takes(0) returns(0) { WRAPPED() }"
+    );
+    let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&contract).unwrap()
+}
+
+#[test]
+fn test_call_wrapper() {
+    let wrapper = CallWrapper::new("WRAPPED", CallKind::Call, "[TARGET] sload");
+    let source = wrapper.to_string();
+    assert!(source.contains("#define macro WRAPPED() = takes (0) returns (0) {"));
+    assert!(source.contains("wrapped_success:"));
+
+    // calldatacopy, call with the callvalue, returndatacopy, then revert or return
+    assert_eq!(
+        compile(&wrapper),
+        "36600060003760006000366000346000545af13d600060003e610021573d6000fd5b3d6000f3"
+    );
+}
+
+#[test]
+fn test_call_wrapper_params() {
+    let wrapper = CallWrapper {
+        gas: "0xffff".to_string(),
+        forward_return: false,
+        ..CallWrapper::new("WRAPPED", CallKind::StaticCall, "0x04 calldataload")
+    };
+    let source = wrapper.to_string();
+    assert!(!source.contains("callvalue"));
+    assert!(!source.contains("return\n"));
+
+    // No value is pushed for STATICCALL, and execution continues on success
+    assert_eq!(
+        compile(&wrapper),
+        "3660006000376000600036600060043561fffffa3d600060003e610022573d6000fd5b"
+    );
+}
+
+#[test]
+fn test_call_kind_from_str() {
+    assert_eq!(CallKind::from_str("call"), Ok(CallKind::Call));
+    assert_eq!(CallKind::from_str("StaticCall"), Ok(CallKind::StaticCall));
+    assert_eq!(CallKind::from_str("delegatecall").unwrap().opcode(), "delegatecall");
+    assert!(CallKind::from_str("callcode").is_err());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{compiler, file};
+use huff_codegen::{config::CodegenConfig, Codegen};
+use huff_core::Compiler;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro INNER() = takes(0) returns (0) {
+        0x01 0x02 add pop
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        INNER()
+        stop
+    }
+"#;
+
+#[test]
+fn test_cancellation_token_clones_share_state() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+    assert!(!clone.is_cancelled());
+    token.cancel();
+    assert!(clone.is_cancelled());
+}
+
+#[test]
+fn test_gen_artifact_cancelled() {
+    let compiler = compiler();
+    assert!(compiler.gen_artifact(file("", SOURCE, None)).is_ok());
+
+    compiler.cancellation.cancel();
+    assert_eq!(
+        compiler.gen_artifact(file("", SOURCE, None)).unwrap_err(),
+        CompilerError::Cancelled
+    );
+}
+
+#[test]
+fn test_codegen_cancelled_before_macro_expansion() {
+    let flattened = FileSource::fully_flatten(file("", SOURCE, None));
+    let full_source = FullFileSource { source: &flattened.0, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source).collect::<Result<Vec<Token>, _>>().unwrap();
+    let contract = Parser::new(tokens, None).parse().unwrap();
+
+    let config = CodegenConfig::default();
+    assert!(Codegen::generate_main_bytecode_with_config(&contract, &config).is_ok());
+
+    config.cancellation.cancel();
+    let err = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::Cancelled);
+}
+
+#[test]
+fn test_execute_cancelled_exports_nothing() {
+    let root = std::env::temp_dir().join(format!("huff-cancel-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let path = root.join("Main.huff");
+    std::fs::write(&path, SOURCE).unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    let output = root.join("artifacts");
+    let compiler = Compiler::new(
+        Arc::new(vec![path]),
+        Some(output.to_string_lossy().to_string()),
+        None,
+        None,
+        false,
+        false,
+    );
+    compiler.cancellation.cancel();
+    let result = compiler.execute();
+    assert!(result.artifacts.is_empty());
+    assert_eq!(result.errors, vec![Arc::new(CompilerError::Cancelled)]);
+    assert!(!output.exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::cfg::*;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define function setValue(uint256) nonpayable returns ()
+#define function getValue() view returns (uint256)
+
+#define macro SET_VALUE() = takes (0) returns (0) {
+    0x04 calldataload 0x00 sstore stop
+}
+
+#define macro GET_VALUE() = takes (0) returns (0) {
+    0x00 sload 0x00 mstore
+    0x20 0x00 return
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x00 calldataload 0xe0 shr
+    dup1 __FUNC_SIG(setValue) eq set jumpi
+    dup1 __FUNC_SIG(getValue) eq get jumpi
+    0x00 jump
+    set:
+        SET_VALUE()
+    get:
+        GET_VALUE()
+}
+"#;
+
+fn compile() -> Artifact {
+    compiler().gen_artifact(file("Value.huff", SOURCE, None)).unwrap()
+}
+
+#[test]
+fn test_control_flow_graph() {
+    let graph = ControlFlowGraph::new(&compile());
+    let offsets = graph.blocks.iter().map(|b| b.offset).collect::<Vec<_>>();
+    assert_eq!(offsets, vec![0, 17, 28, 31, 39]);
+    assert_eq!(graph.blocks[0].instructions[..2], ["PUSH1 0x00", "CALLDATALOAD"]);
+    assert_eq!(graph.blocks[0].definition.as_deref(), Some("MAIN"));
+    assert_eq!(graph.blocks[3].definition.as_deref(), Some("SET_VALUE"));
+
+    // The jump to 0x00 isn't to a jumpdest, so is left unresolved
+    assert!(graph.blocks[2].unresolved_jump);
+    assert!(graph.blocks.iter().filter(|b| b.unresolved_jump).count() == 1);
+
+    let edges = graph
+        .edges
+        .iter()
+        .map(|e| (e.from, e.to, e.kind, e.signature.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            (0, 31, EdgeKind::Dispatch, Some("setValue(uint256)")),
+            (0, 17, EdgeKind::Fallthrough, None),
+            (17, 39, EdgeKind::Dispatch, Some("getValue()")),
+            (17, 28, EdgeKind::Fallthrough, None),
+        ]
+    );
+    assert_eq!(graph.edges[0].selector.as_deref(), Some("0x55241077"));
+
+    let json = serde_json::to_value(&graph).unwrap();
+    assert_eq!(json["edges"][0]["kind"], "dispatch");
+    assert!(json["edges"][1].get("selector").is_none());
+
+    let dot = graph.to_dot("Value.huff");
+    assert!(dot.starts_with("digraph \"Value.huff\" {\n"));
+    assert!(dot.contains("    b0 -> b31 [color=green label=\"setValue(uint256)\"];\n"));
+    assert!(dot.contains("    b0 -> b17 [color=red];\n"));
+    assert!(dot.contains("    b28 [label=\"0x001c MAIN\\lPUSH1 0x00\\lJUMP\\l\" style=dashed];\n"));
+}
+
+#[test]
+fn test_jump_and_branch_edges() {
+    let source = r#"#define macro MAIN() = takes (0) returns (0) {
+        0x00 calldataload skip jumpi
+        done jump
+        skip:
+            0x01 pop
+        done:
+            stop
+    }
+    "#;
+    let graph = ControlFlowGraph::new(&compiler().gen_artifact(file("", source, None)).unwrap());
+
+    let edges = graph.edges.iter().map(|e| (e.from, e.to, e.kind)).collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            (0, 11, EdgeKind::Branch),
+            (0, 7, EdgeKind::Fallthrough),
+            (7, 15, EdgeKind::Jump),
+            (11, 15, EdgeKind::Fallthrough),
+        ]
+    );
+    assert!(graph.to_dot("").contains("    b11 -> b15 [style=dashed];\n"));
+}
@@ -1,7 +1,8 @@
-use huff_codegen::*;
+use huff_codegen::{config::CodegenConfig, *};
 use huff_lexer::*;
 use huff_parser::*;
 use huff_utils::prelude::*;
+use std::sync::Arc;
 
 #[test]
 fn test_storage_pointers_not_derived() {
@@ -31,6 +32,7 @@ fn test_storage_pointers_not_derived() {
 
     // let const_start = source.find("UNKNOWN_CONSTANT_DEFINITION").unwrap_or(0);
     // let const_end = const_start + "UNKNOWN_CONSTANT_DEFINITION".len();
+    let mint_start = source.rfind("MINT()").unwrap();
 
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source);
@@ -53,7 +55,12 @@ fn test_storage_pointers_not_derived() {
                         Span { start: 44, end: 45, file: None },
                         Span { start: 46, end: 68, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    expansion: vec![AstSpan(vec![
+                        Span { start: mint_start, end: mint_start + 4, file: None },
+                        Span { start: mint_start + 4, end: mint_start + 5, file: None },
+                        Span { start: mint_start + 5, end: mint_start + 6, file: None }
+                    ])],
                 }
             )
         }
@@ -90,6 +97,7 @@ fn test_invalid_constant_definition() {
 
     let const_start = source.find("UNKNOWN_CONSTANT_DEFINITION").unwrap_or(0);
     let const_end = const_start + "UNKNOWN_CONSTANT_DEFINITION".len();
+    let mint_start = source.rfind("MINT()").unwrap();
 
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source);
@@ -109,7 +117,12 @@ fn test_invalid_constant_definition() {
                         "UNKNOWN_CONSTANT_DEFINITION".to_string()
                     ),
                     span: AstSpan(vec![Span { start: const_start, end: const_end, file: None }]),
-                    token: None
+                    token: None,
+                    expansion: vec![AstSpan(vec![
+                        Span { start: mint_start, end: mint_start + 4, file: None },
+                        Span { start: mint_start + 4, end: mint_start + 5, file: None },
+                        Span { start: mint_start + 5, end: mint_start + 6, file: None }
+                    ])],
                 }
             )
         }
@@ -141,20 +154,12 @@ fn test_missing_constructor() {
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
-    // Create constructor bytecode
-    match Codegen::generate_constructor_bytecode(&contract) {
-        Ok(_) => panic!("moose"),
-        Err(e) => {
-            assert_eq!(
-                e,
-                CodegenError {
-                    kind: CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()),
-                    span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                    token: None
-                }
-            )
-        }
-    }
+    // A missing constructor is empty, leaving the bootstrap code alone in the initcode
+    assert_eq!(Codegen::generate_constructor_bytecode(&contract).unwrap(), "");
+    let main_bytecode = Codegen::generate_main_bytecode(&contract).unwrap();
+    let mut cg = Codegen::new();
+    let artifact = cg.churn(Arc::new(FileSource::default()), vec![], &main_bytecode, "").unwrap();
+    assert_eq!(artifact.bytecode, format!("601a8060093d393df3{}", main_bytecode));
 }
 
 #[test]
@@ -183,7 +188,8 @@ fn test_missing_main() {
                 CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("MAIN".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                    token: None
+                    token: None,
+                    expansion: vec![],
                 }
             )
         }
@@ -228,7 +234,8 @@ fn test_unknown_macro_definition() {
                         Span { start: 351, end: 352, file: None },
                         Span { start: 352, end: 353, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    expansion: vec![],
                 }
             )
         }
@@ -276,9 +283,272 @@ fn test_unmatched_jump_label() {
                         Span { start: 377, end: 380, file: None },
                         Span { start: 380, end: 381, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    expansion: vec![],
                 }
             )
         }
     }
 }
+
+#[test]
+fn test_circular_macro_invocation() {
+    let sources = [
+        // Invoking itself
+        ("#define macro LOOP() = takes(0) returns (0) { LOOP() }", vec!["LOOP", "LOOP"]),
+        // Taking its own size
+        ("#define macro LOOP() = takes(0) returns (0) { __codesize(LOOP) }", vec!["LOOP", "LOOP"]),
+        // Invoking a macro that takes its size
+        (
+            r#"
+        #define macro SIZE() = takes(0) returns (0) { __codesize(LOOP) }
+        #define macro LOOP() = takes(0) returns (0) { SIZE() }
+        "#,
+            vec!["LOOP", "SIZE", "LOOP"],
+        ),
+        // Invoking a macro that invokes it
+        (
+            r#"
+        #define macro INNER() = takes(0) returns (0) { LOOP() }
+        #define macro LOOP() = takes(0) returns (0) { INNER() }
+        "#,
+            vec!["LOOP", "INNER", "LOOP"],
+        ),
+        // Taking the size of a macro that expands into itself
+        (
+            r#"
+        #define macro INNER() = takes(0) returns (0) { __codesize(SIZED) }
+        #define macro SIZED() = takes(0) returns (0) { INNER() }
+        #define macro LOOP() = takes(0) returns (0) { __codesize(SIZED) }
+        "#,
+            vec!["SIZED", "INNER", "SIZED"],
+        ),
+    ];
+
+    for (source, cycle) in sources {
+        let source =
+            &format!("{}\n#define macro MAIN() = takes(0) returns (0) {{ LOOP() }}", source);
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(full_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut contract = parser.parse().unwrap();
+        contract.derive_storage_pointers();
+
+        let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+        let cycle = cycle.into_iter().map(String::from).collect();
+        assert_eq!(err.kind, CodegenErrorKind::CircularMacroInvocation(cycle));
+    }
+}
+
+#[test]
+fn test_invalid_macro_arg_count() {
+    let invocations = [("TRANSFER()", 0), ("TRANSFER(error, 0x01)", 2)];
+
+    for (invocation, found) in invocations {
+        let source = &format!(
+            r#"
+            #define macro TRANSFER(error_label) = takes(0) returns (0) {{
+                0x01 <error_label> jumpi
+            }}
+            #define macro MAIN() = takes(0) returns (0) {{
+                {}
+                error:
+                    0x00 0x00 revert
+            }}
+            "#,
+            invocation
+        );
+        let full_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(full_source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut contract = parser.parse().unwrap();
+        contract.derive_storage_pointers();
+
+        let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+        assert_eq!(
+            err.kind,
+            CodegenErrorKind::InvalidMacroArgCount("TRANSFER".to_string(), 1, found)
+        );
+    }
+}
+
+#[test]
+fn test_missing_event_hash() {
+    let source = r#"
+    #define event Transfer(address,address,uint256)
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __EVENT_HASH(Transfer)
+        __EVENT_HASH(Approval)
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingEventInterface("Approval".to_string()));
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "__EVENT_HASH");
+}
+
+#[test]
+fn test_missing_error_definition() {
+    let source = r#"
+    #define error Unauthorized()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __ERROR(Unauthorized)
+        __ERROR(InsufficientBalance)
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let contract = parser.parse().unwrap();
+
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::MissingErrorDefinition("InsufficientBalance".to_string())
+    );
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "__ERROR");
+}
+
+#[test]
+fn test_unsupported_opcode() {
+    let source = r#"
+    #define macro GET_BALANCE() = takes(0) returns (1) {
+        selfbalance
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x01 0x02 shl pop
+        done:
+            GET_BALANCE() 0x00 sstore
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let contract = parser.parse().unwrap();
+
+    // The default evm version supports every opcode
+    assert_eq!(Codegen::generate_main_bytecode(&contract).unwrap(), "600160021b505b47600055");
+
+    // Opcodes in invoked macros are checked against the target evm version
+    let config = CodegenConfig { evm_version: EvmVersion::Petersburg, ..Default::default() };
+    let err = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::UnsupportedOpcode("selfbalance".to_string(), EvmVersion::Petersburg)
+    );
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "selfbalance");
+
+    // Along with those in the macro body
+    let config = CodegenConfig { evm_version: EvmVersion::Byzantium, ..Default::default() };
+    let err = Codegen::generate_main_bytecode_with_config(&contract, &config).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::UnsupportedOpcode("shl".to_string(), EvmVersion::Byzantium)
+    );
+}
+
+#[test]
+fn test_jump_destination_too_large() {
+    let padding = "00".repeat(0xfff0);
+    let source = format!(
+        r#"
+    #define macro MAIN() = takes(0) returns (0) {{
+        end jump
+        __VERBATIM("0x{}")
+        end:
+            stop
+        past:
+    }}
+    "#,
+        padding
+    );
+
+    let full_source = FullFileSource { source: &source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let contract = parser.parse().unwrap();
+
+    // Destinations up to 0xffff fit in a PUSH2
+    let bytecode = Codegen::generate_main_bytecode(&contract).unwrap();
+    assert_eq!(&bytecode[..8], "61fff456");
+
+    // Further ones fail instead of being truncated
+    let source = source.replace("end jump", "past jump pc pc pc pc pc pc pc pc pc pc");
+    let full_source = FullFileSource { source: &source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let contract = parser.parse().unwrap();
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::JumpDestinationTooLarge("past".to_string(), 0x10000));
+}
+
+#[test]
+fn test_macro_expansion_chain() {
+    let source = r#"
+    #define macro INNER() = takes(0) returns (0) {
+        [MISSING]
+    }
+
+    #define macro OUTER() = takes(0) returns (0) {
+        INNER()
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 OUTER()
+    }
+    "#;
+
+    let file = Arc::new(FileSource {
+        path: "chain.huff".to_string(),
+        source: Some(source.to_string()),
+        ..Default::default()
+    });
+    let full_source = FullFileSource {
+        source,
+        file: Some(Arc::clone(&file)),
+        spans: vec![(Arc::clone(&file), Span { start: 0, end: source.len(), file: None })],
+    };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let contract = parser.parse().unwrap();
+
+    let err = Codegen::generate_main_bytecode(&contract).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingConstantDefinition("MISSING".to_string()));
+
+    // The invocation sites are listed innermost first
+    let inner_start = source.find("INNER()\n").unwrap();
+    let outer_start = source.find("OUTER()\n").unwrap();
+    let starts = err.expansion.iter().map(|s| s.0[0].start).collect::<Vec<usize>>();
+    assert_eq!(starts, vec![inner_start, outer_start]);
+
+    // Each invocation site is noted after the error itself
+    let message = CompilerError::CodegenError(err).to_string();
+    assert!(message.starts_with("\nError: Missing Constant Definition"));
+    assert_eq!(message.matches("Note: In This Macro Invocation").count(), 2);
+    let inner_note = message.find("> 7 |         INNER()").unwrap();
+    let outer_note = message.find("> 11 |         0x00 OUTER()").unwrap();
+    assert!(message.find("> 3 |         [MISSING]").unwrap() < inner_note);
+    assert!(inner_note < outer_note);
+}
@@ -0,0 +1,25 @@
+//! Fixtures shared by the integration tests
+
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+/// A compiler for sources held in memory, with the default options
+pub fn compiler() -> Compiler<'static> {
+    Compiler::new(Arc::new(vec![]), None, None, None, false, false)
+}
+
+/// A file source at a path, with the given dependencies
+pub fn file(
+    path: &str,
+    source: &str,
+    dependencies: Option<Vec<Arc<FileSource>>>,
+) -> Arc<FileSource> {
+    Arc::new(FileSource {
+        path: path.to_string(),
+        source: Some(source.to_string()),
+        dependencies,
+        ..Default::default()
+    })
+}
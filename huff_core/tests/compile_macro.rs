@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use huff_codegen::{config::CodegenConfig, Codegen};
+use huff_core::simulation::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Contract {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+/// Deploys a standalone blob and calls it with the given words as calldata
+fn call_blob(runtime: &str, words: &[u64]) -> TraceOutcome {
+    let artifact =
+        Codegen::new().churn(Arc::new(FileSource::default()), vec![], runtime, "").unwrap();
+    let calldata = words.iter().flat_map(|w| [[0u8; 24].as_slice(), &w.to_be_bytes()].concat());
+    let calldata = calldata.collect::<Vec<u8>>();
+    simulate_call_outcome(&artifact, &calldata, 0, &Prestate::default()).unwrap()
+}
+
+const SOURCE: &str = r#"
+    #define macro SAFE_SUB() = takes (2) returns (1) {
+        // [a, b]
+        dup2 dup2 lt underflow jumpi
+        sub 0x00 mstore 0x20 0x00 return
+        underflow:
+            0x00 dup1 revert
+    }
+
+    #define fn RETURN_WORD() = takes (1) returns (0) {
+        0x00 mstore 0x20 0x00 return
+    }
+
+    #define macro ANSWER() = takes (0) returns (0) {
+        0x2a RETURN_WORD()
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {}
+"#;
+
+#[test]
+fn test_compile_macro() {
+    let contract = parse(SOURCE);
+    let runtime = Codegen::compile_macro("SAFE_SUB", &contract, &CodegenConfig::default()).unwrap();
+
+    // The shim loads the second calldata word first, so the first ends up on top
+    assert!(runtime.starts_with("602035600035"));
+
+    // Labels are placed after the shim
+    assert_eq!(call_blob(&runtime, &[7, 3]), TraceOutcome::Returned(format!("{:064x}", 4)));
+    assert_eq!(call_blob(&runtime, &[3, 7]), TraceOutcome::Reverted(String::new()));
+}
+
+#[test]
+fn test_compile_macros() {
+    let contract = parse(SOURCE);
+    let blobs =
+        Codegen::compile_macros(&["SAFE_SUB", "ANSWER"], &contract, &CodegenConfig::default())
+            .unwrap();
+    assert_eq!(blobs.keys().collect::<Vec<_>>(), vec!["ANSWER", "SAFE_SUB"]);
+
+    // Fns are appended to each blob as they are to MAIN
+    assert_eq!(call_blob(&blobs["ANSWER"], &[]), TraceOutcome::Returned(format!("{:064x}", 42)));
+
+    let err = Codegen::compile_macro("MISSING", &contract, &CodegenConfig::default()).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingMacroDefinition("MISSING".to_string()));
+}
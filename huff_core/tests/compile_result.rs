@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_partial_compilation() {
+    let root = std::env::temp_dir().join(format!("huff-result-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let valid = root.join("Valid.huff");
+    std::fs::write(&valid, "#define macro MAIN() = takes(0) returns(0) { 0x01 }\n").unwrap();
+    let invalid = root.join("Invalid.huff");
+    std::fs::write(&invalid, "#define macro MAIN() = takes(0) returns(0) { MISSING() }\n").unwrap();
+    let missing = root.join("Missing.huff").to_string_lossy().to_string();
+    let sources = vec![
+        valid.to_string_lossy().to_string(),
+        invalid.to_string_lossy().to_string(),
+        missing.clone(),
+    ];
+
+    let output = root.join("artifacts");
+    let compiler = Compiler::new(
+        Arc::new(sources),
+        Some(output.to_string_lossy().to_string()),
+        None,
+        None,
+        false,
+        false,
+    );
+    let result = compiler.execute();
+    assert!(!result.is_ok());
+
+    // The valid contract still compiles and is exported
+    assert_eq!(result.artifacts.len(), 1);
+    assert!(result.artifacts[0].file.path.ends_with("Valid.huff"));
+    assert_eq!(result.artifacts[0].runtime, "6001");
+    assert!(output.exists());
+    assert!(result.timings.contracts.keys().any(|f| f.ends_with("Valid.huff")));
+
+    // The unreadable and invalid files are reported individually
+    assert_eq!(result.errors.len(), 2);
+    assert!(result
+        .errors
+        .iter()
+        .any(|e| **e == CompilerError::FileUnpackError(UnpackError::MissingFile(missing.clone()))));
+    assert!(result.errors.iter().any(|e| matches!(
+        &**e,
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::InvalidMacroInvocation(m),
+            ..
+        }) if m == "MISSING"
+    )));
+    assert!(matches!(
+        &*result.into_result().unwrap_err(),
+        CompilerError::FailedCompiles(errors) if errors.len() == 2
+    ));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_into_result() {
+    let artifact = Arc::new(Artifact::default());
+    let result = huff_core::result::CompileResult {
+        artifacts: vec![Arc::clone(&artifact)],
+        ..Default::default()
+    };
+    assert!(result.is_ok());
+    assert_eq!(result.into_result().unwrap(), vec![artifact]);
+
+    let result = huff_core::result::CompileResult {
+        errors: vec![Arc::new(CompilerError::Cancelled)],
+        ..Default::default()
+    };
+    assert_eq!(*result.into_result().unwrap_err(), CompilerError::Cancelled);
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use huff_core::completion::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_completions() {
+    let utils = "/// The contract owner slot\n#define constant OWNER = 0x01\n/// Stores a value\n#define macro STORE() = takes(2) returns(0) {\n    mstore\n}\n";
+    let main =
+        "#define macro MAIN() = takes(0) returns(0) {\n    [OW] __FU ST ms \n    <va\n}\n#def";
+    let utils_file = Arc::new(FileSource {
+        path: "Utils.huff".to_string(),
+        source: Some(utils.to_string()),
+        ..Default::default()
+    });
+    let main_file = Arc::new(FileSource {
+        path: "Main.huff".to_string(),
+        source: Some(main.to_string()),
+        dependencies: Some(vec![utils_file]),
+        ..Default::default()
+    });
+    let files = vec![main_file];
+    let complete = |after: &str| {
+        completions(&files, &[], "Main.huff", main.find(after).unwrap() + after.len())
+    };
+
+    // Constants inside brackets, with their value and docs
+    assert_eq!(
+        complete("[OW"),
+        vec![CompletionItem {
+            label: "OWNER".to_string(),
+            kind: CompletionKind::Constant,
+            detail: "#define constant OWNER = 0x01".to_string(),
+            docs: "The contract owner slot".to_string(),
+        }]
+    );
+
+    // Builtins after `__`
+    let builtins = complete("__FU");
+    assert_eq!(builtins.len(), 1);
+    assert_eq!(builtins[0].label, "__FUNC_SIG");
+    assert_eq!(builtins[0].kind, CompletionKind::Builtin);
+
+    // Macros and opcodes at statement start, ranked by case and kind
+    let items = complete(" ST");
+    assert_eq!(items[0].label, "STORE");
+    assert_eq!(items[0].docs, "Stores a value");
+    assert!(items.iter().skip(1).all(|i| i.kind == CompletionKind::Opcode));
+    assert!(items.iter().any(|i| i.label == "staticcall"));
+
+    let items = complete(" ms");
+    let labels = items.iter().map(|i| i.label.as_str()).collect::<Vec<&str>>();
+    assert_eq!(labels, vec!["msize", "mstore", "mstore8"]);
+    assert_eq!(items[1].detail, "0x52");
+
+    // Every macro and opcode on an empty prefix, skipping the incomplete main file
+    let all = complete("ms ");
+    assert_eq!(all.len(), OPCODES.len() + 1);
+
+    // Nothing for parameters or outside of macro bodies
+    assert!(complete("<va").is_empty());
+    assert!(complete("#def").is_empty());
+    assert!(completions(&files, &[], "Main.huff", main.len() + 1).is_empty());
+}
+
+#[test]
+fn test_completions_inside_multibyte_char() {
+    let main = "#define macro MAIN() = takes(0) returns(0) {\n    ms // café\n}";
+    let files = vec![Arc::new(FileSource {
+        path: "Main.huff".to_string(),
+        source: Some(main.to_string()),
+        ..Default::default()
+    })];
+    assert!(completions(&files, &[], "Main.huff", main.find('é').unwrap() + 1).is_empty());
+}
@@ -0,0 +1,81 @@
+use huff_codegen::Codegen;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn main_bytecode(source: &str) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&contract)
+}
+
+#[test]
+fn test_constant_expressions() {
+    let source = r#"
+    #define constant BASE = 0x20
+    #define constant TOTAL = ADD(BASE, 0x20)
+    #define constant INFIX = BASE * 2 + 1
+    #define constant WORDS = (TOTAL + 0x1f) / 32
+    #define constant REMAINDER = MOD(TOTAL, 0x30)
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [TOTAL] [INFIX] [WORDS] [REMAINDER]
+    }
+    "#;
+
+    assert_eq!(main_bytecode(source).unwrap(), "6040604160026010");
+}
+
+#[test]
+fn test_constant_expressions_over_storage_pointers() {
+    let source = r#"
+    #define constant SLOT = FREE_STORAGE_POINTER()
+    #define constant NEXT_SLOT = ADD(SLOT, 0x100)
+    #define constant OTHER = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [NEXT_SLOT] sload
+        [OTHER] sload
+    }
+    "#;
+
+    // Constants referenced by an expression are derived where the expression is used
+    assert_eq!(main_bytecode(source).unwrap(), "61010054600154");
+}
+
+#[test]
+fn test_constant_expression_errors() {
+    let cases = [
+        (
+            "#define constant X = MUL(0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff, 2)",
+            CodegenErrorKind::ConstantOverflow("X".to_string()),
+        ),
+        ("#define constant X = 1 - 2", CodegenErrorKind::ConstantOverflow("X".to_string())),
+        (
+            "#define constant X = DIV(1, 0)",
+            CodegenErrorKind::ConstantDivisionByZero("X".to_string()),
+        ),
+        (
+            "#define constant X = MISSING + 1",
+            CodegenErrorKind::MissingConstantDefinition("MISSING".to_string()),
+        ),
+        (
+            "#define constant X = ADD(Y, 1)\n#define constant Y = X * 2",
+            CodegenErrorKind::CircularConstant(vec![
+                "X".to_string(),
+                "Y".to_string(),
+                "X".to_string(),
+            ]),
+        ),
+    ];
+
+    for (constants, kind) in cases {
+        let source =
+            format!("{}\n#define macro MAIN() = takes(0) returns (0) {{ [X] }}", constants);
+        assert_eq!(main_bytecode(&source).unwrap_err().kind, kind);
+    }
+}
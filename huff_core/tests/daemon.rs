@@ -0,0 +1,214 @@
+use huff_core::{daemon::*, index::SymbolKind, Compiler};
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+};
+
+fn compiler() -> Compiler<'static> {
+    Compiler::new(Arc::new(vec![]), None, None, None, false, false)
+}
+
+#[test]
+fn test_daemon_reuses_unchanged_artifacts() {
+    let root = std::env::temp_dir().join(format!("huff-daemon-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let helper = root.join("Helper.huff");
+    let main = root.join("Main.huff");
+    std::fs::write(&helper, "#define macro HELPER() = takes(0) returns(0) { 0x01 }\n").unwrap();
+    std::fs::write(
+        &main,
+        "#include \"./Helper.huff\"\n#define macro MAIN() = takes(0) returns(0) { HELPER() }\n",
+    )
+    .unwrap();
+    let paths = vec![main.to_string_lossy().to_string()];
+
+    let daemon = Daemon::new(compiler());
+    let first = daemon.compile(&paths);
+    assert!(first.errors.is_empty());
+    assert!(first.cached.is_empty());
+    assert!(first.artifacts[0].runtime.contains("6001"));
+
+    // An unchanged file is served from memory
+    let second = daemon.compile(&paths);
+    assert_eq!(second.cached, paths);
+    assert_eq!(second.artifacts, first.artifacts);
+
+    // Changing an import recompiles the files including it
+    std::fs::write(&helper, "#define macro HELPER() = takes(0) returns(0) { 0x02 }\n").unwrap();
+    let third = daemon.compile(&paths);
+    assert!(third.cached.is_empty());
+    assert!(third.artifacts[0].runtime.contains("6002"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_daemon_attributes_imported_warnings() {
+    let root = std::env::temp_dir().join(format!("huff-daemon-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(
+        root.join("Helper.huff"),
+        "#define macro HELPER() = takes(0) returns(0) { 0x20 0x00 sha3 }\n",
+    )
+    .unwrap();
+    let main = root.join("Main.huff");
+    std::fs::write(
+        &main,
+        "#include \"./Helper.huff\"\n#define macro MAIN() = takes(0) returns(0) { HELPER() }\n",
+    )
+    .unwrap();
+    let paths = vec![main.to_string_lossy().to_string()];
+
+    // The warning raised in the import is reported for the file including it, also once cached
+    let daemon = Daemon::new(compiler());
+    let first = daemon.compile(&paths);
+    assert_eq!(first.warnings.len(), 1);
+    assert!(first.warnings[0].contains("sha3"));
+    assert_eq!(daemon.compile(&paths).warnings, first.warnings);
+    let diagnostics = daemon.diagnostics(&paths);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].file, paths[0]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_daemon_json_rpc() {
+    let daemon = Daemon::new(compiler());
+
+    let response: Value = serde_json::from_str(&daemon.handle("not json")).unwrap();
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+    let response: Value =
+        serde_json::from_str(&daemon.handle(r#"{"jsonrpc":"2.0","id":1,"method":"build"}"#))
+            .unwrap();
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+    let response: Value =
+        serde_json::from_str(&daemon.handle(r#"{"jsonrpc":"2.0","id":2,"method":"compile"}"#))
+            .unwrap();
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+    let request =
+        r#"{"jsonrpc":"2.0","id":3,"method":"compile","params":{"paths":["./missing.huff"]}}"#;
+    let response: Value = serde_json::from_str(&daemon.handle(request)).unwrap();
+    assert_eq!(response["result"]["artifacts"], Value::Array(vec![]));
+    assert_eq!(response["result"]["errors"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_daemon_serves_until_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || Daemon::new(compiler()).serve(listener));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":1,"method":"shutdown"}}"#).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"], Value::Null);
+
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_daemon_serves_connections_concurrently() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || Daemon::new(compiler()).serve(listener));
+
+    // A client leaving mid-request doesn't stop the daemon
+    let mut gone = TcpStream::connect(address).unwrap();
+    write!(gone, r#"{{"jsonrpc":"2.0","id":1,"#).unwrap();
+    drop(gone);
+
+    // A connection held open doesn't block the others
+    let _idle = TcpStream::connect(address).unwrap();
+    let mut stream = TcpStream::connect(address).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":2,"method":"build"}}"#).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+    // Shutting down closes the idle connection too
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":3,"method":"shutdown"}}"#).unwrap();
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response["id"], 3);
+
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_daemon_only_listens_on_loopback() {
+    let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+    let err = Daemon::new(compiler()).serve(listener).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_daemon_diagnostics_selectors_and_ast() {
+    let root = std::env::temp_dir().join(format!("huff-daemon-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let token = root.join("Token.huff");
+    let broken = root.join("Broken.huff");
+    std::fs::write(
+        &token,
+        r#"
+#define function transfer(address,uint256) nonpayable returns ()
+#define event Transfer(address indexed,address indexed,uint256)
+#define error Unauthorized(address)
+
+#define macro TRANSFER() = takes(0) returns(0) { 0x01 }
+#define macro MAIN() = takes(0) returns(0) { TRANSFER() }
+"#,
+    )
+    .unwrap();
+    let broken_source = "#define macro MAIN() = takes(0) returns(0) { MISSING() }\n";
+    std::fs::write(&broken, broken_source).unwrap();
+    let token = token.to_string_lossy().to_string();
+    let broken = broken.to_string_lossy().to_string();
+
+    let daemon = Daemon::new(compiler());
+    let diagnostics = daemon.diagnostics(&[token.clone(), broken.clone()]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].file, broken);
+    let location = diagnostics[0].location.as_ref().unwrap();
+    assert_eq!(location.file, broken);
+    assert_eq!(&broken_source[location.start..location.end], "MISSING");
+
+    let selectors = daemon.selectors(std::slice::from_ref(&token));
+    let selectors = &selectors[&token];
+    assert_eq!(selectors.functions["transfer(address,uint256)"], "a9059cbb");
+    assert_eq!(
+        selectors.events["Transfer(address,address,uint256)"],
+        "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    );
+    assert_eq!(selectors.errors["Unauthorized(address)"], "8e4a23d6");
+
+    let index = daemon.ast(std::slice::from_ref(&token)).unwrap();
+    let transfer = index.find(SymbolKind::Macro, "TRANSFER").unwrap();
+    assert_eq!(transfer.references.len(), 1);
+    assert!(daemon.ast(&[root.join("Missing.huff").to_string_lossy().to_string()]).is_err());
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 4,
+        "method": "selectors",
+        "params": { "paths": [token] }
+    });
+    let response: Value = serde_json::from_str(&daemon.handle(&request.to_string())).unwrap();
+    assert_eq!(response["result"][&token]["functions"]["transfer(address,uint256)"], "a9059cbb");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,68 @@
+use huff_core::docs::*;
+
+#[test]
+fn test_generate_docs() {
+    let source = r#"
+        /// Emitted when tokens move
+        #define event Transfer(address indexed from, address indexed to, uint256)
+
+        /// Returns the balance of an account
+        /// @param account The account
+        #define function balanceOf(address account) view returns (uint256)
+
+        // A regular comment is not documentation
+        #define constant BALANCE_SLOT = FREE_STORAGE_POINTER()
+        #define constant DECIMALS = 0x12
+
+        /**
+         * Loads the balance of an account
+         * @dev Expects the account on the stack
+         */
+        #define macro BALANCE_OF(slot) = takes (1) returns (1) {
+            <slot> sload
+        }
+
+        #define fn HELPER() = takes (0) returns (0) {}
+    "#;
+
+    let docs = ContractDocs::generate("Token.huff", source).unwrap();
+    assert_eq!(
+        docs.events,
+        vec![ItemDoc {
+            name: "Transfer".to_string(),
+            signature: "#define event Transfer(address indexed from, address indexed to, uint256)"
+                .to_string(),
+            docs: "Emitted when tokens move".to_string(),
+        }]
+    );
+    assert_eq!(
+        docs.functions[0].signature,
+        "#define function balanceOf(address account) view returns (uint256)"
+    );
+    assert_eq!(
+        docs.functions[0].docs,
+        "Returns the balance of an account\n@param account The account"
+    );
+    assert_eq!(
+        docs.constants.iter().map(|c| c.signature.as_str()).collect::<Vec<&str>>(),
+        vec![
+            "#define constant BALANCE_SLOT = FREE_STORAGE_POINTER()",
+            "#define constant DECIMALS = 0x12"
+        ]
+    );
+    assert!(docs.constants.iter().all(|c| c.docs.is_empty()));
+    assert_eq!(docs.macros[0].signature, "#define macro BALANCE_OF(slot) = takes (1) returns (1)");
+    assert_eq!(
+        docs.macros[0].docs,
+        "Loads the balance of an account\n@dev Expects the account on the stack"
+    );
+    assert_eq!(docs.macros[1].signature, "#define fn HELPER() = takes (0) returns (0)");
+    assert!(docs.macros[1].docs.is_empty());
+
+    let markdown = docs.to_markdown();
+    assert!(markdown.starts_with("# Token.huff\n\n## Macros\n\n### `BALANCE_OF`\n"));
+    assert!(markdown.contains("```huff\n#define constant DECIMALS = 0x12\n```\n"));
+
+    let json = serde_json::to_value(&docs).unwrap();
+    assert_eq!(json["macros"][0]["name"], "BALANCE_OF");
+}
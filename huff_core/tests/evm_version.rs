@@ -0,0 +1,61 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+fn compile(source: &str, evm_version: EvmVersion) -> Result<Artifact, CompilerError<'static>> {
+    let compiler = Compiler { evm_version, ..compiler() };
+    compiler.gen_artifact(file("Target.huff", source, None))
+}
+
+#[test]
+fn test_push0() {
+    let source = r#"#define constant ZERO = 0x00
+#define constant ONE = 0x01
+
+#define macro STORE(value) = takes (0) returns (0) {
+    <value> [ZERO] sstore
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x00 [ONE] sstore
+    STORE(0x00)
+}
+"#;
+
+    // Zero is pushed with PUSH1 until shanghai
+    assert_eq!(compile(source, EvmVersion::London).unwrap().runtime, "60006001556000600055");
+    assert_eq!(compile(source, EvmVersion::Paris).unwrap().runtime, "60006001556000600055");
+
+    // Literals, constants, and macro arguments alike
+    assert_eq!(compile(source, EvmVersion::Shanghai).unwrap().runtime, "5f6001555f5f55");
+    assert_eq!(compile(source, EvmVersion::Cancun).unwrap().runtime, "5f6001555f5f55");
+}
+
+#[test]
+fn test_cancun_opcodes() {
+    let source = r#"#define macro MAIN() = takes (0) returns (0) {
+    0x01 0x00 tstore
+    0x00 tload pop
+    0x20 0x00 0x20 mcopy
+    blobbasefee pop
+}
+"#;
+
+    assert_eq!(
+        compile(source, EvmVersion::Cancun).unwrap().runtime,
+        "60015f5d5f5c5060205f60205e4a50"
+    );
+
+    let err = match compile(source, EvmVersion::Shanghai).unwrap_err() {
+        CompilerError::CodegenError(e) => e,
+        e => panic!("Expected a codegen error, got {:?}", e),
+    };
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::UnsupportedOpcode("tstore".to_string(), EvmVersion::Shanghai)
+    );
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "tstore");
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use huff_core::expansion::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_expansion_preview() {
+    let utils = "#define constant SLOT = 0x07\n#define macro STORE_AT(value) = takes(0) returns(0) {\n    <value> [SLOT] sstore\n}\n";
+    let main = "#define macro JUMP_TO(dest) = takes(0) returns(0) {\n    <dest> jump\n}\n#define macro MAIN() = takes(0) returns(0) {\n    STORE_AT(0x2a) JUMP_TO(done)\n    done:\n        caller pop\n}\n";
+    let utils_file = Arc::new(FileSource {
+        path: "Utils.huff".to_string(),
+        source: Some(utils.to_string()),
+        ..Default::default()
+    });
+    let main_file = Arc::new(FileSource {
+        path: "Main.huff".to_string(),
+        source: Some(main.to_string()),
+        dependencies: Some(vec![utils_file]),
+        ..Default::default()
+    });
+    let files = vec![main_file];
+    let expand = |at: &str| Expansion::at(&files, &[], "Main.huff", main.find(at).unwrap() + 1);
+
+    // Invocations expand with their arguments, from the name or inside the arguments
+    let store = expand("STORE_AT(").unwrap();
+    assert_eq!(store.name, "STORE_AT");
+    assert_eq!(&main[store.range.start..store.range.end], "STORE_AT(0x2a)");
+    assert_eq!(store.bytecode, "602a600755");
+    assert_eq!(store.size, 5);
+    assert_eq!(store.instructions, vec!["PUSH1 0x2a", "PUSH1 0x07", "SSTORE"]);
+    assert_eq!(store.summary(), "STORE_AT expands to 5 bytes: PUSH1 0x2a PUSH1 0x07 SSTORE");
+    assert_eq!(expand("0x2a)"), Some(store));
+
+    // Jumps to labels outside of the expansion are left unresolved
+    let jump = expand("JUMP_TO(done)").unwrap();
+    assert_eq!(jump.instructions, vec!["PUSH2 ????", "JUMP"]);
+
+    // Definitions expand on their own, leaving their arguments unresolved
+    let main_def = expand("MAIN()").unwrap();
+    assert_eq!(main_def.name, "MAIN");
+    assert_eq!(main_def.size, 12);
+    assert_eq!(main_def.instructions[3..5], ["PUSH2 0x0009", "JUMP"]);
+    let jump_def = expand("JUMP_TO(dest)").unwrap();
+    assert_eq!(jump_def.instructions, vec!["PUSH2 ????", "JUMP"]);
+    assert!(expand("caller").is_none());
+}
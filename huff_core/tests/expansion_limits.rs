@@ -0,0 +1,87 @@
+use huff_codegen::{config::*, Codegen};
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+/// A chain of macros invoked from MAIN, each invoking the next, ending in a push
+fn nested_macros(depth: usize) -> String {
+    let mut source = String::from("#define macro MAIN() = takes(0) returns(0) { M0() }\n");
+    for i in 0..depth {
+        source.push_str(&format!(
+            "#define macro M{}() = takes(0) returns(0) {{ M{}() }}\n",
+            i,
+            i + 1
+        ));
+    }
+    source.push_str(&format!("#define macro M{}() = takes(0) returns(0) {{ 0x01 }}\n", depth));
+    source
+}
+
+fn main_bytecode(source: &str, config: &CodegenConfig) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(flattened_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode_with_config(&contract, config)
+}
+
+#[test]
+fn test_default_expansion_depth() {
+    let config = CodegenConfig::default();
+    let depth = DEFAULT_MAX_EXPANSION_DEPTH;
+    assert_eq!(main_bytecode(&nested_macros(depth - 2), &config).unwrap(), "6001");
+    let e = main_bytecode(&nested_macros(depth - 1), &config).unwrap_err();
+    let name = format!("M{}", depth - 1);
+    assert_eq!(e.kind, CodegenErrorKind::MacroExpansionTooDeep(name, depth));
+    assert_eq!(e.expansion.len(), depth);
+}
+
+#[test]
+fn test_deep_expansion_fits_thread_stack() {
+    // Raised limits are reached without overflowing the default stack of a spawned thread
+    let handle = std::thread::spawn(|| {
+        let config = CodegenConfig { max_expansion_depth: Some(400), ..Default::default() };
+        assert_eq!(main_bytecode(&nested_macros(398), &config).unwrap(), "6001");
+        let e = main_bytecode(&nested_macros(400), &config).unwrap_err();
+        assert_eq!(e.kind, CodegenErrorKind::MacroExpansionTooDeep("M399".to_string(), 400));
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_max_expansion_depth() {
+    let config = CodegenConfig { max_expansion_depth: Some(3), ..Default::default() };
+    // MAIN, M0, and M1 are expanded within each other
+    assert_eq!(main_bytecode(&nested_macros(1), &config).unwrap(), "6001");
+
+    let source = nested_macros(5);
+    let e = main_bytecode(&source, &config).unwrap_err();
+    assert_eq!(e.kind, CodegenErrorKind::MacroExpansionTooDeep("M2".to_string(), 3));
+
+    // The chain of invocations leading to the macro is kept, innermost first
+    let sites = e
+        .expansion
+        .iter()
+        .map(|s| source[s.0[0].start..s.0[0].end].to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(sites, vec!["M2", "M1", "M0"]);
+}
+
+#[test]
+fn test_max_expanded_size() {
+    // Each macro expands the previous one ten times, for 10^4 pushes of 2 bytes
+    let mut source = String::from("#define macro E0() = takes(0) returns(0) { 0x01 }\n");
+    for i in 1..=4 {
+        let body = vec![format!("E{}()", i - 1); 10].join(" ");
+        source.push_str(&format!("#define macro E{}() = takes(0) returns(0) {{ {} }}\n", i, body));
+    }
+    source.push_str("#define macro MAIN() = takes(0) returns(0) { E4() }\n");
+
+    let bytecode = main_bytecode(&source, &CodegenConfig::default()).unwrap();
+    assert_eq!(bytecode.len() / 2, 20000);
+
+    let config = CodegenConfig { max_expanded_size: Some(1000), ..Default::default() };
+    let e = main_bytecode(&source, &config).unwrap_err();
+    assert_eq!(e.kind, CodegenErrorKind::ExpandedBytecodeTooLarge("E1".to_string(), 1000));
+    assert_eq!(e.expansion.len(), 4);
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use common::{compiler, file};
+use proptest::prelude::*;
+
+/// Token-valid source fragments
+fn fragment() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(vec![
+            "#define",
+            "macro",
+            "fn",
+            "function",
+            "event",
+            "constant",
+            "jumptable",
+            "jumptable__packed",
+            "table",
+            "takes",
+            "returns",
+            "(",
+            ")",
+            "{",
+            "}",
+            "[",
+            "]",
+            "<",
+            ">",
+            "=",
+            ",",
+            ":",
+            "-",
+            "FREE_STORAGE_POINTER()",
+            "MAIN",
+            "CONSTRUCTOR",
+            "__FUNC_SIG",
+            "__EVENT_HASH",
+            "__tablesize",
+            "__tablestart",
+            "__codesize",
+            "__CREATE_ADDRESS",
+            "__CREATE2_ADDRESS",
+            "__MASK",
+            "__SHL_CONST",
+            "__RIGHTPAD",
+            "__VERBATIM",
+            "__DISPATCH",
+            "__CONSTRUCTOR_ARG",
+            "__IMMUTABLE",
+            "__SET_IMMUTABLE",
+            "add",
+            "mstore",
+            "jump",
+            "jumpi",
+            "uint256",
+            "int8",
+            "address",
+            "bytes32",
+            "uint256[]",
+            "view",
+            "nonpayable",
+            "payable",
+            "pure",
+            "indexed",
+            "// comment\n",
+            "/* block */",
+        ])
+        .prop_map(String::from),
+        "[A-Z_][A-Z_0-9]{0,3}",
+        "[a-z][a-z]{0,3}",
+        "0x[0-9a-f]{0,70}",
+        "[0-9]{1,80}",
+        "\"[a-zA-Z(),0-9]{0,12}\"",
+    ]
+}
+
+/// Macro definitions with token-valid bodies
+fn contract() -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec(fragment(), 0..8),
+        prop::collection::vec(fragment(), 0..24),
+        0usize..300,
+        0usize..300,
+    )
+        .prop_map(|(definitions, body, takes, returns)| {
+            format!(
+                "{}\n#define macro MAIN() = takes({}) returns({}) {{\n    {}\n}}\n",
+                definitions.join(" "),
+                takes,
+                returns,
+                body.join(" ")
+            )
+        })
+}
+
+/// Statements that parse in a macro body
+fn statement() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(vec![
+            "add",
+            "mstore",
+            "jump",
+            "jumpi",
+            "dup1",
+            "pop",
+            "[CONST]",
+            "[POINTER]",
+            "[MISSING]",
+            "label:",
+            "label",
+            "HELPER()",
+            "HELPER(label)",
+            "HELPER(<arg>)",
+            "MISSING()",
+            "<arg>",
+            "__FUNC_SIG(transfer)",
+            "__FUNC_SIG(\"foo(uint256)\")",
+            "__FUNC_SIG(missing)",
+            "__EVENT_HASH(Transfer)",
+            "__EVENT_HASH(missing)",
+            "__tablesize(TABLE)",
+            "__tablestart(TABLE)",
+            "__tablesize(CODE)",
+            "__tablestart(missing)",
+            "__codesize(HELPER)",
+            "__codesize(MAIN)",
+            "__codesize(missing)",
+            "__MASK(8, 0)",
+            "__MASK(300, 300)",
+            "__SHL_CONST(1, 255)",
+            "__SHL_CONST(0xff, 300)",
+            "__RIGHTPAD(0xdeadbeef)",
+            "__VERBATIM(0x60ff57)",
+            "__DISPATCH()",
+            "__CONSTRUCTOR_ARG(0)",
+            "__IMMUTABLE(missing)",
+            "__CREATE_ADDRESS(0x01, 1)",
+            "__CREATE2_ADDRESS(0x01, 0x02, 0x03)",
+        ])
+        .prop_map(String::from),
+        "0x[0-9a-f]{0,64}",
+    ]
+}
+
+/// Contracts with valid definitions and arbitrary macro bodies
+fn valid_contract() -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec(statement(), 0..24),
+        prop::collection::vec(statement(), 0..8),
+        "0x[0-9a-f]{0,64}",
+        "[0-9a-f]{0,80}",
+    )
+        .prop_map(|(main, helper, constant, code)| {
+            format!(
+                r#"#define function transfer(address,uint256) nonpayable returns ()
+#define event Transfer(address indexed,uint256)
+#define constant CONST = {}
+#define constant POINTER = FREE_STORAGE_POINTER()
+#define jumptable TABLE {{ label label }}
+#define table CODE {{ 0x{} }}
+#define macro HELPER(arg) = takes(0) returns(0) {{
+    {}
+}}
+#define macro MAIN() = takes(0) returns(0) {{
+    {}
+}}
+"#,
+                constant,
+                code,
+                helper.join(" "),
+                main.join(" ")
+            )
+        })
+}
+
+/// Compiles a source without touching the filesystem
+fn compile(source: String) {
+    // Reporting an error must not panic either
+    if let Err(e) = compiler().gen_artifact(file("./Fuzz.huff", &source, None)) {
+        let _ = e.to_string();
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn compiling_token_soup_never_panics(fragments in prop::collection::vec(fragment(), 0..40)) {
+        compile(fragments.join(" "));
+    }
+
+    #[test]
+    fn compiling_macro_bodies_never_panics(source in contract()) {
+        compile(source);
+    }
+
+    #[test]
+    fn compiling_valid_contracts_never_panics(source in valid_contract()) {
+        compile(source);
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use huff_core::Compiler;
 use huff_utils::prelude::*;
@@ -89,9 +89,97 @@ fn test_missing_constructor_with_inputs() {
                 CompilerError::CodegenError(CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: Some(arc_source) }]),
-                    token: None
+                    token: None,
+                    expansion: vec![],
                 })
             )
         }
     }
 }
+
+#[test]
+fn test_typed_constructor_args() {
+    let source = r#"
+    #define function CONSTRUCTOR(address owner, bytes4 tag) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {}
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+    let full_source = Arc::new(FileSource {
+        source: Some(source.to_string()),
+        id: uuid::Uuid::new_v4(),
+        path: "".to_string(),
+        access: None,
+        dependencies: None,
+    });
+    let compile = |args: Vec<&str>| {
+        let args = args.into_iter().map(String::from).collect();
+        Compiler::new(Arc::new(vec![]), None, Some(args), None, false, false)
+            .gen_artifact(Arc::clone(&full_source))
+    };
+
+    // Arguments are parsed as the declared types, so bytes4 is left aligned
+    let owner = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    let artifact = compile(vec![owner, "0xdeadbeef"]).unwrap();
+    assert!(artifact.bytecode.ends_with(&format!(
+        "{}{}deadbeef{}",
+        "00".repeat(12),
+        &owner[2..],
+        "00".repeat(28)
+    )));
+
+    // Building without any arguments still leaves them out
+    let bare = compile(vec![]).unwrap();
+    assert_eq!(artifact.bytecode.len(), bare.bytecode.len() + 128);
+    assert!(artifact.bytecode.starts_with(&bare.bytecode));
+
+    let invalid = |args: Vec<&str>| match compile(args) {
+        Err(CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(msg),
+            ..
+        })) => msg,
+        _ => panic!("expected invalid constructor arguments"),
+    };
+    assert_eq!(invalid(vec![owner]), "Expected 2 constructor arguments, found 1");
+    assert_eq!(
+        invalid(vec![owner, "0xdead"]),
+        "Invalid constructor argument 1 of type bytes4: \"0xdead\" is 2 bytes long, expected 4 \
+         for bytes4"
+    );
+}
+
+#[test]
+fn test_method_identifiers() {
+    let source = r#"
+    #define function CONSTRUCTOR() nonpayable returns ()
+    #define function transfer(address,uint256) nonpayable returns ()
+    #define function balanceOf(address) view returns (uint256)
+
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+    let full_source =
+        Arc::new(FileSource { source: Some(source.to_string()), ..Default::default() });
+    let compiler = Compiler::new(Arc::new(vec![]), None, None, None, false, false);
+    let artifact = compiler.gen_artifact(full_source).unwrap();
+
+    // The constructor has no selector
+    assert_eq!(
+        artifact.method_identifiers,
+        BTreeMap::from([
+            ("balanceOf(address)".to_string(), "70a08231".to_string()),
+            ("transfer(address,uint256)".to_string(), "a9059cbb".to_string()),
+        ])
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&JsonSerializer::default().serialize(&artifact).unwrap()).unwrap();
+    assert_eq!(json["methodIdentifiers"]["transfer(address,uint256)"], "a9059cbb");
+
+    // Contracts without functions leave it out
+    let source = "#define macro MAIN() = takes(0) returns (0) {}";
+    let full_source =
+        Arc::new(FileSource { source: Some(source.to_string()), ..Default::default() });
+    let artifact = compiler.gen_artifact(full_source).unwrap();
+    let json = JsonSerializer::default().serialize(&artifact).unwrap();
+    assert!(!json.contains("methodIdentifiers"));
+}
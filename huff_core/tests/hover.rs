@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use huff_core::{hover::*, index::SymbolKind};
+use huff_utils::prelude::*;
+
+#[test]
+fn test_hover() {
+    let utils = "/// The owner slot\n#define constant OWNER_SLOT = FREE_STORAGE_POINTER()\n#define constant BALANCE_SLOT = FREE_STORAGE_POINTER()\n#define constant VALUE = 0x2a\n";
+    let main = "#define function transfer(address,uint256) nonpayable returns ()\n#define event Transfer(address,address,uint256)\n/// Loads the owner\n#define macro GET_OWNER() = takes(0) returns(1) {\n    [OWNER_SLOT] sload\n}\n#define macro MAIN() = takes(0) returns(0) {\n    [BALANCE_SLOT] GET_OWNER() __FUNC_SIG(transfer) __EVENT_HASH(Transfer) [VALUE]\n    done:\n}\n";
+    let utils_file = Arc::new(FileSource {
+        path: "Utils.huff".to_string(),
+        source: Some(utils.to_string()),
+        ..Default::default()
+    });
+    let main_file = Arc::new(FileSource {
+        path: "Main.huff".to_string(),
+        source: Some(main.to_string()),
+        dependencies: Some(vec![utils_file]),
+        ..Default::default()
+    });
+    let files = vec![main_file];
+    let hover = |file: &str, source: &str, at: &str| {
+        Hover::at(&files, &[], file, source.find(at).unwrap() + 1).unwrap()
+    };
+
+    // Free storage pointers resolve to the slot assigned when compiling the main file
+    let owner = hover("Main.huff", main, "OWNER_SLOT]");
+    assert_eq!(owner.kind, SymbolKind::Constant);
+    assert_eq!(owner.range.file, "Main.huff");
+    assert_eq!(owner.docs, "The owner slot");
+    assert_eq!(owner.signature, "#define constant OWNER_SLOT = FREE_STORAGE_POINTER()");
+    assert_eq!(
+        owner.info,
+        Some(HoverInfo::Constant { value: Some("0x01".to_string()), slot: Some(1) })
+    );
+    let balance = hover("Main.huff", main, "BALANCE_SLOT]");
+    assert_eq!(
+        balance.info,
+        Some(HoverInfo::Constant { value: Some("0x00".to_string()), slot: Some(0) })
+    );
+    let value = hover("Main.huff", main, "VALUE]");
+    assert_eq!(
+        value.info,
+        Some(HoverInfo::Constant { value: Some("0x2a".to_string()), slot: None })
+    );
+
+    // Function selectors and event topics
+    let transfer = hover("Main.huff", main, "transfer)");
+    assert_eq!(
+        transfer.signature,
+        "#define function transfer(address, uint256) nonpayable returns ()"
+    );
+    assert_eq!(transfer.info, Some(HoverInfo::Function { selector: "0xa9059cbb".to_string() }));
+    let event = hover("Main.huff", main, "Transfer)");
+    assert_eq!(
+        event.info,
+        Some(HoverInfo::Event {
+            topic: "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string()
+        })
+    );
+
+    // Macro stack effects with estimated size and gas
+    let get_owner = hover("Main.huff", main, "GET_OWNER()");
+    assert_eq!(get_owner.docs, "Loads the owner");
+    assert_eq!(
+        get_owner.info,
+        Some(HoverInfo::Macro { takes: 0, returns: 1, size: Some(3), gas: Some(103) })
+    );
+    let md = get_owner.to_markdown();
+    assert!(md.contains("Size: `3` bytes"));
+    assert!(md.contains("Static Gas: `103`"));
+
+    // Labels have no computed values, and offsets outside of symbols have no hover
+    let label = hover("Main.huff", main, "done:");
+    assert_eq!((label.kind, label.info), (SymbolKind::Label, None));
+    assert_eq!(Hover::at(&files, &[], "Main.huff", main.find("sload").unwrap()), None);
+}
@@ -0,0 +1,124 @@
+use huff_core::{simulation::*, Compiler};
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compiler, file};
+
+const SOURCE: &str = r#"
+    #define immutable OWNER
+    #define immutable LIMIT
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        caller __SET_IMMUTABLE(OWNER)
+        0x2a __SET_IMMUTABLE(LIMIT)
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __IMMUTABLE(LIMIT) __IMMUTABLE(OWNER) add
+        0x00 mstore 0x20 0x00 return
+    }
+"#;
+
+fn compile(source: &str) -> Result<Artifact, CompilerError<'static>> {
+    Compiler { simulate: true, ..compiler() }.gen_artifact(file("", source, None))
+}
+
+#[test]
+fn test_immutable_placeholders() {
+    let compiler = Compiler { simulate: true, ..compiler() };
+    let artifact = compiler.gen_artifact(file("", SOURCE, None)).unwrap();
+    let placeholder = format!("7f{}", "00".repeat(32));
+    assert!(artifact.runtime.starts_with(&format!("{}{}01", placeholder, placeholder)));
+    assert_eq!(artifact.immutable_references.get("LIMIT"), Some(&vec![1]));
+    assert_eq!(artifact.immutable_references.get("OWNER"), Some(&vec![34]));
+
+    // The patched runtime only differs from the generated one in the placeholders
+    let simulation = artifact.simulation.as_ref().unwrap();
+    assert_eq!(&simulation.runtime[2..66], format!("{:064x}", 0x2a));
+    assert_eq!(simulation.runtime[134..], artifact.runtime[134..]);
+    assert!(compiler.warnings.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_read_immutables() {
+    // Simulated deployments are sent from the SIMULATION_SENDER, 0x00..01
+    let artifact = compile(SOURCE).unwrap();
+    assert_eq!(format!("{:?}", SIMULATION_SENDER), format!("0x{:040x}", 1));
+    assert_eq!(
+        simulate_call(&artifact, &[], 0, &Prestate::default()).unwrap().outcome,
+        TraceOutcome::Returned(format!("{:064x}", 1 + 0x2a))
+    );
+}
+
+#[test]
+fn test_immutables_survive_scratch_space() {
+    // Constructor arguments are copied into scratch space, below the staged immutables
+    let source = r#"
+    #define function CONSTRUCTOR(uint256) nonpayable returns ()
+    #define immutable LIMIT
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        __CONSTRUCTOR_ARG(0) __SET_IMMUTABLE(LIMIT)
+        __CONSTRUCTOR_ARG(0) 0x01 add 0x00 mstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __IMMUTABLE(LIMIT) 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+    let compiler =
+        Compiler { simulate: true, construct_args: Some(vec!["7".to_string()]), ..compiler() };
+    let artifact = compiler.gen_artifact(file("", source, None)).unwrap();
+    assert_eq!(
+        simulate_call(&artifact, &[], 0, &Prestate::default()).unwrap().outcome,
+        TraceOutcome::Returned(format!("{:064x}", 7))
+    );
+}
+
+#[test]
+fn test_immutable_read_in_constructor() {
+    let source = r#"
+    #define immutable OWNER
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        caller __SET_IMMUTABLE(OWNER)
+        __IMMUTABLE(OWNER) pop
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+    match compile(source).unwrap_err() {
+        CompilerError::CodegenError(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::ImmutableReadInConstructor("OWNER".to_string()))
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_missing_immutable() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        __IMMUTABLE(OWNER) pop
+    }
+    "#;
+    match compile(source).unwrap_err() {
+        CompilerError::CodegenError(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::MissingImmutableDefinition("OWNER".to_string()))
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_immutables_with_custom_bootstrap() {
+    let compiler =
+        Compiler { bootstrap: Some("{size}80{offset}3d393df3".to_string()), ..compiler() };
+    match compiler.gen_artifact(file("", SOURCE, None)).unwrap_err() {
+        CompilerError::CodegenError(e) => {
+            assert!(matches!(e.kind, CodegenErrorKind::InvalidArguments(_)))
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+const STORE: &str = "#define macro STORE() = takes (1) returns (0) {\n    [SLOT] sstore\n}\n";
+
+fn compile(file: Arc<FileSource>) -> Result<Artifact, CompilerError<'static>> {
+    compiler().gen_artifact(file)
+}
+
+#[test]
+fn test_included_definitions() {
+    let lib = format!("#define constant SLOT = 0x01\n{}", STORE);
+    let main = "#define macro MAIN() = takes (0) returns (0) {\n    caller STORE()\n}\n";
+    let artifact = compile(file("Main.huff", main, Some(vec![file("Lib.huff", &lib, None)])));
+    assert_eq!(artifact.unwrap().runtime, "33600155");
+}
+
+#[test]
+fn test_diamond_includes() {
+    // Both dependencies include the same file, which is only flattened once
+    let shared = file("Common.huff", "#define constant SLOT = 0x01\n", None);
+    let a = file("A.huff", STORE, Some(vec![Arc::clone(&shared)]));
+    let load = "#define macro LOAD() = takes (0) returns (1) {\n    [SLOT] sload\n}\n";
+    let b = file("B.huff", load, Some(vec![shared]));
+    let main = "#define macro MAIN() = takes (0) returns (0) {\n    LOAD() STORE()\n}\n";
+    let artifact = compile(file("Main.huff", main, Some(vec![a, b])));
+    assert_eq!(artifact.unwrap().runtime, "600154600155");
+}
+
+#[test]
+fn test_shadowed_definitions() {
+    let lib = format!("#define constant SLOT = 0x01\n{}", STORE);
+    let main = format!(
+        "#define constant SLOT = 0x02\n{}",
+        "#define macro MAIN() = takes (0) returns (0) {\n    caller STORE()\n}\n"
+    );
+    let artifact = compile(file("Main.huff", &main, Some(vec![file("Lib.huff", &lib, None)])));
+    assert_eq!(artifact.unwrap().runtime, "33600255");
+}
+
+#[test]
+fn test_conflicting_definitions() {
+    let a = file("A.huff", &format!("#define constant SLOT = 0x01\n{}", STORE), None);
+    let b = file("B.huff", STORE, None);
+    let main = "#define macro MAIN() = takes (0) returns (0) {\n    caller STORE()\n}\n";
+    let err = match compile(file("Main.huff", main, Some(vec![a, b]))) {
+        Err(CompilerError::CodegenError(e)) => e,
+        res => panic!("expected a codegen error, got {:?}", res),
+    };
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::DuplicateDefinition("macro".to_string(), "STORE".to_string())
+    );
+
+    // Both definition sites are named
+    let sites = err
+        .span
+        .0
+        .iter()
+        .map(|s| (s.file.as_ref().unwrap().path.as_str(), s.start, s.end))
+        .collect::<Vec<(&str, usize, usize)>>();
+    assert_eq!(sites, vec![("A.huff", 43, 48), ("B.huff", 14, 19)]);
+
+    // Definitions repeated in the same file conflict too
+    let main = "#define constant SLOT = 0x01\n#define constant SLOT = 0x02\n";
+    let err = match compile(file("Main.huff", main, None)) {
+        Err(CompilerError::CodegenError(e)) => e,
+        res => panic!("expected a codegen error, got {:?}", res),
+    };
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::DuplicateDefinition("constant".to_string(), "SLOT".to_string())
+    );
+    assert_eq!(err.span.0.len(), 2);
+}
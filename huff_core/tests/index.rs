@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use huff_core::{index::*, Compiler};
+use huff_utils::prelude::*;
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_symbol_index_across_imports() {
+    let root = std::env::temp_dir().join(format!("huff-index-{}", uuid::Uuid::new_v4()));
+    let utils = "#define constant OWNER = 0x01\n#define macro HELPER(dest) = takes(0) returns(0) {\n    <dest> jumpi\n}\n";
+    let main = "#include \"./Utils.huff\"\n#define function transfer(address,uint256) nonpayable returns ()\n#define macro MAIN() = takes(0) returns(0) {\n    [OWNER] __FUNC_SIG(transfer)\n    HELPER(done)\n    done:\n        [OWNER] done jump\n}\n";
+    write(&root.join("Utils.huff"), utils);
+    write(&root.join("Main.huff"), main);
+
+    let main_path = root.join("Main.huff").to_string_lossy().to_string();
+    let utils_path = root.join("Utils.huff").to_string_lossy().to_string();
+    let file = Compiler::fetch_sources(vec![root.join("Main.huff")]).remove(0).unwrap();
+    let files = vec![Compiler::recurse_deps(file).unwrap()];
+    let index = SymbolIndex::build(&files, &Remapping::project()).unwrap();
+
+    // Constants are defined in the imported file and referenced from the main file
+    let owner = index.find(SymbolKind::Constant, "OWNER").unwrap();
+    let owner_start = utils.find("OWNER").unwrap();
+    assert_eq!(
+        owner.definition,
+        Location { file: utils_path.clone(), start: owner_start, end: owner_start + 5 }
+    );
+    assert_eq!(owner.references.len(), 2);
+    assert!(owner.references.iter().all(|r| r.file == main_path));
+
+    // Go to definition from a reference
+    let reference = main.find("[OWNER]").unwrap() + 1;
+    assert_eq!(index.definition_at(&main_path, reference), Some(&owner.definition));
+
+    // Macros, functions, and builtin arguments
+    let helper = index.find(SymbolKind::Macro, "HELPER").unwrap();
+    assert_eq!(helper.definition.file, utils_path);
+    assert_eq!(helper.references.len(), 1);
+    let transfer = index.find(SymbolKind::Function, "transfer").unwrap();
+    assert_eq!(transfer.references.len(), 1);
+    let sig = main.find("(transfer)").unwrap() + 1;
+    assert_eq!(transfer.references[0].start, sig);
+
+    // Labels and parameters are scoped to their macro
+    let label = index.symbol_at(&main_path, main.find("done:").unwrap()).unwrap();
+    assert_eq!(label.kind, SymbolKind::Label);
+    assert_eq!(label.scope, Some("MAIN".to_string()));
+    assert_eq!(label.references.len(), 2);
+    let dest = index.symbol_at(&utils_path, utils.find("<dest>").unwrap() + 1).unwrap();
+    assert_eq!(dest.kind, SymbolKind::Parameter);
+    assert_eq!(dest.scope, Some("HELPER".to_string()));
+    assert_eq!(dest.references.len(), 1);
+    assert!(index.find(SymbolKind::Label, "done").is_none());
+
+    // Offsets outside of any symbol resolve to nothing
+    assert!(index.symbol_at(&main_path, 0).is_none());
+    assert!(index.to_json().unwrap().contains("\"kind\": \"constant\""));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_rename_across_files() {
+    let root = std::env::temp_dir().join(format!("huff-index-{}", uuid::Uuid::new_v4()));
+    let utils = "#define macro HELPER(dest) = takes(0) returns(0) {\n    <dest> jumpi\n}\n#define macro OTHER() = takes(0) returns(0) {}\n";
+    let main = "#include \"./Utils.huff\"\n#define macro MAIN() = takes(0) returns(0) {\n    HELPER(done) __codesize(HELPER)\n    done:\n        HELPER(done)\n}\n";
+    write(&root.join("Utils.huff"), utils);
+    write(&root.join("Main.huff"), main);
+
+    let main_path = root.join("Main.huff").to_string_lossy().to_string();
+    let utils_path = root.join("Utils.huff").to_string_lossy().to_string();
+    let file = Compiler::fetch_sources(vec![root.join("Main.huff")]).remove(0).unwrap();
+    let files = vec![Compiler::recurse_deps(file).unwrap()];
+    let index = SymbolIndex::build(&files, &Remapping::project()).unwrap();
+
+    // Renaming a macro from an invocation edits its definition and every reference
+    let edits = index.rename(&main_path, main.find("HELPER").unwrap(), "ASSIST").unwrap();
+    assert_eq!(edits.len(), 4);
+    assert_eq!(apply_edits(&main_path, main, &edits), main.replace("HELPER", "ASSIST"));
+    assert_eq!(
+        apply_edits(&utils_path, utils, &edits),
+        utils.replace("HELPER(dest)", "ASSIST(dest)")
+    );
+
+    // Renaming a label includes its uses as macro arguments
+    let edits = index.rename(&main_path, main.find("done:").unwrap(), "end").unwrap();
+    assert_eq!(edits.len(), 3);
+    assert_eq!(apply_edits(&main_path, main, &edits), main.replace("done", "end"));
+
+    // Renaming a parameter stays within its macro
+    let edits = index.rename(&utils_path, utils.find("dest").unwrap(), "target").unwrap();
+    assert_eq!(apply_edits(&utils_path, utils, &edits), utils.replace("dest", "target"));
+
+    assert_eq!(
+        index.rename(&main_path, 0, "ASSIST"),
+        Err(RenameError::NoSymbol(main_path.clone(), 0))
+    );
+    let helper = main.find("HELPER").unwrap();
+    assert_eq!(
+        index.rename(&main_path, helper, "1abc"),
+        Err(RenameError::InvalidName("1abc".to_string()))
+    );
+    for reserved in ["mstore", "takes", "jumptable__packed", "__FUNC_SIG"] {
+        assert_eq!(
+            index.rename(&main_path, helper, reserved),
+            Err(RenameError::InvalidName(reserved.to_string()))
+        );
+    }
+    assert_eq!(
+        index.rename(&main_path, helper, "OTHER"),
+        Err(RenameError::NameConflict("OTHER".to_string()))
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Result<Artifact, CompilerError<'static>> {
+    compiler().gen_artifact(file("", source, None))
+}
+
+const SKIP: &str = r#"
+    #define macro SKIP() = takes (0) returns (0) {
+        skip jump
+        0x01
+        skip:
+    }
+"#;
+
+#[test]
+fn test_labels_scoped_to_invocation() {
+    let source = [SKIP, "#define macro MAIN() = takes (0) returns (0) { SKIP() SKIP() }"].concat();
+
+    // Each invocation jumps to its own label
+    let artifact = compile(&source).unwrap();
+    assert_eq!(artifact.runtime, "6100065660015b61000d5660015b");
+}
+
+#[test]
+fn test_labels_referenced_across_macros() {
+    let source = r#"
+    #define macro FAIL() = takes (0) returns (0) {
+        fail:
+            0x00 dup1 revert
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        fail jump
+        FAIL()
+    }
+    "#;
+
+    // A label defined by a single invocation is visible to the invoking macro
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "610004565b600080fd");
+}
+
+#[test]
+fn test_own_labels_shadow_invoked_labels() {
+    let source =
+        [SKIP, "#define macro MAIN() = takes (0) returns (0) { SKIP() skip jump skip: }"].concat();
+
+    let artifact = compile(&source).unwrap();
+    assert_eq!(artifact.runtime, "6100065660015b61000b565b");
+}
+
+#[test]
+fn test_ambiguous_labels_are_reported() {
+    let source =
+        [SKIP, "#define macro MAIN() = takes (0) returns (0) { SKIP() SKIP() skip jump }"].concat();
+
+    // The label is defined by both invocations, so the jump can't tell which one it targets
+    let err = compile(&source).unwrap_err();
+    let kind = match err {
+        CompilerError::CodegenError(CodegenError { kind, .. }) => kind,
+        e => panic!("unexpected error: {:?}", e),
+    };
+    assert_eq!(kind, CodegenErrorKind::AmbiguousJumpLabel("skip".to_string(), vec![6, 13]));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{compiler, file};
+use huff_codegen::config::CustomBuiltin;
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Artifact {
+    compiler().gen_artifact(file("", source, None)).unwrap()
+}
+
+#[test]
+fn test_macro_gas() {
+    let source = r#"
+    #define macro ADD_ONE() = takes (1) returns (1) {
+        0x01 add
+    }
+
+    #define macro STORE() = takes (1) returns (0) {
+        ADD_ONE() 0x00 sstore
+    }
+
+    #define fn DOUBLE() = takes (1) returns (1) {
+        dup1 add
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        0x00 calldataload STORE()
+        0x01 DOUBLE() pop
+    }
+    "#;
+
+    let gas = compile(source).macro_gas;
+    assert_eq!(gas["ADD_ONE"], MacroGas { gas: 6, dynamic: false });
+    // SSTORE costs more than its static gas, so the estimate is a lower bound
+    assert_eq!(gas["STORE"], MacroGas { gas: 9 + 100, dynamic: true });
+    assert_eq!(gas["DOUBLE"], MacroGas { gas: 6, dynamic: false });
+    // Fns are estimated as if inlined
+    assert_eq!(gas["MAIN"].gas, 6 + 109 + 3 + 6 + 2);
+    assert!(gas["MAIN"].dynamic);
+}
+
+#[test]
+fn test_macro_gas_uses_build_options() {
+    let source = r#"
+    #define macro ZERO() = takes (0) returns (1) {
+        __ZERO()
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        0x00 pop
+    }
+    "#;
+    let mut compiler = Compiler { evm_version: EvmVersion::Shanghai, ..compiler() };
+    compiler.register_builtin(CustomBuiltin::new("__ZERO", 0, |_| Ok([0u8; 32])).unwrap());
+    let gas = compiler.gen_artifact(file("", source, None)).unwrap().macro_gas;
+
+    // Zero literals are pushed with PUSH0 on shanghai, and macros using custom builtins compile
+    assert_eq!(gas["MAIN"], MacroGas { gas: 2 + 2, dynamic: false });
+    assert_eq!(gas["ZERO"], MacroGas { gas: 3, dynamic: false });
+}
+
+#[test]
+fn test_estimate_static_gas() {
+    // PUSH1 0x00 CALLDATALOAD PUSH32 0x5b.. MSTORE
+    let bytecode = format!("6000357f{}52", "5b".repeat(32));
+    assert_eq!(estimate_static_gas(&bytecode), (3 + 3 + 3 + 3, true));
+    assert_eq!(estimate_static_gas("6001600101"), (9, false));
+    assert!(has_dynamic_gas(0xf1) && !has_dynamic_gas(0x01));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::{compiler, file};
+use ethers_core::utils::{hex, keccak256};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define macro MAIN() = takes (0) returns (0) {
+    0x01 0x00 sstore
+}
+"#;
+
+fn compile(metadata: bool) -> Artifact {
+    let compiler = Compiler { metadata, ..compiler() };
+    compiler.gen_artifact(file("Store.huff", SOURCE, None)).unwrap()
+}
+
+#[test]
+fn test_metadata_trailer() {
+    // Off by default
+    let plain = compile(false);
+    assert_eq!(plain.runtime, "6001600055");
+    assert!(plain.metadata.is_none());
+
+    let artifact = compile(true);
+    let metadata = artifact.metadata.clone().unwrap();
+    assert_eq!(metadata.compiler, "huffc");
+    assert_eq!(metadata.version, COMPILER_VERSION);
+    assert_eq!(metadata.source_hash, format!("0x{}", hex::encode(keccak256(SOURCE))));
+
+    // The trailer follows the runtime, starting with a STOP and ending with the CBOR length
+    assert_eq!(artifact.runtime, format!("{}{}", plain.runtime, metadata.trailer));
+    assert!(artifact.bytecode.ends_with(&artifact.runtime));
+    let trailer = hex::decode(&metadata.trailer).unwrap();
+    let cbor_len = u16::from_be_bytes([trailer[trailer.len() - 2], trailer[trailer.len() - 1]]);
+    assert_eq!(trailer[0], 0x00);
+    assert_eq!(cbor_len as usize, trailer.len() - 3);
+
+    // {"huffc": version, "keccak256": source_hash}
+    let cbor = &trailer[1..trailer.len() - 2];
+    assert_eq!(&cbor[..7], &[0xa2, 0x65, b'h', b'u', b'f', b'f', b'c']);
+    assert_eq!(cbor[7], 0x43);
+    assert_eq!(&cbor[11..21], &[&[0x69][..], b"keccak256"].concat()[..]);
+    assert_eq!(&cbor[21..23], &[0x58, 0x20]);
+    assert_eq!(&cbor[23..], &keccak256(SOURCE)[..]);
+
+    // The trailer isn't mapped to the source
+    let source_map = artifact.source_map.unwrap();
+    let segments = source_map.runtime.split(';').collect::<Vec<&str>>();
+    assert_eq!(segments[3], "-1:-1:-1");
+    assert!(segments[4..].iter().all(|s| s.is_empty()));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::mutation::*;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define constant LIMIT = 0x0a
+
+#define macro CHECK_LIMIT() = takes (1) returns (1) {
+    dup1 [LIMIT] gt iszero fail jumpi
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x04 calldataload CHECK_LIMIT()
+    0x00 mstore
+    0x20 0x00 return
+    fail:
+        0x00 0x00 revert
+}
+"#;
+
+fn compile() -> Artifact {
+    compiler().gen_artifact(file("Limit.huff", SOURCE, None)).unwrap()
+}
+
+/// A case calling with the given word after a selector, which is returned unless it reverts
+fn case(word: u8, reverts: bool) -> CallCase {
+    let calldata = format!("0xdeadbeef{:064x}", word);
+    let output = if reverts { String::new() } else { format!("{:064x}", word) };
+    CallCase { calldata, output: Some(output), reverts, ..Default::default() }
+}
+
+#[test]
+fn test_mutants() {
+    let artifact = compile();
+    let mutants = mutants(&artifact);
+    let described =
+        mutants.iter().map(|m| format!("{} -> {}", m.original, m.mutated)).collect::<Vec<_>>();
+
+    // The jump destination isn't mutated
+    assert_eq!(
+        described,
+        vec![
+            "PUSH1 0x04 -> PUSH1 0x05",
+            "PUSH1 0x04 -> PUSH1 0x03",
+            "PUSH1 0x0a -> PUSH1 0x0b",
+            "PUSH1 0x0a -> PUSH1 0x09",
+            "GT -> LT",
+            "PUSH1 0x00 -> PUSH1 0x01",
+            "PUSH1 0x20 -> PUSH1 0x21",
+            "PUSH1 0x20 -> PUSH1 0x1f",
+            "PUSH1 0x00 -> PUSH1 0x01",
+            "PUSH1 0x00 -> PUSH1 0x01",
+            "PUSH1 0x00 -> PUSH1 0x01",
+        ]
+    );
+    let gt = &mutants[4];
+    assert_eq!(gt.location.definition, Some("CHECK_LIMIT".to_string()));
+    assert_eq!(gt.location.line, 4);
+
+    // Mutations apply to both the runtime and the bytecode deploying it
+    let mutated = gt.apply(&artifact);
+    assert_ne!(mutated.runtime, artifact.runtime);
+    assert!(mutated.bytecode.contains(&mutated.runtime));
+}
+
+#[test]
+fn test_mutation_score() {
+    let artifact = compile();
+
+    // Without the boundary, the off by one limits survive
+    let report = mutation_test(&artifact, &[case(5, false), case(20, true)]).unwrap();
+    assert_eq!(report.mutants, 11);
+    let survivors = report.survivors.iter().map(|m| m.pc).collect::<Vec<usize>>();
+    assert_eq!(survivors, vec![4, 4, 23]);
+    assert!(report.to_string().starts_with("8/11 mutants killed (72.73%)\n"));
+
+    // Testing either side of it kills them, leaving the revert offset, which doesn't matter when
+    // reverting without data
+    let cases = [case(5, false), case(9, false), case(10, true), case(20, true)];
+    let report = mutation_test(&artifact, &cases).unwrap();
+    assert_eq!(report.survivors.len(), 1);
+    assert_eq!(
+        report.survivors[0].to_string(),
+        "   23 PUSH1 0x00 -> PUSH1 0x01  MAIN Limit.huff:12 | 0x00 0x00 revert"
+    );
+}
+
+#[test]
+fn test_failing_cases() {
+    let artifact = compile();
+    assert_eq!(
+        mutation_test(&artifact, &[case(5, false), case(20, false)]).unwrap_err(),
+        "Case 1 fails against the unmutated \"Limit.huff\""
+    );
+    assert!(mutation_test(&artifact, &[]).is_err());
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compiler, file};
+
+const SOURCE: &str = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        0x20 0x00 sha3
+        difficulty
+    }
+"#;
+
+fn compile(allow_opcode_aliases: bool) -> (Artifact, Vec<CompilerWarning>) {
+    let compiler = Compiler { allow_opcode_aliases, ..compiler() };
+    let artifact = compiler.gen_artifact(file("Legacy.huff", SOURCE, None)).unwrap();
+    let warnings = compiler.warnings.lock().unwrap().clone();
+    (artifact, warnings)
+}
+
+#[test]
+fn test_opcode_alias_warnings() {
+    let (artifact, warnings) = compile(false);
+    assert_eq!(artifact.runtime, "602060002044");
+    assert_eq!(
+        warnings,
+        vec![
+            CompilerWarning {
+                file: "Legacy.huff".to_string(),
+                kind: CompilerWarningKind::OpcodeAlias("sha3".to_string(), "keccak256".to_string()),
+            },
+            CompilerWarning {
+                file: "Legacy.huff".to_string(),
+                kind: CompilerWarningKind::OpcodeAlias(
+                    "difficulty".to_string(),
+                    "prevrandao".to_string()
+                ),
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "Warning: \"sha3\" in \"Legacy.huff\" is a historical alias, use \"keccak256\" instead"
+    );
+}
+
+#[test]
+fn test_allow_opcode_aliases() {
+    let (artifact, warnings) = compile(true);
+    assert_eq!(artifact.runtime, "602060002044");
+    assert!(warnings.is_empty());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_opcode_names() {
+    assert_eq!(Opcode::Sha3, Opcode::Keccak256);
+    assert_eq!(Opcode::Difficulty, Opcode::Prevrandao);
+    assert_eq!(Opcode::from_str("Sha3"), Ok(Opcode::Keccak256));
+    assert_eq!(Opcode::from_str("Keccak256"), Ok(Opcode::Keccak256));
+    assert_eq!(Opcode::from_str("Difficulty"), Ok(Opcode::Prevrandao));
+    assert_eq!(OPCODES_MAP["sha3"], OPCODES_MAP["keccak256"]);
+    assert_eq!(OPCODES_MAP["difficulty"], OPCODES_MAP["prevrandao"]);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::str::FromStr;
+
+use common::{compiler, file};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define function get() view returns (uint256)
+
+    #define constant VALUE = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [VALUE] sload 0x00 mstore
+        0x20 0x00 return
+    }
+
+    #define test TEST_MAIN() = takes(0) returns (0) {
+        MAIN()
+    }
+"#;
+
+fn compile(selection: &[ArtifactOutput]) -> Artifact {
+    let compiler = Compiler { output_selection: selection.iter().copied().collect(), ..compiler() };
+    compiler.gen_artifact(file("", SOURCE, None)).unwrap()
+}
+
+#[test]
+fn test_default_output_selection() {
+    let artifact = compile(&ArtifactOutput::DEFAULT);
+    assert!(!artifact.runtime.is_empty());
+    assert!(artifact.abi.is_some());
+    assert!(!artifact.method_identifiers.is_empty());
+    assert!(artifact.source_map.is_some());
+    assert!(!artifact.storage_layout.storage.is_empty());
+    assert!(!artifact.macro_gas.is_empty());
+    assert!(artifact.stats.is_some());
+    assert!(!artifact.tests.is_empty());
+    assert!(artifact.asm.is_none());
+}
+
+#[test]
+fn test_abi_only() {
+    let artifact = compile(&[ArtifactOutput::Abi]);
+    assert!(artifact.abi.is_some());
+    assert!(artifact.bytecode.is_empty());
+    assert!(artifact.runtime.is_empty());
+    assert_eq!(artifact.sizes, BytecodeSizes::default());
+    assert!(artifact.method_identifiers.is_empty());
+    assert!(artifact.source_map.is_none());
+    assert!(artifact.storage_layout.storage.is_empty());
+    assert!(artifact.macro_gas.is_empty());
+    assert!(artifact.stats.is_none());
+    assert!(artifact.tests.is_empty());
+
+    // Skipped outputs are left out of the json entirely
+    let json = JsonSerializer::default().serialize(&artifact).unwrap();
+    for key in ["sourceMap", "macroGas", "stats", "tests", "asm"] {
+        assert!(!json.contains(&format!("\"{}\"", key)), "{}", key);
+    }
+}
+
+#[test]
+fn test_bytecode_and_asm() {
+    let artifact = compile(&[ArtifactOutput::Bytecode, ArtifactOutput::Asm]);
+    assert_eq!(artifact.runtime, "60005460005260206000f3");
+    assert!(artifact.abi.is_none());
+    assert!(artifact.asm.unwrap().contains("SLOAD"));
+}
+
+#[test]
+fn test_parse_artifact_outputs() {
+    assert_eq!(ArtifactOutput::from_str("sourceMap").unwrap(), ArtifactOutput::SourceMap);
+    assert_eq!(
+        ArtifactOutput::from_str("methodIdentifiers").unwrap(),
+        ArtifactOutput::MethodIdentifiers
+    );
+    assert!(ArtifactOutput::from_str("source_map").is_err());
+}
@@ -0,0 +1,91 @@
+use std::{path::Path, process::Command, str::FromStr};
+
+use huff_core::packages::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_parse_package_spec() {
+    let spec = PackageSpec::from_str("huff-language/huffmate@v0.0.2").unwrap();
+    assert_eq!(spec.owner, "huff-language");
+    assert_eq!(spec.repo, "huffmate");
+    assert_eq!(spec.tag, Some("v0.0.2".to_string()));
+    assert_eq!(spec.url(), "https://github.com/huff-language/huffmate");
+    assert_eq!(spec.to_string(), "huff-language/huffmate@v0.0.2");
+
+    assert_eq!(PackageSpec::from_str("huff-language/huffmate").unwrap().tag, None);
+    for invalid in [
+        "huffmate",
+        "huff-language/",
+        "/huffmate",
+        "a/b@",
+        "a/b/c",
+        "a/../b",
+        "../b",
+        "a/..",
+        "a/.",
+    ] {
+        assert_eq!(
+            PackageSpec::from_str(invalid),
+            Err(PackageError::InvalidSpec(invalid.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_install_vendors_package_and_writes_remappings() {
+    let root = std::env::temp_dir().join(format!("huff-packages-{}", uuid::Uuid::new_v4()));
+    let upstream = root.join("upstream");
+    std::fs::create_dir_all(upstream.join("src/auth")).unwrap();
+    std::fs::write(
+        upstream.join("src/auth/Owned.huff"),
+        "#define macro OWNED() = takes(0) returns(0) {}\n",
+    )
+    .unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=huff", "-c", "user.email=huff@huff.sh"])
+            .args(args)
+            .current_dir(&upstream)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "-A"]);
+    git(&["commit", "--quiet", "-m", "init"]);
+    git(&["tag", "v1.0.0"]);
+
+    let spec = PackageSpec::from_str("huff-language/huffmate@v1.0.0").unwrap();
+    let lib = root.join("lib");
+    let remapping = install_from(&spec, &upstream.to_string_lossy(), &lib).unwrap();
+
+    let vendored = lib.join("huffmate");
+    assert!(vendored.join("src/auth/Owned.huff").is_file());
+    assert!(!vendored.join(".git").exists());
+    assert_eq!(remapping.prefix, "huffmate/");
+    assert_eq!(remapping.path, format!("{}/", vendored.join("src").to_string_lossy()));
+
+    // Installing twice fails instead of overwriting the vendored copy
+    assert!(matches!(
+        install_from(&spec, &upstream.to_string_lossy(), &lib),
+        Err(PackageError::AlreadyInstalled(_))
+    ));
+
+    // Remappings are merged into an existing file, replacing the same prefix
+    let remappings_file = root.join("remappings.txt");
+    std::fs::write(&remappings_file, "huffmate/=old/\nsolmate/=lib/solmate/src/\n").unwrap();
+    write_remappings(&remappings_file, vec![remapping.clone()]).unwrap();
+    assert_eq!(
+        Remapping::read(&remappings_file),
+        vec![remapping, Remapping::from_str("solmate/=lib/solmate/src/").unwrap()]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_package_remapping_without_src() {
+    let remapping = package_remapping("lib", Path::new("lib/missing"));
+    assert_eq!(remapping.to_string(), "lib/=lib/missing/");
+}
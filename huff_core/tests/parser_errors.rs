@@ -80,7 +80,7 @@ fn test_unexpected_type() {
 
 #[test]
 fn test_invalid_definition() {
-    let source = "#define test func() returns ()";
+    let source = "#define unknown func() returns ()";
 
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source);
@@ -93,14 +93,14 @@ fn test_invalid_definition() {
             assert_eq!(
                 e,
                 ParserError {
-                    kind: ParserErrorKind::InvalidDefinition(TokenKind::Ident("test".to_string())),
+                    kind: ParserErrorKind::InvalidDefinition(TokenKind::Ident("unknown".to_string())),
                     hint: Some(
-                        "Definition must be one of: `function`, `event`, `constant`, `macro`, or `fn`."
+                        "Definition must be one of: `function`, `event`, `error`, `constant`, `immutable`, `macro`, `fn`, or `test`."
                             .to_string()
                     ),
                     spans: AstSpan(vec![Span {
-                        start: source.find("test").unwrap_or(0),
-                        end: source.find("test").unwrap_or(0) + "test".len(),
+                        start: source.find("unknown").unwrap_or(0),
+                        end: source.find("unknown").unwrap_or(0) + "unknown".len(),
                         file: None
                     }]),
                 }
@@ -112,11 +112,9 @@ fn test_invalid_definition() {
 #[test]
 fn test_invalid_constant_value() {
     let invalid_constant_values = vec![
-        ("ident", TokenKind::Ident("ident".to_string())),
         ("<", TokenKind::LeftAngle),
         ("{", TokenKind::OpenBrace),
         ("[", TokenKind::OpenBracket),
-        ("(", TokenKind::OpenParen),
         (":", TokenKind::Colon),
         (",", TokenKind::Comma),
         ("+", TokenKind::Add),
@@ -139,7 +137,7 @@ fn test_invalid_constant_value() {
                     ParserError {
                         kind: ParserErrorKind::InvalidConstantValue(kind),
                         hint: Some(
-                            "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                            "Expected constant value to be a literal, an expression, or `FREE_STORAGE_POINTER()`"
                                 .to_string()
                         ),
                         spans: AstSpan(vec![Span {
@@ -246,12 +244,13 @@ fn test_invalid_token_in_label_definition() {
 }
 
 #[test]
+#[allow(clippy::single_range_in_vec_init)]
 fn test_invalid_single_arg() {
     for _ in [0..10_000] {
         let random_char = rand::random::<u8>() as char;
         if random_char.is_numeric() ||
             !random_char.is_alphabetic() ||
-            random_char.to_string().as_bytes().len() > 1
+            random_char.to_string().len() > 1
         {
             continue
         }
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::Compiler;
+
+const SOURCE: &str = r#"#define constant SLOT = 0x0000
+#define constant DECIMAL = 1
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x0001 [SLOT] sstore
+    0x00ff [DECIMAL] add
+    0x000000 pop
+    label:
+        0x0002 jump
+}
+"#;
+
+fn compile(preserve_push_width: bool) -> String {
+    let compiler = Compiler { preserve_push_width, ..compiler() };
+    compiler.gen_artifact(file("Padded.huff", SOURCE, None)).unwrap().runtime
+}
+
+#[test]
+fn test_minimal_push_width() {
+    // Padded literals are pushed with the smallest PUSH instruction that fits them
+    assert_eq!(compile(false), "600160005560ff6001016000505b600256");
+}
+
+#[test]
+fn test_preserved_push_width() {
+    // Hex literals and constants keep their written width, decimal constants have none
+    assert_eq!(compile(true), "610001610000556100ff60010162000000505b61000256");
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use huff_core::{retained::*, Compiler};
+use huff_utils::prelude::*;
+
+fn artifact(path: &str, bytecode: &str, runtime: &str) -> Artifact {
+    Artifact {
+        file: Arc::new(FileSource { path: path.to_string(), ..Default::default() }),
+        bytecode: bytecode.to_string(),
+        runtime: runtime.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_build_settings_keys() {
+    let shanghai = BuildSettings { evm_version: EvmVersion::Shanghai, ..Default::default() };
+    let key = shanghai.key();
+    assert!(key.starts_with("shanghai-"));
+    assert!(is_build_key(&key));
+    assert_eq!(key, shanghai.clone().key());
+
+    // Any setting changing the output changes the key
+    let debug = BuildSettings { debug: true, ..shanghai.clone() };
+    let limited = BuildSettings { max_expansion_depth: Some(64), ..shanghai.clone() };
+    let overridden = BuildSettings {
+        constant_overrides: [("OWNER".to_string(), "0x01".to_string())].into(),
+        ..shanghai.clone()
+    };
+    for settings in [debug, limited, overridden] {
+        assert!(settings.key().starts_with("shanghai-"));
+        assert_ne!(settings.key(), key);
+    }
+
+    assert!(!is_build_key("CONTRACTS"));
+    assert!(!is_build_key("shanghai-optimized"));
+    assert!(!is_build_key("frontier-1f2e3d4c"));
+}
+
+#[test]
+fn test_compare_retained_builds() {
+    let london = BuildSettings { evm_version: EvmVersion::London, ..Default::default() };
+    let shanghai = BuildSettings { evm_version: EvmVersion::Shanghai, ..Default::default() };
+    let out_dir = std::env::temp_dir().join(format!("huff-retained-{}", uuid::Uuid::new_v4()));
+    let out = out_dir.to_string_lossy().to_string();
+    let serializers: [Arc<dyn Serializer>; 1] = [Arc::new(JsonSerializer::default())];
+
+    // Each build only replaces the artifacts of its own settings
+    let builds = [
+        (&london, "600580600a3d393df36000600055", "6000600055"),
+        (&shanghai, "600380600a3d393df35f5f55", "5f5f55"),
+    ];
+    for (settings, bytecode, runtime) in builds {
+        let artifacts = vec![
+            Arc::new(artifact("./contracts/A.huff", bytecode, runtime)),
+            Arc::new(artifact("./contracts/B.huff", "6001", "00")),
+        ];
+        let output = OutputLocation(build_dir(&out, settings));
+        Compiler::export_artifacts(&artifacts, &output, &serializers);
+    }
+    std::fs::create_dir_all(out_dir.join("CONTRACTS")).unwrap();
+
+    let retained = RetainedBuilds::load(&out_dir).unwrap();
+    let (london, shanghai) = (london.key(), shanghai.key());
+    assert_eq!(retained.builds.keys().collect::<Vec<_>>(), vec![&london, &shanghai]);
+
+    let comparisons = retained.compare(Some(&shanghai));
+    assert_eq!(comparisons.len(), 2);
+    assert_eq!(comparisons[0].contract, "./contracts/A.huff");
+    assert_eq!(
+        comparisons[0].builds,
+        vec![
+            BuildSizes { build: shanghai.clone(), runtime: 3, initcode: 12, same_runtime: true },
+            BuildSizes { build: london.clone(), runtime: 5, initcode: 14, same_runtime: false },
+        ]
+    );
+    assert!(comparisons[1].builds.iter().all(|b| b.same_runtime));
+    assert_eq!(
+        comparisons_to_text(&comparisons[..1]),
+        format!(
+            "./contracts/A.huff\n  {}  runtime 3 bytes, initcode 12 bytes\n  {}    runtime 5 bytes (+2), initcode 14 bytes (+2)\n",
+            shanghai, london
+        )
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&comparisons_to_json(&comparisons).unwrap()).unwrap();
+    assert_eq!(json[0]["builds"][1]["build"], london);
+
+    let _ = std::fs::remove_dir_all(out_dir);
+}
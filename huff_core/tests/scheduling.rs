@@ -0,0 +1,82 @@
+use huff_core::scheduling::*;
+
+fn stack(items: &[&str]) -> Vec<String> {
+    items.iter().map(|i| i.to_string()).collect()
+}
+
+#[test]
+fn test_parse_stack_comment() {
+    assert_eq!(parse_stack_comment("// [a, b]"), Some(stack(&["a", "b"])));
+    assert_eq!(parse_stack_comment("// []"), Some(vec![]));
+    assert_eq!(
+        parse_stack_comment("// [mem[0x00], f(x, y)] after the call"),
+        Some(stack(&["mem[0x00]", "f(x, y)"]))
+    );
+    assert_eq!(parse_stack_comment("// Returns the balance"), None);
+    assert_eq!(parse_stack_comment("// [unterminated"), None);
+}
+
+#[test]
+fn test_shortest_shuffle() {
+    let from = stack(&["a", "b", "c"]);
+    assert_eq!(shortest_shuffle(&from, &from, 0), Some(vec![]));
+    assert_eq!(shortest_shuffle(&from, &stack(&["b", "a", "c"]), 4), Some(vec![StackOp::Swap(1)]));
+    assert_eq!(
+        shortest_shuffle(&from, &stack(&["c", "a", "b", "c"]), 4),
+        Some(vec![StackOp::Dup(3)])
+    );
+    assert_eq!(
+        shortest_shuffle(&from, &stack(&["c", "b"]), 4),
+        Some(vec![StackOp::Pop, StackOp::Swap(1)])
+    );
+    // Too long for the limit, or producing an item that isn't on the stack
+    assert_eq!(shortest_shuffle(&from, &stack(&["c", "b"]), 1), None);
+    assert_eq!(shortest_shuffle(&from, &stack(&["d", "a", "b", "c"]), 4), None);
+
+    // Searches of deep stacks give up rather than exhausting memory
+    let items = (0..16).map(|i| i.to_string()).collect::<Vec<_>>();
+    let reversed = items.iter().rev().cloned().collect::<Vec<_>>();
+    assert_eq!(shortest_shuffle(&items, &reversed, MAX_SHUFFLE_LENGTH), None);
+}
+
+#[test]
+fn test_shuffle_hints() {
+    let source = r#"
+        #define macro SHUFFLE() = takes (2) returns (2) {
+            // [a, b]
+            dup1 swap1      // [a, a, b]
+            pop             // [a, b]
+            swap1           // [b, a]
+        }
+
+        #define macro WRONG_COMMENT() = takes (2) returns (2) {
+            // [a, b]
+            dup1 pop dup1   // [b, a]
+        }
+
+        #define macro RESET() = takes (1) returns (2) {
+            // [a]
+            0x01 dup1 pop   // [0x01, a]
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            // [x, y]
+            dup1 pop swap1  // [y, x]
+        }
+    "#;
+    let hints = shuffle_hints(source);
+    assert_eq!(hints.len(), 2);
+
+    // Each run between adjacent comments is checked on its own
+    assert_eq!(hints[0].macro_name, "SHUFFLE");
+    assert_eq!(hints[0].line, 4);
+    assert_eq!(hints[0].written, vec![StackOp::Dup(1), StackOp::Swap(1)]);
+    assert_eq!(hints[0].suggested, vec![StackOp::Dup(1)]);
+
+    assert_eq!(hints[1].macro_name, "MAIN");
+    assert_eq!(hints[1].suggested, vec![StackOp::Swap(1)]);
+    assert_eq!(
+        hints[1].to_string(),
+        "line 21 in MAIN: [x, y] -> [y, x] with \"swap1\" instead of \"dup1 pop swap1\""
+    );
+}
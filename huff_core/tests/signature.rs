@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use huff_core::signature::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_signature_help() {
+    let utils = "/// Stores a value at an offset\n#define macro STORE(value, offset) = takes(1) returns(0) {\n    <value> <offset> mstore\n}\n";
+    let main = "#define macro MAIN() = takes(0) returns(0) {\n    STORE(0x01, 0x20) STORE(0x02, \n";
+    let utils_file = Arc::new(FileSource {
+        path: "Utils.huff".to_string(),
+        source: Some(utils.to_string()),
+        ..Default::default()
+    });
+    let main_file = Arc::new(FileSource {
+        path: "Main.huff".to_string(),
+        source: Some(main.to_string()),
+        dependencies: Some(vec![utils_file]),
+        ..Default::default()
+    });
+    let files = vec![main_file];
+    let help = |offset| SignatureHelp::at(&files, &[], "Main.huff", offset);
+
+    // The incomplete main file is skipped when resolving the definition
+    let first = main.find("0x01").unwrap();
+    let expected = SignatureHelp {
+        name: "STORE".to_string(),
+        label: "#define macro STORE(value, offset) = takes (1) returns (0)".to_string(),
+        parameters: vec!["value".to_string(), "offset".to_string()],
+        takes: 1,
+        returns: 0,
+        docs: "Stores a value at an offset".to_string(),
+        active_parameter: 0,
+    };
+    assert_eq!(help(first), Some(expected.clone()));
+    assert_eq!(
+        help(main.find("0x20").unwrap()),
+        Some(SignatureHelp { active_parameter: 1, ..expected.clone() })
+    );
+    assert_eq!(help(main.len()), Some(SignatureHelp { active_parameter: 1, ..expected }));
+
+    // Outside of invocations, in a closed invocation, or in a definition's parameters
+    assert_eq!(help(main.find("STORE").unwrap()), None);
+    assert_eq!(help(main.find("0x20)").unwrap() + 5), None);
+    assert_eq!(help(main.find("MAIN(").unwrap() + 5), None);
+    assert_eq!(SignatureHelp::at(&files, &[], "Missing.huff", 0), None);
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::{simulation::*, Compiler};
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Result<Artifact, CompilerError<'static>> {
+    Compiler { simulate: true, ..compiler() }.gen_artifact(file("", source, None))
+}
+
+#[test]
+fn test_simulate_constructor() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        0x42 [OWNER] sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER] sload 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    let simulation = artifact.simulation.unwrap();
+    assert_eq!(simulation.runtime, artifact.runtime);
+    assert_eq!(simulation.storage.len(), 1);
+    assert_eq!(simulation.storage.get(&format!("0x{:064x}", 0)), Some(&"0x42".to_string()));
+    assert!(simulation.gas_used > 0);
+}
+
+#[test]
+fn test_simulate_reverting_constructor() {
+    let source = r#"
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        0x00 0x00 revert
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 0x00 return
+    }
+    "#;
+
+    assert_eq!(
+        compile(source).unwrap_err(),
+        CompilerError::SimulationError(SimulationError {
+            kind: SimulationErrorKind::Reverted("".to_string()),
+            file: "".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_simulate_custom_bootstrap() {
+    let source = r#"
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        0x42 0x00 sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 sload 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    // The runtime is still deployed past a callvalue check
+    let bootstrap = "341558600801573d3dfd5b{size}80{offset}3d393df3".to_string();
+    let compiler = Compiler { simulate: true, bootstrap: Some(bootstrap), ..compiler() };
+    let artifact = compiler.gen_artifact(file("", source, None)).unwrap();
+    assert!(artifact.bytecode.contains("341558600801573d3dfd5b"));
+    assert_eq!(artifact.simulation.unwrap().runtime, artifact.runtime);
+}
+
+#[test]
+fn test_simulate_bootstrap_with_args() {
+    let source = r#"
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {}
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 sload 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    // The bootstrap stores the constructor argument copied from the end of the code
+    let bootstrap = "{args_size}{args_offset}3d393d513d55{size}80{offset}3d393df3".to_string();
+    let compiler = Compiler {
+        construct_args: Some(vec!["5".to_string()]),
+        simulate: true,
+        bootstrap: Some(bootstrap),
+        ..compiler()
+    };
+    let simulation = compiler.gen_artifact(file("", source, None)).unwrap().simulation.unwrap();
+    assert_eq!(simulation.storage.get(&format!("0x{:064x}", 0)), Some(&"0x5".to_string()));
+}
+
+#[test]
+fn test_simulate_constructor_args() {
+    let source = r#"
+    #define function CONSTRUCTOR(uint256, address) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        __CONSTRUCTOR_ARG(0) 0x00 sstore
+        __CONSTRUCTOR_ARG(1) 0x01 sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+
+    let args = vec!["5".to_string(), format!("0x{:040x}", 0xaa)];
+    let word_args = Compiler { construct_args: Some(args), simulate: true, ..compiler() };
+    let simulation = word_args.gen_artifact(file("", source, None)).unwrap().simulation.unwrap();
+    assert_eq!(simulation.storage.get(&format!("0x{:064x}", 0)), Some(&"0x5".to_string()));
+    assert_eq!(simulation.storage.get(&format!("0x{:064x}", 1)), Some(&"0xaa".to_string()));
+
+    // A string's encoding moves the arguments before it back from the end of the code
+    let source = r#"
+    #define function CONSTRUCTOR(uint256, string) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        __CONSTRUCTOR_ARG(0) 0x00 sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+    let args = vec!["5".to_string(), "hi".to_string()];
+    let string_args = Compiler { construct_args: Some(args), simulate: true, ..compiler() };
+    let err = string_args.gen_artifact(file("", source, None)).unwrap_err();
+    assert!(matches!(
+        err,
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::InvalidArguments(_),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_simulate_call_trace() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        0x42 [OWNER] sstore
+    }
+
+    #define macro GET_OWNER() = takes(0) returns (1) {
+        [OWNER] sload
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        GET_OWNER() 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    let trace = simulate_call(&artifact, &[], 0, &Prestate::default()).unwrap();
+    assert_eq!(trace.outcome, TraceOutcome::Returned(format!("{:064x}", 0x42)));
+    assert!(trace.gas_used > 21000);
+
+    let opcodes = trace.steps.iter().map(|s| s.opcode.as_str()).collect::<Vec<&str>>();
+    assert_eq!(opcodes, vec!["PUSH1", "SLOAD", "PUSH1", "MSTORE", "PUSH1", "PUSH1", "RETURN"]);
+    assert!(trace.steps.iter().all(|s| s.depth == 1 && s.address == trace.address));
+
+    // Instructions are located in the macro they were written in
+    let sload = trace.steps[1].location.clone().unwrap();
+    assert_eq!(sload.definition, Some("GET_OWNER".to_string()));
+    assert_eq!(sload.line, 9);
+    assert_eq!(sload.code, "[OWNER] sload");
+    let mstore = trace.steps[3].location.clone().unwrap();
+    assert_eq!(mstore.definition, Some("MAIN".to_string()));
+    assert_eq!(mstore.line, 13);
+
+    let printed = trace.to_string();
+    assert!(printed.contains("SLOAD"));
+    assert!(printed.contains("GET_OWNER :9 | [OWNER] sload"));
+    assert!(printed.ends_with(&format!("Returned 0x{:064x} using {} gas\n", 0x42, trace.gas_used)));
+}
+
+#[test]
+fn test_simulate_reverting_call() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        callvalue iszero success jumpi
+        0x00 0x00 revert
+        success:
+            stop
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(
+        simulate_call(&artifact, &[], 0, &Prestate::default()).unwrap().outcome,
+        TraceOutcome::Returned("".to_string())
+    );
+    let trace = simulate_call(&artifact, &[], 1, &Prestate::default()).unwrap();
+    assert_eq!(trace.outcome, TraceOutcome::Reverted("".to_string()));
+    let revert = trace.steps.last().unwrap();
+    assert_eq!(revert.opcode, "REVERT");
+    assert_eq!(revert.location.as_ref().unwrap().code, "0x00 0x00 revert");
+}
+
+#[test]
+fn test_simulate_call_with_prestate() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        0x20 0x00 0x00 0x00 0xbeef gas staticcall pop
+        0xbeef balance 0x20 mstore
+        0x40 0x00 return
+    }
+    "#;
+
+    // The called account returns its first storage slot
+    let prestate: Prestate = serde_json::from_str(
+        r#"{
+            "0x000000000000000000000000000000000000beef": {
+                "balance": "0x2a",
+                "code": "0x60005460005260206000f3",
+                "storage": { "0x00": "0x01" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let artifact = compile(source).unwrap();
+    let trace = simulate_call(&artifact, &[], 0, &prestate).unwrap();
+    assert_eq!(trace.outcome, TraceOutcome::Returned(format!("{:064x}{:064x}", 1, 0x2a)));
+
+    // The called account's instructions are traced one call deeper, without locations
+    let sload = trace.steps.iter().find(|s| s.opcode == "SLOAD").unwrap();
+    assert_eq!(sload.depth, 2);
+    assert_eq!(sload.address, "0x000000000000000000000000000000000000beef");
+    assert!(sload.location.is_none());
+
+    // Invalid accounts fail the simulation
+    let invalid = Prestate::from([("0xbeef".to_string(), PrestateAccount::default())]);
+    let err = simulate_call(&artifact, &[], 0, &invalid).unwrap_err();
+    assert!(matches!(err.kind, SimulationErrorKind::InvalidPrestate(_)));
+}
+
+#[test]
+fn test_trace_definitions_follow_spans() {
+    // Text that looks like a definition doesn't change the macro instructions are located in
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        // #define macro COMMENTED() = takes(0) returns (0) {}
+        0x00 0x00 return
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    let trace = simulate_call(&artifact, &[], 0, &Prestate::default()).unwrap();
+    let location = trace.steps.last().unwrap().location.clone().unwrap();
+    assert_eq!(location.definition, Some("MAIN".to_string()));
+    assert_eq!(location.code, "0x00 0x00 return");
+}
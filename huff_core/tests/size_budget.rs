@@ -0,0 +1,49 @@
+mod common;
+
+use common::{compiler, file};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro INNER() = takes(0) returns (0) {
+        0x01 0x02 add pop
+    }
+
+    #define macro OUTER() = takes(0) returns (0) {
+        INNER()
+        0x03 pop
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        OUTER()
+        INNER()
+        stop
+    }
+"#;
+
+fn compile(size_budget: Option<usize>) -> Result<Artifact, CompilerError<'static>> {
+    Compiler { size_budget, ..compiler() }.gen_artifact(file("", SOURCE, None))
+}
+
+#[test]
+fn test_within_size_budget() {
+    // INNER = 6 bytes, OUTER = INNER + 3 bytes, MAIN = OUTER + INNER + 1 byte
+    let artifact = compile(Some(16)).unwrap();
+    assert_eq!(artifact.runtime.len() / 2, 16);
+}
+
+#[test]
+fn test_exceeds_size_budget() {
+    assert_eq!(
+        compile(Some(15)).unwrap_err(),
+        CompilerError::SizeBudgetError(SizeBudgetError {
+            file: "".to_string(),
+            size: 16,
+            budget: 15,
+            report: vec![
+                ("INNER".to_string(), MacroSize { invocations: 2, size: 12 }),
+                ("OUTER".to_string(), MacroSize { invocations: 1, size: 9 }),
+            ],
+        })
+    );
+}
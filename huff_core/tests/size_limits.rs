@@ -0,0 +1,66 @@
+mod common;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+/// Compiles a contract whose runtime and constructor each copy a table of the given size
+fn compile(runtime_table: usize, constructor_table: usize) -> (Artifact, Vec<CompilerWarning>) {
+    let source = format!(
+        r#"
+        #define table RUNTIME {{ 0x{} }}
+        #define table INIT {{ 0x{} }}
+
+        #define macro CONSTRUCTOR() = takes(0) returns (0) {{
+            __tablesize(INIT) __tablestart(INIT) 0x00 codecopy
+        }}
+
+        #define macro MAIN() = takes(0) returns (0) {{
+            __tablesize(RUNTIME) __tablestart(RUNTIME) 0x00 codecopy
+        }}
+        "#,
+        "ab".repeat(runtime_table),
+        "cd".repeat(constructor_table),
+    );
+    let compiler = compiler();
+    let artifact = compiler.gen_artifact(file("Large.huff", &source, None)).unwrap();
+    let warnings = compiler.warnings.lock().unwrap().clone();
+    (artifact, warnings)
+}
+
+#[test]
+fn test_within_size_limits() {
+    let (artifact, warnings) = compile(32, 32);
+    assert_eq!(artifact.sizes.runtime, artifact.runtime.len() / 2);
+    assert_eq!(artifact.sizes.initcode, artifact.bytecode.len() / 2);
+    assert!(artifact.sizes.runtime > 32);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_exceeds_runtime_size_limit() {
+    let (artifact, warnings) = compile(MAX_RUNTIME_SIZE, 32);
+    assert!(artifact.sizes.runtime > MAX_RUNTIME_SIZE);
+    assert!(artifact.sizes.initcode < MAX_INITCODE_SIZE);
+    assert_eq!(
+        warnings,
+        vec![CompilerWarning {
+            file: "Large.huff".to_string(),
+            kind: CompilerWarningKind::RuntimeSizeLimit(artifact.sizes.runtime),
+        }]
+    );
+}
+
+#[test]
+fn test_exceeds_initcode_size_limit() {
+    // The initcode includes the runtime, so both tables count towards it
+    let (artifact, warnings) = compile(20480, 32768);
+    assert!(artifact.sizes.runtime < MAX_RUNTIME_SIZE);
+    assert!(artifact.sizes.initcode > MAX_INITCODE_SIZE);
+    assert_eq!(
+        warnings,
+        vec![CompilerWarning {
+            file: "Large.huff".to_string(),
+            kind: CompilerWarningKind::InitcodeSizeLimit(artifact.sizes.initcode),
+        }]
+    );
+}
@@ -0,0 +1,105 @@
+use huff_codegen::config::CodegenConfig;
+use huff_core::snippet::SnippetCompiler;
+use huff_utils::prelude::*;
+
+const DEFINITIONS: &str = r#"
+    #define constant SLOT = FREE_STORAGE_POINTER()
+
+    #define macro REQUIRE_NONZERO() = takes (1) returns (0) {
+        ok jumpi
+        0x00 dup1 revert
+        ok:
+    }
+"#;
+
+fn compiler(offset: usize) -> SnippetCompiler {
+    SnippetCompiler { definitions: DEFINITIONS.to_string(), offset, ..Default::default() }
+}
+
+#[test]
+fn test_snippet_stack_delta() {
+    let snippet = compiler(0).compile("[SLOT] sload add", 1).unwrap();
+    assert_eq!(snippet.bytes, vec![0x60, 0x00, 0x54, 0x01]);
+    assert_eq!(snippet.takes, 1);
+    assert_eq!(snippet.stack_delta, Some(0));
+    assert_eq!(snippet.max_stack_depth, 2);
+
+    let snippet = compiler(0).compile("dup2 dup2 gt", 2).unwrap();
+    assert_eq!(snippet.stack_delta, Some(1));
+
+    // Halting snippets never leave the stack
+    let snippet = compiler(0).compile("0x00 dup1 revert", 0).unwrap();
+    assert_eq!(snippet.stack_delta, None);
+}
+
+#[test]
+fn test_snippet_jumps_at_offset() {
+    let snippet = compiler(0).compile("REQUIRE_NONZERO()", 1).unwrap();
+    assert_eq!(snippet.bytes, vec![0x61, 0x00, 0x08, 0x57, 0x60, 0x00, 0x80, 0xfd, 0x5b]);
+    assert_eq!(snippet.stack_delta, Some(-1));
+
+    // Jump destinations are resolved relative to where the snippet is embedded
+    let snippet = compiler(0x0100).compile("REQUIRE_NONZERO()", 1).unwrap();
+    assert_eq!(&snippet.bytes[..3], &[0x61, 0x01, 0x08]);
+}
+
+#[test]
+fn test_snippet_tables_at_offset() {
+    let compiler = SnippetCompiler {
+        definitions: "#define table TABLE { 0xdeadbeef }".to_string(),
+        offset: 0x10,
+        ..Default::default()
+    };
+    let snippet = compiler.compile("__tablestart(TABLE)", 0).unwrap();
+    assert_eq!(snippet.bytes, vec![0x61, 0x00, 0x13, 0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(snippet.body_len, 3);
+}
+
+#[test]
+fn test_snippet_body_len() {
+    let compiler = SnippetCompiler {
+        definitions: "#define fn ONE() = takes (0) returns (1) { 0x01 }".to_string(),
+        ..Default::default()
+    };
+    let snippet = compiler.compile("ONE() 0x02 add", 0).unwrap();
+    // The jump into the function, its return destination and the addition precede the function
+    assert_eq!(snippet.body_len, 11);
+    assert_eq!(snippet.bytes[snippet.body_len..], [0x5b, 0x60, 0x01, 0x90, 0x56]);
+    assert_eq!(snippet.stack_delta, Some(1));
+
+    // Outlined functions are appended even after an empty body
+    let snippet = compiler.compile("", 0).unwrap();
+    assert_eq!(snippet.body_len, 0);
+    assert_eq!(snippet.bytes.len(), 5);
+
+    let snippet = SnippetCompiler::default().compile("0x02", 0).unwrap();
+    assert_eq!(snippet.body_len, snippet.bytes.len());
+}
+
+#[test]
+fn test_snippet_errors() {
+    // Popping more than the declared input stack underflows
+    assert!(matches!(
+        compiler(0).compile("pop pop", 1),
+        Err(CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::StackUnderflow(_),
+            ..
+        }))
+    ));
+
+    // Labels must be defined within the snippet or the macros it invokes
+    assert!(matches!(
+        compiler(0).compile("missing jump", 0),
+        Err(CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::UnmatchedJumpLabel,
+            ..
+        }))
+    ));
+
+    // Opcodes are checked against the targeted evm version
+    let compiler = SnippetCompiler {
+        config: CodegenConfig { evm_version: EvmVersion::London, ..Default::default() },
+        ..Default::default()
+    };
+    assert!(compiler.compile("push0", 0).is_err());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define macro STORE() = takes (1) returns (0) {
+    0x00 sstore
+}
+
+#define macro CONSTRUCTOR() = takes (0) returns (0) {
+    caller STORE()
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x00 sload
+    dup1 done jumpi
+    STORE()
+    done:
+        stop
+}
+"#;
+
+fn compile(file: Arc<FileSource>) -> Artifact {
+    compiler().gen_artifact(file).unwrap()
+}
+
+#[test]
+fn test_source_map_generation() {
+    let artifact = compile(file("Store.huff", SOURCE, None));
+    let source_map = artifact.source_map.unwrap();
+    assert_eq!(source_map.sources, vec!["Store.huff".to_string()]);
+
+    // caller, then STORE's `00 sstore`, then the unmapped bootstrap code
+    assert_eq!(source_map.bytecode, "125:6:0:-;54:2;57:6;-1:-1:-1;;;;;;");
+
+    // Instructions expanded from STORE map to the macro's own statements
+    let segments = source_map.runtime.split(';').collect::<Vec<&str>>();
+    assert_eq!(segments.len(), 9);
+    assert_eq!(segments[0], "196:2:0:-");
+    assert_eq!(&SOURCE[196..198], "00");
+    assert_eq!(&SOURCE[199..204], "sload");
+    assert_eq!(segments[1], "199:5");
+    assert_eq!(segments[5..7], ["54:2", "57:6"]);
+    assert_eq!(&SOURCE[255..259], "stop");
+    assert_eq!(segments[8], "255:4");
+}
+
+#[test]
+fn test_source_map_imports() {
+    let lib = "#define macro STORE() = takes (1) returns (0) {\n    0x00 sstore\n}\n";
+    let main = "#define macro MAIN() = takes (0) returns (0) {\n    caller STORE()\n}\n";
+    let artifact = compile(file("Main.huff", main, Some(vec![file("Lib.huff", lib, None)])));
+    let source_map = artifact.source_map.unwrap();
+
+    assert_eq!(source_map.sources, vec!["Main.huff".to_string(), "Lib.huff".to_string()]);
+    assert_eq!(source_map.runtime, format!("{}:6:0:-;54:2:1;57:6", main.find("caller").unwrap()));
+    assert_eq!(source_map.bytecode, "-1:-1:-1:-;;;;;;");
+}
+
+#[test]
+fn test_source_map_export() {
+    let artifact = compile(file("Store.huff", SOURCE, None));
+    let serialized = JsonSerializer::default().serialize(&artifact).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(value["sourceMap"]["sources"][0], "Store.huff");
+    assert_eq!(value["sourceMap"]["bytecode"], "125:6:0:-;54:2;57:6;-1:-1:-1;;;;;;");
+
+    let imported: Artifact = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(imported.source_map, artifact.source_map);
+}
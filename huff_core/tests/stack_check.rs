@@ -0,0 +1,189 @@
+use huff_codegen::{config::CodegenConfig, *};
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse(source: &str) -> Contract {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut contract = Parser::new(tokens, None).parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+fn check(contract: &Contract) -> Result<String, CodegenError> {
+    let config = CodegenConfig { check_stack: true, ..Default::default() };
+    Codegen::generate_main_bytecode_with_config(contract, &config)
+}
+
+#[test]
+fn test_stack_effects() {
+    assert_eq!(stack_effect(0x01), (2, 1));
+    assert_eq!(stack_effect(0x08), (3, 1));
+    assert_eq!(stack_effect(0x33), (0, 1));
+    assert_eq!(stack_effect(0x57), (2, 0));
+    assert_eq!(stack_effect(0x80), (1, 2));
+    assert_eq!(stack_effect(0x8f), (16, 17));
+    assert_eq!(stack_effect(0x90), (2, 2));
+    assert_eq!(stack_effect(0xa2), (4, 0));
+    assert_eq!(stack_effect(0xf1), (7, 1));
+    assert_eq!(stack_effect(0xfd), (2, 0));
+}
+
+#[test]
+fn test_valid_stack_heights() {
+    let source = r#"
+        #define function get() view returns (uint256)
+
+        #define macro REQUIRE_OWNER() = takes (0) returns (0) {
+            caller 0x00 sload eq authorized jumpi
+            0x00 dup1 revert
+            authorized:
+        }
+
+        #define macro RETURN_WORD() = takes (1) returns (0) {
+            0x00 mstore
+            0x20 0x00 return
+        }
+
+        #define macro LOG() = takes (1) returns (1) {
+            dup1 pop
+        }
+
+        #define fn DOUBLE() = takes (1) returns (1) {
+            dup1 add
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload 0xe0 shr __DEBUG(LOG)
+            __FUNC_SIG(get) eq get jumpi
+            0x00 dup1 revert
+
+            get:
+                REQUIRE_OWNER()
+                0x01 DOUBLE()
+                RETURN_WORD()
+        }
+    "#;
+    assert!(check(&parse(source)).is_ok());
+}
+
+#[test]
+fn test_stack_underflow() {
+    let source = r#"
+        #define macro STORE() = takes (1) returns (0) {
+            sstore
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 STORE()
+        }
+    "#;
+    let err = check(&parse(source)).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StackUnderflow("STORE".to_string()));
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "sstore");
+
+    // Invocations can't take more than is on the stack either
+    let source = r#"
+        #define macro STORE() = takes (2) returns (0) {
+            sstore
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 STORE()
+        }
+    "#;
+    let err = check(&parse(source)).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StackUnderflow("MAIN".to_string()));
+}
+
+#[test]
+fn test_stack_height_mismatch() {
+    let source = r#"
+        #define macro LOAD() = takes (0) returns (1) {
+            0x00 sload 0x01
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            LOAD() pop
+        }
+    "#;
+    let contract = parse(source);
+    let err = check(&contract).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StackHeightMismatch("LOAD".to_string(), 1, 2));
+
+    // The checks are opt in
+    assert_eq!(Codegen::generate_main_bytecode(&contract).unwrap(), "600054600150");
+
+    // Invoked macros are checked against their own annotations before their callers rely on them
+    let source = r#"
+        #define macro LOAD() = takes (0) returns (0) {
+            0x00 sload
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            LOAD() 0x00 sstore
+        }
+    "#;
+    let err = check(&parse(source)).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StackHeightMismatch("LOAD".to_string(), 0, 1));
+}
+
+#[test]
+fn test_label_stack_heights() {
+    // Labels only reached by a jump start from the height the jump left
+    let source = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            0x01 0x02 done jump
+            done:
+                sstore pop
+        }
+    "#;
+    let err = check(&parse(source)).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StackUnderflow("MAIN".to_string()));
+    let span = &err.span.0[0];
+    assert_eq!(&source[span.start..span.end], "pop");
+
+    // Labels that are never jumped to with a known height aren't checked
+    let source = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            stop
+            unknown:
+                sstore pop
+        }
+    "#;
+    assert!(check(&parse(source)).is_ok());
+}
+
+#[test]
+fn test_max_stack_depths() {
+    let source = r#"
+        #define macro PUSH_THREE() = takes (1) returns (4) {
+            0x01 0x02 0x03
+        }
+
+        #define macro SUM() = takes (0) returns (1) {
+            0x00 PUSH_THREE() add add add
+        }
+
+        #define macro UNDERFLOW() = takes (0) returns (0) {
+            pop pop 0x01 0x02
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 0x00 SUM() pop pop pop
+            UNDERFLOW()
+        }
+    "#;
+    let depths = Codegen::max_stack_depths(&parse(source));
+    assert_eq!(depths["PUSH_THREE"], StackDepth { max: 4, near_limit: false });
+    // Invoked macros are simulated in place, from the stack they're invoked with
+    assert_eq!(depths["SUM"].max, 4);
+    assert_eq!(depths["MAIN"].max, 6);
+    // Underflows empty the stack instead of failing
+    assert_eq!(depths["UNDERFLOW"].max, 2);
+
+    assert!(!StackDepth::new(STACK_LIMIT - STACK_DEPTH_MARGIN - 1).near_limit);
+    assert!(StackDepth::new(STACK_LIMIT - STACK_DEPTH_MARGIN).near_limit);
+}
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use ethers_core::utils::hex;
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{AccountInfo, Address, Bytecode, Bytes, ExecutionResult, TxKind, U256},
+    Evm,
+};
+
+const MATH: &str = r#"
+#include <std/math.huff>
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x24 calldataload 0x04 calldataload     // [a, b]
+    0x00 calldataload 0xe0 shr              // [op, a, b]
+    dup1 0x01 eq add_op jumpi
+    dup1 0x02 eq sub_op jumpi
+    dup1 0x03 eq mul_op jumpi
+    pop SAFE_DIV() done jump
+    add_op: pop SAFE_ADD() done jump
+    sub_op: pop SAFE_SUB() done jump
+    mul_op: pop SAFE_MUL()
+    done: 0x00 mstore 0x20 0x00 return
+}
+"#;
+
+/// The address the test contracts are deployed at
+const TARGET: Address = Address::repeat_byte(0x11);
+
+/// Compiles the given source, returning its runtime bytecode
+fn compile(source: &str) -> String {
+    compile_profile(source, false)
+}
+
+/// Compiles the given source as a debug or release build, returning its runtime bytecode
+fn compile_profile(source: &str, debug: bool) -> String {
+    let root = std::env::temp_dir().join(format!("huff-stdlib-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let file = root.join("Std.huff");
+    std::fs::write(&file, source).unwrap();
+
+    let sources = Arc::new(vec![file.to_string_lossy().to_string()]);
+    let compiler = Compiler { debug, ..Compiler::new(sources, None, None, None, false, false) };
+    let artifacts = compiler.execute().into_result().unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+    artifacts[0].runtime.clone()
+}
+
+/// Calls the runtime with the given calldata, funding it with the given balance
+fn call(runtime: &str, data: Vec<u8>, balance: U256) -> ExecutionResult {
+    let mut db = CacheDB::new(EmptyDB::default());
+    let code = Bytecode::new_raw(Bytes::from(hex::decode(runtime).unwrap()));
+    db.insert_account_info(TARGET, AccountInfo::new(balance, 1, code.hash_slow(), code));
+
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .modify_tx_env(|tx| {
+            tx.transact_to = TxKind::Call(TARGET);
+            tx.data = Bytes::from(data);
+            tx.gas_limit = 1_000_000;
+        })
+        .build();
+    evm.transact().unwrap().result
+}
+
+/// Encodes a 4 byte operation selector followed by the given words
+fn calldata(op: u32, words: &[U256]) -> Vec<u8> {
+    let mut data = op.to_be_bytes().to_vec();
+    words.iter().for_each(|w| data.extend(w.to_be_bytes::<32>()));
+    data
+}
+
+fn assert_returns(res: ExecutionResult, expected: U256) {
+    assert!(res.is_success(), "{:?}", res);
+    assert_eq!(U256::from_be_slice(&res.into_output().unwrap()), expected);
+}
+
+fn assert_reverts(res: ExecutionResult, expected: Vec<u8>) {
+    match res {
+        ExecutionResult::Revert { output, .. } => assert_eq!(output.to_vec(), expected),
+        res => panic!("Expected a revert, got {:?}", res),
+    }
+}
+
+fn assert_panics(res: ExecutionResult, code: u8) {
+    let mut expected = hex::decode("4e487b71").unwrap();
+    expected.extend([0u8; 31]);
+    expected.push(code);
+    assert_reverts(res, expected);
+}
+
+#[test]
+fn test_std_math() {
+    let runtime = compile(MATH);
+    let math = |op: u32, a: U256, b: U256| call(&runtime, calldata(op, &[a, b]), U256::ZERO);
+    let (two, three) = (U256::from(2), U256::from(3));
+
+    assert_returns(math(1, two, three), U256::from(5));
+    assert_returns(math(2, three, two), U256::from(1));
+    assert_returns(math(3, two, three), U256::from(6));
+    assert_returns(math(3, U256::ZERO, U256::MAX), U256::ZERO);
+    assert_returns(math(4, U256::from(7), two), three);
+
+    assert_panics(math(1, U256::MAX, U256::from(1)), 0x11);
+    assert_panics(math(2, two, three), 0x11);
+    assert_panics(math(3, U256::MAX, two), 0x11);
+    assert_panics(math(4, three, U256::ZERO), 0x12);
+}
+
+#[test]
+fn test_std_transfer() {
+    let runtime = compile(
+        r#"
+        #include <std/transfer.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x24 calldataload 0x04 calldataload     // [to, amount]
+            SAFE_TRANSFER_ETH()
+            0x01 0x00 mstore 0x20 0x00 return
+        }
+    "#,
+    );
+    let to = U256::from(0xbeef);
+    assert_returns(
+        call(&runtime, calldata(0, &[to, U256::from(10)]), U256::from(10)),
+        U256::from(1),
+    );
+    assert_reverts(
+        call(&runtime, calldata(0, &[to, U256::from(11)]), U256::from(10)),
+        hex::decode("b12d13eb").unwrap(),
+    );
+
+    // Transferring tokens without code fails, since nothing can have been transferred
+    let runtime = compile(
+        r#"
+        #include <std/transfer.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x44 calldataload 0x24 calldataload 0x04 calldataload   // [token, to, amount]
+            SAFE_TRANSFER()
+            0x01 0x00 mstore 0x20 0x00 return
+        }
+    "#,
+    );
+    assert_reverts(
+        call(&runtime, calldata(0, &[U256::from(0xdead), to, U256::from(1)]), U256::ZERO),
+        hex::decode("90b8ec18").unwrap(),
+    );
+}
+
+#[test]
+fn test_std_reentrancy_and_console() {
+    let runtime = compile(
+        r#"
+        #include <std/console.huff>
+        #include <std@STD_VERSION/reentrancy.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            NON_REENTRANT()
+            0x00 calldataload 0xe0 shr              // [op]
+            0x04 calldataload dup1 LOG_UINT()       // [value, op]
+            swap1 reenter jumpi                     // [value]
+            NON_REENTRANT_END()
+            0x00 mstore 0x20 0x00 return
+            reenter: NON_REENTRANT()
+        }
+    "#
+        .replace("STD_VERSION", STD_VERSION)
+        .as_str(),
+    );
+    assert_returns(call(&runtime, calldata(0, &[U256::from(7)]), U256::ZERO), U256::from(7));
+    assert_reverts(
+        call(&runtime, calldata(1, &[U256::from(7)]), U256::ZERO),
+        hex::decode("ab143c06").unwrap(),
+    );
+}
+
+#[test]
+fn test_std_debug_logging() {
+    let source = r#"
+        #include <std/console.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x04 calldataload __DEBUG(DEBUG_UINT)   // [value]
+            0x00 mstore 0x20 0x00 return
+        }
+    "#;
+    let console = "636f6e736f6c652e6c6f67";
+
+    // Release builds don't call the console
+    let release = compile_profile(source, false);
+    assert!(!release.contains(console));
+    assert_returns(call(&release, calldata(0, &[U256::from(7)]), U256::ZERO), U256::from(7));
+
+    let debug = compile_profile(source, true);
+    assert!(debug.contains(console));
+    assert_returns(call(&debug, calldata(0, &[U256::from(7)]), U256::ZERO), U256::from(7));
+}
+
+#[test]
+fn test_std_assertions() {
+    let source = r#"
+        #include <std/assert.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x24 calldataload 0x04 calldataload     // [a, b]
+            __DEBUG(ASSERT_LT)                      // [a, b]
+            add __DEBUG(ASSERT)                     // [a + b]
+            0x00 mstore 0x20 0x00 return
+        }
+    "#;
+    let (one, two) = (U256::from(1), U256::from(2));
+
+    // Debug builds check the invariants
+    let debug = compile_profile(source, true);
+    assert_returns(call(&debug, calldata(0, &[one, two]), U256::ZERO), U256::from(3));
+    assert_panics(call(&debug, calldata(0, &[two, one]), U256::ZERO), 0x01);
+    assert_panics(call(&debug, calldata(0, &[one, U256::MAX]), U256::ZERO), 0x01);
+
+    // Release builds compile them out
+    let release = compile_profile(source, false);
+    assert!(release.len() < debug.len());
+    assert_returns(call(&release, calldata(0, &[two, one]), U256::ZERO), U256::from(3));
+}
+
+#[test]
+fn test_std_sources() {
+    assert!(std_source("<std/math.huff>").unwrap().contains("SAFE_ADD"));
+    assert!(std_source("std/math.huff").is_none());
+    assert!(std_source("<std/unknown.huff>").is_none());
+
+    // Imports pinned to another version don't resolve
+    let pinned = format!("<std@{}/console.huff>", STD_VERSION);
+    assert_eq!(std_source(&pinned), std_source("<std/console.huff>"));
+    assert!(std_source("<std@0.0.0/console.huff>").is_none());
+
+    let fs = Arc::new(FileSource {
+        path: "Unknown.huff".to_string(),
+        source: Some("#include <std/unknown.huff>".to_string()),
+        ..Default::default()
+    });
+    let err = Compiler::recurse_deps(fs).unwrap_err();
+    assert_eq!(
+        *err,
+        CompilerError::FileUnpackError(UnpackError::MissingFile("<std/unknown.huff>".to_string()))
+    );
+}
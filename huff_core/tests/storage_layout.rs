@@ -0,0 +1,140 @@
+mod common;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Artifact {
+    compiler().gen_artifact(file("storage.huff", source, None)).unwrap()
+}
+
+#[test]
+fn test_storage_layout_generation() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+    #define constant UNUSED = 0x1234
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [BALANCE] sload
+        [OWNER] sload
+        [UNUSED]
+    }
+    "#;
+
+    let artifact = compile(source);
+    let slots = artifact
+        .storage_layout
+        .storage
+        .iter()
+        .map(|s| (s.name.as_str(), s.slot.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    assert_eq!(slots, vec![("BALANCE", "0x00"), ("OWNER", "0x01")]);
+}
+
+#[test]
+fn test_storage_layout_reassignment() {
+    let previous = compile(
+        r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER] sload
+        [BALANCE] sload
+    }
+    "#,
+    );
+
+    // Appending a new slot is compatible
+    let appended = compile(
+        r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+    #define constant PAUSED = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER] sload
+        [BALANCE] sload
+        [PAUSED] sload
+    }
+    "#,
+    );
+    assert!(appended.storage_layout.compare(&previous.storage_layout).is_empty());
+
+    // Inserting a slot before existing ones reassigns them
+    let inserted = compile(
+        r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+    #define constant PAUSED = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [PAUSED] sload
+        [OWNER] sload
+        [BALANCE] sload
+    }
+    "#,
+    );
+    assert_eq!(
+        inserted.storage_layout.compare(&previous.storage_layout),
+        vec![
+            StorageLayoutConflict::Reassigned {
+                name: "BALANCE".to_string(),
+                previous: "0x01".to_string(),
+                current: "0x02".to_string(),
+            },
+            StorageLayoutConflict::Reassigned {
+                name: "OWNER".to_string(),
+                previous: "0x00".to_string(),
+                current: "0x01".to_string(),
+            },
+            StorageLayoutConflict::Overwritten {
+                slot: "0x00".to_string(),
+                previous: "OWNER".to_string(),
+                current: "PAUSED".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_storage_layout_literal_slots_and_type_hints() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER() // address
+    #define constant BALANCES = 0x1234 // mapping(address => uint256)
+    #define constant AMOUNT = 0x20 // uint256
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [AMOUNT] [BALANCES] sstore
+        [OWNER] sload
+    }
+    "#;
+
+    let artifact = compile(source);
+    let owner_start = source.find("#define constant OWNER").unwrap();
+    let balances_start = source.find("#define constant BALANCES").unwrap();
+    assert_eq!(
+        artifact.storage_layout.storage,
+        vec![
+            StorageSlot {
+                name: "BALANCES".to_string(),
+                slot: "0x1234".to_string(),
+                type_hint: Some("mapping(address => uint256)".to_string()),
+                file: Some("storage.huff".to_string()),
+                start: balances_start,
+                end: balances_start + "#define constant BALANCES = 0x1234".len(),
+            },
+            StorageSlot {
+                name: "OWNER".to_string(),
+                slot: "0x00".to_string(),
+                type_hint: Some("address".to_string()),
+                file: Some("storage.huff".to_string()),
+                start: owner_start,
+                end: owner_start + "#define constant OWNER = FREE_STORAGE_POINTER()".len(),
+            },
+        ]
+    );
+
+    // Literal constants used as anything but a slot aren't storage
+    assert!(artifact.storage_layout.get_slot("AMOUNT").is_none());
+}
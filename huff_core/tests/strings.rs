@@ -0,0 +1,78 @@
+use huff_codegen::Codegen;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn main_bytecode(source: &str) -> Result<String, CodegenError> {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    Codegen::generate_main_bytecode(&contract)
+}
+
+fn encode(s: &str) -> String {
+    s.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_string_literal_pushes() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns (0) {
+            "INSUFFICIENT_BALANCE" 0x00 mstore
+            'say "hi"' "a\\b"
+            "it\'s" 'it\'s'
+            ""
+        }
+    "#;
+
+    // Strings are right padded like `__RIGHTPAD`, with their escapes removed
+    let padded = |s: &str| format!("7f{}{}", encode(s), "00".repeat(32 - s.len()));
+    assert_eq!(
+        main_bytecode(source).unwrap(),
+        [
+            padded("INSUFFICIENT_BALANCE"),
+            "600052".to_string(),
+            padded("say \"hi\""),
+            padded("a\\b"),
+            padded("it's"),
+            padded("it's"),
+            padded(""),
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_string_literal_too_long() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns (0) {
+            dup1 iszero fail jumpi stop
+            fail:
+                "this string is longer than a word"
+        }
+    "#;
+    let err = main_bytecode(source).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::StringLiteralTooLong(33));
+    assert_eq!(err.span, AstSpan(vec![Span { start: 129, end: 164, file: None }]));
+}
+
+#[test]
+fn test_code_table_strings() {
+    let source = r#"
+        #define table MESSAGE {
+            "this string is longer than a word" 0x0a
+        }
+
+        #define macro MAIN() = takes(0) returns (0) {
+            __tablesize(MESSAGE) __tablestart(MESSAGE) 0x00 codecopy
+        }
+    "#;
+
+    // Strings in code tables are unpadded, so can be any length
+    let bytecode = main_bytecode(source).unwrap();
+    assert_eq!(&bytecode[..4], "6022");
+    assert!(bytecode.ends_with(&format!("{}0a", encode("this string is longer than a word"))));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{compiler, file};
+use ethers_core::utils::{hex, keccak256};
+use huff_core::symbolic::*;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define function setValue(uint256) nonpayable returns ()
+#define function getValue() view returns (uint256)
+
+#define macro ASSERT_LT_TEN() = takes (1) returns (1) {
+    dup1 0x0a gt ok jumpi
+    __FUNC_SIG("Panic(uint256)") 0xe0 shl 0x00 mstore
+    0x01 0x04 mstore
+    0x24 0x00 revert
+    ok:
+}
+
+#define macro SET_VALUE() = takes (0) returns (0) {
+    0x04 calldataload ASSERT_LT_TEN()
+    0x00 sstore stop
+}
+
+#define macro GET_VALUE() = takes (0) returns (0) {
+    0x00 sload 0x00 mstore
+    0x20 0x00 return
+}
+
+#define macro MAIN() = takes (0) returns (0) {
+    0x00 calldataload 0xe0 shr
+    dup1 __FUNC_SIG(setValue) eq set jumpi
+    dup1 __FUNC_SIG(getValue) eq get jumpi
+    0x00 0x00 revert
+    set:
+        SET_VALUE()
+    get:
+        GET_VALUE()
+}
+"#;
+
+fn compile() -> Artifact {
+    compiler().gen_artifact(file("Value.huff", SOURCE, None)).unwrap()
+}
+
+#[test]
+fn test_symbolic_target() {
+    let artifact = compile();
+    let target = SymbolicTarget::new(&artifact);
+    assert_eq!(target.runtime, artifact.runtime);
+
+    // Both functions are dispatched to a jumpdest
+    let code = hex::decode(&target.runtime).unwrap();
+    let signatures = target.entry_points.iter().map(|e| e.signature.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        signatures,
+        vec![Some("setValue(uint256)".to_string()), Some("getValue()".to_string())]
+    );
+    for entry_point in &target.entry_points {
+        let signature = entry_point.signature.clone().unwrap();
+        let selector = hex::encode(&keccak256(signature.as_bytes())[..4]);
+        assert_eq!(entry_point.selector, format!("0x{}", selector));
+        assert_eq!(code[entry_point.pc], 0x5b);
+    }
+
+    // The only assertion is the revert of the panic
+    assert_eq!(target.assertions.len(), 1);
+    let assertion = &target.assertions[0];
+    assert_eq!(code[assertion.pc], 0xfd);
+    assert_eq!(assertion.macro_name, "ASSERT_LT_TEN");
+    assert_eq!(assertion.file, "Value.huff");
+    assert_eq!(assertion.line, 8);
+
+    let json = serde_json::to_value(&target).unwrap();
+    assert_eq!(json["entryPoints"][0]["signature"], "setValue(uint256)");
+    assert_eq!(json["assertions"][0]["macro"], "ASSERT_LT_TEN");
+}
+
+#[test]
+fn test_fails_assertion() {
+    let artifact = compile();
+    let selector = hex::encode(&keccak256("setValue(uint256)")[..4]);
+    assert!(fails_assertion(&artifact, &format!("0x{}{:064x}", selector, 10)));
+    assert!(!fails_assertion(&artifact, &format!("0x{}{:064x}", selector, 9)));
+
+    // Reverting without the panic isn't an assertion failure
+    assert!(!fails_assertion(&artifact, "0xdeadbeef"));
+}
+
+#[test]
+fn test_hevm_counterexample() {
+    let artifact = compile();
+    let target = SymbolicTarget::new(&artifact);
+    let entry_point = &target.entry_points[0];
+    let selector = hex::encode(&keccak256("setValue(uint256)")[..4]);
+
+    // The calldata of the counterexample is replayed to confirm it
+    let failing = format!("0x{}{:064x}", selector, 10);
+    let output = format!(
+        "Discovered the following counterexamples:\n\nCounterexample:\n\n  \
+         result:   Revert: 0x4e487b71\n  calldata: {}\n",
+        failing
+    );
+    let result = SymbolicResult::from_hevm(&artifact, entry_point, false, output.clone());
+    assert!(!result.passed);
+    assert_eq!(result.counterexample, Some(failing));
+    assert_eq!(result.output, output);
+
+    // A counterexample that doesn't fail an assertion is dropped
+    let passing = format!("0x{}{:064x}", selector, 9);
+    let output = format!("Counterexample:\n  calldata: {}\n", passing);
+    let result = SymbolicResult::from_hevm(&artifact, entry_point, false, output);
+    assert_eq!(result.counterexample, None);
+
+    // Output without a counterexample has none
+    let result = SymbolicResult::from_hevm(&artifact, entry_point, true, "QED".to_string());
+    assert!(result.passed);
+    assert_eq!(result.counterexample, None);
+}
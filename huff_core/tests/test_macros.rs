@@ -0,0 +1,78 @@
+mod common;
+
+use common::{compiler, file};
+use huff_utils::prelude::*;
+
+fn compile(source: &str) -> Result<Artifact, CompilerError<'static>> {
+    compiler().gen_artifact(file("", source, None))
+}
+
+#[test]
+fn test_test_macros() {
+    let source = r#"
+    #define macro ADD_ONE() = takes (1) returns (1) {
+        0x01 add
+    }
+
+    #define fn DOUBLE() = takes (1) returns (1) {
+        dup1 add
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        0x00 calldataload ADD_ONE()
+        0x00 mstore 0x20 0x00 return
+    }
+
+    #define test TEST_ADD_ONE() = takes (0) returns (0) {
+        0x01 ADD_ONE() 0x02 eq success jumpi
+        0x00 dup1 revert
+        success:
+            stop
+    }
+
+    #define test TEST_DOUBLE() = takes (0) returns (0) {
+        0x02 DOUBLE() pop stop
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+
+    // Tests are excluded from the contract's bytecode
+    let without_tests = source.split("#define test").next().unwrap();
+    assert_eq!(artifact.runtime, compile(without_tests).unwrap().runtime);
+
+    // Each test is compiled on its own, with the contract's fns appended
+    assert_eq!(artifact.tests.len(), 2);
+    assert_eq!(artifact.tests["TEST_ADD_ONE"], "600160010160021461001057600080fd5b005b80019056");
+    assert!(artifact.tests["TEST_DOUBLE"].starts_with("6002"));
+    assert!(artifact.tests["TEST_DOUBLE"].ends_with("5b80019056"));
+
+    // Tests are exported with the artifact, and omitted when there are none
+    let json = JsonSerializer::default().serialize(&artifact).unwrap();
+    assert!(json.contains("\"TEST_ADD_ONE\""));
+    let json = JsonSerializer::default().serialize(&compile(without_tests).unwrap()).unwrap();
+    assert!(!json.contains("\"tests\""));
+}
+
+#[test]
+fn test_test_macro_errors() {
+    // Tests share the macro namespace
+    let source = r#"
+    #define macro MAIN() = takes (0) returns (0) {}
+    #define test MAIN() = takes (0) returns (0) {}
+    "#;
+    assert!(matches!(
+        compile(source).unwrap_err(),
+        CompilerError::CodegenError(CodegenError {
+            kind: CodegenErrorKind::DuplicateDefinition(..),
+            ..
+        })
+    ));
+
+    // Contract macros can't invoke tests
+    let source = r#"
+    #define test TEST_ONE() = takes (0) returns (0) { stop }
+    #define macro MAIN() = takes (0) returns (0) { TEST_ONE() }
+    "#;
+    assert!(compile(source).is_err());
+}
@@ -0,0 +1,52 @@
+use std::{sync::Arc, time::Duration};
+
+use huff_core::{timings::*, Compiler};
+
+#[test]
+fn test_timings_report() {
+    let mut timings = Timings::default();
+    assert_eq!(timings.report(), "No stages were timed, artifacts were cached\n");
+
+    timings.record("./A.huff", Stage::Lex, Duration::from_millis(2));
+    timings.record("./A.huff", Stage::Lex, Duration::from_millis(1));
+    timings.record("./A.huff", Stage::Codegen, Duration::from_millis(4));
+    timings.record("./B.huff", Stage::Lex, Duration::from_millis(5));
+
+    let totals = timings.totals();
+    assert_eq!(totals[&Stage::Lex], Duration::from_millis(8));
+    assert_eq!(totals[&Stage::Codegen], Duration::from_millis(4));
+    assert!(!totals.contains_key(&Stage::Export));
+
+    let report = timings.report();
+    let lines = report.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("contract"));
+    assert!(lines[0].contains("imports") && lines[0].contains("export"));
+    assert!(lines[1].starts_with("./A.huff"));
+    assert!(lines[1].contains("3.00ms") && lines[1].contains("7.00ms"));
+    assert!(lines[2].starts_with("./B.huff") && lines[2].contains('-'));
+    assert!(lines[3].starts_with("total") && lines[3].contains("12.00ms"));
+}
+
+#[test]
+fn test_compiler_records_timings() {
+    let root = std::env::temp_dir().join(format!("huff-timings-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&root).unwrap();
+    let path = root.join("Main.huff");
+    std::fs::write(&path, "#define macro MAIN() = takes(0) returns(0) { 0x01 }\n").unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    let output = root.join("artifacts").to_string_lossy().to_string();
+    let compiler =
+        Compiler::new(Arc::new(vec![path.clone()]), Some(output), None, None, false, false);
+    let result = compiler.execute();
+    assert!(result.is_ok());
+
+    let stages = &result.timings.contracts[&path];
+    for stage in Stage::ALL {
+        assert!(stages.contains_key(&stage), "missing {}", stage.name());
+    }
+    assert!(compiler.timings.lock().unwrap().contracts.is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,136 @@
+use std::{path::Path, sync::Arc};
+
+use huff_core::{workspace::*, Compiler};
+use huff_utils::prelude::*;
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_workspace_cross_package_imports() {
+    let root = std::env::temp_dir().join(format!("huff-workspace-{}", uuid::Uuid::new_v4()));
+    write(&root.join("huff.toml"), "[workspace]\nmembers = [\"token\", \"vault\"]\n");
+    write(&root.join("remappings.txt"), "huffmate/=lib/huffmate/src/\n");
+    write(
+        &root.join("token/contracts/Token.huff"),
+        "#define macro TOKEN_HELPER() = takes(0) returns(0) { 0x01 }\n#define macro MAIN() = takes(0) returns(0) { TOKEN_HELPER() }\n",
+    );
+    write(&root.join("vault/huff.toml"), "[package]\nname = \"vaults\"\nsrc = \"src\"\n");
+    write(
+        &root.join("vault/src/Vault.huff"),
+        "#include \"token/Token.huff\"\n#define macro MAIN() = takes(0) returns(0) { TOKEN_HELPER() 0x02 }\n",
+    );
+
+    let workspace = Workspace::load(&root).unwrap();
+    let names = workspace.members.iter().map(|m| m.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names, vec!["token", "vaults"]);
+
+    let sources = workspace.sources().unwrap();
+    assert_eq!(sources.len(), 2);
+    assert!(sources[0].ends_with("Token.huff"));
+    assert!(sources[1].ends_with("Vault.huff"));
+
+    let remappings = workspace.remappings();
+    let root_str = root.to_string_lossy();
+    assert_eq!(
+        Remapping::apply(&remappings, "vaults/Vault.huff"),
+        Some(format!("{}/vault/src/Vault.huff", root_str))
+    );
+    assert_eq!(
+        Remapping::apply(&remappings, "huffmate/auth/Owned.huff"),
+        Some(format!("{}/lib/huffmate/src/auth/Owned.huff", root_str))
+    );
+
+    // Compile all members together, resolving the vault's import of the token package
+    let mut compiler = Compiler::new(Arc::new(sources), None, None, None, false, false);
+    compiler.remappings = remappings;
+    let artifacts = compiler.execute().into_result().unwrap();
+    assert_eq!(artifacts.len(), 2);
+    let vault = artifacts.iter().find(|a| a.file.path.ends_with("Vault.huff")).unwrap();
+    assert_eq!(vault.runtime, "60016002");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_workspace_errors() {
+    let root = std::env::temp_dir().join(format!("huff-workspace-{}", uuid::Uuid::new_v4()));
+    assert!(matches!(Workspace::load(&root), Err(WorkspaceError::MissingManifest(_))));
+
+    write(&root.join("huff.toml"), "[workspace]\nmembers = [\"missing\"]\n");
+    assert!(matches!(Workspace::load(&root), Err(WorkspaceError::MissingMember(_))));
+
+    write(&root.join("huff.toml"), "[workspace]\nmembers = [\"a\", \"b\"]\n");
+    write(&root.join("a/huff.toml"), "[package]\nname = \"shared\"\n");
+    write(&root.join("b/huff.toml"), "[package]\nname = \"shared\"\n");
+    assert_eq!(Workspace::load(&root), Err(WorkspaceError::DuplicateMember("shared".to_string())));
+
+    write(&root.join("huff.toml"), "[workspace]\nmembers = \"a\"\n");
+    assert!(matches!(Workspace::load(&root), Err(WorkspaceError::InvalidManifest(_, _))));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_network_profiles() {
+    let root = std::env::temp_dir().join(format!("huff-workspace-{}", uuid::Uuid::new_v4()));
+    write(
+        &root.join("huff.toml"),
+        r#"
+[networks.mainnet]
+
+[networks.legacy]
+evm_version = "petersburg"
+
+[networks.legacy.constants]
+OWNER = "0x02"
+"#,
+    );
+    write(
+        &root.join("contracts/Owned.huff"),
+        "#define constant OWNER = 0x01\n#define macro MAIN() = takes(0) returns(0) { [OWNER] selfbalance }\n",
+    );
+    let sources =
+        Arc::new(vec![format!("{}", root.join("contracts/Owned.huff").to_string_lossy())]);
+
+    // Profiles default to london, without overrides
+    let mainnet = NetworkProfile::load(&root, "mainnet").unwrap();
+    assert_eq!(mainnet.evm_version, EvmVersion::London);
+    assert!(mainnet.constant_overrides().unwrap().is_empty());
+    let compiler = Compiler::new(Arc::clone(&sources), None, None, None, false, false);
+    let artifacts = compiler.execute().into_result().unwrap();
+    assert_eq!(artifacts[0].runtime, "600147");
+
+    // Building for a network applies its constants and rejects unsupported opcodes
+    let legacy = NetworkProfile::load(&root.join("huff.toml"), "legacy").unwrap();
+    let overrides = legacy.constant_overrides().unwrap();
+    assert_eq!(overrides.get("OWNER"), Some(&str_to_bytes32("02")));
+    let compiler =
+        Compiler::new(Arc::clone(&sources), None, None, Some(overrides.clone()), false, false);
+    assert_eq!(compiler.execute().into_result().unwrap()[0].runtime, "600247");
+    let compiler = Compiler {
+        evm_version: legacy.evm_version,
+        ..Compiler::new(Arc::clone(&sources), None, None, Some(overrides), false, false)
+    };
+    let err = compiler.execute().into_result().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Opcode \"selfbalance\" Is Not Supported By The petersburg EVM"));
+
+    assert_eq!(
+        NetworkProfile::load(&root, "sepolia"),
+        Err(WorkspaceError::MissingNetwork("sepolia".to_string()))
+    );
+    let invalid = NetworkProfile {
+        constants: [("OWNER".to_string(), "42".to_string())].into_iter().collect(),
+        ..Default::default()
+    };
+    assert_eq!(
+        invalid.constant_overrides(),
+        Err(WorkspaceError::InvalidConstantOverride("OWNER".to_string(), "42".to_string()))
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
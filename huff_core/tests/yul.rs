@@ -0,0 +1,65 @@
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compiler, file};
+
+const SOURCE: &str = r#"
+    #define function get() view returns (uint256)
+    #define immutable OWNER
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {
+        caller __SET_IMMUTABLE(OWNER)
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __IMMUTABLE(OWNER) 0x00 mstore
+        0x00 calldataload 0xe0 shr
+        __FUNC_SIG(get) eq get jumpi
+        0x00 0x00 revert
+        get:
+            0x20 0x00 return
+    }
+"#;
+
+fn compile(selection: &[ArtifactOutput]) -> Artifact {
+    let compiler = Compiler { output_selection: selection.iter().copied().collect(), ..compiler() };
+    compiler.gen_artifact(file("./src/Owned.huff", SOURCE, None)).unwrap()
+}
+
+#[test]
+fn test_yul_is_opt_in() {
+    assert!(!ArtifactOutput::DEFAULT.contains(&ArtifactOutput::Yul));
+    assert!(compile(&ArtifactOutput::DEFAULT).yul.is_none());
+}
+
+#[test]
+fn test_yul_object() {
+    let artifact = compile(&[ArtifactOutput::Bytecode, ArtifactOutput::Yul]);
+    let yul = artifact.yul.unwrap();
+
+    assert!(yul.starts_with("/// @use-src 0:\"./src/Owned.huff\"\n"));
+    assert!(yul.contains("object \"Owned\" {"));
+    assert!(yul.contains("object \"Owned_deployed\" {"));
+
+    // The constructor stages the immutable, then the bootstrap copies and patches the runtime
+    assert!(yul.contains("verbatim_0i_0o(hex\"33604052\")"));
+    assert!(yul
+        .contains("datacopy(0x60, dataoffset(\"Owned_deployed\"), datasize(\"Owned_deployed\"))"));
+    assert!(yul.contains("mstore(0x61, mload(0x40))"));
+    assert!(yul.contains("return(0x60, datasize(\"Owned_deployed\"))"));
+
+    // The runtime is split into a verbatim block per basic block
+    let runtime = yul.split("Owned_deployed\" {").nth(1).unwrap();
+    assert_eq!(runtime.matches("verbatim_0i_0o").count(), 3);
+    assert_eq!(runtime.matches("/// @src 0:").count(), 3);
+    assert!(runtime.contains("verbatim_0i_0o(hex\"5b60206000f3\")"));
+
+    // The blocks concatenate back to the runtime bytecode
+    let code = runtime
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("verbatim_0i_0o(hex\""))
+        .map(|l| l.trim_end_matches("\")"))
+        .collect::<String>();
+    assert_eq!(code, artifact.runtime);
+}
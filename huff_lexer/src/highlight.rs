@@ -0,0 +1,186 @@
+use crate::Lexer;
+use huff_utils::prelude::{FullFileSource, Span, Token, TokenKind};
+
+/// A Semantic Token Classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SemanticTokenKind {
+    /// A keyword, ie. `#define`, `macro`, or `takes`
+    Keyword,
+    /// An EVM opcode
+    Opcode,
+    /// A constant definition or reference
+    Constant,
+    /// A macro definition or invocation
+    Macro,
+    /// A function or event name
+    Function,
+    /// A table name
+    Table,
+    /// A macro parameter or argument call
+    Parameter,
+    /// A label definition or jump destination
+    Label,
+    /// A builtin function, ie. `__FUNC_SIG`
+    Builtin,
+    /// A line or block comment
+    Comment,
+    /// A hex literal or number
+    Number,
+    /// A string literal
+    String,
+    /// An EVM type
+    Type,
+    /// An arithmetic operator
+    Operator,
+}
+
+impl SemanticTokenKind {
+    /// The name of the classification, for use in editor legends
+    pub fn name(&self) -> &'static str {
+        match self {
+            SemanticTokenKind::Keyword => "keyword",
+            SemanticTokenKind::Opcode => "opcode",
+            SemanticTokenKind::Constant => "constant",
+            SemanticTokenKind::Macro => "macro",
+            SemanticTokenKind::Function => "function",
+            SemanticTokenKind::Table => "table",
+            SemanticTokenKind::Parameter => "parameter",
+            SemanticTokenKind::Label => "label",
+            SemanticTokenKind::Builtin => "builtin",
+            SemanticTokenKind::Comment => "comment",
+            SemanticTokenKind::Number => "number",
+            SemanticTokenKind::String => "string",
+            SemanticTokenKind::Type => "type",
+            SemanticTokenKind::Operator => "operator",
+        }
+    }
+}
+
+/// A Classified Source Span
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// The classification
+    pub kind: SemanticTokenKind,
+    /// The classified source span
+    pub span: Span,
+}
+
+/// Classifies the tokens of a source string for syntax highlighting.
+///
+/// Whitespace and punctuation are omitted. Classification stops at the first lexical error so
+/// that partially written sources can still be highlighted.
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let tokens = Lexer::new(full_source)
+        .map_while(Result::ok)
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Eof))
+        .collect::<Vec<Token>>();
+
+    // The definition keyword of the enclosing definition and the brace depth inside its body
+    let mut definition: Option<TokenKind> = None;
+    let mut depth = 0usize;
+
+    let mut semantic_tokens = vec![];
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| &tokens[p].kind);
+        let next = tokens.get(i + 1).map(|t| &t.kind);
+        let kind = match &token.kind {
+            TokenKind::Comment(_) => Some(SemanticTokenKind::Comment),
+            TokenKind::Define | TokenKind::Include => {
+                definition = None;
+                Some(SemanticTokenKind::Keyword)
+            }
+            k @ (TokenKind::Macro |
+            TokenKind::Fn |
+            TokenKind::Function |
+            TokenKind::Event |
+            TokenKind::Constant |
+            TokenKind::JumpTable |
+            TokenKind::JumpTablePacked |
+            TokenKind::CodeTable) => {
+                if prev == Some(&TokenKind::Define) {
+                    definition = Some(k.clone());
+                }
+                Some(SemanticTokenKind::Keyword)
+            }
+            TokenKind::Takes |
+            TokenKind::Returns |
+            TokenKind::View |
+            TokenKind::Pure |
+            TokenKind::Payable |
+            TokenKind::NonPayable |
+            TokenKind::Indexed |
+            TokenKind::FreeStoragePointer => Some(SemanticTokenKind::Keyword),
+            TokenKind::Opcode(_) => Some(SemanticTokenKind::Opcode),
+            TokenKind::BuiltinFunction(_) => Some(SemanticTokenKind::Builtin),
+            TokenKind::Label(_) => Some(SemanticTokenKind::Label),
+            TokenKind::Literal(_) | TokenKind::Num(_) => Some(SemanticTokenKind::Number),
+            TokenKind::Str(_) => Some(SemanticTokenKind::String),
+            TokenKind::PrimitiveType(_) | TokenKind::ArrayType(_, _) => {
+                Some(SemanticTokenKind::Type)
+            }
+            TokenKind::Add | TokenKind::Sub | TokenKind::Mul | TokenKind::Div => {
+                Some(SemanticTokenKind::Operator)
+            }
+            TokenKind::OpenBrace => {
+                depth += 1;
+                None
+            }
+            TokenKind::CloseBrace => {
+                depth = depth.saturating_sub(1);
+                None
+            }
+            TokenKind::Ident(_) => Some(match (&definition, prev, next) {
+                (_, Some(TokenKind::OpenBracket), _) => SemanticTokenKind::Constant,
+                (_, Some(TokenKind::LeftAngle), _) => SemanticTokenKind::Parameter,
+                // Definition names
+                (Some(TokenKind::Constant), Some(TokenKind::Constant), _) => {
+                    SemanticTokenKind::Constant
+                }
+                (
+                    Some(TokenKind::Macro | TokenKind::Fn),
+                    Some(TokenKind::Macro | TokenKind::Fn),
+                    _,
+                ) => SemanticTokenKind::Macro,
+                (
+                    Some(TokenKind::Function | TokenKind::Event),
+                    Some(TokenKind::Function | TokenKind::Event),
+                    _,
+                ) => SemanticTokenKind::Function,
+                (
+                    Some(TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable),
+                    Some(TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable),
+                    _,
+                ) => SemanticTokenKind::Table,
+                // Code table bodies are lexed as identifiers
+                (Some(TokenKind::CodeTable), _, _) if depth > 0 => SemanticTokenKind::Number,
+                // Inside macro bodies, identifiers are invocations or jump destinations
+                (Some(TokenKind::Macro | TokenKind::Fn), _, Some(TokenKind::OpenParen))
+                    if depth > 0 =>
+                {
+                    SemanticTokenKind::Macro
+                }
+                (Some(_), _, _) if depth > 0 => SemanticTokenKind::Label,
+                // Everything else is a parameter name
+                _ => SemanticTokenKind::Parameter,
+            }),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            semantic_tokens.push(SemanticToken { kind, span: literal_span(source, token) });
+        }
+    }
+    semantic_tokens
+}
+
+/// Widens a hex literal's span to include its `0x` prefix
+fn literal_span(source: &str, token: &Token) -> Span {
+    let mut span = token.span.clone();
+    if matches!(token.kind, TokenKind::Literal(_)) &&
+        span.start >= 2 &&
+        source.get(span.start - 2..span.start) == Some("0x")
+    {
+        span.start -= 2;
+    }
+    span
+}
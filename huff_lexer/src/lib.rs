@@ -3,7 +3,6 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 #![forbid(unsafe_code)]
-#![forbid(where_clauses_object_safety)]
 
 use huff_utils::prelude::*;
 use regex::Regex;
@@ -13,6 +12,9 @@ use std::{
     str::Chars,
 };
 
+/// Semantic Token Classification Module
+pub mod highlight;
+
 /// Defines a context in which the lexing happens.
 /// Allows to differientate between EVM types and opcodes that can either
 /// be identical or the latter being a substring of the former (example : bytes32 and byte)
@@ -58,6 +60,10 @@ pub struct Lexer<'a> {
     pub eof_returned: bool,
     /// Current context.
     pub context: Context,
+    /// The names of the custom builtins registered with codegen, lexed as builtin calls.
+    pub custom_builtins: Vec<String>,
+    /// The historical opcode aliases lexed so far, with their spans.
+    pub aliases: Vec<(String, Span)>,
 }
 
 impl<'a> Lexer<'a> {
@@ -72,6 +78,8 @@ impl<'a> Lexer<'a> {
             eof: false,
             eof_returned: false,
             context: Context::Global,
+            custom_builtins: vec![],
+            aliases: vec![],
         }
     }
 
@@ -112,34 +120,40 @@ impl<'a> Lexer<'a> {
 
                     // Skip over whitespace
                     while peekable_source.peek().is_some() {
-                        if !peekable_source.peek().unwrap().is_whitespace() {
+                        if peekable_source.next_if(|c| c.is_whitespace()).is_none() {
                             break
-                        } else {
-                            peekable_source.next();
                         }
                     }
 
                     // Then we should have an import path between quotes
-                    if let Some(char) = peekable_source.peek() {
-                        match char {
-                            '"' | '\'' => {
-                                peekable_source.next();
-                                let mut import = String::new();
-                                while peekable_source.peek().is_some() {
-                                    if let Some(c) = peekable_source.next() {
-                                        if matches!(c, '"' | '\'') {
-                                            imports.push(import);
-                                            break
-                                        } else {
-                                            import.push(c);
-                                        }
-                                    }
+                    // Non-include tokens are ignored
+                    if let Some('"' | '\'') = peekable_source.peek() {
+                        peekable_source.next();
+                        let mut import = String::new();
+                        while peekable_source.peek().is_some() {
+                            if let Some(c) = peekable_source.next() {
+                                if matches!(c, '"' | '\'') {
+                                    imports.push(import);
+                                    break
+                                } else {
+                                    import.push(c);
                                 }
                             }
-                            _ => { /* Ignore non-include tokens */ }
                         }
                     }
-                } else if nc.ne(&include_chars_iterator.next().unwrap()) {
+
+                    // Standard library imports are kept wrapped in angle brackets
+                    if let Some('<') = peekable_source.peek() {
+                        let mut import = String::new();
+                        for c in peekable_source.by_ref() {
+                            import.push(c);
+                            if c == '>' {
+                                imports.push(import);
+                                break
+                            }
+                        }
+                    }
+                } else if include_chars_iterator.next() != Some(nc) {
                     include_chars_iterator = "#include".chars().peekable();
                     break
                 }
@@ -149,12 +163,12 @@ impl<'a> Lexer<'a> {
     }
 
     /// Public associated function that returns a shared reference to the current lexing span.
-    pub fn current_span(&self) -> Ref<Span> {
+    pub fn current_span(&self) -> Ref<'_, Span> {
         self.span.borrow()
     }
 
     /// Public associated function that returns an exclusive reference to the current lexing span.
-    pub fn current_span_mut(&self) -> RefMut<Span> {
+    pub fn current_span_mut(&self) -> RefMut<'_, Span> {
         self.span.borrow_mut()
     }
 
@@ -180,8 +194,8 @@ impl<'a> Lexer<'a> {
     pub fn dyn_peek(&mut self, f: impl Fn(&char) -> bool + Copy) -> String {
         let mut chars: Vec<char> = Vec::new();
         let mut current_pos = self.current_span().start;
-        while self.nth_peek(current_pos).map(|x| f(&x)).unwrap_or(false) {
-            chars.push(self.nth_peek(current_pos).unwrap());
+        while let Some(c) = self.nth_peek(current_pos).filter(f) {
+            chars.push(c);
             current_pos += 1;
         }
         chars.iter().collect()
@@ -213,19 +227,24 @@ impl<'a> Lexer<'a> {
 
     /// Peek n chars from a given start point in the source
     pub fn peek_n_chars_from(&mut self, n: usize, from: usize) -> String {
-        self.source.source[Span::new(from..(from + n), None).range().unwrap()].to_string()
+        self.source.source.get(from..(from + n)).unwrap_or_default().to_string()
     }
 
     /// Gets the current slice of the source code covered by span
     pub fn slice(&self) -> String {
-        self.source.source[self.current_span().range().unwrap()].to_string()
+        self.source_slice().to_string()
+    }
+
+    /// Borrows the current slice of the source code covered by span
+    pub fn source_slice(&self) -> &'a str {
+        let source: &'a str = self.source.source;
+        self.current_span().range().and_then(|r| source.get(r)).unwrap_or_default()
     }
 
     /// Consumes the characters
     pub fn consume(&mut self) -> Option<char> {
-        self.chars.next().map(|x| {
+        self.chars.next().inspect(|_x| {
             self.current_span_mut().end += 1;
-            x
         })
     }
 
@@ -239,7 +258,7 @@ impl<'a> Lexer<'a> {
     /// Consume characters until a sequence matches
     pub fn seq_consume(&mut self, word: &str) {
         let mut current_pos = self.current_span().start;
-        while self.peek() != None {
+        while self.peek().is_some() {
             let peeked = self.peek_n_chars_from(word.len(), current_pos);
             if word == peeked {
                 break
@@ -268,8 +287,8 @@ impl<'a> Lexer<'a> {
     /// `TokenKind::Ident`.
     ///
     /// Rules:
-    /// - The `macro`, `fn`, `function`, `constant`, `event`, `jumptable`, `jumptable__packed`, and
-    ///   `table` keywords must be preceded by a `#define` keyword.
+    /// - The `macro`, `fn`, `test`, `function`, `constant`, `immutable`, `event`, `jumptable`,
+    ///   `jumptable__packed`, and `table` keywords must be preceded by a `#define` keyword.
     /// - The `takes` keyword must be preceded by an assignment operator: `=`.
     /// - The `nonpayable`, `payable`, `view`, and `pure` keywords must be preceeded by one of these
     ///   keywords or a close paren.
@@ -279,9 +298,12 @@ impl<'a> Lexer<'a> {
         match found_kind {
             Some(TokenKind::Macro) |
             Some(TokenKind::Fn) |
+            Some(TokenKind::Test) |
             Some(TokenKind::Function) |
             Some(TokenKind::Constant) |
+            Some(TokenKind::Immutable) |
             Some(TokenKind::Event) |
+            Some(TokenKind::Error) |
             Some(TokenKind::JumpTable) |
             Some(TokenKind::JumpTablePacked) |
             Some(TokenKind::CodeTable) => self.checked_lookback(TokenKind::Define),
@@ -381,11 +403,14 @@ impl<'a> Iterator for Lexer<'a> {
                     let keys = [
                         TokenKind::Macro,
                         TokenKind::Fn,
+                        TokenKind::Test,
                         TokenKind::Function,
                         TokenKind::Constant,
+                        TokenKind::Immutable,
                         TokenKind::Takes,
                         TokenKind::Returns,
                         TokenKind::Event,
+                        TokenKind::Error,
                         TokenKind::NonPayable,
                         TokenKind::Payable,
                         TokenKind::Indexed,
@@ -421,10 +446,12 @@ impl<'a> Iterator for Lexer<'a> {
 
                     if let Some(kind) = &found_kind {
                         match kind {
-                            TokenKind::Macro | TokenKind::Fn => {
+                            TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
                                 self.context = Context::MacroDefinition
                             }
-                            TokenKind::Function | TokenKind::Event => self.context = Context::Abi,
+                            TokenKind::Function | TokenKind::Event | TokenKind::Error => {
+                                self.context = Context::Abi
+                            }
                             TokenKind::Constant => self.context = Context::Constant,
                             TokenKind::CodeTable => self.context = Context::CodeTableBody,
                             _ => (),
@@ -451,7 +478,7 @@ impl<'a> Iterator for Lexer<'a> {
 
                     let potential_label: String =
                         self.dyn_peek(|c| c.is_alphanumeric() || c == &'_' || c == &':');
-                    if let true = potential_label.ends_with(':') {
+                    if potential_label.ends_with(':') {
                         self.dyn_consume(|c| c.is_alphanumeric() || c == &'_');
                         let label = self.slice();
                         if let Some(l) = label.get(0..label.len()) {
@@ -473,7 +500,7 @@ impl<'a> Iterator for Lexer<'a> {
 
                     // goes over all opcodes
                     for opcode in OPCODES {
-                        if self.context != Context::MacroBody || found_kind != None {
+                        if self.context != Context::MacroBody || found_kind.is_some() {
                             break
                         }
                         if opcode == pot_op {
@@ -487,6 +514,14 @@ impl<'a> Iterator for Lexer<'a> {
                         }
                     }
 
+                    // Historical aliases are lexed as their canonical opcode
+                    if self.context == Context::MacroBody && found_kind.is_none() {
+                        if let Some(canonical) = OPCODE_ALIASES.get(pot_op.as_str()) {
+                            self.dyn_consume(|c| c.is_alphanumeric());
+                            found_kind = Some(TokenKind::Opcode(OPCODES_MAP[*canonical]));
+                        }
+                    }
+
                     // Last case ; we are in ABI context and
                     // we are parsing an EVM type
                     if self.context == Context::AbiArgs {
@@ -506,24 +541,23 @@ impl<'a> Iterator for Lexer<'a> {
                                     .collect();
                                 let mut size_vec: Vec<usize> = Vec::new();
                                 // go over all array sizes
-                                let sizes = words.get(1..words.len()).unwrap();
-                                for size in sizes.iter() {
+                                for size in words.iter().skip(1) {
                                     match size.is_empty() {
                                         true => size_vec.push(0),
                                         false => {
-                                            let arr_size: usize = size
-                                                .parse::<usize>()
-                                                .map_err(|_| {
+                                            let arr_size = match size.parse::<usize>() {
+                                                Ok(s) => s,
+                                                Err(_) => {
                                                     let err = LexicalError {
                                                         kind: LexicalErrorKind::InvalidArraySize(
-                                                            &words[1],
+                                                            self.source_slice().into(),
                                                         ),
                                                         span: self.current_span().clone(),
                                                     };
                                                     tracing::error!(target: "lexer", "{}", format!("{:?}", err));
-                                                    err
-                                                })
-                                                .unwrap();
+                                                    return Some(Err(err))
+                                                }
+                                            };
                                             size_vec.push(arr_size);
                                         }
                                     }
@@ -533,7 +567,9 @@ impl<'a> Iterator for Lexer<'a> {
                                     found_kind = Some(TokenKind::ArrayType(primitive, size_vec));
                                 } else {
                                     let err = LexicalError {
-                                        kind: LexicalErrorKind::InvalidPrimitiveType(&words[0]),
+                                        kind: LexicalErrorKind::InvalidPrimitiveType(
+                                            words[0].as_str().into(),
+                                        ),
                                         span: self.current_span().clone(),
                                     };
                                     tracing::error!(target: "lexer", "{}", format!("{:?}", err));
@@ -555,16 +591,11 @@ impl<'a> Iterator for Lexer<'a> {
                         self.dyn_consume(|c| c.is_alphanumeric() || c.eq(&'_'));
 
                         let slice = self.slice();
-                        // Check for built-in function calls
+                        // Check for built-in function calls, including calls to custom builtins
+                        // registered with codegen
                         if self.context == Context::MacroBody &&
-                            matches!(
-                                slice.as_ref(),
-                                "__codesize" |
-                                    "__tablesize" |
-                                    "__tablestart" |
-                                    "__FUNC_SIG" |
-                                    "__EVENT_HASH" /* TODO: Clean this process up */
-                            )
+                            (BuiltinFunctionKind::NAMES.contains(&slice.as_ref()) ||
+                                self.custom_builtins.contains(&slice))
                         {
                             TokenKind::BuiltinFunction(slice)
                         } else {
@@ -573,7 +604,7 @@ impl<'a> Iterator for Lexer<'a> {
                     }
                 }
                 // If it's the start of a hex literal
-                ch if ch == '0' && self.peek().unwrap() == 'x' => {
+                ch if ch == '0' && self.peek() == Some('x') => {
                     self.consume(); // Consume the 'x' after '0' (separated from the `dyn_consume` so we don't have
                                     // to match `x` in the actual hex)
                     self.dyn_consume(|c| {
@@ -588,6 +619,12 @@ impl<'a> Iterator for Lexer<'a> {
                         // the code as an Ident, and it is appended to the end of the runtime
                         // bytecode in codegen.
                         TokenKind::Ident(self.slice())
+                    } else if self.current_span().end - self.current_span().start > 64 {
+                        tracing::error!(target: "lexer", "LITERAL EXCEEDS 32 BYTES");
+                        return Some(Err(LexicalError::new(
+                            LexicalErrorKind::LiteralOverflow(self.source_slice().into()),
+                            self.current_span().clone(),
+                        )))
                     } else {
                         TokenKind::Literal(str_to_bytes32(self.slice().as_ref()))
                     }
@@ -626,6 +663,20 @@ impl<'a> Iterator for Lexer<'a> {
                 '+' => TokenKind::Add,
                 '-' => TokenKind::Sub,
                 '*' => TokenKind::Mul,
+                // Standard library import paths, ie. `#include <std/math.huff>`
+                '<' if matches!(self.lookback, Some(Token { kind: TokenKind::Include, .. })) => {
+                    self.dyn_consume(|c| *c != '>' && *c != '\n');
+                    if self.peek() != Some('>') {
+                        self.eof = self.peek().is_none();
+                        tracing::error!(target: "lexer", "UNTERMINATED IMPORT PATH");
+                        return Some(Err(LexicalError::new(
+                            LexicalErrorKind::UnexpectedEof,
+                            self.current_span().clone(),
+                        )))
+                    }
+                    self.consume();
+                    TokenKind::Str(self.slice())
+                }
                 '<' => TokenKind::LeftAngle,
                 '>' => TokenKind::RightAngle,
                 // NOTE: TokenKind::Div is lexed further up since it overlaps with comment
@@ -634,14 +685,23 @@ impl<'a> Iterator for Lexer<'a> {
                 ',' => TokenKind::Comma,
                 '0'..='9' => {
                     self.dyn_consume(char::is_ascii_digit);
-                    TokenKind::Num(self.slice().parse().unwrap())
+                    match self.slice().parse() {
+                        Ok(n) => TokenKind::Num(n),
+                        Err(_) => {
+                            tracing::error!(target: "lexer", "NUMBER OVERFLOW");
+                            return Some(Err(LexicalError::new(
+                                LexicalErrorKind::NumberOverflow(self.source_slice().into()),
+                                self.current_span().clone(),
+                            )))
+                        }
+                    }
                 }
                 // Lexes Spaces and Newlines as Whitespace
                 ch if ch.is_ascii_whitespace() => {
                     self.dyn_consume(char::is_ascii_whitespace);
                     TokenKind::Whitespace
                 }
-                // String literals
+                // String literals, where either quote can be escaped like the parser unescapes
                 '"' => loop {
                     match self.peek() {
                         Some('"') => {
@@ -649,7 +709,9 @@ impl<'a> Iterator for Lexer<'a> {
                             let str = self.slice();
                             break TokenKind::Str((str[1..str.len() - 1]).to_string())
                         }
-                        Some('\\') if matches!(self.nth_peek(1), Some('\\') | Some('"')) => {
+                        Some('\\')
+                            if matches!(self.chars.clone().nth(1), Some('\\' | '"' | '\'')) =>
+                        {
                             self.consume();
                         }
                         Some(_) => {}
@@ -672,7 +734,9 @@ impl<'a> Iterator for Lexer<'a> {
                             let str = self.slice();
                             break TokenKind::Str((str[1..str.len() - 1]).to_string())
                         }
-                        Some('\\') if matches!(self.nth_peek(1), Some('\\') | Some('\'')) => {
+                        Some('\\')
+                            if matches!(self.chars.clone().nth(1), Some('\\' | '"' | '\'')) =>
+                        {
                             self.consume();
                         }
                         Some(_) => {}
@@ -710,6 +774,9 @@ impl<'a> Iterator for Lexer<'a> {
                     self.current_span().clone()
                 }
             };
+            if matches!(kind, TokenKind::Opcode(_)) && OPCODE_ALIASES.contains_key(&self.slice()) {
+                self.aliases.push((self.slice(), new_span.clone()));
+            }
             let token = Token { kind, span: new_span };
             if token.kind != TokenKind::Whitespace {
                 self.lookback = Some(token.clone());
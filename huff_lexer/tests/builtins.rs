@@ -4,7 +4,23 @@ use std::ops::Deref;
 
 #[test]
 fn parses_builtin_function_in_macro_body() {
-    let builtin_funcs = ["__codesize", "__tablesize", "__tablestart", "__FUNC_SIG", "__EVENT_HASH"];
+    let builtin_funcs = [
+        "__codesize",
+        "__tablesize",
+        "__tablestart",
+        "__FUNC_SIG",
+        "__EVENT_HASH",
+        "__CREATE_ADDRESS",
+        "__CREATE2_ADDRESS",
+        "__MASK",
+        "__SHL_CONST",
+        "__RIGHTPAD",
+        "__VERBATIM",
+        "__DISPATCH",
+        "__CONSTRUCTOR_ARG",
+        "__IMMUTABLE",
+        "__SET_IMMUTABLE",
+    ];
 
     for builtin in builtin_funcs {
         let source = &format!(
@@ -69,7 +85,23 @@ fn parses_builtin_function_in_macro_body() {
 #[test]
 #[should_panic]
 fn fails_to_parse_builtin_outside_macro_body() {
-    let builtin_funcs = ["__codesize", "__tablesize", "__tablestart", "__FUNC_SIG", "__EVENT_HASH"];
+    let builtin_funcs = [
+        "__codesize",
+        "__tablesize",
+        "__tablestart",
+        "__FUNC_SIG",
+        "__EVENT_HASH",
+        "__CREATE_ADDRESS",
+        "__CREATE2_ADDRESS",
+        "__MASK",
+        "__SHL_CONST",
+        "__RIGHTPAD",
+        "__VERBATIM",
+        "__DISPATCH",
+        "__CONSTRUCTOR_ARG",
+        "__IMMUTABLE",
+        "__SET_IMMUTABLE",
+    ];
 
     for builtin in builtin_funcs {
         let source = &format!("{}(MAIN)", builtin);
@@ -95,3 +127,23 @@ fn fails_to_parse_builtin_outside_macro_body() {
         assert!(lexer.eof);
     }
 }
+
+#[test]
+fn lexes_custom_builtin_calls_in_macro_body() {
+    let source = r#"
+        #define macro TEST() = takes(0) returns(0) {
+            __PACK(0x01, 0x02) __HELPER()
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    lexer.custom_builtins = vec!["__PACK".to_string()];
+    let kinds = lexer
+        .map(|t| t.unwrap().kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace))
+        .collect::<Vec<TokenKind>>();
+
+    // Only registered names are builtins, other `__` prefixed calls are macro invocations
+    assert!(kinds.contains(&TokenKind::BuiltinFunction("__PACK".to_string())));
+    assert!(kinds.contains(&TokenKind::Ident("__HELPER".to_string())));
+}
@@ -76,3 +76,19 @@ fn converts_literal_to_hex_string() {
         assert_eq!(format!("0x{}", source), bytes32_to_string(&str_to_bytes32(source), true));
     }
 }
+
+#[test]
+fn fails_to_lex_literals_over_32_bytes() {
+    let source = &format!("0x{}", "ff".repeat(33));
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(
+        err,
+        LexicalError::new(
+            LexicalErrorKind::LiteralOverflow(source[2..].into()),
+            Span::new(2..source.len(), None)
+        )
+    );
+}
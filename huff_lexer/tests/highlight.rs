@@ -0,0 +1,71 @@
+use huff_lexer::highlight::*;
+
+#[test]
+fn classifies_semantic_tokens() {
+    let source = r#"// Transfer logic
+#define function transfer(address to, uint256) nonpayable returns ()
+#define constant SLOT = FREE_STORAGE_POINTER()
+#define jumptable TABLE { lab_0 }
+#define macro TRANSFER(err) = takes (0) returns (0) {
+    [SLOT] sload 0x01 add
+    __FUNC_SIG(transfer) HELPER(<err>)
+    done jump
+    done:
+}
+"#;
+
+    let classified = semantic_tokens(source)
+        .into_iter()
+        .map(|t| (&source[t.span.start..t.span.end], t.kind))
+        .collect::<Vec<(&str, SemanticTokenKind)>>();
+    assert_eq!(
+        classified,
+        vec![
+            ("// Transfer logic", SemanticTokenKind::Comment),
+            ("#define", SemanticTokenKind::Keyword),
+            ("function", SemanticTokenKind::Keyword),
+            ("transfer", SemanticTokenKind::Function),
+            ("address", SemanticTokenKind::Type),
+            ("to", SemanticTokenKind::Parameter),
+            ("uint256", SemanticTokenKind::Type),
+            ("nonpayable", SemanticTokenKind::Keyword),
+            ("returns", SemanticTokenKind::Keyword),
+            ("#define", SemanticTokenKind::Keyword),
+            ("constant", SemanticTokenKind::Keyword),
+            ("SLOT", SemanticTokenKind::Constant),
+            ("FREE_STORAGE_POINTER()", SemanticTokenKind::Keyword),
+            ("#define", SemanticTokenKind::Keyword),
+            ("jumptable", SemanticTokenKind::Keyword),
+            ("TABLE", SemanticTokenKind::Table),
+            ("lab_0", SemanticTokenKind::Label),
+            ("#define", SemanticTokenKind::Keyword),
+            ("macro", SemanticTokenKind::Keyword),
+            ("TRANSFER", SemanticTokenKind::Macro),
+            ("err", SemanticTokenKind::Parameter),
+            ("takes", SemanticTokenKind::Keyword),
+            ("0", SemanticTokenKind::Number),
+            ("returns", SemanticTokenKind::Keyword),
+            ("0", SemanticTokenKind::Number),
+            ("SLOT", SemanticTokenKind::Constant),
+            ("sload", SemanticTokenKind::Opcode),
+            ("0x01", SemanticTokenKind::Number),
+            ("add", SemanticTokenKind::Opcode),
+            ("__FUNC_SIG", SemanticTokenKind::Builtin),
+            ("transfer", SemanticTokenKind::Label),
+            ("HELPER", SemanticTokenKind::Macro),
+            ("err", SemanticTokenKind::Parameter),
+            ("done", SemanticTokenKind::Label),
+            ("jump", SemanticTokenKind::Opcode),
+            ("done", SemanticTokenKind::Label),
+        ]
+    );
+    assert_eq!(SemanticTokenKind::Builtin.name(), "builtin");
+}
+
+#[test]
+fn classifies_partial_sources() {
+    // Classification stops at the first lexical error
+    let source = "#define macro MAIN() = takes (0) returns (0) { 0x00 ~ mstore }";
+    let classified = semantic_tokens(source);
+    assert_eq!(classified.last().map(|t| t.kind), Some(SemanticTokenKind::Number));
+}
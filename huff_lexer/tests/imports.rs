@@ -181,3 +181,46 @@ fn include_with_string_single_quote() {
     assert_eq!(lexer.current_span().end, source.len());
     assert!(lexer.eof);
 }
+
+#[test]
+fn lex_std_imports() {
+    let source = r#"
+    #include <std/math.huff>
+    #include "./Utils.huff"
+    "#;
+    let lexed_imports = Lexer::lex_imports(source);
+    assert_eq!(lexed_imports, vec!["<std/math.huff>".to_string(), "./Utils.huff".to_string()]);
+}
+
+#[test]
+fn include_with_std_path() {
+    let source = "#include <std/math.huff>";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    // The bracketed path is lexed as a single string literal
+    assert_eq!(tokens[0].kind, TokenKind::Include);
+    assert_eq!(
+        tokens[2],
+        Token::new(TokenKind::Str("<std/math.huff>".to_string()), Span::new(9..24, None))
+    );
+
+    // Angle brackets are still lexed as such outside of includes
+    let source = "#define macro MAIN() = takes(0) returns(0) { <x> }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    assert!(tokens.iter().any(|t| t.kind == TokenKind::LeftAngle));
+}
+
+#[test]
+fn include_with_unterminated_std_path() {
+    let source = "#include <std/math.huff";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().collect::<Vec<Result<Token, LexicalError>>>();
+    assert!(tokens
+        .iter()
+        .any(|t| matches!(t, Err(LexicalError { kind: LexicalErrorKind::UnexpectedEof, .. }))));
+}
@@ -136,6 +136,37 @@ fn parses_event_keyword() {
     assert!(lexer.eof);
 }
 
+#[test]
+fn parses_error_keyword() {
+    let source = "#define error TestError(uint256)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let _ = lexer.next(); // #define
+    let _ = lexer.next(); // whitespace
+
+    // The error keyword is lexed in the definition
+    let tok = lexer.next();
+    let unwrapped = tok.unwrap().unwrap();
+    let error_span = Span::new(8..13, None);
+    assert_eq!(unwrapped, Token::new(TokenKind::Error, error_span.clone()));
+    assert_eq!(lexer.current_span().deref(), &error_span);
+
+    let _ = lexer.next(); // whitespace
+    let tok = lexer.next().unwrap().unwrap();
+    let name = TokenKind::Ident("TestError".to_string());
+    assert_eq!(tok, Token::new(name, Span::new(14..23, None)));
+    let _ = lexer.next(); // open parenthesis
+    let tok = lexer.next().unwrap().unwrap();
+    let uint = TokenKind::PrimitiveType(PrimitiveEVMType::Uint(256));
+    assert_eq!(tok, Token::new(uint, Span::new(24..31, None)));
+    let _ = lexer.next(); // close parenthesis
+
+    // We covered the whole source
+    assert_eq!(lexer.current_span().end, source.len());
+    assert!(lexer.eof);
+}
+
 #[test]
 fn parses_constant_keyword() {
     let source = "#define constant";
@@ -573,6 +604,8 @@ fn parses_keyword_arbitrary_whitespace() {
     let key_words = [
         ("macro", TokenKind::Macro),
         ("fn", TokenKind::Fn),
+        ("test", TokenKind::Test),
+        ("immutable", TokenKind::Immutable),
         ("constant", TokenKind::Constant),
         ("function", TokenKind::Function),
     ];
@@ -33,3 +33,19 @@ fn lexes_large_numbers() {
     assert_eq!(lexer.current_span().end, source.len());
     assert!(lexer.eof);
 }
+
+#[test]
+fn fails_to_lex_overflowing_numbers() {
+    let source = &format!("{}0", usize::MAX);
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(
+        err,
+        LexicalError::new(
+            LexicalErrorKind::NumberOverflow(source.as_str().into()),
+            Span::new(0..source.len(), None)
+        )
+    );
+}
@@ -1,7 +1,7 @@
 /// Tests lexing the Free Storage Pointer Keyword
 use huff_lexer::*;
 use huff_utils::{
-    evm::{OPCODES, OPCODES_MAP},
+    evm::{OPCODES, OPCODES_MAP, OPCODE_ALIASES},
     prelude::*,
 };
 
@@ -32,3 +32,37 @@ fn opcodes() {
         );
     }
 }
+
+#[test]
+fn opcode_aliases() {
+    let source = "#define macro TEST() = takes(0) returns(0) { sha3 difficulty suicide datahash }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source);
+    let opcodes = lexer
+        .by_ref()
+        .map(|x| x.unwrap())
+        .filter_map(|x| match x.kind {
+            TokenKind::Opcode(o) => Some(o),
+            _ => None,
+        })
+        .collect::<Vec<Opcode>>();
+
+    // Aliases are lexed as their canonical opcode, and recorded with their spans
+    assert_eq!(
+        opcodes,
+        ["sha3", "difficulty", "suicide", "datahash"]
+            .iter()
+            .map(|alias| OPCODES_MAP[OPCODE_ALIASES[alias]])
+            .collect::<Vec<Opcode>>()
+    );
+    assert_eq!(opcodes[0], Opcode::Keccak256);
+    assert_eq!(
+        lexer.aliases,
+        vec![
+            ("sha3".to_string(), Span::new(45..49, None)),
+            ("difficulty".to_string(), Span::new(50..60, None)),
+            ("suicide".to_string(), Span::new(61..68, None)),
+            ("datahash".to_string(), Span::new(69..77, None)),
+        ]
+    );
+}
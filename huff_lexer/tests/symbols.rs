@@ -281,3 +281,26 @@ fn lexes_comma_sparse() {
     assert_eq!(lexer.current_span().end, source.len());
     assert!(lexer.eof);
 }
+
+#[test]
+fn lexes_escaped_quotes_in_strings() {
+    let source = r#""say \"hi\"" 'it\'s' "a\\" "it\'s""#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let strings = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|t| t.kind != TokenKind::Whitespace && t.kind != TokenKind::Eof)
+        .collect::<Vec<Token>>();
+
+    // Escapes are kept in the token, and only end the string when the backslash is escaped
+    assert_eq!(
+        strings,
+        vec![
+            Token::new(TokenKind::Str(r#"say \"hi\""#.to_string()), Span::new(0..12, None)),
+            Token::new(TokenKind::Str(r"it\'s".to_string()), Span::new(13..20, None)),
+            Token::new(TokenKind::Str(r"a\\".to_string()), Span::new(21..26, None)),
+            Token::new(TokenKind::Str(r"it\'s".to_string()), Span::new(27..34, None)),
+        ]
+    );
+}
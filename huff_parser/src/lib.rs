@@ -2,12 +2,12 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 #![forbid(unsafe_code)]
-#![forbid(where_clauses_object_safety)]
 
 use huff_utils::{
     ast::*,
     error::*,
-    prelude::{str_to_bytes32, FileSource, Span},
+    prelude::{bytes32_to_string, int_to_bytes32, str_to_bytes32, FileSource, Remapping, Span},
+    stdlib::{is_std_import, std_source},
     token::{Token, TokenKind},
     types::*,
 };
@@ -28,20 +28,29 @@ pub struct Parser {
     pub base: Option<String>,
     /// A collection of current spans
     pub spans: Vec<Span>,
+    /// Import path remappings
+    pub remappings: Vec<Remapping>,
 }
 
 impl Parser {
     /// Public associated function that instantiates a Parser.
     pub fn new(tokens: Vec<Token>, base: Option<String>) -> Self {
-        let initial_token = tokens.get(0).unwrap().clone();
-        Self { tokens, cursor: 0, current_token: initial_token, base, spans: vec![] }
+        let initial_token =
+            tokens.first().cloned().unwrap_or(Token::new(TokenKind::Eof, Span::EOF));
+        Self {
+            tokens,
+            cursor: 0,
+            current_token: initial_token,
+            base,
+            spans: vec![],
+            remappings: Remapping::project(),
+        }
     }
 
     /// Resets the current token and cursor to the first token in the parser's token vec
-    ///
-    /// PANICS if the tokens vec is empty!
     pub fn reset(&mut self) {
-        self.current_token = self.tokens.get(0).unwrap().clone();
+        self.current_token =
+            self.tokens.first().cloned().unwrap_or(Token::new(TokenKind::Eof, Span::EOF));
         self.cursor = 0;
     }
 
@@ -83,6 +92,11 @@ impl Parser {
                     tracing::info!(target: "parser", "SUCCESSFULLY PARSED EVENT {}", ev.name);
                     contract.events.push(ev);
                 }
+                TokenKind::Error => {
+                    let err = self.parse_custom_error()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED ERROR {}", err.name);
+                    contract.errors.push(err);
+                }
                 TokenKind::Constant => {
                     let c = self.parse_constant()?;
                     tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONSTANT {}", c.name);
@@ -93,6 +107,16 @@ impl Parser {
                     tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
                     contract.macros.push(m);
                 }
+                TokenKind::Immutable => {
+                    let i = self.parse_immutable()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED IMMUTABLE {}", i.name);
+                    contract.immutables.push(i);
+                }
+                TokenKind::Test => {
+                    let m = self.parse_macro()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED TEST {}", m.name);
+                    contract.tests.push(m);
+                }
                 TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
                     contract.tables.push(self.parse_table()?);
                 }
@@ -104,7 +128,7 @@ impl Parser {
                     );
                     return Err(ParserError {
                         kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
-                        hint: Some("Definition must be one of: `function`, `event`, `constant`, `macro`, or `fn`.".to_string()),
+                        hint: Some("Definition must be one of: `function`, `event`, `error`, `constant`, `immutable`, `macro`, `fn`, or `test`.".to_string()),
                         spans: AstSpan(vec![self.current_token.span.clone()]),
                     })
                 }
@@ -138,19 +162,21 @@ impl Parser {
 
         // Localize import path using out base
         p = match &self.base {
-            Some(b) => FileSource::localize_file(b, &p).unwrap_or_default().replacen(
-                "contracts/contracts",
-                "contracts",
-                1,
-            ),
+            Some(b) => FileSource::resolve_import(b, &p, &self.remappings)
+                .unwrap_or_default()
+                .replacen("contracts/contracts", "contracts", 1),
             None => p,
         };
         tracing::info!(target: "parser", "LOCALIZED IMPORT: {}", p);
 
         let path = Path::new(&p);
 
-        // Validate that a file @ the path exists
-        if !(path.exists() && path.is_file() && path.to_str().unwrap().ends_with(".huff")) {
+        // Validate that a file @ the path exists, or is embedded in the standard library
+        let exists = match is_std_import(&p) {
+            true => std_source(&p).is_some(),
+            false => path.exists() && path.is_file() && p.ends_with(".huff"),
+        };
+        if !exists {
             tracing::error!(target: "parser", "INVALID IMPORT PATH: {:?}", path.to_str());
             let new_spans = self.spans.clone();
             self.spans = vec![];
@@ -188,14 +214,15 @@ impl Parser {
     /// Consumes the next token.
     pub fn consume(&mut self) {
         self.spans.push(self.current_token.span.clone());
-        self.current_token = self.peek().unwrap();
+        if let Some(token) = self.peek() {
+            self.current_token = token;
+        }
         self.cursor += 1;
     }
 
     /// Consumes following tokens until not contained in the kinds vec of TokenKinds.
     pub fn consume_all(&mut self, kinds: Vec<TokenKind>) {
-        loop {
-            let token = self.peek().unwrap();
+        while let Some(token) = self.peek() {
             if !kinds.contains(&token.kind) {
                 break
             }
@@ -206,20 +233,12 @@ impl Parser {
 
     /// Take a look at next token without consuming.
     pub fn peek(&mut self) -> Option<Token> {
-        if self.cursor >= self.tokens.len() {
-            None
-        } else {
-            Some(self.tokens.get(self.cursor + 1).unwrap().clone())
-        }
+        self.tokens.get(self.cursor + 1).cloned()
     }
 
     /// Take a look at the previous token.
     pub fn peek_behind(&self) -> Option<Token> {
-        if self.cursor == 0 || self.cursor > self.tokens.len() {
-            None
-        } else {
-            Some(self.tokens.get(self.cursor - 1).unwrap().clone())
-        }
+        self.cursor.checked_sub(1).and_then(|i| self.tokens.get(i)).cloned()
     }
 
     /// Parses a function.
@@ -271,7 +290,7 @@ impl Parser {
         let mut signature = [0u8; 4]; // Only keep first 4 bytes
         let mut hasher = Keccak::v256();
         let input_types =
-            inputs.iter().map(|i| i.arg_type.as_ref().unwrap().clone()).collect::<Vec<_>>();
+            inputs.iter().map(|i| i.arg_type.clone().unwrap_or_default()).collect::<Vec<_>>();
         hasher.update(format!("{}({})", name, input_types.join(",")).as_bytes());
         hasher.finalize(&mut signature);
 
@@ -312,13 +331,47 @@ impl Parser {
         let mut hash = [0u8; 32];
         let mut hasher = Keccak::v256();
         let input_types =
-            parameters.iter().map(|i| i.arg_type.as_ref().unwrap().clone()).collect::<Vec<_>>();
+            parameters.iter().map(|i| i.arg_type.clone().unwrap_or_default()).collect::<Vec<_>>();
         hasher.update(format!("{}({})", name, input_types.join(",")).as_bytes());
         hasher.finalize(&mut hash);
 
         Ok(Event { name, parameters, span: AstSpan(self.spans.clone()), hash })
     }
 
+    /// Parse a custom error.
+    pub fn parse_custom_error(&mut self) -> Result<ErrorDefinition, ParserError> {
+        // The error should start with `TokenKind::Error`
+        self.match_kind(TokenKind::Error)?;
+
+        // Parse the error name
+        self.match_kind(TokenKind::Ident("x".to_string()))?;
+        let tok = self.peek_behind().unwrap().kind;
+
+        let name = match tok {
+            TokenKind::Ident(error_name) => error_name,
+            _ => {
+                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED IDENT, GOT: {}", tok);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidName(tok.clone()),
+                    hint: Some(format!("Expected error name, found: \"{}\"", tok)),
+                    spans: AstSpan(self.spans.clone()),
+                })
+            }
+        };
+
+        // Parse the error's parameters
+        let parameters: Vec<Argument> = self.parse_args(true, true, false, false)?;
+
+        let mut selector = [0u8; 4];
+        let mut hasher = Keccak::v256();
+        let input_types =
+            parameters.iter().map(|i| i.arg_type.clone().unwrap_or_default()).collect::<Vec<_>>();
+        hasher.update(format!("{}({})", name, input_types.join(",")).as_bytes());
+        hasher.finalize(&mut selector);
+
+        Ok(ErrorDefinition { name, parameters, span: AstSpan(self.spans.clone()), selector })
+    }
+
     /// Parse a constant.
     pub fn parse_constant(&mut self) -> Result<ConstantDefinition, ParserError> {
         // Constant Identifier
@@ -344,34 +397,126 @@ impl Parser {
         // We must assign a value to the constant
         self.match_kind(TokenKind::Assign)?;
 
-        let value: ConstVal = match self.current_token.kind.clone() {
+        let value: ConstVal = match self.current_token.kind {
             TokenKind::FreeStoragePointer => {
                 self.consume();
                 ConstVal::FreeStoragePointer(FreeStoragePointer {})
             }
+            _ => match self.parse_constant_expression()? {
+                ConstExpr::Literal(l) => ConstVal::Literal(l),
+                expr => ConstVal::Expression(expr),
+            },
+        };
+
+        // Clone spans and set to nothing
+        let new_spans = self.spans.clone();
+        self.spans = vec![];
+
+        // Return the Constant Definition
+        Ok(ConstantDefinition { name, value, span: AstSpan(new_spans) })
+    }
+
+    /// Parses a constant expression.
+    ///
+    /// Expressions are made of literals, other constants, parentheses, the `+`, `-`, `*` and `/`
+    /// operators with the usual precedence, and calls to `ADD`, `SUB`, `MUL`, `DIV` and `MOD`.
+    pub fn parse_constant_expression(&mut self) -> Result<ConstExpr, ParserError> {
+        let mut expr = self.parse_constant_term()?;
+        loop {
+            let op = match self.current_token.kind {
+                TokenKind::Add => ConstOp::Add,
+                TokenKind::Sub => ConstOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.consume();
+            expr = ConstExpr::Operation(op, Box::new(expr), Box::new(self.parse_constant_term()?));
+        }
+    }
+
+    /// Parses a product or quotient of a constant expression.
+    fn parse_constant_term(&mut self) -> Result<ConstExpr, ParserError> {
+        let mut expr = self.parse_constant_factor()?;
+        loop {
+            let op = match self.current_token.kind {
+                TokenKind::Mul => ConstOp::Mul,
+                TokenKind::Div => ConstOp::Div,
+                _ => return Ok(expr),
+            };
+            self.consume();
+            expr =
+                ConstExpr::Operation(op, Box::new(expr), Box::new(self.parse_constant_factor()?));
+        }
+    }
+
+    /// Parses an operand of a constant expression.
+    fn parse_constant_factor(&mut self) -> Result<ConstExpr, ParserError> {
+        match self.current_token.kind.clone() {
             TokenKind::Literal(l) => {
                 self.consume();
-                ConstVal::Literal(l)
+                Ok(ConstExpr::Literal(l))
+            }
+            TokenKind::Num(n) => {
+                self.consume();
+                Ok(ConstExpr::Literal(str_to_bytes32(&format!("{:x}", n))))
+            }
+            // Negative decimals are stored in two's complement
+            TokenKind::Sub if matches!(self.peek().map(|t| t.kind), Some(TokenKind::Num(_))) => {
+                self.consume();
+                let n = self.match_kind(TokenKind::Num(0))?;
+                let value = int_to_bytes32(&format!("-{}", n), 256).map_err(|e| ParserError {
+                    kind: ParserErrorKind::InvalidConstantValue(n),
+                    hint: Some(e),
+                    spans: AstSpan(self.spans.clone()),
+                })?;
+                Ok(ConstExpr::Literal(value))
+            }
+            TokenKind::Ident(name) => {
+                let span = self.current_token.span.clone();
+                self.consume();
+                match ConstOp::from_function(&name) {
+                    Some(op) if self.current_token.kind == TokenKind::OpenParen => {
+                        self.consume();
+                        let lhs = self.parse_constant_expression()?;
+                        self.match_kind(TokenKind::Comma)?;
+                        let rhs = self.parse_constant_expression()?;
+                        self.match_kind(TokenKind::CloseParen)?;
+                        Ok(ConstExpr::Operation(op, Box::new(lhs), Box::new(rhs)))
+                    }
+                    _ => Ok(ConstExpr::Constant(name, AstSpan(vec![span]))),
+                }
+            }
+            TokenKind::OpenParen => {
+                self.consume();
+                let expr = self.parse_constant_expression()?;
+                self.match_kind(TokenKind::CloseParen)?;
+                Ok(expr)
             }
             kind => {
                 tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED FreeStoragePointer OR Literal, GOT: {}", self.current_token.kind);
-                return Err(ParserError {
+                Err(ParserError {
                     kind: ParserErrorKind::InvalidConstantValue(kind),
                     hint: Some(
-                        "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                        "Expected constant value to be a literal, an expression, or `FREE_STORAGE_POINTER()`"
                             .to_string(),
                     ),
                     spans: AstSpan(vec![self.current_token.span.clone()]),
                 })
             }
-        };
+        }
+    }
+
+    /// Parses an immutable.
+    ///
+    /// It should parse the following : immutable IMMUTABLE_NAME
+    pub fn parse_immutable(&mut self) -> Result<ImmutableDefinition, ParserError> {
+        self.match_kind(TokenKind::Immutable)?;
+        let name = self.match_kind(TokenKind::Ident("IMMUTABLE_NAME".to_string()))?.to_string();
 
         // Clone spans and set to nothing
         let new_spans = self.spans.clone();
         self.spans = vec![];
 
-        // Return the Constant Definition
-        Ok(ConstantDefinition { name, value, span: AstSpan(new_spans) })
+        Ok(ImmutableDefinition { name, span: AstSpan(new_spans) })
     }
 
     /// Parses a macro.
@@ -379,7 +524,10 @@ impl Parser {
     /// It should parse the following : macro MACRO_NAME(args...) = takes (x) returns (n) {...}
     pub fn parse_macro(&mut self) -> Result<MacroDefinition, ParserError> {
         let outlined = self.check(TokenKind::Fn);
-        self.match_kind(if outlined { TokenKind::Fn } else { TokenKind::Macro })?;
+        match self.check(TokenKind::Test) {
+            true => self.match_kind(TokenKind::Test)?,
+            false => self.match_kind(if outlined { TokenKind::Fn } else { TokenKind::Macro })?,
+        };
         let macro_name: String =
             self.match_kind(TokenKind::Ident("MACRO_NAME".to_string()))?.to_string();
         tracing::info!(target: "parser", "PARSING MACRO: \"{}\"", macro_name);
@@ -421,6 +569,15 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Str(s) => {
+                    let curr_spans = vec![self.current_token.span.clone()];
+                    tracing::info!(target: "parser", "PARSING MACRO BODY: [STRING: {}]", s);
+                    self.consume();
+                    statements.push(Statement {
+                        ty: StatementType::StringLiteral(unescape(&s)),
+                        span: AstSpan(curr_spans),
+                    });
+                }
                 TokenKind::Opcode(o) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING MACRO BODY: [OPCODE: {}]", o);
@@ -549,6 +706,15 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Str(s) => {
+                    let curr_spans = vec![self.current_token.span.clone()];
+                    tracing::info!(target: "parser", "PARSING LABEL BODY: [STRING: {}]", s);
+                    self.consume();
+                    statements.push(Statement {
+                        ty: StatementType::StringLiteral(unescape(&s)),
+                        span: AstSpan(curr_spans),
+                    });
+                }
                 TokenKind::Opcode(o) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING LABEL BODY: [OPCODE: {}]", o);
@@ -666,6 +832,35 @@ impl Parser {
                 continue
             }
 
+            // Builtins evaluated at compile time accept literals and numbers as input, which are
+            // placed in the "name" field as a prefixed hex string.
+            let literal = match &self.current_token.kind {
+                TokenKind::Literal(l) => Some(*l),
+                TokenKind::Num(n) => Some(str_to_bytes32(&format!("{:x}", n))),
+                _ => None,
+            };
+            if let Some(l) = literal {
+                if !is_builtin {
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidArgs(self.current_token.kind.clone()),
+                        hint: None,
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    })
+                }
+
+                args.push(Argument {
+                    name: Some(bytes32_to_string(&l, true)),
+                    arg_type: None,
+                    indexed: false,
+                    span: AstSpan(vec![self.current_token.span.clone()]),
+                });
+                self.consume();
+                if self.check(TokenKind::Comma) {
+                    self.consume();
+                }
+                continue
+            }
+
             let mut arg = Argument::default();
             let mut arg_spans = vec![];
 
@@ -860,6 +1055,14 @@ impl Parser {
                     });
                     self.consume();
                 }
+                // Strings are placed in code tables as is, without padding
+                TokenKind::Str(s) if is_code_table => {
+                    statements.push(Statement {
+                        ty: StatementType::Code(hex::encode(unescape(s))),
+                        span: AstSpan(new_spans),
+                    });
+                    self.consume();
+                }
                 kind => {
                     tracing::error!("Invalid Table Body Token: {:?}", kind);
                     return Err(ParserError {
@@ -1004,3 +1207,17 @@ impl Parser {
         }
     }
 }
+
+/// Removes the escapes the lexer allows in string literals, `\"`, `\'` and `\\`, which are the same
+/// in strings quoted with either `"` or `'`
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('\\' | '"' | '\'')) => unescaped.extend(chars.next()),
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
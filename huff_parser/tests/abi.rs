@@ -23,3 +23,23 @@ fn build_abi_from_ast() {
     );
     assert_eq!(abi.functions.get("test").unwrap().inputs[1].kind, FunctionParamType::String);
 }
+
+#[test]
+fn build_abi_errors_from_ast() {
+    let source = "#define error InsufficientBalance(address account, uint256)";
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    let abi = Abi::from(contract);
+
+    let error = abi.errors.get("InsufficientBalance").unwrap();
+    assert_eq!(error.signature(), "InsufficientBalance(address,uint256)");
+    assert_eq!(error.inputs[0].name, "account");
+
+    let json = json_abi(Some(&abi));
+    assert_eq!(json[0]["type"], "error");
+    assert_eq!(json[0]["inputs"][1]["type"], "uint256");
+}
@@ -60,3 +60,108 @@ fn test_parses_literal_constant() {
         }
     );
 }
+
+#[test]
+fn test_parses_decimal_constants() {
+    let source = r#"
+        #define constant POSITIVE = 1000
+        #define constant NEGATIVE = -1
+        #define constant NEGATIVE_LARGE = -1000
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    // Negative values are stored in two's complement
+    let values =
+        contract.constants.borrow().iter().map(|c| c.value.clone()).collect::<Vec<ConstVal>>();
+    assert_eq!(
+        values,
+        vec![
+            ConstVal::Literal(str_to_bytes32("03e8")),
+            ConstVal::Literal([0xff; 32]),
+            ConstVal::Literal(str_to_bytes32(
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffc18"
+            )),
+        ]
+    );
+}
+
+#[test]
+fn test_parses_constant_expressions() {
+    let source = r#"
+        #define constant TOTAL = ADD(BASE, 0x20)
+        #define constant INFIX = BASE + 2 * (OFFSET - 1)
+        #define constant ALIAS = BASE
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    // The nth reference to a constant, with its span
+    let constant = |name: &str, nth: usize| {
+        let start = source.match_indices(name).nth(nth).unwrap().0;
+        Box::new(ConstExpr::Constant(
+            name.to_string(),
+            AstSpan(vec![Span { start, end: start + name.len(), file: None }]),
+        ))
+    };
+    let literal = |hex: &str| Box::new(ConstExpr::Literal(str_to_bytes32(hex)));
+    let values =
+        contract.constants.borrow().iter().map(|c| c.value.clone()).collect::<Vec<ConstVal>>();
+
+    // Multiplication binds tighter than addition
+    assert_eq!(
+        values,
+        vec![
+            ConstVal::Expression(ConstExpr::Operation(
+                ConstOp::Add,
+                constant("BASE", 0),
+                literal("20")
+            )),
+            ConstVal::Expression(ConstExpr::Operation(
+                ConstOp::Add,
+                constant("BASE", 1),
+                Box::new(ConstExpr::Operation(
+                    ConstOp::Mul,
+                    literal("02"),
+                    Box::new(ConstExpr::Operation(
+                        ConstOp::Sub,
+                        constant("OFFSET", 0),
+                        literal("01")
+                    ))
+                ))
+            )),
+            ConstVal::Expression(*constant("BASE", 2)),
+        ]
+    );
+}
+
+#[test]
+fn test_parses_immutable() {
+    let source = "#define immutable OWNER";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    assert_eq!(
+        contract.immutables,
+        vec![ImmutableDefinition {
+            name: "OWNER".to_string(),
+            span: AstSpan(vec![
+                Span { start: 0, end: 7, file: None },
+                Span { start: 8, end: 17, file: None },
+                Span { start: 18, end: 23, file: None }
+            ])
+        }]
+    );
+}
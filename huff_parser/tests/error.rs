@@ -0,0 +1,65 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{ast::ErrorDefinition, prelude::*};
+
+#[test]
+fn test_parse_custom_error() {
+    let source = "#define error Panic(uint256 code)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    assert_eq!(
+        contract.errors,
+        vec![ErrorDefinition {
+            name: "Panic".to_string(),
+            parameters: vec![Argument {
+                arg_type: Some(String::from("uint256")),
+                name: Some(String::from("code")),
+                indexed: false,
+                span: AstSpan(vec![
+                    // "uint256"
+                    Span { start: 20, end: 27, file: None },
+                    // "code"
+                    Span { start: 28, end: 32, file: None },
+                ]),
+            }],
+            span: AstSpan(vec![
+                // "#define"
+                Span { start: 0, end: 7, file: None },
+                // "error"
+                Span { start: 8, end: 13, file: None },
+                // "Panic"
+                Span { start: 14, end: 19, file: None },
+                // "("
+                Span { start: 19, end: 20, file: None },
+                // "uint256"
+                Span { start: 20, end: 27, file: None },
+                // "code"
+                Span { start: 28, end: 32, file: None },
+                // ")"
+                Span { start: 32, end: 33, file: None },
+            ]),
+            // `Panic(uint256)` selector = 0x4e487b71
+            selector: [0x4e, 0x48, 0x7b, 0x71],
+        }]
+    );
+}
+
+#[test]
+fn test_parse_custom_error_without_parameters() {
+    let source = "#define error Unauthorized()";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let contract = parser.parse().unwrap();
+
+    assert_eq!(contract.errors.len(), 1);
+    assert_eq!(contract.errors[0].name, "Unauthorized");
+    assert!(contract.errors[0].parameters.is_empty());
+    // `Unauthorized()` selector = 0x82b42900
+    assert_eq!(contract.errors[0].selector, [0x82, 0xb4, 0x29, 0x00]);
+}
@@ -716,6 +716,24 @@ fn empty_outlined_macro() {
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 }
 
+#[test]
+fn test_macro() {
+    let source = "#define test TEST_ADD() = takes(0) returns(0) { 0x01 0x02 add }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    // Tests are kept apart from the contract's macros
+    let contract = parser.parse().unwrap();
+    assert!(contract.macros.is_empty());
+    assert_eq!(contract.tests.len(), 1);
+    assert_eq!(contract.tests[0].name, "TEST_ADD");
+    assert_eq!(contract.tests[0].statements.len(), 3);
+    assert!(!contract.tests[0].outlined);
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+}
+
 #[test]
 fn outlined_macro_with_simple_body() {
     let source = "#define fn HELLO_WORLD() = takes(3) returns(0) {\n0x00 mstore\n 0x01 0x02 add\n}";
@@ -781,3 +799,40 @@ fn outlined_macro_with_simple_body() {
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 }
+
+#[test]
+fn macro_with_string_literals() {
+    let source = r#"#define macro ERR() = takes(0) returns(1) { "NOPE" end: 'a \'b\'' }"#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    assert_eq!(
+        macro_definition.statements,
+        vec![
+            Statement {
+                ty: StatementType::StringLiteral("NOPE".to_string()),
+                span: AstSpan(vec![Span { start: 44, end: 50, file: None }]),
+            },
+            Statement {
+                ty: StatementType::Label(Label {
+                    name: "end".to_string(),
+                    inner: vec![Statement {
+                        ty: StatementType::StringLiteral("a 'b'".to_string()),
+                        span: AstSpan(vec![Span { start: 56, end: 65, file: None }]),
+                    }],
+                    span: AstSpan(vec![
+                        Span { start: 51, end: 54, file: None },
+                        Span { start: 56, end: 65, file: None }
+                    ]),
+                }),
+                span: AstSpan(vec![
+                    Span { start: 51, end: 54, file: None },
+                    Span { start: 56, end: 65, file: None }
+                ]),
+            },
+        ]
+    );
+}
@@ -77,3 +77,34 @@ fn derives_storage_pointers() {
     );
     assert_eq!(contract.constants.borrow()[2].value, ConstVal::Literal(str_to_bytes32("a57B")));
 }
+
+#[test]
+fn derives_storage_pointers_once_across_expansions() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant BALANCE = FREE_STORAGE_POINTER()
+
+    #define macro LOAD_BALANCE() = takes (0) returns (1) {
+        [BALANCE] sload
+    }
+
+    #define macro MAIN() = takes (0) returns (0) {
+        LOAD_BALANCE()
+        [OWNER] sload
+        LOAD_BALANCE()
+        [BALANCE] sload
+    }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None);
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // Slots increment in order of first use, and every later expansion reuses the same slot
+    let constants = contract.constants.borrow();
+    assert_eq!(constants[0].value, ConstVal::Literal(str_to_bytes32("1")));
+    assert_eq!(constants[1].value, ConstVal::Literal(str_to_bytes32("0")));
+}
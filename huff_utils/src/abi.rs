@@ -29,13 +29,17 @@
 //!         span: AstSpan(vec![]),
 //!     }],
 //!     events: vec![],
+//!     errors: vec![],
 //!     tables: vec![],
+//!     tests: vec![],
+//!     immutables: vec![],
 //! };
 //!
 //! // Create an ABI using that generate contract
 //! let abi: Abi = contract.into();
 //! ```
 
+use ethers_core::utils::{hex, keccak256};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt};
 
@@ -52,6 +56,9 @@ pub struct Abi {
     pub functions: BTreeMap<String, Function>,
     /// A list of events and their definitions
     pub events: BTreeMap<String, Event>,
+    /// A list of custom errors and their definitions
+    #[serde(default)]
+    pub errors: BTreeMap<String, AbiError>,
     /// If the contract defines receive logic
     pub receive: bool,
     /// If the contract defines fallback logic
@@ -63,6 +70,145 @@ impl Abi {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Maps each function's canonical signature to its hex encoded 4 byte selector, like solc's
+    /// `methodIdentifiers`.
+    pub fn method_identifiers(&self) -> BTreeMap<String, String> {
+        self.functions
+            .values()
+            .map(|f| {
+                let signature = f.signature();
+                let selector = hex::encode(&keccak256(signature.as_bytes())[..4]);
+                (signature, selector)
+            })
+            .collect()
+    }
+
+    /// Maps each event's canonical signature to its hex encoded topic.
+    pub fn event_topics(&self) -> BTreeMap<String, String> {
+        self.events
+            .values()
+            .map(|e| {
+                let signature = e.signature();
+                let topic = hex::encode(keccak256(signature.as_bytes()));
+                (signature, topic)
+            })
+            .collect()
+    }
+
+    /// Maps each custom error's canonical signature to its hex encoded 4 byte selector.
+    pub fn error_selectors(&self) -> BTreeMap<String, String> {
+        self.errors
+            .values()
+            .map(|e| {
+                let signature = e.signature();
+                let selector = hex::encode(&keccak256(signature.as_bytes())[..4]);
+                (signature, selector)
+            })
+            .collect()
+    }
+
+    /// Compares this abi against a previous build's abi.
+    ///
+    /// Returns every breaking interface change: removed or changed function signatures, changed
+    /// function mutability, and removed or changed event topics. Added functions and events are
+    /// compatible and not reported.
+    pub fn compare(&self, previous: &Abi) -> Vec<AbiChange> {
+        let mut changes = vec![];
+        for (name, prev) in &previous.functions {
+            match self.functions.get(name) {
+                Some(curr) => {
+                    // Like the inputs in the signature, outputs are compared by their types alone
+                    let changed_outputs = !curr
+                        .outputs
+                        .iter()
+                        .map(|o| &o.kind)
+                        .eq(prev.outputs.iter().map(|o| &o.kind));
+                    if curr.signature() != prev.signature() || changed_outputs {
+                        changes.push(AbiChange::FunctionChanged {
+                            previous: prev.to_string(),
+                            current: curr.to_string(),
+                        });
+                    } else if curr.state_mutability != prev.state_mutability {
+                        changes.push(AbiChange::MutabilityChanged {
+                            function: prev.signature(),
+                            previous: prev.state_mutability.clone(),
+                            current: curr.state_mutability.clone(),
+                        });
+                    }
+                }
+                None => changes.push(AbiChange::FunctionRemoved(prev.to_string())),
+            }
+        }
+        for (name, prev) in &previous.events {
+            match self.events.get(name) {
+                Some(curr) => {
+                    if curr.to_string() != prev.to_string() || curr.anonymous != prev.anonymous {
+                        changes.push(AbiChange::EventChanged {
+                            previous: prev.to_string(),
+                            current: curr.to_string(),
+                        });
+                    }
+                }
+                None => changes.push(AbiChange::EventRemoved(prev.to_string())),
+            }
+        }
+        changes
+    }
+}
+
+/// #### AbiChange
+///
+/// A breaking change between two abis.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AbiChange {
+    /// A function was removed
+    FunctionRemoved(String),
+    /// A function's inputs or outputs changed
+    FunctionChanged {
+        /// The previous function definition
+        previous: String,
+        /// The current function definition
+        current: String,
+    },
+    /// A function's state mutability changed
+    MutabilityChanged {
+        /// The function signature
+        function: String,
+        /// The previous state mutability
+        previous: FunctionType,
+        /// The current state mutability
+        current: FunctionType,
+    },
+    /// An event was removed
+    EventRemoved(String),
+    /// An event's topics changed
+    EventChanged {
+        /// The previous event definition
+        previous: String,
+        /// The current event definition
+        current: String,
+    },
+}
+
+impl fmt::Display for AbiChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbiChange::FunctionRemoved(func) => write!(f, "function \"{}\" removed", func),
+            AbiChange::FunctionChanged { previous, current } => {
+                write!(f, "function \"{}\" changed to \"{}\"", previous, current)
+            }
+            AbiChange::MutabilityChanged { function, previous, current } => write!(
+                f,
+                "function \"{}\" mutability changed from {:?} to {:?}",
+                function, previous, current
+            ),
+            AbiChange::EventRemoved(event) => write!(f, "event \"{}\" removed", event),
+            AbiChange::EventChanged { previous, current } => {
+                write!(f, "event \"{}\" changed to \"{}\"", previous, current)
+            }
+        }
+    }
 }
 
 // Allows for simple ABI Generation by directly translating the AST
@@ -76,7 +222,7 @@ impl From<ast::Contract> for Abi {
             .filter(|m| m.name.to_lowercase() == "constructor")
             .cloned()
             .collect::<Vec<ast::Function>>()
-            .get(0)
+            .first()
             .map(|func| Constructor {
                 inputs: func
                     .inputs
@@ -95,7 +241,7 @@ impl From<ast::Contract> for Abi {
                     .filter(|m| m.name == "CONSTRUCTOR")
                     .cloned()
                     .collect::<Vec<ast::MacroDefinition>>()
-                    .get(0)
+                    .first()
                     .map(|func| Constructor {
                         inputs: func
                             .parameters
@@ -177,7 +323,25 @@ impl From<ast::Contract> for Abi {
                 let _ = events.insert(val.0, val.1);
             });
 
-        Self { constructor, functions, events, receive: false, fallback: false }
+        // Translate contract errors
+        let errors = contract
+            .errors
+            .iter()
+            .map(|error| {
+                let inputs = error
+                    .parameters
+                    .iter()
+                    .map(|argument| FunctionParam {
+                        name: argument.name.clone().unwrap_or_default(),
+                        kind: argument.arg_type.clone().unwrap_or_default().into(),
+                        internal_type: None,
+                    })
+                    .collect();
+                (error.name.to_string(), AbiError { name: error.name.to_string(), inputs })
+            })
+            .collect();
+
+        Self { constructor, functions, events, errors, receive: false, fallback: false }
     }
 }
 
@@ -198,6 +362,28 @@ pub struct Function {
     pub state_mutability: FunctionType,
 }
 
+impl Function {
+    /// Returns the canonical function signature, ie. `transfer(address,uint256)`
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs.iter().map(|i| i.kind.to_string()).collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} returns ({})",
+            self.signature(),
+            self.outputs.iter().map(|o| o.kind.to_string()).collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
 /// #### Event
 ///
 /// An Event definition.
@@ -211,6 +397,54 @@ pub struct Event {
     pub anonymous: bool,
 }
 
+impl Event {
+    /// Returns the canonical event signature, ie. `Transfer(address,address,uint256)`
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs.iter().map(|i| i.kind.to_string()).collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.name,
+            self.inputs
+                .iter()
+                .map(|i| if i.indexed { format!("{} indexed", i.kind) } else { i.kind.to_string() })
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+/// #### AbiError
+///
+/// A custom error definition.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct AbiError {
+    /// The error name
+    pub name: String,
+    /// The error inputs
+    pub inputs: Vec<FunctionParam>,
+}
+
+impl AbiError {
+    /// Returns the canonical error signature, ie. `InsufficientBalance(uint256)`
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs.iter().map(|i| i.kind.to_string()).collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
 /// #### EventParam
 ///
 /// Event parameters.
@@ -313,9 +547,7 @@ impl FunctionParamType {
                 fpt,
                 sizes
                     .iter()
-                    .map(|s| (!s.eq(&0))
-                        .then(|| format!("[{}]", s))
-                        .unwrap_or_else(|| "[]".to_string()))
+                    .map(|s| if !s.eq(&0) { format!("[{}]", s) } else { "[]".to_string() })
                     .collect::<Vec<_>>()
                     .join("")
             ),
@@ -339,14 +571,14 @@ impl FunctionParamType {
                 .map(|x| if x.is_empty() { "0".to_owned() } else { x })
                 .collect();
             let func_type = FunctionParamType::convert_string_to_type(&cleaned.remove(0))?;
-            let sizes: Vec<usize> = cleaned.iter().map(|x| x.parse::<usize>().unwrap()).collect();
+            let sizes = cleaned.iter().map(|x| x.parse::<usize>()).collect::<Result<_, _>>()?;
             return Ok(Self::Array(Box::new(func_type), sizes))
         }
         if input.starts_with("uint") {
             // Default to 256 if no size
             let size = match input.get(4..input.len()) {
                 Some(s) => match s.is_empty() {
-                    false => s.parse::<usize>().unwrap(),
+                    false => s.parse::<usize>()?,
                     true => 256,
                 },
                 None => 256,
@@ -357,7 +589,7 @@ impl FunctionParamType {
             // Default to 256 if no size
             let size = match input.get(3..input.len()) {
                 Some(s) => match s.is_empty() {
-                    false => s.parse::<usize>().unwrap(),
+                    false => s.parse::<usize>()?,
                     true => 256,
                 },
                 None => 256,
@@ -365,7 +597,7 @@ impl FunctionParamType {
             return Ok(Self::Int(size))
         }
         if input.starts_with("bytes") && input.len() != 5 {
-            let size = input.get(5..input.len()).unwrap().parse::<usize>().unwrap();
+            let size = input[5..].parse::<usize>()?;
             return Ok(Self::FixedBytes(size))
         }
         if input.starts_with("bool") {
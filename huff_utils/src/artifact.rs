@@ -2,11 +2,21 @@
 //!
 //! The artifacts generated from codegen.
 
+use ethers_core::utils::{hex, keccak256};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+use strum_macros::EnumString;
 
 pub use crate::abi::Abi;
-use crate::prelude::FileSource;
+use crate::prelude::{
+    BytecodeStats, EvmVersion, FileSource, JsonSerializer, MacroGasTable, Metadata, Serializer,
+    SourceMap, StorageLayout,
+};
 
 /// A Codegen Artifact
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -19,12 +29,185 @@ pub struct Artifact {
     pub runtime: String,
     /// The abi
     pub abi: Option<Abi>,
+    /// The 4 byte selector of each function, keyed by canonical signature
+    #[serde(default, rename = "methodIdentifiers", skip_serializing_if = "BTreeMap::is_empty")]
+    pub method_identifiers: BTreeMap<String, String>,
+    /// The runtime offsets of the placeholder words patched with each immutable
+    #[serde(default, rename = "immutableReferences", skip_serializing_if = "BTreeMap::is_empty")]
+    pub immutable_references: BTreeMap<String, Vec<usize>>,
+    /// The sizes of the bytecode
+    #[serde(default)]
+    pub sizes: BytecodeSizes,
+    /// The storage layout
+    #[serde(default, rename = "storageLayout")]
+    pub storage_layout: StorageLayout,
+    /// The static gas of a single expansion of each macro
+    #[serde(default, rename = "macroGas", skip_serializing_if = "BTreeMap::is_empty")]
+    pub macro_gas: MacroGasTable,
+    /// The opcode histogram and size breakdown of the runtime bytecode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<BytecodeStats>,
+    /// The bytecode of each test macro, for test runners to execute
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tests: BTreeMap<String, String>,
+    /// The result of simulating the constructor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<ConstructorSimulation>,
+    /// The compiler settings the artifact was built with, if not the default ones
+    #[serde(default, skip_serializing_if = "BuildSettings::is_default")]
+    pub settings: BuildSettings,
+    /// The source maps of the bytecode
+    #[serde(default, rename = "sourceMap", skip_serializing_if = "Option::is_none")]
+    pub source_map: Option<SourceMap>,
+    /// The metadata appended to the runtime bytecode, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+    /// The assembly listing of the runtime bytecode, if selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asm: Option<String>,
+    /// The experimental Yul object embedding the bytecode, if selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yul: Option<String>,
+}
+
+/// A Selectable Artifact Output
+///
+/// Named like its artifact key, so `sourceMap` selects the `sourceMap` output.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactOutput {
+    /// The deployed and runtime bytecode, and their sizes
+    Bytecode,
+    /// The abi
+    Abi,
+    /// The selector of each function
+    MethodIdentifiers,
+    /// The source maps
+    SourceMap,
+    /// The storage layout
+    StorageLayout,
+    /// The static gas of each macro
+    MacroGas,
+    /// The opcode histogram and size breakdown
+    Stats,
+    /// The bytecode of each test macro
+    Tests,
+    /// The assembly listing of the runtime bytecode
+    Asm,
+    /// The experimental Yul object embedding the bytecode
+    Yul,
+}
+
+impl ArtifactOutput {
+    /// The outputs selected by default, every one except the assembly listing and Yul object
+    pub const DEFAULT: [ArtifactOutput; 8] = [
+        ArtifactOutput::Bytecode,
+        ArtifactOutput::Abi,
+        ArtifactOutput::MethodIdentifiers,
+        ArtifactOutput::SourceMap,
+        ArtifactOutput::StorageLayout,
+        ArtifactOutput::MacroGas,
+        ArtifactOutput::Stats,
+        ArtifactOutput::Tests,
+    ];
+}
+
+/// The compiler settings that change a build's output
+///
+/// Cached artifacts are only read back by builds with the same settings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BuildSettings {
+    /// Whether `__DEBUG` macros were expanded
+    pub debug: bool,
+    /// Whether hex literals were pushed with their written width
+    pub preserve_push_width: bool,
+    /// The evm version targeted
+    pub evm_version: EvmVersion,
+    /// Whether a metadata trailer was appended to the runtime bytecode
+    pub metadata: bool,
+    /// The overridden constants, with their hex values
+    pub constant_overrides: BTreeMap<String, String>,
+    /// The constructor bootstrap template, if not the default one
+    pub bootstrap: Option<String>,
+    /// The outputs included in the artifact
+    pub output_selection: BTreeSet<ArtifactOutput>,
+    /// The limit on the number of macros expanded within each other, if not the default one
+    pub max_expansion_depth: Option<usize>,
+    /// The limit on the size of the expanded bytecode, if not the default one
+    pub max_expanded_size: Option<usize>,
+}
+
+impl Default for BuildSettings {
+    fn default() -> Self {
+        Self {
+            debug: false,
+            preserve_push_width: false,
+            evm_version: EvmVersion::default(),
+            metadata: false,
+            constant_overrides: BTreeMap::new(),
+            bootstrap: None,
+            output_selection: ArtifactOutput::DEFAULT.into(),
+            max_expansion_depth: None,
+            max_expanded_size: None,
+        }
+    }
+}
+
+impl BuildSettings {
+    /// Whether these are the default settings
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A short name for the settings, ie. `shanghai-1f2e3d4c`
+    ///
+    /// The targeted evm version is followed by the first 4 bytes of the keccak256 hash of the
+    /// settings' json, so builds differing in any setting get different keys.
+    pub fn key(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        format!("{}-{}", self.evm_version, hex::encode(&keccak256(json)[..4]))
+    }
+}
+
+/// The sizes of an artifact's bytecode, in bytes
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BytecodeSizes {
+    /// The size of the runtime bytecode, limited to 24576 bytes by EIP-170
+    pub runtime: usize,
+    /// The size of the deployment bytecode including constructor arguments, limited to 49152
+    /// bytes by EIP-3860
+    pub initcode: usize,
+}
+
+/// The result of executing the constructor against an empty state
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ConstructorSimulation {
+    /// The runtime bytecode returned by the constructor
+    pub runtime: String,
+    /// The storage slots written by the constructor, keyed by the 32 byte hex slot like storage
+    /// layouts
+    pub storage: BTreeMap<String, String>,
+    /// The gas used
+    pub gas_used: u64,
 }
 
 impl Artifact {
     /// Exports an artifact to a json file
     pub fn export(&self, out: &str) -> std::result::Result<(), std::io::Error> {
-        let serialized_artifact = serde_json::to_string_pretty(self)?;
+        self.export_with(out, &JsonSerializer::default())
+    }
+
+    /// Exports an artifact to a file using the given serializer
+    pub fn export_with(
+        &self,
+        out: &str,
+        serializer: &dyn Serializer,
+    ) -> std::result::Result<(), std::io::Error> {
+        let serialized_artifact = serializer.serialize(self)?;
         let file_path = Path::new(out);
         if let Some(p) = file_path.parent() {
             tracing::debug!(target: "abi", "Creating directory: \"{:?}\"", p);
@@ -32,4 +215,10 @@ impl Artifact {
         }
         fs::write(file_path, serialized_artifact)
     }
+
+    /// Imports an artifact from a json file
+    pub fn import(path: &str) -> std::result::Result<Self, std::io::Error> {
+        let serialized_artifact = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&serialized_artifact)?)
+    }
 }
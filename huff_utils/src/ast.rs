@@ -1,12 +1,14 @@
+use ethers_core::types::U256;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     bytecode::*,
     bytes_util::*,
-    error::CodegenError,
+    error::{CodegenError, CodegenErrorKind},
     evm::Opcode,
     prelude::{Span, TokenKind},
+    recursion::with_stack,
 };
 use std::{
     cell::RefCell,
@@ -74,6 +76,30 @@ impl AstSpan {
         )
     }
 
+    /// Narrows a definition's span to the span of its name, or returns it whole if none matches
+    pub fn name_span(&self, name: &str) -> AstSpan {
+        self.0
+            .iter()
+            .find(|s| {
+                s.file
+                    .as_ref()
+                    .and_then(|f| f.source.as_ref())
+                    .and_then(|source| source.get(s.start..s.end)) ==
+                    Some(name)
+            })
+            .map(|s| AstSpan(vec![s.clone()]))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Whether this span is in a file that includes, directly or transitively, the file of another
+    pub fn shadows(&self, other: &AstSpan) -> bool {
+        let file = |span: &AstSpan| span.0.iter().find_map(|s| s.file.clone());
+        match (file(self), file(other)) {
+            (Some(file), Some(other)) => file.includes(&other.path),
+            _ => false,
+        }
+    }
+
     /// Print just the file for missing
     pub fn file(&self) -> String {
         self.0.iter().fold("".to_string(), |acc, span| match &span.file {
@@ -103,8 +129,14 @@ pub struct Contract {
     pub functions: Vec<Function>,
     /// Events
     pub events: Vec<Event>,
+    /// Custom Errors
+    pub errors: Vec<ErrorDefinition>,
     /// Tables
     pub tables: Vec<TableDefinition>,
+    /// Test macros, compiled into test bytecode instead of the contract's
+    pub tests: Vec<MacroDefinition>,
+    /// Immutables, set by the constructor and embedded in the runtime bytecode
+    pub immutables: Vec<ImmutableDefinition>,
 }
 
 impl Contract {
@@ -118,6 +150,42 @@ impl Contract {
         }
     }
 
+    /// Returns the first macro, constant, or table defined more than once, as its kind, name, and
+    /// the spans of the two conflicting definitions' names
+    ///
+    /// These are looked up by name in codegen, where the first definition is used. A definition
+    /// may shadow another in a file its own file includes, since that file is flattened after it.
+    pub fn duplicate_definition(&self) -> Option<(&'static str, String, AstSpan, AstSpan)> {
+        let constants = self.constants.borrow();
+        let definitions = self
+            .macros
+            .iter()
+            .chain(&self.tests)
+            .map(|m| ("macro", &m.name, &m.span))
+            .chain(constants.iter().map(|c| ("constant", &c.name, &c.span)))
+            .chain(self.tables.iter().map(|t| ("table", &t.name, &t.span)))
+            .chain(self.immutables.iter().map(|i| ("immutable", &i.name, &i.span)));
+
+        let mut seen: Vec<(&str, &String, &AstSpan)> = vec![];
+        for (kind, name, span) in definitions {
+            let conflict = seen
+                .iter()
+                .filter(|(k, n, _)| *k == kind && *n == name)
+                .find(|(_, _, first)| !first.shadows(span));
+            if let Some((_, _, first)) = conflict {
+                return Some((kind, name.clone(), first.name_span(name), span.name_span(name)))
+            }
+            seen.push((kind, name, span));
+        }
+        None
+    }
+
+    /// Returns the index of the immutable with the provided name, which orders its memory slot
+    /// in the constructor
+    pub fn find_immutable_index(&self, name: &str) -> Option<usize> {
+        self.immutables.iter().position(|i| i.name == name)
+    }
+
     /// Returns the first table that matches the provided name
     pub fn find_table_by_name(&self, name: &str) -> Option<TableDefinition> {
         if let Some(t) = self.tables.iter().find(|t| t.name == name) {
@@ -128,32 +196,70 @@ impl Contract {
         }
     }
 
+    /// Returns the names of the constants defined as `FREE_STORAGE_POINTER()`
+    pub fn free_storage_pointers(&self) -> Vec<String> {
+        self.constants
+            .borrow()
+            .iter()
+            .filter(|c| matches!(c.value, ConstVal::FreeStoragePointer(_)))
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    /// Returns the names of the literal and expression constants used as storage slots, ie.
+    /// `[SLOT] sload`
+    pub fn literal_storage_slots(&self) -> Vec<String> {
+        fn used_as_slots(statements: &[Statement], slots: &mut Vec<String>) {
+            for pair in statements.windows(2) {
+                if let (StatementType::Constant(name), StatementType::Opcode(op)) =
+                    (&pair[0].ty, &pair[1].ty)
+                {
+                    if matches!(op, Opcode::Sload | Opcode::Sstore) {
+                        slots.push(name.clone());
+                    }
+                }
+            }
+            for statement in statements {
+                if let StatementType::Label(l) = &statement.ty {
+                    used_as_slots(&l.inner, slots);
+                }
+            }
+        }
+
+        let mut slots = vec![];
+        self.macros.iter().for_each(|m| used_as_slots(&m.statements, &mut slots));
+        self.constants
+            .borrow()
+            .iter()
+            .filter(|c| {
+                !matches!(c.value, ConstVal::FreeStoragePointer(_)) && slots.contains(&c.name)
+            })
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
     /// Derives the FreeStoragePointers into their bytes32 representation
     pub fn derive_storage_pointers(&mut self) {
+        self.derive_storage_pointers_from(&["CONSTRUCTOR", "MAIN"]);
+    }
+
+    /// Derives the FreeStoragePointers used by the given macros, in order, into their bytes32
+    /// representation
+    pub fn derive_storage_pointers_from(&mut self, macro_names: &[&str]) {
         let mut storage_pointers: Vec<(String, [u8; 32])> = Vec::new();
         let mut last_assigned_free_pointer = 0;
 
-        // Derive Constructor Storage Pointers
-        match self.find_macro_by_name("CONSTRUCTOR") {
-            Some(m) => self.recurse_ast_constants(
-                &m,
-                &mut storage_pointers,
-                &mut last_assigned_free_pointer,
-            ),
-            None => {
-                tracing::error!(target: "ast", "'CONSTRUCTOR' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!")
-            }
-        }
-
-        // Derive Main Storage Pointers
-        match self.find_macro_by_name("MAIN") {
-            Some(m) => self.recurse_ast_constants(
-                &m,
-                &mut storage_pointers,
-                &mut last_assigned_free_pointer,
-            ),
-            None => {
-                tracing::error!(target: "ast", "'MAIN' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!")
+        for name in macro_names {
+            match self.find_macro_by_name(name) {
+                Some(m) => self.recurse_ast_constants(
+                    &m,
+                    &mut storage_pointers,
+                    &mut last_assigned_free_pointer,
+                    &mut vec![],
+                ),
+                None => {
+                    tracing::error!(target: "ast", "'{}' MACRO NOT FOUND WHILE DERIVING STORAGE POINTERS!", name)
+                }
             }
         }
 
@@ -166,7 +272,7 @@ impl Contract {
                 .iter()
                 .filter(|pointer| pointer.0.eq(&c.name))
                 .collect::<Vec<&(String, [u8; 32])>>()
-                .get(0)
+                .first()
             {
                 Some(p) => {
                     *c = ConstantDefinition {
@@ -191,15 +297,37 @@ impl Contract {
     ///       updated out `last_p` tracker value
     ///     - If it's a literal constant, we can set the constant value directly to the literal if
     ///       not already set
+    ///     - If it's an expression constant, derive the constants it references in its place
     ///     - If it's a macro invocation, look for the macro definition and recurse into that macro
     ///       definition using `recurse_ast_constants`
+    ///
+    /// Macros already in `visited` are skipped, so recursive invocations terminate.
     pub fn recurse_ast_constants(
         &self,
         macro_def: &MacroDefinition,
         storage_pointers: &mut Vec<(String, [u8; 32])>,
         last_p: &mut i32,
+        visited: &mut Vec<String>,
     ) {
+        // Nested invocations grow the stack when it runs low, like their expansion does
+        with_stack(|| self.derive_macro_constants(macro_def, storage_pointers, last_p, visited))
+    }
+
+    /// Sets the Storage Pointers of a Macro Definition, recursing into the macros it invokes
+    fn derive_macro_constants(
+        &self,
+        macro_def: &MacroDefinition,
+        storage_pointers: &mut Vec<(String, [u8; 32])>,
+        last_p: &mut i32,
+        visited: &mut Vec<String>,
+    ) {
+        if visited.contains(&macro_def.name) {
+            return
+        }
+        visited.push(macro_def.name.clone());
+
         let mut statements = macro_def.statements.clone();
+        let mut expressions: Vec<String> = vec![];
         let mut i = 0;
         loop {
             if i >= statements.len() {
@@ -212,8 +340,7 @@ impl Contract {
                         .iter()
                         .filter(|pointer| pointer.0.eq(const_name))
                         .collect::<Vec<&(String, [u8; 32])>>()
-                        .get(0)
-                        .is_none()
+                        .is_empty()
                     {
                         tracing::debug!(target: "ast", "No storage pointer already set for \"{}\"!", const_name);
                         // Get the associated constant
@@ -223,16 +350,34 @@ impl Contract {
                             .iter()
                             .filter(|c| c.name.eq(const_name))
                             .collect::<Vec<&ConstantDefinition>>()
-                            .get(0)
+                            .first()
                         {
                             Some(c) => {
-                                let new_value = match c.value {
-                                    ConstVal::Literal(l) => l,
+                                let new_value = match &c.value {
+                                    ConstVal::Literal(l) => *l,
                                     ConstVal::FreeStoragePointer(_) => {
                                         let old_p = *last_p;
                                         *last_p += 1;
                                         str_to_bytes32(&format!("{}", old_p))
                                     }
+                                    // Expressions are evaluated in codegen, but the constants
+                                    // they reference are derived as if used in their place
+                                    ConstVal::Expression(expr) => {
+                                        if !expressions.contains(const_name) {
+                                            expressions.push(const_name.to_string());
+                                            for name in expr.constants().into_iter().rev() {
+                                                statements.insert(
+                                                    i + 1,
+                                                    Statement {
+                                                        ty: StatementType::Constant(name),
+                                                        span: statements[i].span.clone(),
+                                                    },
+                                                );
+                                            }
+                                        }
+                                        i += 1;
+                                        continue
+                                    }
                                 };
                                 storage_pointers.push((const_name.to_string(), new_value));
                             }
@@ -249,9 +394,11 @@ impl Contract {
                         .iter()
                         .filter(|md| md.name.eq(&mi.macro_name))
                         .collect::<Vec<&MacroDefinition>>()
-                        .get(0)
+                        .first()
                     {
-                        Some(&md) => self.recurse_ast_constants(md, storage_pointers, last_p),
+                        Some(&md) => {
+                            self.recurse_ast_constants(md, storage_pointers, last_p, visited)
+                        }
                         None => {
                             tracing::warn!(target: "ast", "MACRO \"{}\" INVOKED BUT NOT FOUND IN AST!", mi.macro_name)
                         }
@@ -266,11 +413,14 @@ impl Contract {
                                 .iter()
                                 .filter(|md| md.name.eq(name))
                                 .collect::<Vec<&MacroDefinition>>()
-                                .get(0)
+                                .first()
                             {
-                                Some(&md) => {
-                                    self.recurse_ast_constants(md, storage_pointers, last_p)
-                                }
+                                Some(&md) => self.recurse_ast_constants(
+                                    md,
+                                    storage_pointers,
+                                    last_p,
+                                    visited,
+                                ),
                                 None => {
                                     tracing::warn!(target: "ast", "BUILTIN HAS ARG NAME \"{}\" BUT NOT FOUND IN AST!", name)
                                 }
@@ -387,6 +537,19 @@ pub struct Event {
     pub hash: Literal,
 }
 
+/// A Custom Error Definition
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ErrorDefinition {
+    /// The name of the error
+    pub name: String,
+    /// The parameters of the error
+    pub parameters: Vec<Argument>,
+    /// The error span
+    pub span: AstSpan,
+    /// The 4 byte error selector
+    pub selector: [u8; 4],
+}
+
 /// A Table Definition
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TableDefinition {
@@ -459,7 +622,7 @@ pub struct MacroDefinition {
 
 impl ToIRBytecode<CodegenError> for MacroDefinition {
     fn to_irbytecode(&self) -> Result<IRBytecode, CodegenError> {
-        let inner_irbytes: Vec<IRBytes> = MacroDefinition::to_irbytes(&self.statements);
+        let inner_irbytes: Vec<IRBytes> = MacroDefinition::try_to_irbytes(&self.statements)?;
         Ok(IRBytecode(inner_irbytes))
     }
 }
@@ -487,32 +650,54 @@ impl MacroDefinition {
     }
 
     /// Translate statements into IRBytes
+    ///
+    /// Code that isn't whole bytes of hex is left out, use
+    /// [try_to_irbytes](MacroDefinition::try_to_irbytes) to reject it instead.
     pub fn to_irbytes(statements: &[Statement]) -> Vec<IRBytes> {
+        MacroDefinition::statements_to_irbytes(statements, &mut vec![])
+    }
+
+    /// Translate statements into IRBytes, failing on code that isn't whole bytes of hex
+    pub fn try_to_irbytes(statements: &[Statement]) -> Result<Vec<IRBytes>, CodegenError> {
+        let mut invalid_code = vec![];
+        let irbytes = MacroDefinition::statements_to_irbytes(statements, &mut invalid_code);
+        match invalid_code.into_iter().next() {
+            Some((len, span)) => {
+                Err(CodegenError::new(CodegenErrorKind::InvalidCodeLength(len), span, None))
+            }
+            None => Ok(irbytes),
+        }
+    }
+
+    /// Translate statements into IRBytes, collecting the length and span of any code that isn't
+    /// whole bytes of hex instead of translating it
+    fn statements_to_irbytes(
+        statements: &[Statement],
+        invalid_code: &mut Vec<(usize, AstSpan)>,
+    ) -> Vec<IRBytes> {
         let mut inner_irbytes: Vec<IRBytes> = vec![];
 
-        statements.iter().for_each(|statement| {
+        for statement in statements {
             match &statement.ty {
                 StatementType::Literal(l) => {
-                    let hex_literal: String = bytes32_to_string(l, false);
-                    let push_bytes = format!("{:02x}{}", 95 + hex_literal.len() / 2, hex_literal);
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(push_bytes)),
+                        ty: IRByteType::Bytes(Bytes::push_word(l)),
                         span: statement.span.clone(),
                     });
                 }
                 StatementType::Opcode(o) => {
-                    let opcode_str = o.string();
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(opcode_str)),
+                        ty: IRByteType::Bytes(Bytes::from(*o)),
                         span: statement.span.clone(),
                     });
                 }
-                StatementType::Code(c) => {
-                    inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(c.to_owned())),
+                StatementType::Code(c) => match Bytes::from_hex(c) {
+                    Ok(code) => inner_irbytes.push(IRBytes {
+                        ty: IRByteType::Bytes(code),
                         span: statement.span.clone(),
-                    });
-                }
+                    }),
+                    Err(_) => invalid_code.push((c.len(), statement.span.clone())),
+                },
                 StatementType::MacroInvocation(mi) => {
                     inner_irbytes.push(IRBytes {
                         ty: IRByteType::Statement(Statement {
@@ -557,7 +742,10 @@ impl MacroDefinition {
                     });
 
                     // Recurse label statements to IRBytes Bytes
-                    inner_irbytes.append(&mut MacroDefinition::to_irbytes(&l.inner));
+                    inner_irbytes.append(&mut MacroDefinition::statements_to_irbytes(
+                        &l.inner,
+                        invalid_code,
+                    ));
                 }
                 StatementType::BuiltinFunctionCall(builtin) => {
                     inner_irbytes.push(IRBytes {
@@ -568,8 +756,15 @@ impl MacroDefinition {
                         span: statement.span.clone(),
                     });
                 }
+                StatementType::StringLiteral(_) => {
+                    /* Strings too long to push are only rejected by codegen */
+                    inner_irbytes.push(IRBytes {
+                        ty: IRByteType::Statement(statement.clone()),
+                        span: statement.span.clone(),
+                    });
+                }
             }
-        });
+        }
 
         inner_irbytes
     }
@@ -608,6 +803,81 @@ pub enum ConstVal {
     Literal(Literal),
     /// A Free Storage Pointer
     FreeStoragePointer(FreeStoragePointer),
+    /// An expression over other constants and literals, evaluated at compile time
+    Expression(ConstExpr),
+}
+
+/// A Constant Expression
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstExpr {
+    /// A literal value
+    Literal(Literal),
+    /// A reference to another constant, and its span
+    Constant(String, AstSpan),
+    /// An arithmetic operation over two expressions
+    Operation(ConstOp, Box<ConstExpr>, Box<ConstExpr>),
+}
+
+impl ConstExpr {
+    /// Returns the names of the constants referenced by the expression, in order
+    pub fn constants(&self) -> Vec<String> {
+        match self {
+            ConstExpr::Literal(_) => vec![],
+            ConstExpr::Constant(name, _) => vec![name.clone()],
+            ConstExpr::Operation(_, lhs, rhs) => [lhs.constants(), rhs.constants()].concat(),
+        }
+    }
+}
+
+impl Display for ConstExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstExpr::Literal(l) => write!(f, "{}", bytes32_to_string(l, true)),
+            ConstExpr::Constant(name, _) => write!(f, "{}", name),
+            ConstExpr::Operation(op, lhs, rhs) => write!(f, "{}({}, {})", op, lhs, rhs),
+        }
+    }
+}
+
+/// A Constant Expression Operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstOp {
+    /// Addition, `ADD(a, b)` or `a + b`
+    Add,
+    /// Subtraction, `SUB(a, b)` or `a - b`
+    Sub,
+    /// Multiplication, `MUL(a, b)` or `a * b`
+    Mul,
+    /// Division, `DIV(a, b)` or `a / b`
+    Div,
+    /// Modulo, `MOD(a, b)`
+    Mod,
+}
+
+impl ConstOp {
+    /// Returns the operator called by the given function name, ie. `ADD`
+    pub fn from_function(name: &str) -> Option<Self> {
+        match name {
+            "ADD" => Some(ConstOp::Add),
+            "SUB" => Some(ConstOp::Sub),
+            "MUL" => Some(ConstOp::Mul),
+            "DIV" => Some(ConstOp::Div),
+            "MOD" => Some(ConstOp::Mod),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ConstOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstOp::Add => write!(f, "ADD"),
+            ConstOp::Sub => write!(f, "SUB"),
+            ConstOp::Mul => write!(f, "MUL"),
+            ConstOp::Div => write!(f, "DIV"),
+            ConstOp::Mod => write!(f, "MOD"),
+        }
+    }
 }
 
 /// A Constant Definition
@@ -621,6 +891,106 @@ pub struct ConstantDefinition {
     pub span: AstSpan,
 }
 
+impl ConstantDefinition {
+    /// Evaluates the constant to its value, looking up the constants its expression references.
+    ///
+    /// Expressions are evaluated with checked 256-bit arithmetic, so overflows and divisions by
+    /// zero are errors instead of wrapping.
+    pub fn evaluate(&self, constants: &[ConstantDefinition]) -> Result<Literal, CodegenError> {
+        self.evaluate_in(constants, &mut vec![])
+    }
+
+    fn evaluate_in(
+        &self,
+        constants: &[ConstantDefinition],
+        scope: &mut Vec<String>,
+    ) -> Result<Literal, CodegenError> {
+        let expr = match &self.value {
+            ConstVal::Literal(l) => return Ok(*l),
+            ConstVal::FreeStoragePointer(_) => {
+                tracing::error!(target: "codegen", "STORAGE POINTERS INCORRECTLY DERIVED FOR \"{}\"", self.name);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::StoragePointersNotDerived,
+                    span: self.span.clone(),
+                    token: None,
+                    expansion: vec![],
+                })
+            }
+            ConstVal::Expression(expr) => expr,
+        };
+        if let Some(start) = scope.iter().position(|name| name == &self.name) {
+            let cycle = [&scope[start..], std::slice::from_ref(&self.name)].concat();
+            tracing::error!(target: "codegen", "CIRCULAR CONSTANT \"{}\"", cycle.join(" -> "));
+            return Err(CodegenError {
+                kind: CodegenErrorKind::CircularConstant(cycle),
+                span: self.span.clone(),
+                token: None,
+                expansion: vec![],
+            })
+        }
+
+        scope.push(self.name.clone());
+        let value = self.evaluate_expr(expr, constants, scope)?;
+        scope.pop();
+        let mut literal = [0u8; 32];
+        value.to_big_endian(&mut literal);
+        Ok(literal)
+    }
+
+    fn evaluate_expr(
+        &self,
+        expr: &ConstExpr,
+        constants: &[ConstantDefinition],
+        scope: &mut Vec<String>,
+    ) -> Result<U256, CodegenError> {
+        match expr {
+            ConstExpr::Literal(l) => Ok(U256::from_big_endian(l)),
+            ConstExpr::Constant(name, span) => match constants.iter().find(|c| &c.name == name) {
+                Some(c) => Ok(U256::from_big_endian(&c.evaluate_in(constants, scope)?)),
+                None => {
+                    tracing::error!(target: "codegen", "MISSING CONSTANT DEFINITION \"{}\"", name);
+                    Err(CodegenError {
+                        kind: CodegenErrorKind::MissingConstantDefinition(name.clone()),
+                        span: span.clone(),
+                        token: None,
+                        expansion: vec![],
+                    })
+                }
+            },
+            ConstExpr::Operation(op, lhs, rhs) => {
+                let lhs = self.evaluate_expr(lhs, constants, scope)?;
+                let rhs = self.evaluate_expr(rhs, constants, scope)?;
+                let value = match op {
+                    ConstOp::Add => lhs.checked_add(rhs),
+                    ConstOp::Sub => lhs.checked_sub(rhs),
+                    ConstOp::Mul => lhs.checked_mul(rhs),
+                    ConstOp::Div => lhs.checked_div(rhs),
+                    ConstOp::Mod => lhs.checked_rem(rhs),
+                };
+                value.ok_or_else(|| {
+                    tracing::error!(target: "codegen", "CONSTANT \"{}\" FAILED TO EVALUATE {}", self.name, op);
+                    let kind = match op {
+                        ConstOp::Div | ConstOp::Mod => {
+                            CodegenErrorKind::ConstantDivisionByZero(self.name.clone())
+                        }
+                        _ => CodegenErrorKind::ConstantOverflow(self.name.clone()),
+                    };
+                    CodegenError { kind, span: self.span.clone(), token: None, expansion: vec![] }
+                })
+            }
+        }
+    }
+}
+
+/// An Immutable Definition
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImmutableDefinition {
+    /// The Immutable name
+    pub name: String,
+    /// The Span of the Immutable Definition
+    pub span: AstSpan,
+}
+
 /// A Jump Destination
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Label {
@@ -658,6 +1028,55 @@ pub enum BuiltinFunctionKind {
     FunctionSignature,
     /// Event hash function
     EventHash,
+    /// CREATE address derivation function
+    CreateAddress,
+    /// CREATE2 address derivation function
+    Create2Address,
+    /// Constant bit mask function
+    Mask,
+    /// Constant left shift function
+    ShlConst,
+    /// Right padded literal function
+    RightPad,
+    /// Verbatim bytecode function
+    Verbatim,
+    /// Function dispatcher function
+    Dispatch,
+    /// Constructor argument function
+    ConstructorArg,
+    /// Immutable read function
+    Immutable,
+    /// Immutable write function, for constructors
+    SetImmutable,
+    /// Debug only macro expansion function
+    Debug,
+    /// Custom error selector function
+    Error,
+    /// A user defined builtin, evaluated by codegen
+    Custom(String),
+}
+
+impl BuiltinFunctionKind {
+    /// The names the builtin functions are invoked by
+    pub const NAMES: [&'static str; 17] = [
+        "__tablesize",
+        "__codesize",
+        "__tablestart",
+        "__FUNC_SIG",
+        "__EVENT_HASH",
+        "__CREATE_ADDRESS",
+        "__CREATE2_ADDRESS",
+        "__MASK",
+        "__SHL_CONST",
+        "__RIGHTPAD",
+        "__VERBATIM",
+        "__DISPATCH",
+        "__CONSTRUCTOR_ARG",
+        "__IMMUTABLE",
+        "__SET_IMMUTABLE",
+        "__DEBUG",
+        "__ERROR",
+    ];
 }
 
 impl From<&str> for BuiltinFunctionKind {
@@ -668,7 +1087,19 @@ impl From<&str> for BuiltinFunctionKind {
             "__tablestart" => BuiltinFunctionKind::Tablestart,
             "__FUNC_SIG" => BuiltinFunctionKind::FunctionSignature,
             "__EVENT_HASH" => BuiltinFunctionKind::EventHash,
-            _ => panic!("Invalid Builtin Function Kind"), // TODO: Better error handling
+            "__CREATE_ADDRESS" => BuiltinFunctionKind::CreateAddress,
+            "__CREATE2_ADDRESS" => BuiltinFunctionKind::Create2Address,
+            "__MASK" => BuiltinFunctionKind::Mask,
+            "__SHL_CONST" => BuiltinFunctionKind::ShlConst,
+            "__RIGHTPAD" => BuiltinFunctionKind::RightPad,
+            "__VERBATIM" => BuiltinFunctionKind::Verbatim,
+            "__DISPATCH" => BuiltinFunctionKind::Dispatch,
+            "__CONSTRUCTOR_ARG" => BuiltinFunctionKind::ConstructorArg,
+            "__IMMUTABLE" => BuiltinFunctionKind::Immutable,
+            "__SET_IMMUTABLE" => BuiltinFunctionKind::SetImmutable,
+            "__DEBUG" => BuiltinFunctionKind::Debug,
+            "__ERROR" => BuiltinFunctionKind::Error,
+            custom => BuiltinFunctionKind::Custom(custom.to_string()),
         }
     }
 }
@@ -687,6 +1118,9 @@ pub struct Statement {
 pub enum StatementType {
     /// A Literal Statement
     Literal(Literal),
+    /// A String Literal Statement, pushed right padded to a word, so codegen rejects strings
+    /// longer than 32 bytes
+    StringLiteral(String),
     /// An Opcode Statement
     Opcode(Opcode),
     /// A Code Statement
@@ -709,6 +1143,7 @@ impl Display for StatementType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             StatementType::Literal(l) => write!(f, "LITERAL: {}", bytes32_to_string(l, true)),
+            StatementType::StringLiteral(s) => write!(f, "STRING LITERAL: {:?}", s),
             StatementType::Opcode(o) => write!(f, "OPCODE: {}", o),
             StatementType::Code(s) => write!(f, "CODE: {}", s),
             StatementType::MacroInvocation(m) => {
@@ -2,15 +2,102 @@
 //!
 //! Abstract translating state into bytecode.
 
-use crate::prelude::{AstSpan, Statement, TableDefinition};
+use crate::prelude::{
+    estimate_static_gas, opcode_mnemonic, AstSpan, Instruction, Instructions, Opcode, SourceSpans,
+    Statement, TableDefinition, STACK_LIMIT,
+};
+use ethers_core::utils::hex;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt::{self, Display},
+    str::FromStr,
 };
 
 /// A string of Bytes
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Bytes(pub String);
+///
+/// Bytes are kept binary through codegen, and only encoded as hex at the artifact boundary.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    /// Decodes Bytes from a hex string, failing on odd-length or non-hex input
+    pub fn from_hex(hex: &str) -> Result<Self, hex::FromHexError> {
+        hex::decode(hex).map(Self)
+    }
+
+    /// Encodes the Bytes as a hex string
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+
+    /// The Bytes of a sequence of opcodes
+    pub fn opcodes(opcodes: &[Opcode]) -> Self {
+        Self(opcodes.iter().map(Opcode::byte).collect())
+    }
+
+    /// A `PUSHn` of the given data, which must be between 1 and 32 bytes
+    pub fn push(data: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(data.len() + 1);
+        bytes.push(Opcode::Push1.byte() + data.len() as u8 - 1);
+        bytes.extend_from_slice(data);
+        Self(bytes)
+    }
+
+    /// A `PUSHn` of a word with its leading zero bytes stripped, keeping at least one byte
+    pub fn push_word(word: &[u8; 32]) -> Self {
+        let start = word.iter().position(|b| *b != 0).unwrap_or(word.len() - 1);
+        Self::push(&word[start..])
+    }
+
+    /// A `PUSHn` of a number with the fewest bytes that fit it
+    pub fn push_usize(value: usize) -> Self {
+        let mut word = [0u8; 32];
+        word[32 - std::mem::size_of::<usize>()..].copy_from_slice(&value.to_be_bytes());
+        Self::push_word(&word)
+    }
+
+    /// A `PUSH2` of a placeholder destination, filled in once its label is resolved
+    pub fn push2_placeholder() -> Self {
+        Self::push(&[0, 0])
+    }
+
+    /// The number of bytes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no bytes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Opcode> for Bytes {
+    fn from(o: Opcode) -> Self {
+        Bytes(vec![o.byte()])
+    }
+}
+
+impl FromStr for Bytes {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl From<Bytes> for String {
+    fn from(b: Bytes) -> Self {
+        b.to_hex()
+    }
+}
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
 
 /// Intermediate Bytecode Representation
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -60,7 +147,7 @@ pub trait ToBytecode<'a, E> {
 
 impl From<Vec<Bytes>> for Bytecode {
     fn from(b: Vec<Bytes>) -> Self {
-        Bytecode(b.iter().fold("".to_string(), |acc, b| format!("{}{}", acc, b.0)))
+        Bytecode(b.iter().map(Bytes::to_hex).collect())
     }
 }
 
@@ -76,8 +163,21 @@ pub struct BytecodeRes {
     pub unmatched_jumps: Jumps,
     /// Table Instances
     pub table_instances: Jumps,
+    /// Immutable References, at the offsets of their placeholder words
+    pub immutable_references: Jumps,
     /// Utilized Tables
     pub utilized_tables: Vec<TableDefinition>,
+    /// Inlined Macro Sizes
+    pub macro_sizes: MacroSizes,
+    /// Source Spans of the Resulting Bytes
+    pub spans: SourceSpans,
+}
+
+impl BytecodeRes {
+    /// The number of resulting bytes, before any tables are appended
+    pub fn code_len(&self) -> usize {
+        self.bytes.iter().map(|(_, b)| b.len()).sum()
+    }
 }
 
 impl Display for BytecodeRes {
@@ -89,11 +189,13 @@ impl Display for BytecodeRes {
             label_indices: {:?},
             unmatched_jumps: {:?}
             table_instances: {:?}
+            immutable_references: {:?}
         )"#,
-            self.bytes.iter().fold("".to_string(), |acc, b| format!("{}{}", acc, b.0)),
+            self.bytes.iter().map(|(_, b)| b.to_hex()).collect::<String>(),
             self.label_indices,
             self.unmatched_jumps,
-            self.table_instances
+            self.table_instances,
+            self.immutable_references
         )
     }
 }
@@ -117,3 +219,106 @@ pub type LabelIndices = BTreeMap<String, usize>;
 
 /// Type for a map of bytecode indexes to `Jumps`. Represents a Jump Table.
 pub type JumpTable = BTreeMap<usize, Jumps>;
+
+/// The size a macro contributes to the bytecode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MacroSize {
+    /// Number of times the macro is inlined
+    pub invocations: usize,
+    /// Total bytes of all of the macro's invocations, including nested macros
+    pub size: usize,
+}
+
+/// Type to map macro names to the size they contribute to the bytecode
+pub type MacroSizes = BTreeMap<String, MacroSize>;
+
+/// The static gas of executing a single expansion of a macro straight through
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MacroGas {
+    /// The sum of the static gas of every instruction
+    pub gas: u64,
+    /// Whether any instruction has dynamic costs, ie. `SSTORE` or `CALL`, so that the gas is a
+    /// lower bound
+    pub dynamic: bool,
+}
+
+impl MacroGas {
+    /// Estimates the gas of a macro's hex bytecode
+    pub fn new(bytecode: &str) -> Self {
+        let (gas, dynamic) = estimate_static_gas(bytecode);
+        Self { gas, dynamic }
+    }
+}
+
+/// Type to map macro names to the static gas of a single expansion
+pub type MacroGasTable = BTreeMap<String, MacroGas>;
+
+/// A breakdown of where the bytes of a bytecode go
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytecodeStats {
+    /// The number of times each opcode appears, by mnemonic, excluding push data and tables
+    pub opcodes: BTreeMap<String, usize>,
+    /// The bytes taken by opcodes
+    pub opcode_bytes: usize,
+    /// The bytes taken by push data
+    pub push_data_bytes: usize,
+    /// The bytes taken by jump tables and code tables
+    pub table_bytes: usize,
+    /// The bytes taken by selector comparisons, ie. `dup1 PUSH4 <selector> eq PUSH2 <dest> jumpi`,
+    /// counted in the opcode and push data bytes as well
+    pub dispatcher_bytes: usize,
+}
+
+impl BytecodeStats {
+    /// Computes the stats of a hex bytecode string whose last `table_bytes` bytes are tables
+    pub fn new(bytecode: &str, table_bytes: usize) -> Self {
+        let code = &bytecode[..bytecode.len().saturating_sub(table_bytes * 2)];
+        let mut stats = Self { table_bytes, ..Default::default() };
+
+        // Split the code into its opcodes and the length of their push data
+        let instructions = Instructions::new(code)
+            .map(|Instruction { opcode, data, .. }| (opcode, data.len() / 2))
+            .collect::<Vec<(u8, usize)>>();
+
+        for (index, &(opcode, data)) in instructions.iter().enumerate() {
+            let mnemonic = opcode_mnemonic(opcode)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("unknown(0x{:02x})", opcode));
+            *stats.opcodes.entry(mnemonic).or_default() += 1;
+            stats.opcode_bytes += 1;
+            stats.push_data_bytes += data;
+
+            // PUSH4, EQ, PUSH2, JUMPI, optionally preceded by a DUP1
+            let opcodes = instructions[index..].iter().take(4).map(|(o, _)| *o);
+            if opcodes.eq([0x63, 0x14, 0x61, 0x57]) {
+                let dup = index > 0 && instructions[index - 1].0 == 0x80;
+                stats.dispatcher_bytes += 10 + dup as usize;
+            }
+        }
+        stats
+    }
+}
+
+/// The number of items below the stack limit a macro's depth is flagged from
+pub const STACK_DEPTH_MARGIN: usize = 64;
+
+/// The deepest stack a macro reaches
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StackDepth {
+    /// The most items on the stack, simulated from the macro's `takes` items
+    pub max: usize,
+    /// Whether the depth is within `STACK_DEPTH_MARGIN` items of the stack limit
+    pub near_limit: bool,
+}
+
+impl StackDepth {
+    /// Public associated function to instantiate a new StackDepth, flagging it if it's near the
+    /// stack limit
+    pub fn new(max: usize) -> Self {
+        Self { max, near_limit: max + STACK_DEPTH_MARGIN >= STACK_LIMIT }
+    }
+}
+
+/// Type to map macro names to the deepest stack they reach
+pub type StackDepths = BTreeMap<String, StackDepth>;
@@ -1,18 +1,21 @@
+use ethers_core::types::U256;
+
 /// Convert a string slice to a `[u8; 32]`
 /// Pads zeros to the left of significant bytes in the `[u8; 32]` slice.
 /// i.e. 0xa57b becomes `[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 /// 0, 0, 0, 0, 0, 165, 123]`
+/// Pairs that aren't hex digits are read as zero, and only the last 32 bytes are kept.
 pub fn str_to_bytes32(s: &str) -> [u8; 32] {
     let s = format_even_bytes(String::from(s));
 
-    let bytes: Vec<u8> =
-        (0..s.len()).step_by(2).map(|c| u8::from_str_radix(&s[c..c + 2], 16).unwrap()).collect();
+    let bytes: Vec<u8> = (0..s.len())
+        .step_by(2)
+        .map(|c| s.get(c..c + 2).and_then(|b| u8::from_str_radix(b, 16).ok()).unwrap_or_default())
+        .collect();
 
     let mut padded = [0u8; 32];
-
-    for i in 32 - bytes.len()..32 {
-        padded[i] = bytes[bytes.len() - (32 - i)];
-    }
+    let len = bytes.len().min(32);
+    padded[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
 
     padded
 }
@@ -52,3 +55,28 @@ pub fn str_to_vec(s: &str) -> Result<Vec<u8>, std::num::ParseIntError> {
         (0..s.len()).step_by(2).map(|c| u8::from_str_radix(&s[c..c + 2], 16)).collect();
     bytes
 }
+
+/// Convert a signed decimal string to its two's complement `[u8; 32]` representation,
+/// validating that it fits in an `int` of the given bit size.
+/// i.e. -1 becomes `[255; 32]`
+pub fn int_to_bytes32(s: &str, bits: usize) -> Result<[u8; 32], String> {
+    if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+        return Err(format!("Invalid int size: {}", bits))
+    }
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let magnitude = U256::from_dec_str(digits).map_err(|e| format!("{}: {}", s, e))?;
+
+    // int<bits> ranges from -2^(bits - 1) to 2^(bits - 1) - 1
+    let limit = U256::one() << (bits - 1);
+    if (negative && magnitude > limit) || (!negative && magnitude >= limit) {
+        return Err(format!("{} is out of range for int{}", s, bits))
+    }
+
+    let value = if negative { (!magnitude).overflowing_add(U256::one()).0 } else { magnitude };
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    Ok(bytes)
+}
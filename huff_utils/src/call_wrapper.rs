@@ -0,0 +1,121 @@
+use std::{fmt, str::FromStr};
+
+/// The Opcode a Call Wrapper Calls With
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    /// CALL, forwarding a value
+    Call,
+    /// STATICCALL
+    StaticCall,
+    /// DELEGATECALL
+    DelegateCall,
+}
+
+impl CallKind {
+    /// The lowercase opcode name
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            CallKind::Call => "call",
+            CallKind::StaticCall => "staticcall",
+            CallKind::DelegateCall => "delegatecall",
+        }
+    }
+}
+
+impl FromStr for CallKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "call" => Ok(CallKind::Call),
+            "staticcall" => Ok(CallKind::StaticCall),
+            "delegatecall" => Ok(CallKind::DelegateCall),
+            _ => Err(format!(
+                "Invalid call kind \"{}\", expected call, staticcall or delegatecall",
+                s
+            )),
+        }
+    }
+}
+
+/// An External Call Wrapper Macro
+///
+/// The generated macro copies the calldata to memory, calls the target with it, and reverts with
+/// the callee's revert data if the call fails. The gas, value, and target are Huff snippets that
+/// each push a single word, ie. `gas`, `callvalue`, or `[TARGET] sload`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallWrapper {
+    /// The macro name
+    pub name: String,
+    /// The call opcode
+    pub kind: CallKind,
+    /// Pushes the gas forwarded to the call
+    pub gas: String,
+    /// Pushes the value sent with the call, unused unless calling with CALL
+    pub value: String,
+    /// Pushes the address called
+    pub target: String,
+    /// Whether to return the returndata on success, instead of continuing execution with the
+    /// returndata copied to memory at offset 0
+    pub forward_return: bool,
+}
+
+impl CallWrapper {
+    /// Public associated function to instantiate a wrapper forwarding all gas and the callvalue,
+    /// and returning the returndata on success
+    pub fn new(name: &str, kind: CallKind, target: &str) -> Self {
+        CallWrapper {
+            name: name.to_string(),
+            kind,
+            gas: "gas".to_string(),
+            value: "callvalue".to_string(),
+            target: target.to_string(),
+            forward_return: true,
+        }
+    }
+}
+
+impl fmt::Display for CallWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let success = format!("{}_success", self.name.to_lowercase());
+        let width = [&self.gas, &self.value, &self.target, &success]
+            .iter()
+            .map(|s| s.len() + 7)
+            .chain([40])
+            .max()
+            .unwrap_or_default();
+        let line = |f: &mut fmt::Formatter, code: &str, comment: &str| {
+            writeln!(f, "    {:<width$}// {}", code, comment, width = width)
+        };
+
+        writeln!(
+            f,
+            "/// Calls `{}` with the calldata using {}, bubbling up the revert data on failure",
+            self.target,
+            self.kind.opcode().to_uppercase()
+        )?;
+        writeln!(f, "#define macro {}() = takes (0) returns (0) {{", self.name)?;
+        writeln!(f, "    // Store the calldata in memory")?;
+        line(f, "calldatasize 0x00 0x00 calldatacopy", "[]")?;
+        writeln!(f)?;
+        writeln!(f, "    // Call the target, copying the returndata afterwards")?;
+        line(f, "0x00 0x00 calldatasize 0x00", "[args_offset, args_size, ret_offset, ret_size]")?;
+        if self.kind == CallKind::Call {
+            line(f, &self.value, "[value, args_offset, args_size, ret_offset, ret_size]")?;
+        }
+        line(f, &self.target, "[target, ...]")?;
+        line(f, &self.gas, "[gas, target, ...]")?;
+        line(f, self.kind.opcode(), "[success]")?;
+        line(f, "returndatasize 0x00 0x00 returndatacopy", "[success]")?;
+        writeln!(f)?;
+        writeln!(f, "    // Bubble up the revert data on failure")?;
+        line(f, &format!("{} jumpi", success), "[]")?;
+        line(f, "returndatasize 0x00 revert", "[]")?;
+        writeln!(f)?;
+        writeln!(f, "    {}:", success)?;
+        if self.forward_return {
+            writeln!(f, "        returndatasize 0x00 return")?;
+        }
+        writeln!(f, "}}")
+    }
+}
@@ -0,0 +1,120 @@
+//! ## Decode
+//!
+//! Decodes calldata and return data against a contract's abi, ie. to check what a dispatcher was
+//! called with or returned.
+
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::I256,
+    utils::{hex, keccak256, to_checksum},
+};
+use std::fmt;
+
+use crate::abi::{Abi, FunctionParam, FunctionParamType};
+
+/// #### DecodedCall
+///
+/// Calldata, revert data, or return data decoded against an abi.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    /// The signature of the function or error decoded against, ie. `transfer(address,uint256)`
+    pub signature: String,
+    /// The decoded parameters, with their values
+    pub params: Vec<(FunctionParam, Token)>,
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.signature)?;
+        for (i, (param, value)) in self.params.iter().enumerate() {
+            let name = if param.name.is_empty() { format!("[{}]", i) } else { param.name.clone() };
+            writeln!(f, "  {}: {} = {}", name, param.kind, format_token(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl Abi {
+    /// Decodes calldata by its selector, falling back to the abi's custom errors so that revert
+    /// data can be decoded too.
+    pub fn decode_calldata(&self, data: &[u8]) -> Result<DecodedCall, String> {
+        if data.len() < 4 {
+            return Err(format!("Data of {} bytes is too short to have a selector", data.len()))
+        }
+        let (selector, args) = data.split_at(4);
+
+        let functions = self.functions.values().map(|f| (f.signature(), &f.inputs));
+        let errors = self.errors.values().map(|e| (e.signature(), &e.inputs));
+        match functions.chain(errors).find(|(sig, _)| keccak256(sig.as_bytes())[..4] == *selector) {
+            Some((signature, inputs)) => decode_params(signature, inputs, args),
+            None => {
+                Err(format!("No function or error has the selector 0x{}", hex::encode(selector)))
+            }
+        }
+    }
+
+    /// Decodes the data a function returned against its outputs.
+    ///
+    /// The function can be given by name, or by signature if it's overloaded.
+    pub fn decode_returndata(&self, function: &str, data: &[u8]) -> Result<DecodedCall, String> {
+        match self.functions.values().find(|f| f.name == function || f.signature() == function) {
+            Some(f) => decode_params(format!("{}", f), &f.outputs, data),
+            None => Err(format!("No function \"{}\" in the abi", function)),
+        }
+    }
+}
+
+/// Decodes abi encoded data into the given parameters
+fn decode_params(
+    signature: String,
+    params: &[FunctionParam],
+    data: &[u8],
+) -> Result<DecodedCall, String> {
+    let types = params.iter().map(|p| p.kind.param_type()).collect::<Vec<ParamType>>();
+    let values = abi::decode(&types, data)
+        .map_err(|e| format!("Failed to decode data for \"{}\": {}", signature, e))?;
+    Ok(DecodedCall { signature, params: params.iter().cloned().zip(values).collect() })
+}
+
+/// Formats a decoded value as it would be written in solidity
+pub fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(a) => to_checksum(a, None),
+        Token::Bytes(b) | Token::FixedBytes(b) => format!("0x{}", hex::encode(b)),
+        Token::Uint(u) => u.to_string(),
+        Token::Int(i) => I256::from_raw(*i).to_string(),
+        Token::Bool(b) => b.to_string(),
+        Token::String(s) => format!("{:?}", s),
+        Token::Array(t) | Token::FixedArray(t) => {
+            format!("[{}]", t.iter().map(format_token).collect::<Vec<String>>().join(", "))
+        }
+        Token::Tuple(t) => {
+            format!("({})", t.iter().map(format_token).collect::<Vec<String>>().join(", "))
+        }
+    }
+}
+
+impl FunctionParamType {
+    /// Converts the type to an ethabi param type, for abi encoding and decoding
+    pub fn param_type(&self) -> ParamType {
+        match self {
+            FunctionParamType::Address => ParamType::Address,
+            FunctionParamType::Bytes => ParamType::Bytes,
+            FunctionParamType::Int(size) => ParamType::Int(*size),
+            FunctionParamType::Uint(size) => ParamType::Uint(*size),
+            FunctionParamType::Bool => ParamType::Bool,
+            FunctionParamType::String => ParamType::String,
+            // Sizes are listed innermost first, with 0 for dynamic arrays
+            FunctionParamType::Array(inner, sizes) => {
+                sizes.iter().fold(inner.param_type(), |ty, size| match size {
+                    0 => ParamType::Array(Box::new(ty)),
+                    s => ParamType::FixedArray(Box::new(ty), *s),
+                })
+            }
+            FunctionParamType::FixedBytes(size) => ParamType::FixedBytes(*size),
+            FunctionParamType::Tuple(inner) => {
+                ParamType::Tuple(inner.iter().map(|t| t.param_type()).collect())
+            }
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use crate::{
+    bytecode::MacroSize,
+    evm::{EvmVersion, MAX_INITCODE_SIZE, MAX_RUNTIME_SIZE},
     files::{Span, Spanned},
     io::UnpackError,
     prelude::{parse_extension, AstSpan},
     report::{Report, Reporter},
     token::TokenKind,
 };
-use std::{ffi::OsString, fmt, io::Write};
+use std::{borrow::Cow, ffi::OsString, fmt, io::Write};
 
 /// A Parser Error
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -71,10 +73,31 @@ impl<'a> LexicalError<'a> {
     pub fn new(kind: LexicalErrorKind<'a>, span: Span) -> Self {
         Self { kind, span }
     }
+
+    /// Copies the borrowed source segments so the error can outlive the lexed source
+    pub fn into_owned(self) -> LexicalError<'static> {
+        let kind = match self.kind {
+            LexicalErrorKind::UnexpectedEof => LexicalErrorKind::UnexpectedEof,
+            LexicalErrorKind::InvalidCharacter(c) => LexicalErrorKind::InvalidCharacter(c),
+            LexicalErrorKind::InvalidArraySize(s) => {
+                LexicalErrorKind::InvalidArraySize(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::InvalidPrimitiveType(s) => {
+                LexicalErrorKind::InvalidPrimitiveType(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::NumberOverflow(s) => {
+                LexicalErrorKind::NumberOverflow(Cow::Owned(s.into_owned()))
+            }
+            LexicalErrorKind::LiteralOverflow(s) => {
+                LexicalErrorKind::LiteralOverflow(Cow::Owned(s.into_owned()))
+            }
+        };
+        LexicalError { kind, span: self.span }
+    }
 }
 
 /// A Lexical Error Kind
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum LexicalErrorKind<'a> {
     /// Unexpected end of file
     UnexpectedEof,
@@ -82,9 +105,13 @@ pub enum LexicalErrorKind<'a> {
     InvalidCharacter(char),
     /// Invalid Array Size
     /// String param expected to be usize parsable
-    InvalidArraySize(&'a str),
+    InvalidArraySize(Cow<'a, str>),
     /// Invalid Primitive EVM Type
-    InvalidPrimitiveType(&'a str),
+    InvalidPrimitiveType(Cow<'a, str>),
+    /// Number too large to fit in a usize
+    NumberOverflow(Cow<'a, str>),
+    /// Hex literal longer than 32 bytes
+    LiteralOverflow(Cow<'a, str>),
 }
 
 impl<'a> Spanned for LexicalError<'a> {
@@ -95,7 +122,7 @@ impl<'a> Spanned for LexicalError<'a> {
 
 impl<'a, W: Write> Report<W> for LexicalError<'a> {
     fn report(&self, f: &mut Reporter<'_, W>) -> std::io::Result<()> {
-        match self.kind {
+        match &self.kind {
             LexicalErrorKind::InvalidCharacter(ch) => write!(f.out, "Invalid character '{}'", ch),
             LexicalErrorKind::UnexpectedEof => write!(f.out, "Found unexpected EOF"),
             LexicalErrorKind::InvalidArraySize(str) => {
@@ -104,6 +131,10 @@ impl<'a, W: Write> Report<W> for LexicalError<'a> {
             LexicalErrorKind::InvalidPrimitiveType(str) => {
                 write!(f.out, "Invalid Primitive EVM Type '{}'", str)
             }
+            LexicalErrorKind::NumberOverflow(str) => write!(f.out, "Number overflow: '{}'", str),
+            LexicalErrorKind::LiteralOverflow(str) => {
+                write!(f.out, "Literal exceeds 32 bytes: '0x{}'", str)
+            }
         }
     }
 }
@@ -117,12 +148,20 @@ pub struct CodegenError {
     pub span: AstSpan,
     /// An Optional Token Kind
     pub token: Option<TokenKind>,
+    /// The invocation sites of the macros the error occurred in, innermost first
+    pub expansion: Vec<AstSpan>,
 }
 
 impl CodegenError {
     /// Public associated function to instatiate a new CodegenError.
     pub fn new(kind: CodegenErrorKind, spans: AstSpan, token: Option<TokenKind>) -> Self {
-        Self { kind, span: spans, token }
+        Self { kind, span: spans, token, expansion: vec![] }
+    }
+
+    /// Records the invocation site of the macro enclosing those recorded so far.
+    pub fn expanded_from(mut self, site: AstSpan) -> Self {
+        self.expansion.push(site);
+        self
     }
 }
 
@@ -139,8 +178,16 @@ pub enum CodegenErrorKind {
     MissingFunctionInterface(String),
     /// The Event Interface is Missing
     MissingEventInterface(String),
+    /// The Custom Error Definition is Missing
+    MissingErrorDefinition(String),
     /// Missing Constant Definition
     MissingConstantDefinition(String),
+    /// Missing Immutable Definition
+    MissingImmutableDefinition(String),
+    /// An Immutable Is Read Before the Constructor Sets It
+    ImmutableReadInConstructor(String),
+    /// A String Literal Longer Than A Word
+    StringLiteralTooLong(usize),
     /// Abi Generation Failure
     AbiGenerationFailure,
     /// Unmatched Jump
@@ -161,11 +208,47 @@ pub enum CodegenErrorKind {
     InvalidTableStatement(String),
     /// Invalid Code Length
     InvalidCodeLength(usize),
+    /// Macro Expansion Includes Itself, Through the Cycle of Macros Expanded
+    CircularMacroInvocation(Vec<String>),
+    /// Compilation Cancelled
+    Cancelled,
+    /// The Builtin Function is Not Registered
+    UnknownBuiltin(String),
+    /// Macro Invoked With the Wrong Number of Arguments: (macro, expected, found)
+    InvalidMacroArgCount(String, usize, usize),
+    /// Opcode Not Available in the Target EVM Version: (opcode, version)
+    UnsupportedOpcode(String, EvmVersion),
+    /// A Statement Pops More Stack Items Than the Macro Has
+    StackUnderflow(String),
+    /// Macro Leaves a Different Number of Stack Items Than It Returns: (macro, returns, found)
+    StackHeightMismatch(String, usize, usize),
+    /// A Macro, Constant, or Table Is Defined More Than Once: (kind, name)
+    DuplicateDefinition(String, String),
+    /// Overloaded Functions Share the Label `__DISPATCH` Jumps To
+    OverloadedDispatch(String),
+    /// A Jump Label Is Defined by Several Macro Invocations: (label, definition offsets)
+    AmbiguousJumpLabel(String, Vec<usize>),
+    /// A Jump Destination Doesn't Fit in a PUSH2: (label, destination)
+    JumpDestinationTooLarge(String, usize),
+    /// A Constant Expression Overflows 256 Bits
+    ConstantOverflow(String),
+    /// A Constant Expression Divides By Zero
+    ConstantDivisionByZero(String),
+    /// A Constant Expression References Itself, Through the Cycle of Constants Referenced
+    CircularConstant(Vec<String>),
+    /// Macros Are Expanded Within Each Other Past the Depth Limit: (macro, limit)
+    MacroExpansionTooDeep(String, usize),
+    /// The Expanded Bytecode Exceeds the Size Limit: (macro, limit)
+    ExpandedBytecodeTooLarge(String, usize),
+    /// An Artifact Failed to Serialize
+    SerializationError(String),
+    /// There Is No Artifact to Export
+    MissingArtifact,
 }
 
 impl Spanned for CodegenError {
     fn span(&self) -> Span {
-        self.span.0[0].clone()
+        self.span.0.first().cloned().unwrap_or(Span::EOF)
     }
 }
 
@@ -188,9 +271,21 @@ impl<W: Write> Report<W> for CodegenError {
             CodegenErrorKind::MissingEventInterface(str) => {
                 write!(f.out, "Missing Event Interface for \"{}\"!", str)
             }
+            CodegenErrorKind::MissingErrorDefinition(str) => {
+                write!(f.out, "Missing Error Definition for \"{}\"!", str)
+            }
             CodegenErrorKind::MissingConstantDefinition(cd) => {
                 write!(f.out, "Missing Constant Definition for \"{}\"!", cd)
             }
+            CodegenErrorKind::MissingImmutableDefinition(id) => {
+                write!(f.out, "Missing Immutable Definition for \"{}\"!", id)
+            }
+            CodegenErrorKind::ImmutableReadInConstructor(id) => {
+                write!(f.out, "Immutable \"{}\" can't be read in the constructor!", id)
+            }
+            CodegenErrorKind::StringLiteralTooLong(len) => {
+                write!(f.out, "String literal of {} bytes is too long to push!", len)
+            }
             CodegenErrorKind::AbiGenerationFailure => write!(f.out, "Abi generation failure!"),
             CodegenErrorKind::UnmatchedJumpLabel => write!(f.out, "Unmatched jump label!"),
             CodegenErrorKind::IOError(ioe) => write!(f.out, "IO ERROR: {:?}", ioe),
@@ -210,10 +305,116 @@ impl<W: Write> Report<W> for CodegenError {
             CodegenErrorKind::InvalidCodeLength(len) => {
                 write!(f.out, "Invalid code length: {}", len)
             }
+            CodegenErrorKind::CircularMacroInvocation(cycle) => {
+                write!(f.out, "Circular macro invocation: {}!", cycle.join(" -> "))
+            }
+            CodegenErrorKind::Cancelled => write!(f.out, "Compilation cancelled"),
+            CodegenErrorKind::UnknownBuiltin(str) => {
+                write!(f.out, "Unknown builtin function \"{}\"!", str)
+            }
+            CodegenErrorKind::InvalidMacroArgCount(name, expected, found) => {
+                write!(
+                    f.out,
+                    "Macro \"{}\" takes {} argument(s), but {} were given!",
+                    name, expected, found
+                )
+            }
+            CodegenErrorKind::UnsupportedOpcode(opcode, version) => {
+                write!(f.out, "Opcode \"{}\" is not supported by the {} evm!", opcode, version)
+            }
+            CodegenErrorKind::StackUnderflow(name) => {
+                write!(f.out, "Stack underflow in macro \"{}\"!", name)
+            }
+            CodegenErrorKind::StackHeightMismatch(name, returns, found) => {
+                write!(
+                    f.out,
+                    "Macro \"{}\" returns {} stack item(s), but leaves {}!",
+                    name, returns, found
+                )
+            }
+            CodegenErrorKind::DuplicateDefinition(kind, name) => {
+                write!(f.out, "The {} \"{}\" is defined more than once!", kind, name)
+            }
+            CodegenErrorKind::OverloadedDispatch(name) => {
+                write!(
+                    f.out,
+                    "Function \"{}\" is overloaded, so __DISPATCH can't jump to it!",
+                    name
+                )
+            }
+            CodegenErrorKind::AmbiguousJumpLabel(label, offsets) => {
+                write!(
+                    f.out,
+                    "Jump label \"{}\" is ambiguous, defined by invocations at offsets {}!",
+                    label,
+                    offsets.iter().map(|o| format!("0x{:x}", o)).collect::<Vec<_>>().join(", ")
+                )
+            }
+            CodegenErrorKind::JumpDestinationTooLarge(label, destination) => {
+                write!(
+                    f.out,
+                    "Jump destination 0x{:x} of \"{}\" exceeds 0xffff!",
+                    destination, label
+                )
+            }
+            CodegenErrorKind::ConstantOverflow(name) => {
+                write!(f.out, "Constant \"{}\" overflows 256 bits!", name)
+            }
+            CodegenErrorKind::ConstantDivisionByZero(name) => {
+                write!(f.out, "Constant \"{}\" divides by zero!", name)
+            }
+            CodegenErrorKind::CircularConstant(cycle) => {
+                write!(f.out, "Circular constant reference: {}!", cycle.join(" -> "))
+            }
+            CodegenErrorKind::MacroExpansionTooDeep(name, limit) => {
+                write!(f.out, "Expanding \"{}\" nests more than {} macros!", name, limit)
+            }
+            CodegenErrorKind::ExpandedBytecodeTooLarge(name, limit) => {
+                write!(f.out, "Expanding \"{}\" exceeds {} bytes of bytecode!", name, limit)
+            }
+            CodegenErrorKind::SerializationError(e) => {
+                write!(f.out, "Failed to serialize artifact: {}", e)
+            }
+            CodegenErrorKind::MissingArtifact => write!(f.out, "No artifact to export!"),
         }
     }
 }
 
+/// A Constructor Simulation Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SimulationError {
+    /// The kind of simulation error
+    pub kind: SimulationErrorKind,
+    /// The path of the simulated file
+    pub file: String,
+}
+
+/// The Constructor Simulation Error Kind
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SimulationErrorKind {
+    /// The constructor reverted with the given hex encoded output
+    Reverted(String),
+    /// The constructor halted for the given reason
+    Halted(String),
+    /// The evm failed to execute the transaction
+    EvmError(String),
+    /// The state to simulate against is invalid
+    InvalidPrestate(String),
+}
+
+/// A Contract Size Budget Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SizeBudgetError {
+    /// The path of the compiled file
+    pub file: String,
+    /// The runtime bytecode size in bytes
+    pub size: usize,
+    /// The configured size budget in bytes
+    pub budget: usize,
+    /// The macros contributing to the runtime bytecode, largest first
+    pub report: Vec<(String, MacroSize)>,
+}
+
 /// CompilerError
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CompilerError<'a> {
@@ -227,14 +428,20 @@ pub enum CompilerError<'a> {
     PathBufRead(OsString),
     /// Bytecode Generation Error
     CodegenError(CodegenError),
+    /// Constructor Simulation Error
+    SimulationError(SimulationError),
+    /// Runtime Size Budget Exceeded
+    SizeBudgetError(SizeBudgetError),
     /// Multiple Failed Compiles
     FailedCompiles(Vec<CompilerError<'a>>),
+    /// Compilation Cancelled
+    Cancelled,
 }
 
 impl<'a> fmt::Display for CompilerError<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            CompilerError::LexicalError(le) => match le.kind {
+            CompilerError::LexicalError(le) => match &le.kind {
                 LexicalErrorKind::UnexpectedEof => {
                     write!(
                         f,
@@ -270,6 +477,24 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         le.span.source_seg()
                     )
                 }
+                LexicalErrorKind::NumberOverflow(n) => {
+                    write!(
+                        f,
+                        "\nError: Number Overflow: \"{}\" {}{}\n",
+                        n,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
+                LexicalErrorKind::LiteralOverflow(l) => {
+                    write!(
+                        f,
+                        "\nError: Literal Exceeds 32 Bytes: \"0x{}\" {}{}\n",
+                        l,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
             },
             CompilerError::FileUnpackError(ue) => match ue {
                 UnpackError::InvalidDirectory(id) => {
@@ -431,6 +656,13 @@ impl<'a> fmt::Display for CompilerError<'a> {
                     os_str.as_os_str().to_str().unwrap_or("<unknown import>")
                 )
             }
+            CompilerError::CodegenError(ce) if !ce.expansion.is_empty() => {
+                let inner = CodegenError { expansion: vec![], ..ce.clone() };
+                write!(f, "{}", CompilerError::CodegenError(inner))?;
+                ce.expansion.iter().try_for_each(|site| {
+                    write!(f, "\nNote: In This Macro Invocation\n{}\n", site.error(None))
+                })
+            }
             CompilerError::CodegenError(ce) => match &ce.kind {
                 CodegenErrorKind::StoragePointersNotDerived => {
                     write!(f, "\nError: Storage Pointers Not Derived\n{}\n", ce.span.error(None))
@@ -470,9 +702,41 @@ impl<'a> fmt::Display for CompilerError<'a> {
                         ce.span.error(None)
                     )
                 }
+                CodegenErrorKind::MissingErrorDefinition(error) => {
+                    write!(
+                        f,
+                        "\nError: Missing Error Definition: \"{}\"\n{}\n",
+                        error,
+                        ce.span.error(None)
+                    )
+                }
                 CodegenErrorKind::MissingConstantDefinition(_) => {
                     write!(f, "\nError: Missing Constant Definition\n{}\n", ce.span.error(None))
                 }
+                CodegenErrorKind::MissingImmutableDefinition(id) => {
+                    write!(
+                        f,
+                        "\nError: Missing Immutable Definition: \"{}\"\n{}\n",
+                        id,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::ImmutableReadInConstructor(id) => {
+                    write!(
+                        f,
+                        "\nError: Immutable \"{}\" Read in Constructor\n{}\n",
+                        id,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::StringLiteralTooLong(len) => {
+                    write!(
+                        f,
+                        "\nError: String Literal Of {} Bytes Is Too Long To Push\n{}\n",
+                        len,
+                        ce.span.error(None)
+                    )
+                }
                 CodegenErrorKind::AbiGenerationFailure => {
                     write!(f, "\nError: ABI Generation Failed\n{}\n", ce.span.error(None))
                 }
@@ -505,13 +769,351 @@ impl<'a> fmt::Display for CompilerError<'a> {
                 CodegenErrorKind::InvalidCodeLength(_) => {
                     write!(f, "\nError: Invalid Code Length\n{}\n", ce.span.error(None))
                 }
+                CodegenErrorKind::CircularMacroInvocation(cycle) => {
+                    write!(
+                        f,
+                        "\nError: Circular Macro Invocation: \"{}\"\n{}\n",
+                        cycle.join(" -> "),
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::Cancelled => write!(f, "\nError: Compilation Cancelled\n"),
+                CodegenErrorKind::UnknownBuiltin(ub) => {
+                    write!(
+                        f,
+                        "\nError: Unknown Builtin Function: \"{}\"\n{}\n",
+                        ub,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::InvalidMacroArgCount(name, expected, found) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Takes {} Argument(s), Found {}\n{}\n",
+                        name,
+                        expected,
+                        found,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::UnsupportedOpcode(opcode, version) => {
+                    write!(
+                        f,
+                        "\nError: Opcode \"{}\" Is Not Supported By The {} EVM\n{}\n",
+                        opcode,
+                        version,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::StackUnderflow(name) => {
+                    write!(
+                        f,
+                        "\nError: Stack Underflow In Macro \"{}\"\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::StackHeightMismatch(name, returns, found) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Returns {} Stack Item(s), Found {}\n{}\n",
+                        name,
+                        returns,
+                        found,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::DuplicateDefinition(kind, name) => {
+                    write!(
+                        f,
+                        "\nError: Duplicate Definition Of {} \"{}\"\n{}\n",
+                        kind,
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::OverloadedDispatch(name) => {
+                    write!(
+                        f,
+                        "\nError: Overloaded Function \"{}\" Can't Be Dispatched\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::AmbiguousJumpLabel(label, offsets) => {
+                    write!(
+                        f,
+                        "\nError: Ambiguous Jump Label \"{}\", Defined At Offsets {}\n{}\n",
+                        label,
+                        offsets.iter().map(|o| format!("0x{:x}", o)).collect::<Vec<_>>().join(", "),
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::JumpDestinationTooLarge(label, destination) => {
+                    write!(
+                        f,
+                        "\nError: Jump Destination 0x{:x} Of \"{}\" Exceeds 0xffff\n{}\n",
+                        destination,
+                        label,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::ConstantOverflow(name) => {
+                    write!(
+                        f,
+                        "\nError: Constant \"{}\" Overflows 256 Bits\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::ConstantDivisionByZero(name) => {
+                    write!(
+                        f,
+                        "\nError: Constant \"{}\" Divides By Zero\n{}\n",
+                        name,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::CircularConstant(cycle) => {
+                    write!(
+                        f,
+                        "\nError: Circular Constant Reference: \"{}\"\n{}\n",
+                        cycle.join(" -> "),
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::MacroExpansionTooDeep(name, limit) => {
+                    write!(
+                        f,
+                        "\nError: Expanding \"{}\" Nests More Than {} Macros\n{}\n",
+                        name,
+                        limit,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::ExpandedBytecodeTooLarge(name, limit) => {
+                    write!(
+                        f,
+                        "\nError: Expanding \"{}\" Exceeds {} Bytes Of Bytecode\n{}\n",
+                        name,
+                        limit,
+                        ce.span.error(None)
+                    )
+                }
+                CodegenErrorKind::SerializationError(e) => {
+                    write!(f, "\nError: Failed To Serialize Artifact: {}\n{}", e, ce.span.file())
+                }
+                CodegenErrorKind::MissingArtifact => {
+                    write!(f, "\nError: No Artifact To Export\n{}", ce.span.file())
+                }
             },
+            CompilerError::SimulationError(se) => match &se.kind {
+                SimulationErrorKind::Reverted(output) => {
+                    write!(
+                        f,
+                        "\nError: Constructor Reverted With Output \"0x{}\"\n-> {}\n",
+                        output, se.file
+                    )
+                }
+                SimulationErrorKind::Halted(reason) => {
+                    write!(f, "\nError: Constructor Halted: {}\n-> {}\n", reason, se.file)
+                }
+                SimulationErrorKind::EvmError(e) => {
+                    write!(f, "\nError: Constructor Simulation Failed: {}\n-> {}\n", e, se.file)
+                }
+                SimulationErrorKind::InvalidPrestate(e) => {
+                    write!(f, "\nError: Invalid Prestate: {}\n-> {}\n", e, se.file)
+                }
+            },
+            CompilerError::SizeBudgetError(sbe) => {
+                write!(
+                    f,
+                    "\nError: Runtime Size Of {} Bytes Exceeds Budget Of {} Bytes\n-> {}\n",
+                    sbe.size, sbe.budget, sbe.file
+                )?;
+                sbe.report.iter().try_for_each(|(name, ms)| {
+                    writeln!(
+                        f,
+                        "   {:>6} bytes  {} ({} invocation{})",
+                        ms.size,
+                        name,
+                        ms.invocations,
+                        if ms.invocations == 1 { "" } else { "s" }
+                    )
+                })
+            }
             CompilerError::FailedCompiles(v) => {
                 v.iter().for_each(|ce| {
                     let _ = write!(f, "{}", ce);
                 });
                 Ok(())
             }
+            CompilerError::Cancelled => write!(f, "\nError: Compilation Cancelled\n"),
+        }
+    }
+}
+
+/// A Non-Fatal Compiler Diagnostic
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CompilerWarning {
+    /// The path of the compiled file
+    pub file: String,
+    /// The warning kind
+    pub kind: CompilerWarningKind,
+}
+
+/// The Compiler Warning Kind
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CompilerWarningKind {
+    /// The abi could not be generated, so the artifact has none
+    AbiGenerationFailure(CodegenErrorKind),
+    /// The simulated constructor returned a different runtime than the generated one
+    SimulatedRuntimeMismatch,
+    /// The runtime bytecode exceeds the EIP-170 size limit: (size)
+    RuntimeSizeLimit(usize),
+    /// The deployment bytecode exceeds the EIP-3860 initcode size limit: (size)
+    InitcodeSizeLimit(usize),
+    /// A historical opcode alias was used: (alias, canonical)
+    OpcodeAlias(String, String),
+}
+
+impl fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            CompilerWarningKind::AbiGenerationFailure(kind) => {
+                write!(f, "Warning: Failed to generate abi for \"{}\": {:?}", self.file, kind)
+            }
+            CompilerWarningKind::SimulatedRuntimeMismatch => write!(
+                f,
+                "Warning: Simulated runtime differs from generated runtime for \"{}\"",
+                self.file
+            ),
+            CompilerWarningKind::RuntimeSizeLimit(size) => write!(
+                f,
+                "Warning: Runtime bytecode of \"{}\" is {} bytes, exceeding the EIP-170 limit of {} bytes",
+                self.file, size, MAX_RUNTIME_SIZE
+            ),
+            CompilerWarningKind::InitcodeSizeLimit(size) => write!(
+                f,
+                "Warning: Initcode of \"{}\" is {} bytes, exceeding the EIP-3860 limit of {} bytes",
+                self.file, size, MAX_INITCODE_SIZE
+            ),
+            CompilerWarningKind::OpcodeAlias(alias, canonical) => write!(
+                f,
+                "Warning: \"{}\" in \"{}\" is a historical alias, use \"{}\" instead",
+                alias, self.file, canonical
+            ),
+        }
+    }
+}
+
+/// A Package Installation Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PackageError {
+    /// The package spec is not of the form `owner/repo[@tag]`
+    InvalidSpec(String),
+    /// The package is already installed at the given path
+    AlreadyInstalled(String),
+    /// Running git failed with the given output
+    GitFailed(String),
+    /// Reading or writing the package files failed
+    Io(String),
+}
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackageError::InvalidSpec(s) => {
+                write!(f, "\nError: Invalid Package \"{}\", Expected \"owner/repo[@tag]\"\n", s)
+            }
+            PackageError::AlreadyInstalled(p) => {
+                write!(f, "\nError: Package Already Installed At \"{}\"\n", p)
+            }
+            PackageError::GitFailed(e) => write!(f, "\nError: Git Failed: {}\n", e),
+            PackageError::Io(e) => write!(f, "\nError: Package IO Failed: {}\n", e),
+        }
+    }
+}
+
+/// A Workspace Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WorkspaceError {
+    /// The workspace or member manifest could not be read
+    MissingManifest(String),
+    /// The manifest is not valid
+    InvalidManifest(String, String),
+    /// A workspace member directory does not exist
+    MissingMember(String),
+    /// Two workspace members share a name
+    DuplicateMember(String),
+    /// The manifest has no `[networks.<name>]` profile of the given name
+    MissingNetwork(String),
+    /// A network profile constant override is not a valid literal: (name, value)
+    InvalidConstantOverride(String, String),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkspaceError::MissingManifest(p) => {
+                write!(f, "\nError: Missing Workspace Manifest \"{}\"\n", p)
+            }
+            WorkspaceError::InvalidManifest(p, e) => {
+                write!(f, "\nError: Invalid Manifest \"{}\": {}\n", p, e)
+            }
+            WorkspaceError::MissingMember(p) => {
+                write!(f, "\nError: Missing Workspace Member \"{}\"\n", p)
+            }
+            WorkspaceError::DuplicateMember(n) => {
+                write!(f, "\nError: Duplicate Workspace Member Name \"{}\"\n", n)
+            }
+            WorkspaceError::MissingNetwork(n) => {
+                write!(f, "\nError: Missing Network Profile \"{}\"\n", n)
+            }
+            WorkspaceError::InvalidConstantOverride(n, v) => {
+                write!(f, "\nError: Invalid Constant Override \"{} = {}\"\n", n, v)
+            }
+        }
+    }
+}
+
+/// A Rename Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RenameError {
+    /// No symbol is defined or referenced at the given file offset
+    NoSymbol(String, usize),
+    /// The new name is not a valid identifier, or is reserved by the language
+    InvalidName(String),
+    /// The new name is already taken by a symbol in the same scope
+    NameConflict(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenameError::NoSymbol(file, offset) => {
+                write!(f, "\nError: No Symbol At \"{}\" Offset {}\n", file, offset)
+            }
+            RenameError::InvalidName(n) => write!(f, "\nError: Invalid Identifier \"{}\"\n", n),
+            RenameError::NameConflict(n) => {
+                write!(f, "\nError: Name \"{}\" Is Already Defined\n", n)
+            }
         }
     }
 }
+
+/// A Serialization Error
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SerializationError(pub String);
+
+impl From<serde_json::Error> for SerializationError {
+    fn from(e: serde_json::Error) -> Self {
+        SerializationError(e.to_string())
+    }
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\nError: Serialization Failed: {}\n", self.0)
+    }
+}
@@ -1,4 +1,5 @@
 use phf::phf_map;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use strum_macros::EnumString;
 
@@ -6,7 +7,7 @@ use strum_macros::EnumString;
 /// They are arranged in a particular order such that all the opcodes that have common
 /// prefixes are ordered by decreasing length to avoid mismatch when lexing.
 /// Example : [origin, or] or [push32, ..., push3]
-pub const OPCODES: [&str; 142] = [
+pub const OPCODES: [&str; 149] = [
     "lt",
     "gt",
     "slt",
@@ -18,7 +19,7 @@ pub const OPCODES: [&str; 142] = [
     "or",
     "xor",
     "not",
-    "sha3",
+    "keccak256",
     "address",
     "balance",
     "caller",
@@ -32,10 +33,13 @@ pub const OPCODES: [&str; 142] = [
     "coinbase",
     "timestamp",
     "number",
-    "difficulty",
+    "prevrandao",
     "gaslimit",
     "chainid",
     "selfbalance",
+    "basefee",
+    "blobhash",
+    "blobbasefee",
     "pop",
     "mload",
     "mstore8",
@@ -43,6 +47,9 @@ pub const OPCODES: [&str; 142] = [
     "sload",
     "sstore",
     "jumpdest",
+    "tload",
+    "tstore",
+    "mcopy",
     "jumpi",
     "jump",
     "pc",
@@ -117,6 +124,7 @@ pub const OPCODES: [&str; 142] = [
     "push3",
     "push2",
     "push1",
+    "push0",
     "swap16",
     "swap15",
     "swap14",
@@ -163,7 +171,7 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "or" => Opcode::Or,
     "xor" => Opcode::Xor,
     "not" => Opcode::Not,
-    "sha3" => Opcode::Sha3,
+    "keccak256" => Opcode::Keccak256,
     "address" => Opcode::Address,
     "balance" => Opcode::Balance,
     "origin" => Opcode::Origin,
@@ -178,10 +186,13 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "coinbase" => Opcode::Coinbase,
     "timestamp" => Opcode::Timestamp,
     "number" => Opcode::Number,
-    "difficulty" => Opcode::Difficulty,
+    "prevrandao" => Opcode::Prevrandao,
     "gaslimit" => Opcode::Gaslimit,
     "chainid" => Opcode::Chainid,
     "selfbalance" => Opcode::Selfbalance,
+    "basefee" => Opcode::Basefee,
+    "blobhash" => Opcode::Blobhash,
+    "blobbasefee" => Opcode::Blobbasefee,
     "pop" => Opcode::Pop,
     "mload" => Opcode::Mload,
     "mstore" => Opcode::Mstore,
@@ -192,6 +203,7 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "jumpi" => Opcode::Jumpi,
     "pc" => Opcode::Pc,
     "msize" => Opcode::Msize,
+    "push0" => Opcode::Push0,
     "push1" => Opcode::Push1,
     "push2" => Opcode::Push2,
     "push3" => Opcode::Push3,
@@ -256,6 +268,9 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "extcodehash" => Opcode::Extcodehash,
     "gas" => Opcode::Gas,
     "jumpdest" => Opcode::Jumpdest,
+    "tload" => Opcode::Tload,
+    "tstore" => Opcode::Tstore,
+    "mcopy" => Opcode::Mcopy,
     "push11" => Opcode::Push11,
     "push12" => Opcode::Push12,
     "push13" => Opcode::Push13,
@@ -294,7 +309,22 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
     "create2" => Opcode::Create2,
     "revert" => Opcode::Revert,
     "invalid" => Opcode::Invalid,
-    "selfdestruct" => Opcode::Selfdestruct
+    "selfdestruct" => Opcode::Selfdestruct,
+    // Mnemonics of the deprecated `Opcode::Sha3` and `Opcode::Difficulty`, so looking them up
+    // keeps working
+    "sha3" => Opcode::Keccak256,
+    "difficulty" => Opcode::Prevrandao
+};
+
+/// Historical opcode mnemonics, mapped to their canonical name in `OPCODES`.
+///
+/// The lexer accepts them so older sources keep compiling, but records each use so the compiler
+/// can warn about it.
+pub static OPCODE_ALIASES: phf::Map<&'static str, &'static str> = phf_map! {
+    "sha3" => "keccak256",
+    "difficulty" => "prevrandao",
+    "suicide" => "selfdestruct",
+    "datahash" => "blobhash",
 };
 
 /// EVM Opcodes
@@ -357,7 +387,8 @@ pub enum Opcode {
     /// Arithmetic Shift Right Operation
     Sar,
     /// Compute the Keccak-256 hash of a 32-byte word
-    Sha3,
+    #[strum(serialize = "Keccak256", serialize = "Sha3")]
+    Keccak256,
     /// Address of currently executing account
     Address,
     /// Balance of a given account
@@ -396,8 +427,9 @@ pub enum Opcode {
     Timestamp,
     /// The Current Blocks Number
     Number,
-    /// The Current Blocks Difficulty
-    Difficulty,
+    /// The Previous Blocks RANDAO Mix, or the Current Blocks Difficulty before the merge
+    #[strum(serialize = "Prevrandao", serialize = "Difficulty")]
+    Prevrandao,
     /// The Current Blocks Gas Limit
     Gaslimit,
     /// The Chain ID
@@ -406,6 +438,10 @@ pub enum Opcode {
     Selfbalance,
     /// Base Fee
     Basefee,
+    /// A Versioned Hash of the Current Transactions Blobs
+    Blobhash,
+    /// The Current Blocks Blob Base Fee
+    Blobbasefee,
     /// Removes an Item from the Stack
     Pop,
     /// Loads a word from Memory
@@ -430,6 +466,14 @@ pub enum Opcode {
     Gas,
     /// Marks a valid destination for jumps
     Jumpdest,
+    /// Load a word from Transient Storage
+    Tload,
+    /// Store a word in Transient Storage
+    Tstore,
+    /// Copies an area of Memory to another
+    Mcopy,
+    /// Places the constant 0 on top of the stack
+    Push0,
     /// Places 1 byte item on top of the stack
     Push1,
     /// Places 2 byte item on top of the stack
@@ -593,156 +637,199 @@ pub enum Opcode {
     Extcodehash,
 }
 
+#[allow(non_upper_case_globals)]
 impl Opcode {
+    /// The historical name of [Opcode::Keccak256]
+    #[deprecated(note = "renamed to `Opcode::Keccak256`")]
+    pub const Sha3: Opcode = Opcode::Keccak256;
+
+    /// The historical name of [Opcode::Prevrandao], which returns the difficulty before the merge
+    #[deprecated(note = "renamed to `Opcode::Prevrandao`")]
+    pub const Difficulty: Opcode = Opcode::Prevrandao;
+
+    /// The byte an Opcode is encoded as
+    pub fn byte(&self) -> u8 {
+        match self {
+            Opcode::Stop => 0x00,
+            Opcode::Add => 0x01,
+            Opcode::Mul => 0x02,
+            Opcode::Sub => 0x03,
+            Opcode::Div => 0x04,
+            Opcode::Sdiv => 0x05,
+            Opcode::Mod => 0x06,
+            Opcode::Smod => 0x07,
+            Opcode::Addmod => 0x08,
+            Opcode::Mulmod => 0x09,
+            Opcode::Exp => 0x0a,
+            Opcode::Signextend => 0x0b,
+            Opcode::Lt => 0x10,
+            Opcode::Gt => 0x11,
+            Opcode::Slt => 0x12,
+            Opcode::Sgt => 0x13,
+            Opcode::Eq => 0x14,
+            Opcode::Iszero => 0x15,
+            Opcode::And => 0x16,
+            Opcode::Or => 0x17,
+            Opcode::Xor => 0x18,
+            Opcode::Not => 0x19,
+            Opcode::Byte => 0x1a,
+            Opcode::Shl => 0x1b,
+            Opcode::Shr => 0x1c,
+            Opcode::Sar => 0x1d,
+            Opcode::Keccak256 => 0x20,
+            // Opcode::Keccak => 0x20,
+            Opcode::Address => 0x30,
+            Opcode::Balance => 0x31,
+            Opcode::Origin => 0x32,
+            Opcode::Caller => 0x33,
+            Opcode::Callvalue => 0x34,
+            Opcode::Calldataload => 0x35,
+            Opcode::Calldatasize => 0x36,
+            Opcode::Calldatacopy => 0x37,
+            Opcode::Codesize => 0x38,
+            Opcode::Codecopy => 0x39,
+            Opcode::Gasprice => 0x3a,
+            Opcode::Extcodesize => 0x3b,
+            Opcode::Extcodecopy => 0x3c,
+            Opcode::Returndatasize => 0x3d,
+            Opcode::Returndatacopy => 0x3e,
+            Opcode::Extcodehash => 0x3f,
+            Opcode::Blockhash => 0x40,
+            Opcode::Coinbase => 0x41,
+            Opcode::Timestamp => 0x42,
+            Opcode::Number => 0x43,
+            Opcode::Prevrandao => 0x44,
+            Opcode::Gaslimit => 0x45,
+            Opcode::Chainid => 0x46,
+            Opcode::Selfbalance => 0x47,
+            Opcode::Basefee => 0x48,
+            Opcode::Blobhash => 0x49,
+            Opcode::Blobbasefee => 0x4a,
+            Opcode::Pop => 0x50,
+            Opcode::Mload => 0x51,
+            Opcode::Mstore => 0x52,
+            Opcode::Mstore8 => 0x53,
+            Opcode::Sload => 0x54,
+            Opcode::Sstore => 0x55,
+            Opcode::Jump => 0x56,
+            Opcode::Jumpi => 0x57,
+            Opcode::Pc => 0x58,
+            Opcode::Msize => 0x59,
+            Opcode::Gas => 0x5a,
+            Opcode::Jumpdest => 0x5b,
+            Opcode::Tload => 0x5c,
+            Opcode::Tstore => 0x5d,
+            Opcode::Mcopy => 0x5e,
+            Opcode::Push0 => 0x5f,
+            Opcode::Push1 => 0x60,
+            Opcode::Push2 => 0x61,
+            Opcode::Push3 => 0x62,
+            Opcode::Push4 => 0x63,
+            Opcode::Push5 => 0x64,
+            Opcode::Push6 => 0x65,
+            Opcode::Push7 => 0x66,
+            Opcode::Push8 => 0x67,
+            Opcode::Push9 => 0x68,
+            Opcode::Push10 => 0x69,
+            Opcode::Push11 => 0x6a,
+            Opcode::Push12 => 0x6b,
+            Opcode::Push13 => 0x6c,
+            Opcode::Push14 => 0x6d,
+            Opcode::Push15 => 0x6e,
+            Opcode::Push16 => 0x6f,
+            Opcode::Push17 => 0x70,
+            Opcode::Push18 => 0x71,
+            Opcode::Push19 => 0x72,
+            Opcode::Push20 => 0x73,
+            Opcode::Push21 => 0x74,
+            Opcode::Push22 => 0x75,
+            Opcode::Push23 => 0x76,
+            Opcode::Push24 => 0x77,
+            Opcode::Push25 => 0x78,
+            Opcode::Push26 => 0x79,
+            Opcode::Push27 => 0x7a,
+            Opcode::Push28 => 0x7b,
+            Opcode::Push29 => 0x7c,
+            Opcode::Push30 => 0x7d,
+            Opcode::Push31 => 0x7e,
+            Opcode::Push32 => 0x7f,
+            Opcode::Dup1 => 0x80,
+            Opcode::Dup2 => 0x81,
+            Opcode::Dup3 => 0x82,
+            Opcode::Dup4 => 0x83,
+            Opcode::Dup5 => 0x84,
+            Opcode::Dup6 => 0x85,
+            Opcode::Dup7 => 0x86,
+            Opcode::Dup8 => 0x87,
+            Opcode::Dup9 => 0x88,
+            Opcode::Dup10 => 0x89,
+            Opcode::Dup11 => 0x8a,
+            Opcode::Dup12 => 0x8b,
+            Opcode::Dup13 => 0x8c,
+            Opcode::Dup14 => 0x8d,
+            Opcode::Dup15 => 0x8e,
+            Opcode::Dup16 => 0x8f,
+            Opcode::Swap1 => 0x90,
+            Opcode::Swap2 => 0x91,
+            Opcode::Swap3 => 0x92,
+            Opcode::Swap4 => 0x93,
+            Opcode::Swap5 => 0x94,
+            Opcode::Swap6 => 0x95,
+            Opcode::Swap7 => 0x96,
+            Opcode::Swap8 => 0x97,
+            Opcode::Swap9 => 0x98,
+            Opcode::Swap10 => 0x99,
+            Opcode::Swap11 => 0x9a,
+            Opcode::Swap12 => 0x9b,
+            Opcode::Swap13 => 0x9c,
+            Opcode::Swap14 => 0x9d,
+            Opcode::Swap15 => 0x9e,
+            Opcode::Swap16 => 0x9f,
+            Opcode::Log0 => 0xa0,
+            Opcode::Log1 => 0xa1,
+            Opcode::Log2 => 0xa2,
+            Opcode::Log3 => 0xa3,
+            Opcode::Log4 => 0xa4,
+            Opcode::Create => 0xf0,
+            Opcode::Call => 0xf1,
+            Opcode::Callcode => 0xf2,
+            Opcode::Return => 0xf3,
+            Opcode::Delegatecall => 0xf4,
+            Opcode::Create2 => 0xf5,
+            Opcode::Staticcall => 0xfa,
+            Opcode::Revert => 0xfd,
+            Opcode::Invalid => 0xfe,
+            Opcode::Selfdestruct => 0xff,
+        }
+    }
+
     /// Translates an Opcode into a string
     pub fn string(&self) -> String {
-        let opcode_str = match self {
-            Opcode::Stop => "00",
-            Opcode::Add => "01",
-            Opcode::Mul => "02",
-            Opcode::Sub => "03",
-            Opcode::Div => "04",
-            Opcode::Sdiv => "05",
-            Opcode::Mod => "06",
-            Opcode::Smod => "07",
-            Opcode::Addmod => "08",
-            Opcode::Mulmod => "09",
-            Opcode::Exp => "0a",
-            Opcode::Signextend => "0b",
-            Opcode::Lt => "10",
-            Opcode::Gt => "11",
-            Opcode::Slt => "12",
-            Opcode::Sgt => "13",
-            Opcode::Eq => "14",
-            Opcode::Iszero => "15",
-            Opcode::And => "16",
-            Opcode::Or => "17",
-            Opcode::Xor => "18",
-            Opcode::Not => "19",
-            Opcode::Byte => "1a",
-            Opcode::Shl => "1b",
-            Opcode::Shr => "1c",
-            Opcode::Sar => "1d",
-            Opcode::Sha3 => "20",
-            // Opcode::Keccak => "20",
-            Opcode::Address => "30",
-            Opcode::Balance => "31",
-            Opcode::Origin => "32",
-            Opcode::Caller => "33",
-            Opcode::Callvalue => "34",
-            Opcode::Calldataload => "35",
-            Opcode::Calldatasize => "36",
-            Opcode::Calldatacopy => "37",
-            Opcode::Codesize => "38",
-            Opcode::Codecopy => "39",
-            Opcode::Gasprice => "3a",
-            Opcode::Extcodesize => "3b",
-            Opcode::Extcodecopy => "3c",
-            Opcode::Returndatasize => "3d",
-            Opcode::Returndatacopy => "3e",
-            Opcode::Extcodehash => "3f",
-            Opcode::Blockhash => "40",
-            Opcode::Coinbase => "41",
-            Opcode::Timestamp => "42",
-            Opcode::Number => "43",
-            Opcode::Difficulty => "44",
-            Opcode::Gaslimit => "45",
-            Opcode::Chainid => "46",
-            Opcode::Selfbalance => "47",
-            Opcode::Basefee => "48",
-            Opcode::Pop => "50",
-            Opcode::Mload => "51",
-            Opcode::Mstore => "52",
-            Opcode::Mstore8 => "53",
-            Opcode::Sload => "54",
-            Opcode::Sstore => "55",
-            Opcode::Jump => "56",
-            Opcode::Jumpi => "57",
-            Opcode::Pc => "58",
-            Opcode::Msize => "59",
-            Opcode::Gas => "5a",
-            Opcode::Jumpdest => "5b",
-            Opcode::Push1 => "60",
-            Opcode::Push2 => "61",
-            Opcode::Push3 => "62",
-            Opcode::Push4 => "63",
-            Opcode::Push5 => "64",
-            Opcode::Push6 => "65",
-            Opcode::Push7 => "66",
-            Opcode::Push8 => "67",
-            Opcode::Push9 => "68",
-            Opcode::Push10 => "69",
-            Opcode::Push11 => "6a",
-            Opcode::Push12 => "6b",
-            Opcode::Push13 => "6c",
-            Opcode::Push14 => "6d",
-            Opcode::Push15 => "6e",
-            Opcode::Push16 => "6f",
-            Opcode::Push17 => "70",
-            Opcode::Push18 => "71",
-            Opcode::Push19 => "72",
-            Opcode::Push20 => "73",
-            Opcode::Push21 => "74",
-            Opcode::Push22 => "75",
-            Opcode::Push23 => "76",
-            Opcode::Push24 => "77",
-            Opcode::Push25 => "78",
-            Opcode::Push26 => "79",
-            Opcode::Push27 => "7a",
-            Opcode::Push28 => "7b",
-            Opcode::Push29 => "7c",
-            Opcode::Push30 => "7d",
-            Opcode::Push31 => "7e",
-            Opcode::Push32 => "7f",
-            Opcode::Dup1 => "80",
-            Opcode::Dup2 => "81",
-            Opcode::Dup3 => "82",
-            Opcode::Dup4 => "83",
-            Opcode::Dup5 => "84",
-            Opcode::Dup6 => "85",
-            Opcode::Dup7 => "86",
-            Opcode::Dup8 => "87",
-            Opcode::Dup9 => "88",
-            Opcode::Dup10 => "89",
-            Opcode::Dup11 => "8a",
-            Opcode::Dup12 => "8b",
-            Opcode::Dup13 => "8c",
-            Opcode::Dup14 => "8d",
-            Opcode::Dup15 => "8e",
-            Opcode::Dup16 => "8f",
-            Opcode::Swap1 => "90",
-            Opcode::Swap2 => "91",
-            Opcode::Swap3 => "92",
-            Opcode::Swap4 => "93",
-            Opcode::Swap5 => "94",
-            Opcode::Swap6 => "95",
-            Opcode::Swap7 => "96",
-            Opcode::Swap8 => "97",
-            Opcode::Swap9 => "98",
-            Opcode::Swap10 => "99",
-            Opcode::Swap11 => "9a",
-            Opcode::Swap12 => "9b",
-            Opcode::Swap13 => "9c",
-            Opcode::Swap14 => "9d",
-            Opcode::Swap15 => "9e",
-            Opcode::Swap16 => "9f",
-            Opcode::Log0 => "a0",
-            Opcode::Log1 => "a1",
-            Opcode::Log2 => "a2",
-            Opcode::Log3 => "a3",
-            Opcode::Log4 => "a4",
-            Opcode::Create => "f0",
-            Opcode::Call => "f1",
-            Opcode::Callcode => "f2",
-            Opcode::Return => "f3",
-            Opcode::Delegatecall => "f4",
-            Opcode::Create2 => "f5",
-            Opcode::Staticcall => "fa",
-            Opcode::Revert => "fd",
-            Opcode::Invalid => "fe",
-            Opcode::Selfdestruct => "ff",
-        };
-        opcode_str.to_string()
+        format!("{:02x}", self.byte())
+    }
+}
+
+impl Opcode {
+    /// The hardfork that introduced the opcode
+    pub fn introduced_in(&self) -> EvmVersion {
+        match self {
+            Opcode::Returndatasize |
+            Opcode::Returndatacopy |
+            Opcode::Staticcall |
+            Opcode::Revert => EvmVersion::Byzantium,
+            Opcode::Shl | Opcode::Shr | Opcode::Sar | Opcode::Create2 | Opcode::Extcodehash => {
+                EvmVersion::Constantinople
+            }
+            Opcode::Chainid | Opcode::Selfbalance => EvmVersion::Istanbul,
+            Opcode::Basefee => EvmVersion::London,
+            Opcode::Push0 => EvmVersion::Shanghai,
+            Opcode::Tload |
+            Opcode::Tstore |
+            Opcode::Mcopy |
+            Opcode::Blobhash |
+            Opcode::Blobbasefee => EvmVersion::Cancun,
+            _ => EvmVersion::Homestead,
+        }
     }
 }
 
@@ -758,3 +845,184 @@ impl From<Opcode> for String {
         o.string()
     }
 }
+
+/// EVM Hardforks
+///
+/// Ordered by activation, so a version supports every opcode introduced at or before it.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EvmVersion {
+    /// Homestead
+    Homestead,
+    /// Byzantium, introducing `revert`, `staticcall`, and the return data opcodes
+    Byzantium,
+    /// Constantinople, introducing the shift opcodes, `create2`, and `extcodehash`
+    Constantinople,
+    /// Petersburg
+    Petersburg,
+    /// Istanbul, introducing `chainid` and `selfbalance`
+    Istanbul,
+    /// Berlin
+    Berlin,
+    /// London, introducing `basefee`
+    #[default]
+    London,
+    /// Paris, the merge, replacing the block difficulty with the RANDAO mix
+    Paris,
+    /// Shanghai, introducing `push0`
+    Shanghai,
+    /// Cancun, introducing the transient storage opcodes, `mcopy`, and the blob opcodes
+    Cancun,
+}
+
+impl EvmVersion {
+    /// Whether the opcode is available in this version
+    pub fn supports(&self, opcode: Opcode) -> bool {
+        opcode.introduced_in() <= *self
+    }
+}
+
+impl fmt::Display for EvmVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+/// The maximum size of deployed runtime bytecode in bytes, from EIP-170
+pub const MAX_RUNTIME_SIZE: usize = 24576;
+
+/// The maximum size of initcode in bytes, from EIP-3860
+pub const MAX_INITCODE_SIZE: usize = 49152;
+
+/// Returns the lowercase mnemonic of an opcode byte, or `None` if the byte is unassigned.
+pub fn opcode_mnemonic(opcode: u8) -> Option<&'static str> {
+    OPCODES.iter().find(|o| OPCODES_MAP[**o].byte() == opcode).copied()
+}
+
+/// Returns the static gas cost of an opcode byte.
+///
+/// Dynamic costs, ie. memory expansion, cold access, or copied words, are not included.
+pub fn static_gas(opcode: u8) -> u64 {
+    match opcode {
+        0x5b => 1,
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x46 | 0x48 | 0x4a | 0x50 |
+        0x58..=0x5a | 0x5f => 2,
+        0x01 | 0x03 | 0x10..=0x1d | 0x35 | 0x37 | 0x39 | 0x3e | 0x49 | 0x51..=0x53 | 0x5e |
+        0x60..=0x9f => 3,
+        0x02 | 0x04..=0x07 | 0x0b | 0x47 => 5,
+        0x08 | 0x09 | 0x56 => 8,
+        0x0a | 0x57 => 10,
+        0x40 => 20,
+        0x20 => 30,
+        0x31 | 0x3b | 0x3c | 0x3f | 0x54 | 0x55 | 0x5c | 0x5d | 0xf1 | 0xf2 | 0xf4 | 0xfa => 100,
+        0xa0..=0xa4 => 375 * (1 + (opcode - 0xa0) as u64),
+        0xff => 5000,
+        0xf0 | 0xf5 => 32000,
+        _ => 0,
+    }
+}
+
+/// An Instruction of Hex Encoded Bytecode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction<'a> {
+    /// The offset of the instruction in bytes
+    pub pc: usize,
+    /// The opcode byte
+    pub opcode: u8,
+    /// The hex encoded push data, cut short if the bytecode ends before it does
+    pub data: &'a str,
+}
+
+/// An Iterator over the Instructions of Hex Encoded Bytecode
+///
+/// Push data is stepped over and left as written, so unresolved jump destinations like `xxxx`
+/// are kept. Iteration stops at the first opcode that isn't valid hex.
+#[derive(Debug, Clone)]
+pub struct Instructions<'a> {
+    bytecode: &'a str,
+    index: usize,
+}
+
+impl<'a> Instructions<'a> {
+    /// Iterates the instructions of a hex bytecode string, with or without a `0x` prefix
+    pub fn new(bytecode: &'a str) -> Self {
+        Self { bytecode: bytecode.strip_prefix("0x").unwrap_or(bytecode), index: 0 }
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Instruction<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let opcode = u8::from_str_radix(self.bytecode.get(self.index..self.index + 2)?, 16).ok()?;
+        let pc = self.index / 2;
+        let start = self.index + 2;
+        let end = match opcode {
+            0x60..=0x7f => (start + 2 * (opcode - 0x5f) as usize).min(self.bytecode.len()),
+            _ => start,
+        };
+        self.index = end;
+        Some(Instruction { pc, opcode, data: &self.bytecode[start..end] })
+    }
+}
+
+/// The maximum number of items on the stack
+pub const STACK_LIMIT: usize = 1024;
+
+/// Returns whether an opcode byte has costs on top of its static gas, ie. memory expansion, cold
+/// access, copied words, or storage refunds.
+pub fn has_dynamic_gas(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x0a | 0x20 | 0x31 | 0x37 | 0x39 | 0x3b | 0x3c | 0x3e | 0x3f | 0x51..=0x55 | 0x5e |
+            0xa0..=0xa4 | 0xf0..=0xf5 | 0xfa | 0xfd | 0xff
+    )
+}
+
+/// Sums the static gas of every instruction in a hex bytecode string, skipping push data, and
+/// returns whether any instruction has dynamic costs on top.
+pub fn estimate_static_gas(bytecode: &str) -> (u64, bool) {
+    Instructions::new(bytecode).fold((0, false), |(gas, dynamic), Instruction { opcode, .. }| {
+        (gas + static_gas(opcode), dynamic || has_dynamic_gas(opcode))
+    })
+}
+
+/// Returns the number of stack items an opcode byte pops and pushes.
+///
+/// Unassigned opcode bytes neither pop nor push.
+pub fn stack_effect(opcode: u8) -> (usize, usize) {
+    match opcode {
+        0x01..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d | 0x20 => (2, 1),
+        0x08 | 0x09 => (3, 1),
+        0x15 | 0x19 | 0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 | 0x51 | 0x54 | 0x5c => (1, 1),
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x48 | 0x4a | 0x58..=0x5a |
+        0x5f => (0, 1),
+        0x37 | 0x39 | 0x3e | 0x5e => (3, 0),
+        0x3c => (4, 0),
+        0x50 | 0x56 | 0xff => (1, 0),
+        0x52 | 0x53 | 0x55 | 0x57 | 0x5d | 0xf3 | 0xfd => (2, 0),
+        0x60..=0x7f => (0, 1),
+        0x80..=0x8f => ((opcode - 0x7f) as usize, (opcode - 0x7e) as usize),
+        0x90..=0x9f => ((opcode - 0x8e) as usize, (opcode - 0x8e) as usize),
+        0xa0..=0xa4 => ((opcode - 0x9e) as usize, 0),
+        0xf0 => (3, 1),
+        0xf5 => (4, 1),
+        0xf1 | 0xf2 => (7, 1),
+        0xf4 | 0xfa => (6, 1),
+        _ => (0, 0),
+    }
+}
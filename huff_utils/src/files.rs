@@ -1,9 +1,9 @@
+use crate::{remapping::Remapping, stdlib::is_std_import};
 use serde::{Deserialize, Serialize};
 use std::{cell::Ref, path::PathBuf, sync::Arc, time::SystemTime};
 use uuid::Uuid;
 
 #[allow(clippy::to_string_in_format_args)]
-
 /// An aliased output location to derive from the cli arguments.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
 pub struct OutputLocation(pub String);
@@ -45,7 +45,8 @@ pub struct FileSource {
     pub path: String,
     /// File Source
     pub source: Option<String>,
-    /// Last File Access Time
+    /// Last File Access Time, which isn't serialized so that artifacts are stable across builds
+    #[serde(default, skip_serializing)]
     pub access: Option<SystemTime>,
     /// An Ordered List of File Dependencies
     pub dependencies: Option<Vec<Arc<FileSource>>>,
@@ -59,7 +60,20 @@ impl FileSource {
     /// Let's say you have a file, `a.txt` with two dependencies, `b.txt` and `c.txt`,
     /// `fully_flatten()` will generate a source code string with the contents of `b.txt` and
     /// `c.txt` appended to the end of the contents of `a.txt`.
+    ///
+    /// A file included more than once, ie. by two of its dependents, is only appended the first
+    /// time, so that its definitions aren't duplicated.
     pub fn fully_flatten(self_ref: Arc<FileSource>) -> (String, Vec<(Arc<FileSource>, Span)>) {
+        FileSource::flatten_into(self_ref, &mut vec![])
+    }
+
+    /// Flattens a file source and its dependencies, skipping dependencies already included
+    fn flatten_into(
+        self_ref: Arc<FileSource>,
+        included: &mut Vec<String>,
+    ) -> (String, Vec<(Arc<FileSource>, Span)>) {
+        included.push(self_ref.path.clone());
+
         // First grab the parent file source
         let mut full_source =
             if let Some(s) = &self_ref.source { s.clone() } else { String::default() };
@@ -67,24 +81,42 @@ impl FileSource {
         let mut relative_positions = vec![(Arc::clone(&self_ref), span)];
 
         // Then recursively grab source code for dependencies
-        match &self_ref.dependencies {
-            Some(vfs) => {
-                for fs in vfs {
-                    let mut flattened = FileSource::fully_flatten(Arc::clone(fs));
-                    let span =
-                        Span::new(full_source.len()..(full_source.len() + flattened.0.len()), None);
-                    full_source.push_str(&flattened.0);
-                    relative_positions.append(&mut flattened.1);
-                    relative_positions.push((Arc::clone(fs), span))
+        if let Some(vfs) = &self_ref.dependencies {
+            for fs in vfs {
+                if included.contains(&fs.path) {
+                    continue
                 }
+                let offset = full_source.len();
+                let flattened = FileSource::flatten_into(Arc::clone(fs), included);
+                let span = Span::new(offset..(offset + flattened.0.len()), None);
+                full_source.push_str(&flattened.0);
+                relative_positions.extend(
+                    flattened
+                        .1
+                        .into_iter()
+                        .map(|(f, s)| (f, Span::new((s.start + offset)..(s.end + offset), None))),
+                );
+                relative_positions.push((Arc::clone(fs), span))
             }
-            None => {}
         }
 
         // Return the full source
         (full_source, relative_positions)
     }
 
+    /// Whether a file with the given path is a dependency of this file, directly or transitively
+    pub fn includes(&self, path: &str) -> bool {
+        self.dependencies.iter().flatten().any(|fs| fs.path == path || fs.includes(path))
+    }
+
+    /// Finds this file or one of its dependencies by path
+    pub fn find(&self, path: &str) -> Option<&FileSource> {
+        if self.path == path {
+            return Some(self)
+        }
+        self.dependencies.iter().flatten().find_map(|fs| fs.find(path))
+    }
+
     /// Derives a File Path's directory
     pub fn derive_dir(path: &str) -> Option<String> {
         let path = PathBuf::from(path);
@@ -94,6 +126,19 @@ impl FileSource {
         }
     }
 
+    /// Resolves an import path, applying the first matching remapping before localizing
+    ///
+    /// Standard library imports, ie. `<std/math.huff>`, are returned as is.
+    pub fn resolve_import(parent: &str, child: &str, remappings: &[Remapping]) -> Option<String> {
+        if is_std_import(child) {
+            return Some(child.to_string())
+        }
+        match Remapping::apply(remappings, child) {
+            Some(remapped) => Some(remapped),
+            None => FileSource::localize_file(parent, child),
+        }
+    }
+
     /// Localizes a file path, if path is relative
     pub fn localize_file(parent: &str, child: &str) -> Option<String> {
         let mut prefix = match FileSource::derive_dir(parent) {
@@ -185,30 +230,22 @@ impl Span {
     pub fn source_seg(&self) -> String {
         self.file
             .as_ref()
-            .map(|f| {
-                f.source
-                    .as_ref()
-                    .map(|s| {
-                        let line_num =
-                            &s[0..self.start].as_bytes().iter().filter(|&&c| c == b'\n').count() +
-                                1;
-                        let line_start = &s[0..self.start].rfind('\n').unwrap_or(0);
-                        let line_end = self.end +
-                            s[self.end..s.len()]
-                                .find('\n')
-                                .unwrap_or(s.len() - self.end)
-                                .to_owned();
-                        let padding =
-                            (0..line_num.to_string().len()).map(|_| " ").collect::<String>();
-                        format!(
-                            "\n     {}|\n  > {} | {}\n     {}|",
-                            padding,
-                            line_num,
-                            &s[line_start.to_owned()..line_end].replace('\n', ""),
-                            padding
-                        )
-                    })
-                    .unwrap_or_default()
+            .and_then(|f| {
+                f.source.as_ref().and_then(|s| {
+                    let preceding = s.get(0..self.start)?;
+                    let line_num = preceding.matches('\n').count() + 1;
+                    let line_start = preceding.rfind('\n').unwrap_or(0);
+                    let line_end =
+                        self.end + s.get(self.end..)?.find('\n').unwrap_or(s.len() - self.end);
+                    let padding = (0..line_num.to_string().len()).map(|_| " ").collect::<String>();
+                    Some(format!(
+                        "\n     {}|\n  > {} | {}\n     {}|",
+                        padding,
+                        line_num,
+                        s.get(line_start..line_end)?.replace('\n', ""),
+                        padding
+                    ))
+                })
             })
             .unwrap_or_default()
     }
@@ -216,7 +253,7 @@ impl Span {
 
 impl From<Span> for Range<usize> {
     fn from(span: Span) -> Self {
-        span.range().unwrap()
+        span.start..span.end
     }
 }
 
@@ -28,7 +28,7 @@ pub fn unpack_files(path: &str) -> Result<Vec<String>, UnpackError> {
         }
         None => {
             // We have a directory, try to extract huff files and parse
-            match std::fs::read_dir(&path) {
+            match std::fs::read_dir(path) {
                 Ok(files) => {
                     let input_files: Vec<String> =
                         files.map(|x| x.unwrap().path().to_str().unwrap().to_string()).collect();
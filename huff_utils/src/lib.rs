@@ -18,6 +18,15 @@ pub mod ast;
 /// Bytecode Traits Module
 pub mod bytecode;
 
+/// Compilation Cancellation Module
+pub mod cancel;
+
+/// External Call Wrapper Generation Module
+pub mod call_wrapper;
+
+/// Abi Decoding Module
+pub mod decode;
+
 /// Token Module
 pub mod token;
 
@@ -45,10 +54,34 @@ pub mod bytes_util;
 /// Solidity Interface Generator
 pub mod sol_interface;
 
+/// Storage Layout Module
+pub mod storage;
+
+/// Bytecode Metadata Module
+pub mod metadata;
+
+/// Source Map Module
+pub mod source_map;
+
+/// Artifact Serializer Module
+pub mod serializer;
+
+/// Import Remapping Module
+pub mod remapping;
+
+/// Embedded Standard Library Module
+pub mod stdlib;
+
+/// Deep Recursion Module
+pub mod recursion;
+
 /// Prelude wraps common utilities.
+#[allow(ambiguous_glob_reexports)]
 pub mod prelude {
     pub use crate::{
-        abi::*, artifact::*, ast::*, bytecode::*, bytes_util::*, error::*, evm::*, files::*, io::*,
-        report::*, sol_interface::*, token::*, types::*,
+        abi::*, artifact::*, ast::*, bytecode::*, bytes_util::*, call_wrapper::*, cancel::*,
+        decode::*, error::*, evm::*, files::*, io::*, metadata::*, recursion::*, remapping::*,
+        report::*, serializer::*, sol_interface::*, source_map::*, stdlib::*, storage::*, token::*,
+        types::*,
     };
 }
@@ -0,0 +1,85 @@
+//! ## Metadata
+//!
+//! An optional trailer appended to the runtime bytecode, recording the compiler and a hash of
+//! the source it was compiled from, similar to solc's metadata hash.
+
+use ethers_core::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+
+/// The compiler name recorded in metadata trailers
+pub const COMPILER_NAME: &str = "huffc";
+
+/// The compiler version recorded in metadata trailers
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The Metadata Recorded in an Artifact
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Metadata {
+    /// The compiler name
+    pub compiler: String,
+    /// The compiler version
+    pub version: String,
+    /// The keccak256 hash of the flattened source, including its dependencies
+    #[serde(rename = "sourceHash")]
+    pub source_hash: String,
+    /// The hex encoded trailer appended to the runtime bytecode
+    pub trailer: String,
+}
+
+impl Metadata {
+    /// Generates the metadata of a flattened source.
+    ///
+    /// The trailer is a `STOP`, so that execution falling off the end of the code still stops,
+    /// followed by the CBOR encoded map `{"huffc": version, "keccak256": source_hash}` and its
+    /// length as 2 big endian bytes, ie. as solc lays out its metadata.
+    pub fn new(source: &str) -> Self {
+        let source_hash = keccak256(source.as_bytes());
+
+        // Versions are encoded as 3 bytes, ie. 0.3.0 as 0x000300
+        let version = COMPILER_VERSION
+            .split('.')
+            .map(|v| v.parse::<u8>().unwrap_or_default())
+            .chain(std::iter::repeat(0))
+            .take(3)
+            .collect::<Vec<u8>>();
+
+        let mut cbor = vec![0xa2];
+        cbor_text(&mut cbor, COMPILER_NAME);
+        cbor_bytes(&mut cbor, &version);
+        cbor_text(&mut cbor, "keccak256");
+        cbor_bytes(&mut cbor, &source_hash);
+
+        let trailer =
+            [vec![0x00], cbor.clone(), (cbor.len() as u16).to_be_bytes().to_vec()].concat();
+        Metadata {
+            compiler: COMPILER_NAME.to_string(),
+            version: COMPILER_VERSION.to_string(),
+            source_hash: format!("0x{}", hex::encode(source_hash)),
+            trailer: hex::encode(trailer),
+        }
+    }
+}
+
+/// Appends a CBOR item header for the given major type and length
+fn cbor_header(out: &mut Vec<u8>, major: u8, len: usize) {
+    match len {
+        0..=23 => out.push(major << 5 | len as u8),
+        24..=255 => out.extend([major << 5 | 24, len as u8]),
+        _ => {
+            out.push(major << 5 | 25);
+            out.extend((len as u16).to_be_bytes());
+        }
+    }
+}
+
+/// Appends a CBOR text string
+fn cbor_text(out: &mut Vec<u8>, text: &str) {
+    cbor_header(out, 3, text.len());
+    out.extend(text.as_bytes());
+}
+
+/// Appends a CBOR byte string
+fn cbor_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    cbor_header(out, 2, bytes.len());
+    out.extend(bytes);
+}
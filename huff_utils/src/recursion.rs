@@ -0,0 +1,14 @@
+/// The stack space left below which a recursive pass grows the stack
+const STACK_RED_ZONE: usize = 1024 * 1024;
+
+/// The stack space added each time a recursive pass grows the stack
+const STACK_GROWTH: usize = 8 * 1024 * 1024;
+
+/// Runs a level of a recursive pass, growing the stack onto the heap when it runs low
+///
+/// Passes recursing once per nested macro, like expanding or deriving storage pointers, run each
+/// level through this, so the expansion depth limit can be raised past what the stack of the
+/// compiling thread would hold.
+pub fn with_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, f)
+}
@@ -0,0 +1,84 @@
+//! ## Remappings
+//!
+//! Import path remappings, in the `prefix=path` format used by foundry's `remappings.txt`.
+//!
+//! Remappings let contracts import vendored libraries by name, for example
+//! `#include "huffmate/auth/Owned.huff"` with the remapping `huffmate/=lib/huffmate/src/`.
+
+use std::{fmt, path::Path, str::FromStr};
+
+/// The default remappings file, read from the current working directory
+pub const REMAPPINGS_FILE: &str = "remappings.txt";
+
+/// An Import Path Remapping
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Remapping {
+    /// The import prefix to replace
+    pub prefix: String,
+    /// The path the prefix is replaced with
+    pub path: String,
+}
+
+impl FromStr for Remapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().split_once('=') {
+            Some((prefix, path)) if !prefix.is_empty() && !path.is_empty() => {
+                Ok(Remapping { prefix: prefix.to_string(), path: path.to_string() })
+            }
+            _ => Err(format!("Invalid remapping \"{}\", expected \"prefix=path\"", s.trim())),
+        }
+    }
+}
+
+impl fmt::Display for Remapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.prefix, self.path)
+    }
+}
+
+impl Remapping {
+    /// Parses remappings, one per line, skipping blank lines and invalid entries
+    pub fn parse_all(source: &str) -> Vec<Remapping> {
+        source
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| match Remapping::from_str(l) {
+                Ok(r) => Some(r),
+                Err(e) => {
+                    tracing::warn!(target: "remappings", "{}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Reads the remappings from a file, returning none if the file does not exist
+    pub fn read(path: impl AsRef<Path>) -> Vec<Remapping> {
+        std::fs::read_to_string(path).map(|s| Remapping::parse_all(&s)).unwrap_or_default()
+    }
+
+    /// Reads the project remappings from the [REMAPPINGS_FILE](REMAPPINGS_FILE)
+    pub fn project() -> Vec<Remapping> {
+        Remapping::read(REMAPPINGS_FILE)
+    }
+
+    /// Remaps an import using the longest matching prefix
+    pub fn apply(remappings: &[Remapping], import: &str) -> Option<String> {
+        remappings
+            .iter()
+            .filter(|r| import.starts_with(&r.prefix))
+            .max_by_key(|r| r.prefix.len())
+            .map(|r| import.replacen(&r.prefix, &r.path, 1))
+    }
+
+    /// Merges remappings into an existing set, replacing entries with the same prefix
+    pub fn merge(remappings: &mut Vec<Remapping>, new: Vec<Remapping>) {
+        for r in new {
+            remappings.retain(|e| e.prefix != r.prefix);
+            remappings.push(r);
+        }
+        remappings.sort();
+    }
+}
@@ -0,0 +1,411 @@
+//! ## Serializer
+//!
+//! Pluggable artifact serialization.
+//!
+//! Artifacts are written using a [Serializer](Serializer). Builtin serializers for the huff json
+//! format, foundry, hardhat, solc standard json, and solc style raw hex bytecode files are
+//! registered in the default [SerializerRegistry](SerializerRegistry), and downstream build
+//! systems can register their own.
+//!
+//! Toml and yaml serializers are available behind the `toml` and `yaml` features.
+
+use ethers_core::utils::{hex, keccak256};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Debug, path::Path, sync::Arc};
+
+use crate::{
+    abi::{Abi, FunctionParam},
+    artifact::Artifact,
+    ast::FunctionType,
+    files::FileSource,
+    metadata::COMPILER_VERSION,
+};
+
+/// An Artifact Serializer
+pub trait Serializer: Debug + Send + Sync {
+    /// The name the serializer is registered under
+    fn name(&self) -> &str;
+
+    /// The file extension of serialized artifacts
+    fn extension(&self) -> &str;
+
+    /// Serializes an artifact
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error>;
+}
+
+/// Serializes artifacts to the huff json format.
+///
+/// Keys are sorted, so that the same artifact always serializes to the same bytes and can be
+/// committed and diffed.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonSerializer {
+    /// Whether to indent the json
+    pub pretty: bool,
+}
+
+impl Default for JsonSerializer {
+    fn default() -> Self {
+        Self { pretty: true }
+    }
+}
+
+impl Serializer for JsonSerializer {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        // Json values are backed by sorted maps
+        let value = serde_json::to_value(artifact)?;
+        match self.pretty {
+            true => Ok(serde_json::to_string_pretty(&value)?),
+            false => Ok(serde_json::to_string(&value)?),
+        }
+    }
+}
+
+/// Serializes artifacts to the foundry artifact format
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FoundrySerializer;
+
+impl Serializer for FoundrySerializer {
+    fn name(&self) -> &str {
+        "foundry"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        Ok(serde_json::to_string_pretty(&json!({
+            "abi": json_abi(artifact.abi.as_ref()),
+            "bytecode": { "object": format!("0x{}", artifact.bytecode) },
+            "deployedBytecode": { "object": format!("0x{}", artifact.runtime) },
+        }))?)
+    }
+}
+
+/// Serializes artifacts to the hardhat artifact format
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HardhatSerializer;
+
+impl Serializer for HardhatSerializer {
+    fn name(&self) -> &str {
+        "hardhat"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        let contract_name = Path::new(&artifact.file.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Ok(serde_json::to_string_pretty(&json!({
+            "_format": "hh-sol-artifact-1",
+            "contractName": contract_name,
+            "sourceName": artifact.file.path,
+            "abi": json_abi(artifact.abi.as_ref()),
+            "bytecode": format!("0x{}", artifact.bytecode),
+            "deployedBytecode": format!("0x{}", artifact.runtime),
+            "linkReferences": {},
+            "deployedLinkReferences": {},
+        }))?)
+    }
+}
+
+/// Serializes artifacts to the output shape of solc's standard json interface, so that tooling
+/// reading solc output can read huff artifacts.
+///
+/// Each artifact is a standard json output with a single contract, named after its file stem.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StandardJsonSerializer;
+
+impl Serializer for StandardJsonSerializer {
+    fn name(&self) -> &str {
+        "standard-json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        let path = &artifact.file.path;
+        let contract_name = Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let abi = json_abi(artifact.abi.as_ref());
+        let source_map = artifact.source_map.clone().unwrap_or_default();
+        let sources = match source_map.sources.is_empty() {
+            true => vec![path.clone()],
+            false => source_map.sources,
+        };
+
+        // Like solc's, the metadata is a json string hashing the sources it was compiled from
+        let flattened = FileSource::fully_flatten(Arc::clone(&artifact.file)).0;
+        let metadata = json!({
+            "compiler": { "version": COMPILER_VERSION },
+            "language": "Huff",
+            "output": { "abi": abi },
+            "settings": {},
+            "sources": {
+                path.clone(): { "keccak256": format!("0x{}", hex::encode(keccak256(flattened))) }
+            },
+            "version": 1,
+        });
+        let method_identifiers =
+            artifact.abi.as_ref().map(Abi::method_identifiers).unwrap_or_default();
+        // Immutables have no ast ids, so are keyed by name
+        let immutable_references = artifact
+            .immutable_references
+            .iter()
+            .map(|(name, offsets)| {
+                let refs = offsets.iter().map(|o| json!({ "start": o, "length": 32 })).collect();
+                (name.clone(), Value::Array(refs))
+            })
+            .collect::<serde_json::Map<String, Value>>();
+
+        Ok(serde_json::to_string_pretty(&json!({
+            "contracts": {
+                path.clone(): {
+                    contract_name: {
+                        "abi": abi,
+                        "metadata": metadata.to_string(),
+                        "evm": {
+                            "bytecode": {
+                                "object": artifact.bytecode,
+                                "sourceMap": source_map.bytecode,
+                                "linkReferences": {},
+                            },
+                            "deployedBytecode": {
+                                "object": artifact.runtime,
+                                "sourceMap": source_map.runtime,
+                                "linkReferences": {},
+                                "immutableReferences": immutable_references,
+                            },
+                            "methodIdentifiers": method_identifiers,
+                        },
+                    },
+                },
+            },
+            "sources": sources
+                .iter()
+                .enumerate()
+                .map(|(id, source)| (source.clone(), json!({ "id": id })))
+                .collect::<serde_json::Map<String, Value>>(),
+        }))?)
+    }
+}
+
+/// Serializes the deployed bytecode to a raw hex `.bin` file, matching solc's `--bin` output
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BinSerializer;
+
+impl Serializer for BinSerializer {
+    fn name(&self) -> &str {
+        "bin"
+    }
+
+    fn extension(&self) -> &str {
+        "bin"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        Ok(artifact.bytecode.clone())
+    }
+}
+
+/// Serializes the runtime bytecode to a raw hex `.bin-runtime` file, matching solc's
+/// `--bin-runtime` output
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BinRuntimeSerializer;
+
+impl Serializer for BinRuntimeSerializer {
+    fn name(&self) -> &str {
+        "bin-runtime"
+    }
+
+    fn extension(&self) -> &str {
+        "bin-runtime"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        Ok(artifact.runtime.clone())
+    }
+}
+
+/// Serializes artifacts to toml
+#[cfg(feature = "toml")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TomlSerializer;
+
+#[cfg(feature = "toml")]
+impl Serializer for TomlSerializer {
+    fn name(&self) -> &str {
+        "toml"
+    }
+
+    fn extension(&self) -> &str {
+        "toml"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        // Toml has no null or enum variant representation, so convert through json, dropping
+        // nulls. Converting to a toml value also emits nested tables after plain values.
+        fn strip_nulls(value: Value) -> Value {
+            match value {
+                Value::Object(map) => Value::Object(
+                    map.into_iter()
+                        .filter(|(_, v)| !v.is_null())
+                        .map(|(k, v)| (k, strip_nulls(v)))
+                        .collect(),
+                ),
+                Value::Array(arr) => Value::Array(arr.into_iter().map(strip_nulls).collect()),
+                v => v,
+            }
+        }
+        let value = toml::Value::try_from(strip_nulls(serde_json::to_value(artifact)?))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        toml::to_string_pretty(&value)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Serializes artifacts to yaml
+#[cfg(feature = "yaml")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct YamlSerializer;
+
+#[cfg(feature = "yaml")]
+impl Serializer for YamlSerializer {
+    fn name(&self) -> &str {
+        "yaml"
+    }
+
+    fn extension(&self) -> &str {
+        "yaml"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        serde_yaml::to_string(artifact)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A registry of artifact serializers by name
+#[derive(Debug, Clone)]
+pub struct SerializerRegistry {
+    /// The registered serializers
+    pub serializers: BTreeMap<String, Arc<dyn Serializer>>,
+}
+
+impl Default for SerializerRegistry {
+    fn default() -> Self {
+        let mut registry = Self { serializers: BTreeMap::new() };
+        registry.register(Arc::new(JsonSerializer::default()));
+        registry.register(Arc::new(FoundrySerializer));
+        registry.register(Arc::new(HardhatSerializer));
+        registry.register(Arc::new(StandardJsonSerializer));
+        registry.register(Arc::new(BinSerializer));
+        registry.register(Arc::new(BinRuntimeSerializer));
+        #[cfg(feature = "toml")]
+        registry.register(Arc::new(TomlSerializer));
+        #[cfg(feature = "yaml")]
+        registry.register(Arc::new(YamlSerializer));
+        registry
+    }
+}
+
+impl SerializerRegistry {
+    /// Registers a serializer, replacing any serializer registered under the same name
+    pub fn register(&mut self, serializer: Arc<dyn Serializer>) {
+        self.serializers.insert(serializer.name().to_string(), serializer);
+    }
+
+    /// Returns the serializer registered under the given name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Serializer>> {
+        self.serializers.get(name).map(Arc::clone)
+    }
+
+    /// Returns the names of all registered serializers
+    pub fn names(&self) -> Vec<String> {
+        self.serializers.keys().cloned().collect()
+    }
+}
+
+/// Converts an abi into the standard solidity json abi
+pub fn json_abi(abi: Option<&Abi>) -> Value {
+    let abi = match abi {
+        Some(a) => a,
+        None => return json!([]),
+    };
+    let params = |params: &Vec<FunctionParam>| {
+        params
+            .iter()
+            .map(|p| {
+                json!({
+                    "name": p.name,
+                    "type": p.kind.to_string(),
+                    "internalType": p.internal_type.clone().unwrap_or_else(|| p.kind.to_string()),
+                })
+            })
+            .collect::<Vec<Value>>()
+    };
+    let mut entries = vec![];
+    if let Some(constructor) = &abi.constructor {
+        entries.push(json!({
+            "type": "constructor",
+            "inputs": params(&constructor.inputs),
+            "stateMutability": "nonpayable",
+        }));
+    }
+    abi.functions.values().for_each(|f| {
+        entries.push(json!({
+            "type": "function",
+            "name": f.name,
+            "inputs": params(&f.inputs),
+            "outputs": params(&f.outputs),
+            "stateMutability": match f.state_mutability {
+                FunctionType::View => "view",
+                FunctionType::Pure => "pure",
+                FunctionType::Payable => "payable",
+                FunctionType::NonPayable => "nonpayable",
+            },
+        }))
+    });
+    abi.events.values().for_each(|e| {
+        entries.push(json!({
+            "type": "event",
+            "name": e.name,
+            "inputs": e.inputs.iter().map(|p| json!({
+                "name": p.name,
+                "type": p.kind.to_string(),
+                "indexed": p.indexed,
+            })).collect::<Vec<Value>>(),
+            "anonymous": e.anonymous,
+        }))
+    });
+    abi.errors.values().for_each(|e| {
+        entries.push(json!({
+            "type": "error",
+            "name": e.name,
+            "inputs": params(&e.inputs),
+        }))
+    });
+    if abi.receive {
+        entries.push(json!({ "type": "receive", "stateMutability": "payable" }));
+    }
+    if abi.fallback {
+        entries.push(json!({ "type": "fallback", "stateMutability": "nonpayable" }));
+    }
+    Value::Array(entries)
+}
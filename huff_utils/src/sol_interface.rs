@@ -37,6 +37,14 @@ pub fn gen_sol_interfaces(artifacts: &Vec<Arc<Artifact>>) -> Vec<(PathBuf, &str,
                         .join(", "),
                 ));
             });
+            a.errors.iter().for_each(|(_, e)| {
+                defs.push(format!(
+                    "{}error {}({});",
+                    "\t",
+                    e.name,
+                    e.inputs.iter().map(|i| i.kind.to_string()).collect::<Vec<_>>().join(", "),
+                ));
+            });
             a.functions.iter().for_each(|(_, f)| {
                 defs.push(format!(
                     "{}function {}({}) external{}{};",
@@ -72,7 +80,7 @@ pub fn gen_sol_interfaces(artifacts: &Vec<Arc<Artifact>>) -> Vec<(PathBuf, &str,
             });
 
             let interface_name =
-                artifact.file.path.split('/').last().unwrap().split('.').next().unwrap();
+                artifact.file.path.split('/').next_back().unwrap().split('.').next().unwrap();
             interfaces.push((
                 Path::new(&artifact.file.path).parent().unwrap().to_path_buf(),
                 interface_name,
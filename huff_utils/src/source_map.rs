@@ -0,0 +1,161 @@
+//! ## Source Maps
+//!
+//! Solc style source maps, relating each instruction of an artifact's bytecode to the span of
+//! Huff source it was generated from.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::prelude::{AstSpan, Instruction, Instructions};
+
+/// The Source Spans of Generated Bytecode
+///
+/// Each entry is the byte offset a span starts covering, and a byte belongs to the last span
+/// starting at or before it. An empty span marks bytes that weren't generated from source, ie.
+/// appended tables.
+pub type SourceSpans = Vec<(usize, AstSpan)>;
+
+/// The Source Maps of an Artifact
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SourceMap {
+    /// The source file paths, indexed by the file index of each segment
+    pub sources: Vec<String>,
+    /// The source map of the deployment bytecode, up to the start of the runtime bytecode
+    pub bytecode: String,
+    /// The source map of the runtime bytecode
+    pub runtime: String,
+}
+
+/// The Source Location of an Instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The path of the source file
+    pub file: String,
+    /// The byte offset the span starts at
+    pub start: usize,
+    /// The length of the span in bytes
+    pub length: usize,
+}
+
+/// A Decompressed Source Map Segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    start: i64,
+    length: i64,
+    file: i64,
+}
+
+impl SourceMap {
+    /// Builds the source maps of the deployment and runtime bytecode
+    pub fn new(
+        bytecode: &str,
+        bytecode_spans: &[(usize, AstSpan)],
+        runtime: &str,
+        runtime_spans: &[(usize, AstSpan)],
+    ) -> Self {
+        let mut map = SourceMap::default();
+        map.bytecode = map.encode(bytecode, bytecode_spans);
+        map.runtime = map.encode(runtime, runtime_spans);
+        map
+    }
+
+    /// Encodes a `s:l:f:j` segment for each instruction of a hex encoded bytecode string,
+    /// registering the files the spans reference in `sources`.
+    ///
+    /// Segments are compressed like solc's, leaving out fields equal to the previous segment's.
+    /// Unmapped instructions have a file index of -1, and the jump type is always `-`.
+    pub fn encode(&mut self, bytecode: &str, spans: &[(usize, AstSpan)]) -> String {
+        let mut spans = spans.iter().collect::<Vec<&(usize, AstSpan)>>();
+        spans.sort_by_key(|(offset, _)| *offset);
+
+        let mut segments = vec![];
+        let mut current = Segment { start: -1, length: -1, file: -1 };
+        let mut next_span = 0;
+        for Instruction { pc, .. } in Instructions::new(bytecode) {
+            while next_span < spans.len() && spans[next_span].0 <= pc {
+                current = self.segment(&spans[next_span].1);
+                next_span += 1;
+            }
+            segments.push(current.clone());
+        }
+
+        let mut previous: Option<&Segment> = None;
+        let mut encoded = vec![];
+        for segment in &segments {
+            let fields = match previous {
+                Some(p) if p == segment => String::new(),
+                Some(p) => {
+                    let field = |curr: i64, prev: i64| {
+                        if curr == prev {
+                            String::new()
+                        } else {
+                            curr.to_string()
+                        }
+                    };
+                    let fields = format!(
+                        "{}:{}:{}",
+                        field(segment.start, p.start),
+                        field(segment.length, p.length),
+                        field(segment.file, p.file)
+                    );
+                    fields.trim_end_matches(':').to_string()
+                }
+                None => format!("{}:{}:{}:-", segment.start, segment.length, segment.file),
+            };
+            encoded.push(fields);
+            previous = Some(segment);
+        }
+        encoded.join(";")
+    }
+
+    /// Decompresses the source map of a hex encoded bytecode string, returning the location of
+    /// each instruction by its program counter.
+    ///
+    /// Unmapped instructions, and those past the end of the map, are left out.
+    pub fn decode(&self, map: &str, bytecode: &str) -> BTreeMap<usize, SourceLocation> {
+        let mut locations = BTreeMap::new();
+        let mut current = Segment { start: -1, length: -1, file: -1 };
+        for (Instruction { pc, .. }, fields) in Instructions::new(bytecode).zip(map.split(';')) {
+            // Missing and empty fields repeat the previous segment's
+            let mut fields = fields.split(':');
+            let mut field = |prev: i64| match fields.next() {
+                Some(f) if !f.is_empty() => f.parse::<i64>().unwrap_or(prev),
+                _ => prev,
+            };
+            current = Segment {
+                start: field(current.start),
+                length: field(current.length),
+                file: field(current.file),
+            };
+
+            let file = usize::try_from(current.file).ok().and_then(|i| self.sources.get(i));
+            if let (Some(file), Ok(start), Ok(length)) =
+                (file, usize::try_from(current.start), usize::try_from(current.length))
+            {
+                locations.insert(pc, SourceLocation { file: file.clone(), start, length });
+            }
+        }
+        locations
+    }
+
+    /// Decompresses the segment of a span, registering its file
+    fn segment(&mut self, span: &AstSpan) -> Segment {
+        let (first, rest) = match span.0.split_first() {
+            Some(s) => s,
+            None => return Segment { start: -1, length: -1, file: -1 },
+        };
+        let start = rest.iter().map(|s| s.start).fold(first.start, usize::min);
+        let end = rest.iter().map(|s| s.end).fold(first.end, usize::max);
+        let file = match &first.file {
+            Some(f) => match self.sources.iter().position(|s| *s == f.path) {
+                Some(i) => i as i64,
+                None => {
+                    self.sources.push(f.path.clone());
+                    self.sources.len() as i64 - 1
+                }
+            },
+            None => -1,
+        };
+        Segment { start: start as i64, length: (end - start) as i64, file }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::files::FileSource;
+use std::{sync::Arc, time::SystemTime};
+use uuid::Uuid;
+
+/// The version of the embedded standard library, released alongside the compiler
+pub const STD_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The standard library sources embedded in the compiler, keyed by their import path
+pub const STD_LIBRARY: [(&str, &str); 5] = [
+    ("std/assert.huff", include_str!("../std/assert.huff")),
+    ("std/console.huff", include_str!("../std/console.huff")),
+    ("std/math.huff", include_str!("../std/math.huff")),
+    ("std/reentrancy.huff", include_str!("../std/reentrancy.huff")),
+    ("std/transfer.huff", include_str!("../std/transfer.huff")),
+];
+
+/// Whether an import path refers to the embedded standard library, ie. `<std/math.huff>`
+pub fn is_std_import(path: &str) -> bool {
+    path.starts_with('<') && path.ends_with('>')
+}
+
+/// Returns the embedded source for a standard library import
+///
+/// Imports can pin the standard library version, ie. `<std@0.1.0/math.huff>`, which only
+/// resolves if it matches the [STD_VERSION](STD_VERSION) embedded in the compiler.
+pub fn std_source(path: &str) -> Option<&'static str> {
+    let path = path.strip_prefix('<').and_then(|p| p.strip_suffix('>'))?;
+    let path = match path.strip_prefix("std@") {
+        Some(pinned) => {
+            let (version, file) = pinned.split_once('/')?;
+            if version != STD_VERSION {
+                return None
+            }
+            format!("std/{}", file)
+        }
+        None => path.to_string(),
+    };
+    STD_LIBRARY.iter().find(|(p, _)| *p == path).map(|(_, source)| *source)
+}
+
+/// Instantiates a `FileSource` for a standard library import, keeping the bracketed import as
+/// its path
+pub fn std_file_source(path: &str) -> Option<Arc<FileSource>> {
+    std_source(path).map(|source| {
+        Arc::new(FileSource {
+            id: Uuid::new_v4(),
+            path: path.to_string(),
+            source: Some(source.to_string()),
+            access: Some(SystemTime::now()),
+            dependencies: None,
+        })
+    })
+}
@@ -0,0 +1,191 @@
+//! ## Storage Layout
+//!
+//! The storage slots assigned to a contract's `FREE_STORAGE_POINTER()` constants and the literal
+//! constants it uses as slots, and a checker comparing the layout of two builds.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    ast::ConstantDefinition,
+    bytes_util::bytes32_to_string,
+    files::Span,
+    token::{Token, TokenKind},
+};
+
+/// A Storage Slot Assignment
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct StorageSlot {
+    /// The constant name
+    pub name: String,
+    /// The hex encoded slot
+    pub slot: String,
+    /// The type hinted by a line comment trailing the definition, ie. `// address`
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_hint: Option<String>,
+    /// The path of the file defining the constant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The start of the definition in its file
+    #[serde(default)]
+    pub start: usize,
+    /// The end of the definition in its file
+    #[serde(default)]
+    pub end: usize,
+}
+
+/// A Contract Storage Layout
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StorageLayout {
+    /// The assigned storage slots
+    pub storage: Vec<StorageSlot>,
+}
+
+impl StorageLayout {
+    /// Builds the layout from the derived contract constants.
+    ///
+    /// Only the constants named in `pointers` (the constants that were defined as
+    /// `FREE_STORAGE_POINTER()` before derivation, and the literal or expression constants used as
+    /// slots) are included. Each is given the type hinted by its entry in `comments`, if any.
+    pub fn new(
+        pointers: &[String],
+        constants: &[ConstantDefinition],
+        comments: &[(Span, String)],
+    ) -> Self {
+        let mut storage = constants
+            .iter()
+            .filter(|c| pointers.contains(&c.name))
+            .filter_map(|c| match c.evaluate(constants) {
+                Ok(l) => {
+                    let first = c.span.0.first();
+                    let last = c.span.0.last();
+                    Some(StorageSlot {
+                        name: c.name.clone(),
+                        slot: bytes32_to_string(&l, true),
+                        type_hint: last.and_then(|end| {
+                            comments.iter().find(|(s, _)| s == end).map(|(_, hint)| hint.clone())
+                        }),
+                        file: first.and_then(|s| s.file.as_ref()).map(|f| f.path.clone()),
+                        start: first.map_or(0, |s| s.start),
+                        end: last.map_or(0, |s| s.end),
+                    })
+                }
+                Err(e) => {
+                    tracing::warn!(target: "storage", "STORAGE SLOT \"{}\" NOT EVALUATED: {:?}", c.name, e.kind);
+                    None
+                }
+            })
+            .collect::<Vec<StorageSlot>>();
+        storage.sort();
+        Self { storage }
+    }
+
+    /// Returns the slot assigned to the given constant name
+    pub fn get_slot(&self, name: &str) -> Option<&str> {
+        self.storage.iter().find(|s| s.name == name).map(|s| s.slot.as_str())
+    }
+
+    /// Compares this layout against a previous build's layout.
+    ///
+    /// Returns every slot reassignment, where either an existing constant moved to a different
+    /// slot or a previously assigned slot is now held by a different constant.
+    /// Removed constants and newly appended slots are compatible and not reported.
+    pub fn compare(&self, previous: &StorageLayout) -> Vec<StorageLayoutConflict> {
+        let mut conflicts = vec![];
+        for prev in &previous.storage {
+            if let Some(slot) = self.get_slot(&prev.name) {
+                if slot != prev.slot {
+                    conflicts.push(StorageLayoutConflict::Reassigned {
+                        name: prev.name.clone(),
+                        previous: prev.slot.clone(),
+                        current: slot.to_string(),
+                    });
+                }
+            }
+        }
+        for curr in &self.storage {
+            if let Some(prev) = previous.storage.iter().find(|p| p.slot == curr.slot) {
+                if prev.name != curr.name && previous.get_slot(&curr.name).is_none() {
+                    conflicts.push(StorageLayoutConflict::Overwritten {
+                        slot: curr.slot.clone(),
+                        previous: prev.name.clone(),
+                        current: curr.name.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// Returns the text of each line comment trailing a token on the same line, alongside the span of
+/// the token it trails.
+pub fn trailing_comments(tokens: &[Token]) -> Vec<(Span, String)> {
+    let mut comments = vec![];
+    let mut trailed: Option<&Span> = None;
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Comment(c) if c.starts_with("//") => {
+                let text = c.trim_start_matches('/').trim();
+                if let Some(span) = trailed.take().filter(|_| !text.is_empty()) {
+                    comments.push((span.clone(), text.to_string()));
+                }
+            }
+            // Whitespace only keeps the comment on the same line if it has no newline
+            TokenKind::Whitespace => {
+                let newline = token
+                    .span
+                    .file
+                    .as_ref()
+                    .and_then(|f| f.source.as_ref())
+                    .and_then(|s| s.get(token.span.start..token.span.end))
+                    .is_none_or(|ws| ws.contains('\n'));
+                if newline {
+                    trailed = None;
+                }
+            }
+            _ => trailed = Some(&token.span),
+        }
+    }
+    comments
+}
+
+/// A Storage Layout Incompatibility
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StorageLayoutConflict {
+    /// A constant was assigned a different slot
+    Reassigned {
+        /// The constant name
+        name: String,
+        /// The previously assigned slot
+        previous: String,
+        /// The currently assigned slot
+        current: String,
+    },
+    /// A previously assigned slot is held by a new constant
+    Overwritten {
+        /// The slot
+        slot: String,
+        /// The constant previously assigned the slot
+        previous: String,
+        /// The constant currently assigned the slot
+        current: String,
+    },
+}
+
+impl fmt::Display for StorageLayoutConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageLayoutConflict::Reassigned { name, previous, current } => {
+                write!(f, "\"{}\" moved from slot {} to slot {}", name, previous, current)
+            }
+            StorageLayoutConflict::Overwritten { slot, previous, current } => {
+                write!(
+                    f,
+                    "slot {} previously held \"{}\" but now holds \"{}\"",
+                    slot, previous, current
+                )
+            }
+        }
+    }
+}
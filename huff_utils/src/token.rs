@@ -37,10 +37,16 @@ pub enum TokenKind {
     Macro,
     /// "fn" keyword
     Fn,
+    /// "test" keyword
+    Test,
+    /// "immutable" keyword
+    Immutable,
     /// "function" keyword
     Function,
     /// "event" keyword
     Event,
+    /// "error" keyword
+    Error,
     /// "constant" keyword
     Constant,
     /// "takes" keyword
@@ -129,8 +135,11 @@ impl fmt::Display for TokenKind {
             TokenKind::Include => "#include",
             TokenKind::Macro => "macro",
             TokenKind::Fn => "fn",
+            TokenKind::Test => "test",
+            TokenKind::Immutable => "immutable",
             TokenKind::Function => "function",
             TokenKind::Event => "event",
+            TokenKind::Error => "error",
             TokenKind::Constant => "constant",
             TokenKind::View => "view",
             TokenKind::Pure => "pure",
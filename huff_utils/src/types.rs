@@ -1,5 +1,5 @@
-use crate::bytes_util::*;
-use ethers_core::abi::{ethereum_types::*, token::*, Tokenizable};
+use crate::{abi::FunctionParamType, bytes_util::*};
+use ethers_core::abi::{ethereum_types::*, token::*};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{fmt, str::FromStr};
@@ -33,7 +33,7 @@ impl TryFrom<String> for PrimitiveEVMType {
             // Default to 256 if no size
             let size = match input.get(4..input.len()) {
                 Some(s) => match s.is_empty() {
-                    false => s.parse::<usize>().unwrap(),
+                    false => s.parse::<usize>().map_err(|e| e.to_string())?,
                     true => 256,
                 },
                 None => 256,
@@ -44,7 +44,7 @@ impl TryFrom<String> for PrimitiveEVMType {
             // Default to 256 if no size
             let size = match input.get(3..input.len()) {
                 Some(s) => match s.is_empty() {
-                    false => s.parse::<usize>().unwrap(),
+                    false => s.parse::<usize>().map_err(|e| e.to_string())?,
                     true => 256,
                 },
                 None => 256,
@@ -52,7 +52,7 @@ impl TryFrom<String> for PrimitiveEVMType {
             return Ok(PrimitiveEVMType::Int(size))
         }
         if input.starts_with("bytes") && input.len() != 5 {
-            let size = input.get(5..input.len()).unwrap().parse::<usize>().unwrap();
+            let size = input[5..].parse::<usize>().map_err(|e| e.to_string())?;
             return Ok(PrimitiveEVMType::Bytes(size))
         }
         if input.starts_with("bool") {
@@ -102,9 +102,8 @@ impl TryFrom<String> for EToken {
     type Error = String;
 
     fn try_from(input: String) -> Result<Self, Self::Error> {
-        if input.starts_with("0x") {
-            // remove 0x prefix
-            let cleaned_input = input.get(2..input.len()).unwrap();
+        // remove 0x prefix
+        if let Some(cleaned_input) = input.strip_prefix("0x") {
             // either address or fixed bytes
             if cleaned_input.len() <= 64 {
                 // could be either address or fixed bytes
@@ -115,6 +114,9 @@ impl TryFrom<String> for EToken {
                             H160::from_str(cleaned_input).map_err(|e| e.to_string())?,
                         )))
                     }
+                    _ if !cleaned_input.chars().all(|c| c.is_ascii_hexdigit()) => {
+                        return Err(format!("Invalid hex string: {}", input))
+                    }
                     _ => {
                         return Ok(EToken(Token::FixedBytes(str_to_bytes32(cleaned_input).to_vec())))
                     }
@@ -129,17 +131,15 @@ impl TryFrom<String> for EToken {
         // array
         if input.starts_with('[') {
             let trimmed_input = input.trim_start_matches('[').trim_end_matches(']');
-            let v: Vec<String> = trimmed_input
-                .split(',')
-                .map(|x| x.replace(' ', "").replace('"', "").replace('\'', ""))
-                .collect();
+            let v: Vec<String> =
+                trimmed_input.split(',').map(|x| x.replace([' ', '"', '\''], "")).collect();
             let etokens: Result<Vec<EToken>, _> =
                 v.iter().map(|x| EToken::try_from(x.to_owned())).collect();
             let tokens: Vec<Token> = etokens?.iter().map(move |x| x.clone().0).collect();
             return Ok(EToken(Token::Array(tokens)))
         }
         if input.starts_with('-') || input.starts_with('+') {
-            return Ok(EToken(input.parse::<i128>().map_err(|e| e.to_string())?.into_token()))
+            return Ok(EToken(Token::Int(U256::from_big_endian(&int_to_bytes32(&input, 256)?))))
         }
         if input == "true" || input == "false" {
             return Ok(EToken(Token::Bool(input == "true")))
@@ -155,7 +155,7 @@ impl TryFrom<String> for EToken {
             // Try to unwrap something like "100,0x123,20" without brackets
             let e_tokens: Result<Vec<EToken>, _> = input
                 .split(',')
-                .map(|x| x.replace(' ', "").replace('"', "").replace('\'', ""))
+                .map(|x| x.replace([' ', '"', '\''], ""))
                 .map(EToken::try_from)
                 .collect();
             let tokens: Vec<Token> = e_tokens?.into_iter().map(|x| x.0).collect();
@@ -165,3 +165,160 @@ impl TryFrom<String> for EToken {
         }
     }
 }
+
+impl EToken {
+    /// Converts an input string to a token of the given type, validating that integers fit in
+    /// the type's bit size. Signed integers are encoded in two's complement.
+    pub fn try_from_typed(input: String, ty: &PrimitiveEVMType) -> Result<Self, String> {
+        match ty {
+            PrimitiveEVMType::Int(bits) => {
+                Ok(EToken(Token::Int(U256::from_big_endian(&int_to_bytes32(&input, *bits)?))))
+            }
+            PrimitiveEVMType::Uint(bits) => {
+                let digits = input.strip_prefix('+').unwrap_or(&input);
+                let value = U256::from_dec_str(digits).map_err(|e| format!("{}: {}", input, e))?;
+                if *bits < 256 && value >= U256::one() << *bits {
+                    return Err(format!("{} is out of range for uint{}", input, bits))
+                }
+                Ok(EToken(Token::Uint(value)))
+            }
+            _ => EToken::try_from(input),
+        }
+    }
+
+    /// Parses an input string as a value of an abi parameter type.
+    ///
+    /// Arrays are written as `[a, b]` and tuples as `(a, b)`, and may be nested. Strings may be
+    /// quoted, and bytes are hex encoded with a `0x` prefix.
+    pub fn try_from_param(input: &str, kind: &FunctionParamType) -> Result<Self, String> {
+        let input = input.trim();
+        match kind {
+            FunctionParamType::Address => {
+                match input.strip_prefix("0x").filter(|a| a.len() == 40).map(H160::from_str) {
+                    Some(Ok(address)) => Ok(EToken(Token::Address(address))),
+                    _ => Err(format!("\"{}\" is not a 20 byte hex address", input)),
+                }
+            }
+            FunctionParamType::Bool => match input {
+                "true" | "false" => Ok(EToken(Token::Bool(input == "true"))),
+                _ => Err(format!("\"{}\" is not a bool, expected true or false", input)),
+            },
+            FunctionParamType::Int(bits) => {
+                EToken::try_from_typed(input.to_string(), &PrimitiveEVMType::Int(*bits))
+            }
+            FunctionParamType::Uint(bits) => {
+                EToken::try_from_typed(input.to_string(), &PrimitiveEVMType::Uint(*bits))
+            }
+            FunctionParamType::FixedBytes(size) => {
+                let bytes = hex_to_vec(input)?;
+                if bytes.len() != *size {
+                    return Err(format!(
+                        "\"{}\" is {} bytes long, expected {} for bytes{}",
+                        input,
+                        bytes.len(),
+                        size,
+                        size
+                    ))
+                }
+                Ok(EToken(Token::FixedBytes(bytes)))
+            }
+            FunctionParamType::Bytes => Ok(EToken(Token::Bytes(hex_to_vec(input)?))),
+            FunctionParamType::String => {
+                let quoted = input.len() > 1 &&
+                    ['"', '\''].iter().any(|q| input.starts_with(*q) && input.ends_with(*q));
+                let value = if quoted { &input[1..input.len() - 1] } else { input };
+                Ok(EToken(Token::String(value.to_string())))
+            }
+            FunctionParamType::Array(inner, sizes) => {
+                // The outermost dimension is the last one, ie. uint256[2][] is a dynamic array
+                // of uint256[2]
+                let (size, inner_sizes) = match sizes.split_last() {
+                    Some((size, inner_sizes)) => (*size, inner_sizes),
+                    None => return EToken::try_from_param(input, inner),
+                };
+                let element = match inner_sizes.is_empty() {
+                    true => inner.as_ref().clone(),
+                    false => FunctionParamType::Array(inner.clone(), inner_sizes.to_vec()),
+                };
+                let tokens = split_list(input, '[', ']')?
+                    .iter()
+                    .map(|e| EToken::try_from_param(e, &element).map(|t| t.0))
+                    .collect::<Result<Vec<Token>, String>>()?;
+                match size {
+                    0 => Ok(EToken(Token::Array(tokens))),
+                    _ if tokens.len() == size => Ok(EToken(Token::FixedArray(tokens))),
+                    _ => Err(format!(
+                        "\"{}\" has {} elements, expected {} for {}",
+                        input,
+                        tokens.len(),
+                        size,
+                        kind
+                    )),
+                }
+            }
+            FunctionParamType::Tuple(types) => {
+                let elements = split_list(input, '(', ')')?;
+                if elements.len() != types.len() {
+                    return Err(format!(
+                        "\"{}\" has {} elements, expected {} for {}",
+                        input,
+                        elements.len(),
+                        types.len(),
+                        kind
+                    ))
+                }
+                let tokens = elements
+                    .iter()
+                    .zip(types)
+                    .map(|(e, ty)| EToken::try_from_param(e, ty).map(|t| t.0))
+                    .collect::<Result<Vec<Token>, String>>()?;
+                Ok(EToken(Token::Tuple(tokens)))
+            }
+        }
+    }
+}
+
+/// Decodes a `0x` prefixed hex string
+fn hex_to_vec(input: &str) -> Result<Vec<u8>, String> {
+    match input.strip_prefix("0x") {
+        Some(hex) if hex.len() % 2 == 0 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            str_to_vec(hex).map_err(|e| e.to_string())
+        }
+        _ => Err(format!("\"{}\" is not a 0x prefixed hex string of whole bytes", input)),
+    }
+}
+
+/// Splits a bracketed list into its top level elements, ie. `[[1, 2], [3]]` into `[1, 2]` and
+/// `[3]`
+fn split_list(input: &str, open: char, close: char) -> Result<Vec<String>, String> {
+    let inner = input
+        .strip_prefix(open)
+        .and_then(|i| i.strip_suffix(close))
+        .ok_or_else(|| format!("\"{}\" is not wrapped in '{}' and '{}'", input, open, close))?;
+    if inner.trim().is_empty() {
+        return Ok(vec![])
+    }
+
+    let mut elements = vec![];
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' if depth == 0 => return Err(format!("Unbalanced brackets in \"{}\"", input)),
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                elements.push(current.trim().to_string());
+                current.clear();
+                continue
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if depth != 0 {
+        return Err(format!("Unbalanced brackets in \"{}\"", input))
+    }
+    elements.push(current.trim().to_string());
+    Ok(elements)
+}
@@ -1,4 +1,10 @@
-use huff_utils::abi::*;
+use ethers_core::{abi::Token, types::U256, utils::hex};
+use huff_utils::{
+    abi::*,
+    ast::FunctionType,
+    bytes_util::{int_to_bytes32, str_to_bytes32},
+    types::{EToken, PrimitiveEVMType},
+};
 use std::collections::HashMap;
 
 #[test]
@@ -77,3 +83,292 @@ fn test_display_func_param_type() {
         }
     }
 }
+
+#[test]
+fn test_abi_compare() {
+    let param =
+        |kind: &str| FunctionParam { name: "".to_string(), kind: kind.into(), internal_type: None };
+    let function = |name: &str, inputs: Vec<FunctionParam>, mutability: FunctionType| Function {
+        name: name.to_string(),
+        inputs,
+        outputs: vec![param("bool")],
+        constant: false,
+        state_mutability: mutability,
+    };
+    let transfer_event = |indexed: bool| Event {
+        name: "Transfer".to_string(),
+        inputs: vec![
+            EventParam { name: "from".to_string(), kind: FunctionParamType::Address, indexed },
+            EventParam { name: "to".to_string(), kind: FunctionParamType::Address, indexed: true },
+        ],
+        anonymous: false,
+    };
+
+    let mut previous = Abi::new();
+    previous.functions.insert(
+        "transfer".to_string(),
+        function("transfer", vec![param("address"), param("uint256")], FunctionType::NonPayable),
+    );
+    previous.functions.insert(
+        "approve".to_string(),
+        function("approve", vec![param("address"), param("uint256")], FunctionType::NonPayable),
+    );
+    previous.functions.insert("paused".to_string(), function("paused", vec![], FunctionType::View));
+    previous.events.insert("Transfer".to_string(), transfer_event(true));
+
+    // Identical abis and additions are compatible
+    let mut current = previous.clone();
+    assert!(current.compare(&previous).is_empty());
+    current.functions.insert("mint".to_string(), function("mint", vec![], FunctionType::Payable));
+    assert!(current.compare(&previous).is_empty());
+
+    // As are renamed outputs
+    if let Some(paused) = current.functions.get_mut("paused") {
+        paused.outputs[0].name = "isPaused".to_string();
+    }
+    assert!(current.compare(&previous).is_empty());
+
+    // Removals and changes are breaking
+    current.functions.remove("approve");
+    current.functions.insert(
+        "transfer".to_string(),
+        function("transfer", vec![param("address"), param("uint128")], FunctionType::NonPayable),
+    );
+    current
+        .functions
+        .insert("paused".to_string(), function("paused", vec![], FunctionType::Payable));
+    current.events.insert("Transfer".to_string(), transfer_event(false));
+    assert_eq!(
+        current.compare(&previous),
+        vec![
+            AbiChange::FunctionRemoved("approve(address,uint256) returns (bool)".to_string()),
+            AbiChange::MutabilityChanged {
+                function: "paused()".to_string(),
+                previous: FunctionType::View,
+                current: FunctionType::Payable,
+            },
+            AbiChange::FunctionChanged {
+                previous: "transfer(address,uint256) returns (bool)".to_string(),
+                current: "transfer(address,uint128) returns (bool)".to_string(),
+            },
+            AbiChange::EventChanged {
+                previous: "Transfer(address indexed,address indexed)".to_string(),
+                current: "Transfer(address,address indexed)".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_signed_integer_encoding() {
+    assert_eq!(int_to_bytes32("-1", 256).unwrap(), [0xff; 32]);
+    assert_eq!(int_to_bytes32("+5", 8).unwrap(), str_to_bytes32("05"));
+    assert_eq!(int_to_bytes32("127", 8).unwrap(), str_to_bytes32("7f"));
+    assert_eq!(
+        int_to_bytes32("-128", 8).unwrap(),
+        str_to_bytes32("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff80")
+    );
+    assert!(int_to_bytes32("128", 8).is_err());
+    assert!(int_to_bytes32("-129", 8).is_err());
+    assert!(int_to_bytes32("-1", 7).is_err());
+    assert!(int_to_bytes32("-0x01", 256).is_err());
+
+    // int256 bounds
+    let min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+    let mut expected = [0u8; 32];
+    expected[0] = 0x80;
+    assert_eq!(int_to_bytes32(min, 256).unwrap(), expected);
+    assert!(int_to_bytes32(&min[1..], 256).is_err());
+
+    // Typed tokens validate the integer's range
+    let token = EToken::try_from_typed("-2".to_string(), &PrimitiveEVMType::Int(16)).unwrap();
+    assert_eq!(token.0, Token::Int(U256::MAX - 1));
+    assert!(EToken::try_from_typed("40000".to_string(), &PrimitiveEVMType::Int(16)).is_err());
+    assert!(EToken::try_from_typed("256".to_string(), &PrimitiveEVMType::Uint(8)).is_err());
+    assert!(EToken::try_from_typed("-1".to_string(), &PrimitiveEVMType::Uint(8)).is_err());
+    assert_eq!(
+        EToken::try_from_typed("255".to_string(), &PrimitiveEVMType::Uint(8)).unwrap().0,
+        Token::Uint(U256::from(255))
+    );
+}
+
+#[test]
+fn test_str_to_bytes32_bounds() {
+    let mut expected = [0u8; 32];
+    expected[31] = 0x7b;
+
+    // Only the last 32 bytes are kept, and pairs that aren't hex read as zero
+    assert_eq!(str_to_bytes32(&format!("ff{}7b", "00".repeat(31))), expected);
+    assert_eq!(str_to_bytes32("zz7b"), expected);
+    assert_eq!(str_to_bytes32("é7b"), expected);
+}
+
+#[test]
+fn test_typed_param_parsing() {
+    let parse = |input: &str, kind: &str| {
+        EToken::try_from_param(input, &FunctionParamType::from(kind)).map(|t| t.0)
+    };
+
+    let address = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    assert_eq!(parse(address, "address").unwrap(), Token::Address(address.parse().unwrap()));
+    assert_eq!(parse("true", "bool").unwrap(), Token::Bool(true));
+    assert_eq!(parse("255", "uint8").unwrap(), Token::Uint(U256::from(255)));
+    assert_eq!(parse("-1", "int8").unwrap(), Token::Int(U256::MAX));
+    assert_eq!(
+        parse("0xdeadbeef", "bytes4").unwrap(),
+        Token::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(parse("0x", "bytes").unwrap(), Token::Bytes(vec![]));
+    assert_eq!(parse("\"hello, world\"", "string").unwrap(), Token::String("hello, world".into()));
+    assert_eq!(parse("hello", "string").unwrap(), Token::String("hello".into()));
+
+    // uint256[2][] is a dynamic array of uint256[2]
+    assert_eq!(
+        parse("[[1, 2], [3, 4]]", "uint256[2][]").unwrap(),
+        Token::Array(vec![
+            Token::FixedArray(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+            Token::FixedArray(vec![Token::Uint(U256::from(3)), Token::Uint(U256::from(4))]),
+        ])
+    );
+    assert_eq!(parse("[]", "address[]").unwrap(), Token::Array(vec![]));
+    assert_eq!(
+        EToken::try_from_param(
+            "(0x01, [true])",
+            &FunctionParamType::Tuple(vec!["bytes1".into(), "bool[]".into()])
+        )
+        .unwrap()
+        .0,
+        Token::Tuple(vec![Token::FixedBytes(vec![1]), Token::Array(vec![Token::Bool(true)])])
+    );
+
+    // Malformed values are reported against the expected type
+    assert_eq!(parse("0x1234", "address").unwrap_err(), "\"0x1234\" is not a 20 byte hex address");
+    assert_eq!(parse("yes", "bool").unwrap_err(), "\"yes\" is not a bool, expected true or false");
+    assert!(parse("256", "uint8").is_err());
+    assert_eq!(
+        parse("0xdead", "bytes4").unwrap_err(),
+        "\"0xdead\" is 2 bytes long, expected 4 for bytes4"
+    );
+    assert_eq!(
+        parse("0xabc", "bytes").unwrap_err(),
+        "\"0xabc\" is not a 0x prefixed hex string of whole bytes"
+    );
+    assert_eq!(
+        parse("[1, 2, 3]", "uint256[2]").unwrap_err(),
+        "\"[1, 2, 3]\" has 3 elements, expected 2 for uint256[2]"
+    );
+    assert_eq!(parse("1, 2", "uint256[]").unwrap_err(), "\"1, 2\" is not wrapped in '[' and ']'");
+    assert_eq!(
+        parse("[[1], 2]]", "uint256[][]").unwrap_err(),
+        "Unbalanced brackets in \"[[1], 2]]\""
+    );
+}
+
+#[test]
+fn test_abi_decode() {
+    let param = |name: &str, kind: &str| FunctionParam {
+        name: name.to_string(),
+        kind: kind.into(),
+        internal_type: None,
+    };
+    let mut abi = Abi::new();
+    abi.functions.insert(
+        "transfer".to_string(),
+        Function {
+            name: "transfer".to_string(),
+            inputs: vec![param("to", "address"), param("amount", "uint256")],
+            outputs: vec![param("", "bool")],
+            constant: false,
+            state_mutability: FunctionType::NonPayable,
+        },
+    );
+    abi.functions.insert(
+        "batch".to_string(),
+        Function {
+            name: "batch".to_string(),
+            inputs: vec![param("deltas", "int256[]"), param("tags", "bytes4[2]")],
+            outputs: vec![],
+            constant: false,
+            state_mutability: FunctionType::NonPayable,
+        },
+    );
+    abi.errors.insert(
+        "InsufficientBalance".to_string(),
+        AbiError {
+            name: "InsufficientBalance".to_string(),
+            inputs: vec![param("needed", "uint256")],
+        },
+    );
+
+    // Calldata is matched by selector
+    let to = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+    let calldata = [
+        hex::decode("a9059cbb").unwrap(),
+        ethers_core::abi::encode(&[Token::Address(to), Token::Uint(U256::from(100))]),
+    ]
+    .concat();
+    let decoded = abi.decode_calldata(&calldata).unwrap();
+    assert_eq!(decoded.signature, "transfer(address,uint256)");
+    assert_eq!(
+        decoded.to_string(),
+        [
+            "transfer(address,uint256)",
+            "  to: address = 0x00000000000000000000000000000000000000AA",
+            "  amount: uint256 = 100\n",
+        ]
+        .join("\n")
+    );
+
+    // Signed integers and arrays are formatted as in solidity
+    let calldata = [
+        ethers_core::utils::keccak256("batch(int256[],bytes4[2])")[..4].to_vec(),
+        ethers_core::abi::encode(&[
+            Token::Array(vec![Token::Int(U256::MAX), Token::Int(U256::from(2))]),
+            Token::FixedArray(vec![
+                Token::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef]),
+                Token::FixedBytes(vec![0, 0, 0, 1]),
+            ]),
+        ]),
+    ]
+    .concat();
+    assert_eq!(
+        abi.decode_calldata(&calldata).unwrap().to_string(),
+        [
+            "batch(int256[],bytes4[2])",
+            "  deltas: int256[] = [-1, 2]",
+            "  tags: bytes4[2] = [0xdeadbeef, 0x00000001]\n",
+        ]
+        .join("\n")
+    );
+
+    // Revert data falls back to custom errors
+    let revert = [
+        ethers_core::utils::keccak256("InsufficientBalance(uint256)")[..4].to_vec(),
+        ethers_core::abi::encode(&[Token::Uint(U256::from(7))]),
+    ]
+    .concat();
+    let decoded = abi.decode_calldata(&revert).unwrap();
+    assert_eq!(decoded.signature, "InsufficientBalance(uint256)");
+    assert_eq!(decoded.params[0].1, Token::Uint(U256::from(7)));
+
+    // Return data is decoded against a function's outputs
+    let returndata = ethers_core::abi::encode(&[Token::Bool(true)]);
+    let decoded = abi.decode_returndata("transfer", &returndata).unwrap();
+    assert_eq!(
+        decoded.to_string(),
+        "transfer(address,uint256) returns (bool)\n  [0]: bool = true\n"
+    );
+    assert_eq!(abi.decode_returndata("transfer(address,uint256)", &returndata).unwrap(), decoded);
+
+    // Unknown selectors, functions, and malformed data fail
+    assert_eq!(
+        abi.decode_calldata(&[0xde, 0xad, 0xbe, 0xef]).unwrap_err(),
+        "No function or error has the selector 0xdeadbeef"
+    );
+    assert!(abi.decode_calldata(&[0xa9, 0x05]).is_err());
+    assert!(abi.decode_calldata(&hex::decode("a9059cbb00").unwrap()).is_err());
+    assert_eq!(
+        abi.decode_returndata("approve", &returndata).unwrap_err(),
+        "No function \"approve\" in the abi"
+    );
+}
@@ -0,0 +1,57 @@
+use huff_utils::{
+    ast::{AstSpan, MacroDefinition, Statement, StatementType},
+    bytecode::{Bytecode, Bytes, IRByteType},
+    bytes_util::str_to_bytes32,
+    error::CodegenErrorKind,
+    evm::Opcode,
+};
+
+#[test]
+fn test_bytes_hex_round_trip() {
+    let bytes = Bytes::from_hex("6001DEad").unwrap();
+    assert_eq!(bytes.0, vec![0x60, 0x01, 0xde, 0xad]);
+    assert_eq!(bytes.to_hex(), "6001dead");
+    assert_eq!(bytes.to_string(), "6001dead");
+    assert_eq!("6001dead".parse::<Bytes>().unwrap(), bytes);
+    assert_eq!(String::from(bytes), "6001dead");
+
+    // Only whole bytes of hex are accepted
+    assert!(Bytes::from_hex("600").is_err());
+    assert!(Bytes::from_hex("60xx").is_err());
+}
+
+#[test]
+fn test_bytes_pushes() {
+    assert_eq!(Bytes::push(&[0xde, 0xad]).to_hex(), "61dead");
+    assert_eq!(Bytes::push(&[0u8; 32]).len(), 33);
+    assert_eq!(Bytes::push_word(&str_to_bytes32("0100")).to_hex(), "610100");
+    assert_eq!(Bytes::push_word(&[0u8; 32]).to_hex(), "6000");
+    assert_eq!(Bytes::push_usize(0x40).to_hex(), "6040");
+    assert_eq!(Bytes::push_usize(0x1234).to_hex(), "611234");
+    assert_eq!(Bytes::push2_placeholder().to_hex(), "610000");
+}
+
+#[test]
+fn test_bytes_opcodes() {
+    assert_eq!(Opcode::Jumpdest.byte(), 0x5b);
+    assert_eq!(Opcode::Jumpdest.string(), "5b");
+    assert_eq!(Bytes::from(Opcode::Push0).to_hex(), "5f");
+    assert_eq!(Bytes::opcodes(&[Opcode::Dup1, Opcode::Eq, Opcode::Jumpi]).to_hex(), "801457");
+
+    let bytecode = Bytecode::from(vec![Bytes::from(Opcode::Caller), Bytes::push(&[0x00])]);
+    assert_eq!(bytecode.0, "336000");
+}
+
+#[test]
+fn test_code_to_irbytes() {
+    let code =
+        |c: &str| Statement { ty: StatementType::Code(c.to_string()), span: AstSpan(vec![]) };
+    let statements = vec![code("6001"), code("600")];
+
+    // Code that isn't whole bytes is left out, or rejected
+    let irbytes = MacroDefinition::to_irbytes(&statements);
+    assert_eq!(irbytes.len(), 1);
+    assert_eq!(irbytes[0].ty, IRByteType::Bytes(Bytes(vec![0x60, 0x01])));
+    let e = MacroDefinition::try_to_irbytes(&statements).unwrap_err();
+    assert_eq!(e.kind, CodegenErrorKind::InvalidCodeLength(3));
+}
@@ -0,0 +1,20 @@
+use huff_utils::prelude::*;
+
+#[test]
+fn test_instructions_step_over_push_data() {
+    let instructions = Instructions::new("0x608060005261xxxx5661ab").collect::<Vec<Instruction>>();
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction { pc: 0, opcode: 0x60, data: "80" },
+            Instruction { pc: 2, opcode: 0x60, data: "00" },
+            Instruction { pc: 4, opcode: 0x52, data: "" },
+            Instruction { pc: 5, opcode: 0x61, data: "xxxx" },
+            Instruction { pc: 8, opcode: 0x56, data: "" },
+            Instruction { pc: 9, opcode: 0x61, data: "ab" },
+        ]
+    );
+
+    // Iteration stops at an opcode that isn't hex
+    assert_eq!(Instructions::new("00zz00").count(), 1);
+}
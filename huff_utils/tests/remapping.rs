@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use huff_utils::prelude::*;
+
+#[test]
+fn test_parse_remappings() {
+    let remappings =
+        Remapping::parse_all("huffmate/=lib/huffmate/src/\n\n  utils/=lib/utils/  \ninvalid\n");
+    assert_eq!(
+        remappings,
+        vec![
+            Remapping { prefix: "huffmate/".to_string(), path: "lib/huffmate/src/".to_string() },
+            Remapping { prefix: "utils/".to_string(), path: "lib/utils/".to_string() },
+        ]
+    );
+    assert!(Remapping::from_str("=lib/").is_err());
+    assert!(Remapping::from_str("huffmate/=").is_err());
+}
+
+#[test]
+fn test_resolve_remapped_imports() {
+    let remappings =
+        Remapping::parse_all("huffmate/=lib/huffmate/src/\nhuffmate/auth/=lib/auth/src/\n");
+    // The longest matching prefix wins
+    assert_eq!(
+        FileSource::resolve_import(
+            "./contracts/Token.huff",
+            "huffmate/auth/Owned.huff",
+            &remappings
+        ),
+        Some("lib/auth/src/Owned.huff".to_string())
+    );
+    assert_eq!(
+        FileSource::resolve_import(
+            "./contracts/Token.huff",
+            "huffmate/math/Math.huff",
+            &remappings
+        ),
+        Some("lib/huffmate/src/math/Math.huff".to_string())
+    );
+    // Unmapped imports are localized relative to the importing file
+    assert_eq!(
+        FileSource::resolve_import("./contracts/Token.huff", "./utils/Utils.huff", &remappings),
+        Some("./contracts/utils/Utils.huff".to_string())
+    );
+}
@@ -0,0 +1,172 @@
+use huff_utils::prelude::*;
+use std::{collections::BTreeMap, sync::Arc};
+
+fn artifact() -> Artifact {
+    let mut functions = BTreeMap::new();
+    functions.insert(
+        "balanceOf".to_string(),
+        huff_utils::abi::Function {
+            name: "balanceOf".to_string(),
+            inputs: vec![FunctionParam {
+                name: "owner".to_string(),
+                kind: FunctionParamType::Address,
+                internal_type: None,
+            }],
+            outputs: vec![FunctionParam {
+                name: "".to_string(),
+                kind: FunctionParamType::Uint(256),
+                internal_type: None,
+            }],
+            constant: false,
+            state_mutability: FunctionType::View,
+        },
+    );
+    Artifact {
+        file: Arc::new(FileSource {
+            path: "./contracts/ERC20.huff".to_string(),
+            ..Default::default()
+        }),
+        bytecode: "6001".to_string(),
+        runtime: "01".to_string(),
+        abi: Some(Abi { functions, ..Default::default() }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_builtin_serializers() {
+    let registry = SerializerRegistry::default();
+    for name in ["bin", "bin-runtime", "foundry", "hardhat", "json", "standard-json"] {
+        assert!(registry.names().contains(&name.to_string()));
+    }
+
+    let art = artifact();
+
+    let json = registry.get("json").unwrap().serialize(&art).unwrap();
+    assert_eq!(serde_json::from_str::<Artifact>(&json).unwrap(), art);
+
+    let foundry: serde_json::Value =
+        serde_json::from_str(&registry.get("foundry").unwrap().serialize(&art).unwrap()).unwrap();
+    assert_eq!(foundry["bytecode"]["object"], "0x6001");
+    assert_eq!(foundry["deployedBytecode"]["object"], "0x01");
+    assert_eq!(
+        foundry["abi"],
+        serde_json::json!([{
+            "type": "function",
+            "name": "balanceOf",
+            "inputs": [{ "name": "owner", "type": "address", "internalType": "address" }],
+            "outputs": [{ "name": "", "type": "uint256", "internalType": "uint256" }],
+            "stateMutability": "view",
+        }])
+    );
+
+    let hardhat: serde_json::Value =
+        serde_json::from_str(&registry.get("hardhat").unwrap().serialize(&art).unwrap()).unwrap();
+    assert_eq!(hardhat["contractName"], "ERC20");
+    assert_eq!(hardhat["sourceName"], "./contracts/ERC20.huff");
+    assert_eq!(hardhat["bytecode"], "0x6001");
+    assert_eq!(hardhat["deployedBytecode"], "0x01");
+
+    assert_eq!(registry.get("bin").unwrap().serialize(&art).unwrap(), "6001");
+    assert_eq!(registry.get("bin-runtime").unwrap().serialize(&art).unwrap(), "01");
+}
+
+#[test]
+fn test_standard_json_serializer() {
+    let mut art = artifact();
+    art.source_map = Some(SourceMap {
+        sources: vec!["./contracts/ERC20.huff".to_string()],
+        bytecode: "0:2:0".to_string(),
+        runtime: "-1:-1:-1".to_string(),
+    });
+    let output: serde_json::Value =
+        serde_json::from_str(&StandardJsonSerializer.serialize(&art).unwrap()).unwrap();
+
+    let contract = &output["contracts"]["./contracts/ERC20.huff"]["ERC20"];
+    assert_eq!(contract["abi"], json_abi(art.abi.as_ref()));
+    assert_eq!(contract["evm"]["bytecode"]["object"], "6001");
+    assert_eq!(contract["evm"]["bytecode"]["sourceMap"], "0:2:0");
+    assert_eq!(contract["evm"]["deployedBytecode"]["object"], "01");
+    assert_eq!(contract["evm"]["deployedBytecode"]["sourceMap"], "-1:-1:-1");
+    assert_eq!(contract["evm"]["methodIdentifiers"]["balanceOf(address)"], "70a08231");
+    assert_eq!(output["sources"]["./contracts/ERC20.huff"]["id"], 0);
+
+    // The metadata is a json string, as in solc's output
+    let metadata: serde_json::Value =
+        serde_json::from_str(contract["metadata"].as_str().unwrap()).unwrap();
+    assert_eq!(metadata["language"], "Huff");
+    assert_eq!(metadata["output"]["abi"], contract["abi"]);
+    assert!(metadata["sources"]["./contracts/ERC20.huff"]["keccak256"]
+        .as_str()
+        .unwrap()
+        .starts_with("0x"));
+}
+
+#[test]
+fn test_stable_json_serializer() {
+    let mut art = artifact();
+    let json = JsonSerializer::default().serialize(&art).unwrap();
+
+    // Access times aren't serialized, so rebuilding the same file serializes the same bytes
+    art.file =
+        Arc::new(FileSource { access: Some(std::time::SystemTime::now()), ..(*art.file).clone() });
+    assert_eq!(JsonSerializer::default().serialize(&art).unwrap(), json);
+
+    // Keys are sorted
+    let keys =
+        ["\"abi\"", "\"bytecode\"", "\"file\"", "\"runtime\"", "\"sizes\"", "\"storageLayout\""];
+    let offsets = keys.iter().map(|k| json.find(k).unwrap()).collect::<Vec<usize>>();
+    assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+
+    let compact = JsonSerializer { pretty: false }.serialize(&art).unwrap();
+    assert!(!compact.contains('\n'));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&json).unwrap()
+    );
+}
+
+#[derive(Debug)]
+struct AbiSerializer;
+
+impl Serializer for AbiSerializer {
+    fn name(&self) -> &str {
+        "abi"
+    }
+
+    fn extension(&self) -> &str {
+        "abi"
+    }
+
+    fn serialize(&self, artifact: &Artifact) -> Result<String, std::io::Error> {
+        Ok(json_abi(artifact.abi.as_ref()).to_string())
+    }
+}
+
+#[test]
+fn test_custom_serializer() {
+    let mut registry = SerializerRegistry::default();
+    assert!(registry.get("abi").is_none());
+    registry.register(Arc::new(AbiSerializer));
+    let serializer = registry.get("abi").unwrap();
+    assert_eq!(serializer.extension(), "abi");
+    assert!(serializer.serialize(&artifact()).unwrap().contains("\"name\":\"balanceOf\""));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml_serializer() {
+    let serialized = TomlSerializer.serialize(&artifact()).unwrap();
+    let value: toml::Value = toml::from_str(&serialized).unwrap();
+    assert_eq!(value["bytecode"].as_str(), Some("6001"));
+    assert_eq!(value["runtime"].as_str(), Some("01"));
+    assert_eq!(value["file"]["path"].as_str(), Some("./contracts/ERC20.huff"));
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yaml_serializer() {
+    let art = artifact();
+    let serialized = YamlSerializer.serialize(&art).unwrap();
+    assert_eq!(serde_yaml::from_str::<Artifact>(&serialized).unwrap(), art);
+}